@@ -2,6 +2,7 @@
 
 use massa_models::address::ExecutionAddressCycleInfo;
 use massa_models::endorsement::EndorsementId;
+use massa_models::execution::AddressOperation;
 use massa_models::operation::OperationId;
 use massa_models::slot::{IndexedSlot, Slot};
 use massa_models::{address::Address, amount::Amount, block_id::BlockId};
@@ -177,3 +178,24 @@ impl std::fmt::Display for AddressFilter {
         }
     }
 }
+
+/// query of the final operations sent or received by an address
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct AddressOperationsQuery {
+    /// Address
+    pub address: Address,
+    /// optional cursor: only the operations older than this one are returned
+    pub cursor: Option<OperationId>,
+    /// optional maximum number of operations to return
+    pub limit: Option<u64>,
+}
+
+/// A page of the final operations sent or received by an address
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct AddressOperationsPage {
+    /// operations of the page, from the most recent to the oldest one
+    pub operations: Vec<AddressOperation>,
+    /// cursor to put in the query to get the next page,
+    /// `None` if there are no more operations involving the address
+    pub next_cursor: Option<OperationId>,
+}
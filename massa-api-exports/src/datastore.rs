@@ -1,6 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use massa_models::address::Address;
+use massa_models::{address::Address, datastore::DatastoreScanDirection};
 use serde::{Deserialize, Serialize};
 
 /// Datastore entry query input structure
@@ -8,8 +8,32 @@ use serde::{Deserialize, Serialize};
 pub struct DatastoreEntryInput {
     /// associated address of the entry
     pub address: Address,
-    /// datastore key
+    /// datastore key, ignored when a range is scanned
+    #[serde(default)]
     pub key: Vec<u8>,
+    /// optional range of keys to scan instead of reading a single key
+    #[serde(default)]
+    pub range: Option<DatastoreRangeInput>,
+}
+
+/// Range of datastore keys to scan
+#[derive(Debug, Default, Deserialize, Clone, Serialize)]
+pub struct DatastoreRangeInput {
+    /// optional first key of the range
+    pub start: Option<Vec<u8>>,
+    /// exclude the first key from the range, to resume after the last key of a previous page
+    #[serde(default)]
+    pub start_exclusive: bool,
+    /// optional last key of the range, excluded unless `end_inclusive` is set
+    pub end: Option<Vec<u8>>,
+    /// include the last key in the range
+    #[serde(default)]
+    pub end_inclusive: bool,
+    /// optional maximum number of entries to return
+    pub limit: Option<u64>,
+    /// order in which the keys are scanned
+    #[serde(default)]
+    pub direction: DatastoreScanDirection,
 }
 
 /// Datastore entry query output structure
@@ -19,12 +43,37 @@ pub struct DatastoreEntryOutput {
     pub final_value: Option<Vec<u8>>,
     /// candidate datastore entry value
     pub candidate_value: Option<Vec<u8>>,
+    /// entries of the scanned range in scan order, if a range was requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range_entries: Option<Vec<DatastoreRangeEntry>>,
+}
+
+/// Datastore entry found while scanning a range
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct DatastoreRangeEntry {
+    /// datastore key
+    pub key: Vec<u8>,
+    /// final datastore entry value
+    pub final_value: Option<Vec<u8>>,
+    /// candidate datastore entry value
+    pub candidate_value: Option<Vec<u8>>,
 }
 
 impl std::fmt::Display for DatastoreEntryOutput {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "final value: {:?}", self.final_value)?;
-        writeln!(f, "candidate value: {:?}", self.candidate_value)?;
+        match &self.range_entries {
+            Some(entries) => {
+                for entry in entries {
+                    writeln!(f, "key: {:?}", entry.key)?;
+                    writeln!(f, "\tfinal value: {:?}", entry.final_value)?;
+                    writeln!(f, "\tcandidate value: {:?}", entry.candidate_value)?;
+                }
+            }
+            None => {
+                writeln!(f, "final value: {:?}", self.final_value)?;
+                writeln!(f, "candidate value: {:?}", self.candidate_value)?;
+            }
+        }
         Ok(())
     }
 }
@@ -1,7 +1,16 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_async_pool::AsyncMessage;
 use massa_final_state::StateChanges;
-use massa_models::{address::Address, amount::Amount, output_event::SCOutputEvent, slot::Slot};
+use massa_models::{
+    address::Address,
+    amount::Amount,
+    execution::{EventCursor, ExecutionTraceFrame, StateOverride},
+    operation::OperationType,
+    output_event::SCOutputEvent,
+    prehash::PreHashMap,
+    slot::Slot,
+};
 use serde::{Deserialize, Serialize};
 use std::{collections::VecDeque, fmt::Display};
 
@@ -27,6 +36,8 @@ pub struct ExecuteReadOnlyResponse {
     pub gas_cost: u64,
     /// state changes caused by the execution step
     pub state_changes: StateChanges,
+    /// call trace of the execution, if it was requested
+    pub trace: Option<ExecutionTraceFrame>,
 }
 
 impl Display for ExecuteReadOnlyResponse {
@@ -65,6 +76,10 @@ pub struct ReadOnlyBytecodeExecution {
     pub operation_datastore: Option<Vec<u8>>,
     /// fee
     pub fee: Option<Amount>,
+    /// state overrides of some addresses, optional
+    pub state_overrides: Option<PreHashMap<Address, StateOverride>>,
+    /// whether to return the call trace of the execution, optional
+    pub trace: Option<bool>,
 }
 
 /// read SC call request
@@ -84,4 +99,154 @@ pub struct ReadOnlyCall {
     pub coins: Option<Amount>,
     /// fee
     pub fee: Option<Amount>,
+    /// state overrides of some addresses, optional
+    pub state_overrides: Option<PreHashMap<Address, StateOverride>>,
+    /// whether to return the call trace of the execution, optional
+    pub trace: Option<bool>,
+}
+
+/// read-only execution of a multicall
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub enum ReadOnlyMulticallItem {
+    /// bytecode execution
+    Bytecode(ReadOnlyBytecodeExecution),
+    /// SC function call
+    Call(ReadOnlyCall),
+}
+
+/// sequence of read-only executions sharing a single speculative state
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct ReadOnlyMulticall {
+    /// executions to run in order, each one seeing the state changes of the previous ones.
+    /// The state overrides of all the executions are applied before the first one.
+    pub calls: Vec<ReadOnlyMulticallItem>,
+}
+
+/// The response for one of the executions of a read-only multicall
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ReadOnlyMulticallCallResponse {
+    /// The result of the execution.
+    pub result: ReadOnlyResult,
+    /// The output events generated by the execution.
+    pub output_events: VecDeque<SCOutputEvent>,
+    /// The gas cost for the execution
+    pub gas_cost: u64,
+    /// call trace of the execution, if it was requested
+    pub trace: Option<ExecutionTraceFrame>,
+}
+
+/// The response to a request for a read-only multicall.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ExecuteReadOnlyMulticallResponse {
+    /// The slot at which the executions occurred.
+    pub executed_at: Slot,
+    /// The responses of the executions, in order. The sequence stops at the first failed execution.
+    pub calls: Vec<ReadOnlyMulticallCallResponse>,
+    /// state changes caused by the whole sequence of successful executions
+    pub state_changes: StateChanges,
+}
+
+impl Display for ExecuteReadOnlyMulticallResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Executed at slot: {}", self.executed_at)?;
+        for (index, call) in self.calls.iter().enumerate() {
+            writeln!(
+                f,
+                "Call {}: {}",
+                index,
+                match &call.result {
+                    ReadOnlyResult::Error(e) =>
+                        format!("an error occurred during the execution: {}", e),
+                    ReadOnlyResult::Ok(ret) => format!("success, returned value: {:?}", ret),
+                }
+            )?;
+            writeln!(f, "Gas cost: {}", call.gas_cost)?;
+            if !call.output_events.is_empty() {
+                writeln!(f, "Generated events:",)?;
+                for event in call.output_events.iter() {
+                    writeln!(f, "{}", event)?; // id already displayed in event
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// unsigned operation whose costs are to be estimated
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct OperationEstimationInput {
+    /// address that would send the operation
+    pub sender_address: Address,
+    /// content of the operation
+    pub op: OperationType,
+}
+
+/// The estimated costs of an operation
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct OperationEstimation {
+    /// gas used by the operation when executed on top of the current speculative state
+    pub gas_used: u64,
+    /// suggested max gas of the operation: the gas used plus a safety margin
+    pub max_gas: u64,
+    /// net storage costs the operation would pay
+    pub storage_cost: Amount,
+    /// suggested fee for the operation to be included soon
+    pub fee: Amount,
+}
+
+impl Display for OperationEstimation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Gas used: {}", self.gas_used)?;
+        writeln!(f, "Suggested max gas: {}", self.max_gas)?;
+        writeln!(f, "Storage cost: {}", self.storage_cost)?;
+        writeln!(f, "Suggested fee: {}", self.fee)?;
+        Ok(())
+    }
+}
+
+/// A page of smart contract output events
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SCOutputEventsPage {
+    /// events of the page, ordered by slot then index in slot
+    pub events: Vec<SCOutputEvent>,
+    /// cursor to put in the filter to get the next page: the last event of the page,
+    /// or the cursor of the query if the page is empty
+    pub next_cursor: Option<EventCursor>,
+}
+
+/// Query of the messages of the asynchronous pool.
+/// The returned messages match all the criteria that are set.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct AsyncPoolMessagesQuery {
+    /// optional address that sent the messages
+    pub sender: Option<Address>,
+    /// optional address the messages are sent to
+    pub destination: Option<Address>,
+    /// optional id of the message
+    pub message_id: Option<String>,
+    /// optional cursor: only the messages after this one are returned
+    pub cursor: Option<String>,
+    /// optional maximum number of messages to return
+    pub limit: Option<u64>,
+}
+
+/// A message of the asynchronous pool, as seen by the final and the candidate states
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AsyncPoolMessageInfo {
+    /// id of the message
+    pub id: String,
+    /// the message in the final state, `None` if it is not there
+    pub final_message: Option<AsyncMessage>,
+    /// the message in the candidate state, `None` if it is not there
+    pub candidate_message: Option<AsyncMessage>,
+}
+
+/// A page of the messages of the asynchronous pool
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AsyncPoolMessagesPage {
+    /// messages of the page, from the highest priority to the lowest one
+    pub messages: Vec<AsyncPoolMessageInfo>,
+    /// cursor to put in the query to get the next page,
+    /// `None` if there are no more messages matching the query
+    pub next_cursor: Option<String>,
 }
@@ -1,7 +1,9 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_models::{
+    address::Address,
     block_id::BlockId,
+    execution::OperationCheckFailure,
     operation::{OperationId, SecureShareOperation},
 };
 
@@ -21,6 +23,17 @@ pub struct OperationInput {
     pub serialized_content: Vec<u8>,
 }
 
+/// Query of the operations of the pool and of its latest evictions
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PoolOperationsQuery {
+    /// optional address whose operations and evictions are listed
+    pub sender: Option<Address>,
+    /// optional number of operations to skip, from the best score
+    pub offset: Option<u64>,
+    /// optional maximum number of operations, and of latest evictions, to return
+    pub limit: Option<u64>,
+}
+
 /// Operation and contextual info about it
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct OperationInfo {
@@ -65,6 +78,29 @@ impl std::fmt::Display for OperationInfo {
     }
 }
 
+/// Verdict of the dry-run of a signed operation
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OperationCheck {
+    /// id of the operation, `None` if it could not be deserialized
+    pub id: Option<OperationId>,
+    /// reason why the operation would be rejected or would fail when executed,
+    /// `None` if it would be included and executed successfully
+    pub failure: Option<OperationCheckFailure>,
+}
+
+impl std::fmt::Display for OperationCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.id {
+            Some(id) => write!(f, "Operation {}: ", id)?,
+            None => write!(f, "Operation: ")?,
+        }
+        match &self.failure {
+            Some(failure) => writeln!(f, "{}", failure),
+            None => writeln!(f, "ok"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use jsonrpsee::core::__reexports::serde_json::{self, Value};
@@ -14,9 +14,12 @@ use massa_api_exports::error::ApiError;
 use massa_api_exports::page::{PageRequest, PagedVec, PagedVecV2};
 use massa_api_exports::ApiRequest;
 use massa_consensus_exports::{ConsensusBroadcasts, ConsensusController};
-use massa_execution_exports::ExecutionController;
+use massa_execution_exports::{
+    is_event_matching_filter, ExecutionChannels, ExecutionController, SlotExecutionOutput,
+};
 use massa_models::address::Address;
 use massa_models::block_id::BlockId;
+use massa_models::execution::EventFilter;
 use massa_models::slot::Slot;
 use massa_models::timeslots::get_latest_block_slot_at_timestamp;
 use massa_models::version::Version;
@@ -31,6 +34,7 @@ impl API<ApiV2> {
         consensus_controller: Box<dyn ConsensusController>,
         consensus_broadcasts: ConsensusBroadcasts,
         execution_controller: Box<dyn ExecutionController>,
+        execution_channels: ExecutionChannels,
         pool_broadcasts: PoolBroadcasts,
         api_settings: APIConfig,
         version: Version,
@@ -39,6 +43,7 @@ impl API<ApiV2> {
             consensus_controller,
             consensus_broadcasts,
             execution_controller,
+            execution_channels,
             pool_broadcasts,
             api_settings,
             version,
@@ -149,6 +154,31 @@ impl MassaApiServer for API<ApiV2> {
     ) -> SubscriptionResult {
         broadcast_via_ws(self.0.pool_broadcasts.operation_sender.clone(), pending).await
     }
+
+    async fn subscribe_new_sc_events(
+        &self,
+        pending: PendingSubscriptionSink,
+        filter: EventFilter,
+    ) -> SubscriptionResult {
+        filtered_broadcast_via_ws(
+            self.0
+                .execution_channels
+                .slot_execution_output_sender
+                .clone(),
+            pending,
+            move |slot_execution_output| {
+                let events = match slot_execution_output {
+                    SlotExecutionOutput::ExecutedSlot(exec_out)
+                    | SlotExecutionOutput::FinalizedSlot(exec_out) => exec_out.events.0,
+                };
+                events
+                    .into_iter()
+                    .filter(|event| is_event_matching_filter(event, &filter))
+                    .collect()
+            },
+        )
+        .await
+    }
 }
 
 // Brodcast the stream(sender) content via a WebSocket
@@ -156,6 +186,20 @@ async fn broadcast_via_ws<T: Serialize + Send + Clone + 'static>(
     sender: tokio::sync::broadcast::Sender<T>,
     pending: PendingSubscriptionSink,
 ) -> SubscriptionResult {
+    filtered_broadcast_via_ws(sender, pending, |item| vec![item]).await
+}
+
+// Brodcast the items extracted from the stream(sender) content via a WebSocket
+async fn filtered_broadcast_via_ws<T, U, F>(
+    sender: tokio::sync::broadcast::Sender<T>,
+    pending: PendingSubscriptionSink,
+    extract: F,
+) -> SubscriptionResult
+where
+    T: Send + Clone + 'static,
+    U: Serialize,
+    F: Fn(T) -> Vec<U>,
+{
     let sink = pending.accept().await?;
     let closed = sink.closed();
     let stream = BroadcastStream::new(sender.subscribe());
@@ -168,10 +212,12 @@ async fn broadcast_via_ws<T: Serialize + Send + Clone + 'static>(
 
             // received new item from the stream.
             Either::Right((Some(Ok(item)), c)) => {
-                let notif = SubscriptionMessage::from_json(&item)?;
+                for item in extract(item) {
+                    let notif = SubscriptionMessage::from_json(&item)?;
 
-                if sink.send(notif).await.is_err() {
-                    break Ok(());
+                    if sink.send(notif).await.is_err() {
+                        return Ok(());
+                    }
                 }
 
                 closed = c;
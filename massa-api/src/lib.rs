@@ -12,22 +12,27 @@ use jsonrpsee::server::middleware::HostFilterLayer;
 use jsonrpsee::server::{BatchRequestConfig, ServerBuilder, ServerHandle};
 use jsonrpsee::RpcModule;
 use massa_api_exports::{
-    address::{AddressFilter, AddressInfo},
+    address::{AddressFilter, AddressInfo, AddressOperationsPage, AddressOperationsQuery},
     block::{BlockInfo, BlockSummary},
     config::APIConfig,
     datastore::{DatastoreEntryInput, DatastoreEntryOutput},
     endorsement::EndorsementInfo,
     error::ApiError::WrongAPI,
-    execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall},
+    execution::{
+        AsyncPoolMessagesPage, AsyncPoolMessagesQuery, ExecuteReadOnlyMulticallResponse,
+        ExecuteReadOnlyResponse, OperationEstimation, OperationEstimationInput,
+        ReadOnlyBytecodeExecution, ReadOnlyCall, ReadOnlyMulticall, SCOutputEventsPage,
+    },
     node::NodeStatus,
-    operation::{OperationInfo, OperationInput},
+    operation::{OperationCheck, OperationInfo, OperationInput, PoolOperationsQuery},
     page::{PageRequest, PagedVec},
     TimeInterval,
 };
 use massa_consensus_exports::{ConsensusBroadcasts, ConsensusController};
-use massa_execution_exports::ExecutionController;
+use massa_execution_exports::{ExecutionChannels, ExecutionController};
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
+use massa_models::execution::ExecutionTraceFrame;
 use massa_models::node::NodeId;
 use massa_models::operation::OperationId;
 use massa_models::output_event::SCOutputEvent;
@@ -36,7 +41,7 @@ use massa_models::{
     address::Address, block::Block, block_id::BlockId, endorsement::EndorsementId,
     execution::EventFilter, slot::Slot, version::Version,
 };
-use massa_pool_exports::{PoolBroadcasts, PoolController};
+use massa_pool_exports::{PoolBroadcasts, PoolController, PoolOperations};
 use massa_pos_exports::SelectorController;
 use massa_protocol_exports::{ProtocolConfig, ProtocolController};
 use massa_storage::Storage;
@@ -89,6 +94,8 @@ pub struct Private {
     pub protocol_controller: Box<dyn ProtocolController>,
     /// link to the execution component
     pub execution_controller: Box<dyn ExecutionController>,
+    /// link to the pool component
+    pub pool_controller: Box<dyn PoolController>,
     /// API settings
     pub api_settings: APIConfig,
     /// Mechanism by which to gracefully shut down.
@@ -106,6 +113,8 @@ pub struct ApiV2 {
     pub consensus_broadcasts: ConsensusBroadcasts,
     /// link to the execution component
     pub execution_controller: Box<dyn ExecutionController>,
+    /// channels with informations broadcasted by the execution
+    pub execution_channels: ExecutionChannels,
     /// channels with informations broadcasted by the pool
     pub pool_broadcasts: PoolBroadcasts,
     /// API settings
@@ -247,6 +256,32 @@ pub trait MassaRpc {
         arg: Vec<ReadOnlyCall>,
     ) -> RpcResult<Vec<ExecuteReadOnlyResponse>>;
 
+    /// Execute a sequence of bytecodes and SC function calls in read-only mode.
+    /// Each execution sees the state changes of the previous ones.
+    #[method(name = "execute_read_only_multicall")]
+    async fn execute_read_only_multicall(
+        &self,
+        arg: ReadOnlyMulticall,
+    ) -> RpcResult<ExecuteReadOnlyMulticallResponse>;
+
+    /// Estimate the gas, the storage costs and the fee of an unsigned operation,
+    /// by executing it in read-only mode.
+    #[method(name = "estimate_operation")]
+    async fn estimate_operation(
+        &self,
+        arg: OperationEstimationInput,
+    ) -> RpcResult<OperationEstimation>;
+
+    /// Execute again an operation of the active history and return its call trace.
+    /// Nothing is applied to the node state.
+    #[method(name = "trace_operation")]
+    async fn trace_operation(&self, arg: OperationId) -> RpcResult<ExecutionTraceFrame>;
+
+    /// List the operations of the pool with their score, and the latest evictions from the pool.
+    /// If a sender is given, only its operations are listed.
+    #[method(name = "get_pool_operations")]
+    async fn get_pool_operations(&self, arg: PoolOperationsQuery) -> RpcResult<PoolOperations>;
+
     /// Remove a vector of addresses used to stake.
     /// No confirmation to expect.
     #[method(name = "remove_staking_addresses")]
@@ -373,20 +408,50 @@ pub trait MassaRpc {
     #[method(name = "get_addresses_bytecode")]
     async fn get_addresses_bytecode(&self, args: Vec<AddressFilter>) -> RpcResult<Vec<Vec<u8>>>;
 
+    /// Get a page of the final operations sent or received by an address, from the most recent to the oldest one.
+    /// Requires the operation history index to be enabled.
+    #[method(name = "get_address_operations")]
+    async fn get_address_operations(
+        &self,
+        arg: AddressOperationsQuery,
+    ) -> RpcResult<AddressOperationsPage>;
+
+    /// Get a page of the messages of the asynchronous pool, in the final and candidate states,
+    /// from the highest priority to the lowest one.
+    #[method(name = "get_async_pool_messages")]
+    async fn get_async_pool_messages(
+        &self,
+        arg: AsyncPoolMessagesQuery,
+    ) -> RpcResult<AsyncPoolMessagesPage>;
+
     /// Adds operations to pool. Returns operations that were ok and sent to pool.
     #[method(name = "send_operations")]
     async fn send_operations(&self, arg: Vec<OperationInput>) -> RpcResult<Vec<OperationId>>;
 
+    /// Dry-runs operations without adding them to pool: runs the checks done before including them in a block,
+    /// then executes them in order on top of the current speculative state. Returns a verdict for each operation.
+    #[method(name = "check_operations")]
+    async fn check_operations(&self, arg: Vec<OperationInput>) -> RpcResult<Vec<OperationCheck>>;
+
     /// Get events optionally filtered by:
     /// * start slot
     /// * end slot
     /// * emitter address
     /// * original caller address
     /// * operation id
+    /// * cursor and limit
     #[method(name = "get_filtered_sc_output_event")]
     async fn get_filtered_sc_output_event(&self, arg: EventFilter)
         -> RpcResult<Vec<SCOutputEvent>>;
 
+    /// Get a page of events filtered like `get_filtered_sc_output_event`.
+    /// At most `limit` events are returned along with the cursor of the next page.
+    #[method(name = "get_filtered_sc_output_event_page")]
+    async fn get_filtered_sc_output_event_page(
+        &self,
+        arg: EventFilter,
+    ) -> RpcResult<SCOutputEventsPage>;
+
     /// Get OpenRPC specification.
     #[method(name = "rpc.discover")]
     async fn get_openrpc_spec(&self) -> RpcResult<Value>;
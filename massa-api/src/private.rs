@@ -5,25 +5,31 @@ use crate::{MassaRpcServer, Private, RpcServer, StopHandle, Value, API};
 use async_trait::async_trait;
 use jsonrpsee::core::{Error as JsonRpseeError, RpcResult};
 use massa_api_exports::{
-    address::{AddressFilter, AddressInfo},
+    address::{AddressFilter, AddressInfo, AddressOperationsPage, AddressOperationsQuery},
     block::{BlockInfo, BlockSummary},
     config::APIConfig,
     datastore::{DatastoreEntryInput, DatastoreEntryOutput},
     endorsement::EndorsementInfo,
     error::ApiError,
-    execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall},
+    execution::{
+        AsyncPoolMessagesPage, AsyncPoolMessagesQuery, ExecuteReadOnlyMulticallResponse,
+        ExecuteReadOnlyResponse, OperationEstimation, OperationEstimationInput,
+        ReadOnlyBytecodeExecution, ReadOnlyCall, ReadOnlyMulticall, SCOutputEventsPage,
+    },
     node::NodeStatus,
-    operation::{OperationInfo, OperationInput},
+    operation::{OperationCheck, OperationInfo, OperationInput, PoolOperationsQuery},
     page::{PageRequest, PagedVec},
     ListType, ScrudOperation, TimeInterval,
 };
 use massa_execution_exports::ExecutionController;
 use massa_hash::Hash;
+use massa_models::execution::ExecutionTraceFrame;
 use massa_models::{
     address::Address, block::Block, block_id::BlockId, clique::Clique, composite::PubkeySig,
     endorsement::EndorsementId, execution::EventFilter, node::NodeId, operation::OperationId,
     output_event::SCOutputEvent, prehash::PreHashSet, slot::Slot,
 };
+use massa_pool_exports::{PoolController, PoolOperations};
 use massa_protocol_exports::{PeerId, ProtocolController};
 use massa_signature::KeyPair;
 use massa_wallet::Wallet;
@@ -43,6 +49,7 @@ impl API<Private> {
     pub fn new(
         protocol_controller: Box<dyn ProtocolController>,
         execution_controller: Box<dyn ExecutionController>,
+        pool_controller: Box<dyn PoolController>,
         api_settings: APIConfig,
         stop_cv: Arc<(Mutex<bool>, Condvar)>,
         node_wallet: Arc<RwLock<Wallet>>,
@@ -50,6 +57,7 @@ impl API<Private> {
         API(Private {
             protocol_controller,
             execution_controller,
+            pool_controller,
             api_settings,
             stop_cv,
             node_wallet,
@@ -125,6 +133,44 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api::<_>()
     }
 
+    async fn execute_read_only_multicall(
+        &self,
+        _req: ReadOnlyMulticall,
+    ) -> RpcResult<ExecuteReadOnlyMulticallResponse> {
+        crate::wrong_api::<_>()
+    }
+
+    async fn estimate_operation(
+        &self,
+        _req: OperationEstimationInput,
+    ) -> RpcResult<OperationEstimation> {
+        crate::wrong_api::<_>()
+    }
+
+    async fn trace_operation(&self, operation_id: OperationId) -> RpcResult<ExecutionTraceFrame> {
+        self.0
+            .execution_controller
+            .trace_operation(operation_id)
+            .map_err(|e| ApiError::ExecutionError(e.to_string()).into())
+    }
+
+    async fn get_pool_operations(&self, query: PoolOperationsQuery) -> RpcResult<PoolOperations> {
+        let max_arguments = self.0.api_settings.max_arguments;
+        let limit = query.limit.unwrap_or(max_arguments);
+        if limit > max_arguments {
+            return Err(ApiError::BadRequest(format!(
+                "too many operations requested. Only a maximum of {} operations are returned per request",
+                max_arguments
+            ))
+            .into());
+        }
+        let offset = usize::try_from(query.offset.unwrap_or(0)).unwrap_or(usize::MAX);
+        Ok(self
+            .0
+            .pool_controller
+            .get_pool_operations(query.sender, offset, limit as usize))
+    }
+
     async fn remove_staking_addresses(&self, addresses: Vec<Address>) -> RpcResult<()> {
         let node_wallet = self.0.node_wallet.clone();
 
@@ -242,14 +288,39 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api::<Vec<Vec<u8>>>()
     }
 
+    async fn get_address_operations(
+        &self,
+        _: AddressOperationsQuery,
+    ) -> RpcResult<AddressOperationsPage> {
+        crate::wrong_api::<AddressOperationsPage>()
+    }
+
+    async fn get_async_pool_messages(
+        &self,
+        _: AsyncPoolMessagesQuery,
+    ) -> RpcResult<AsyncPoolMessagesPage> {
+        crate::wrong_api::<AsyncPoolMessagesPage>()
+    }
+
     async fn send_operations(&self, _: Vec<OperationInput>) -> RpcResult<Vec<OperationId>> {
         crate::wrong_api::<Vec<OperationId>>()
     }
 
+    async fn check_operations(&self, _: Vec<OperationInput>) -> RpcResult<Vec<OperationCheck>> {
+        crate::wrong_api::<Vec<OperationCheck>>()
+    }
+
     async fn get_filtered_sc_output_event(&self, _: EventFilter) -> RpcResult<Vec<SCOutputEvent>> {
         crate::wrong_api::<Vec<SCOutputEvent>>()
     }
 
+    async fn get_filtered_sc_output_event_page(
+        &self,
+        _: EventFilter,
+    ) -> RpcResult<SCOutputEventsPage> {
+        crate::wrong_api::<SCOutputEventsPage>()
+    }
+
     async fn node_peers_whitelist(&self) -> RpcResult<Vec<IpAddr>> {
         //TODO: Reinvoke
         // let network_command_sender = self.0.network_command_sender.clone();
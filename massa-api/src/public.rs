@@ -6,25 +6,31 @@ use async_trait::async_trait;
 use itertools::{izip, Itertools};
 use jsonrpsee::core::{Error as JsonRpseeError, RpcResult};
 use massa_api_exports::{
-    address::{AddressFilter, AddressInfo},
+    address::{AddressFilter, AddressInfo, AddressOperationsPage, AddressOperationsQuery},
     block::{BlockInfo, BlockInfoContent, BlockSummary},
     config::APIConfig,
-    datastore::{DatastoreEntryInput, DatastoreEntryOutput},
+    datastore::{DatastoreEntryInput, DatastoreEntryOutput, DatastoreRangeEntry},
     endorsement::EndorsementInfo,
     error::ApiError,
-    execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall, ReadOnlyResult},
+    execution::{
+        AsyncPoolMessageInfo, AsyncPoolMessagesPage, AsyncPoolMessagesQuery,
+        ExecuteReadOnlyMulticallResponse, ExecuteReadOnlyResponse, OperationEstimation,
+        OperationEstimationInput, ReadOnlyBytecodeExecution, ReadOnlyCall, ReadOnlyMulticall,
+        ReadOnlyMulticallCallResponse, ReadOnlyMulticallItem, ReadOnlyResult, SCOutputEventsPage,
+    },
     node::NodeStatus,
-    operation::{OperationInfo, OperationInput},
+    operation::{OperationCheck, OperationInfo, OperationInput, PoolOperationsQuery},
     page::{PageRequest, PagedVec},
     slot::SlotAmount,
     TimeInterval,
 };
+use massa_async_pool::{async_message_id_from_str, async_message_id_to_string};
 use massa_consensus_exports::block_status::DiscardReason;
 use massa_consensus_exports::ConsensusController;
 use massa_execution_exports::{
-    ExecutionController, ExecutionQueryRequest, ExecutionQueryRequestItem,
-    ExecutionQueryResponseItem, ExecutionStackElement, ReadOnlyExecutionRequest,
-    ReadOnlyExecutionTarget,
+    AsyncPoolMessageFilter, ExecutionController, ExecutionQueryError, ExecutionQueryRequest,
+    ExecutionQueryRequestItem, ExecutionQueryResponseItem, ExecutionStackElement,
+    ReadOnlyExecutionRequest, ReadOnlyExecutionTarget, ReadOnlyMulticallRequest,
 };
 use massa_models::{
     address::Address,
@@ -34,11 +40,11 @@ use massa_models::{
     clique::Clique,
     composite::PubkeySig,
     config::CompactConfig,
-    datastore::DatastoreDeserializer,
+    datastore::{DatastoreDeserializer, DatastoreRange},
     endorsement::EndorsementId,
     endorsement::SecureShareEndorsement,
     error::ModelsError,
-    execution::EventFilter,
+    execution::{EventFilter, ExecutionTraceFrame, OperationCheckFailure},
     node::NodeId,
     operation::OperationDeserializer,
     operation::OperationId,
@@ -51,7 +57,7 @@ use massa_models::{
     timeslots::{get_latest_block_slot_at_timestamp, time_range_to_slot_range},
     version::Version,
 };
-use massa_pool_exports::PoolController;
+use massa_pool_exports::{PoolController, PoolOperations};
 use massa_pos_exports::SelectorController;
 use massa_protocol_exports::{PeerConnectionType, ProtocolConfig, ProtocolController};
 use massa_serialization::{DeserializeError, Deserializer};
@@ -63,6 +69,7 @@ use massa_versioning::{
 };
 use std::collections::BTreeMap;
 use std::net::{IpAddr, SocketAddr};
+use std::ops::Bound;
 
 impl API<Public> {
     /// generate a new public API
@@ -93,6 +100,118 @@ impl API<Public> {
             keypair_factory: KeyPairFactory { mip_store },
         })
     }
+
+    /// Get the caller address of a read-only execution, or generate one if it is not provided
+    fn read_only_caller_address(&self, address: Option<Address>) -> RpcResult<Address> {
+        if let Some(addr) = address {
+            return Ok(addr);
+        }
+        let now = MassaTime::now();
+        let keypair = self
+            .0
+            .keypair_factory
+            .create(&(), FactoryStrategy::At(now))
+            .map_err(ApiError::from)?;
+        Ok(Address::from_public_key(&keypair.get_public_key()))
+    }
+
+    /// Translate a read-only bytecode execution into an execution request
+    fn bytecode_execution_request(
+        &self,
+        req: ReadOnlyBytecodeExecution,
+    ) -> RpcResult<ReadOnlyExecutionRequest> {
+        let ReadOnlyBytecodeExecution {
+            max_gas,
+            address,
+            bytecode,
+            operation_datastore,
+            fee,
+            state_overrides,
+            trace,
+        } = req;
+
+        let address = self.read_only_caller_address(address)?;
+
+        let op_datastore = match operation_datastore {
+            Some(v) => {
+                let deserializer = DatastoreDeserializer::new(
+                    self.0.api_settings.max_op_datastore_entry_count,
+                    self.0.api_settings.max_op_datastore_key_length,
+                    self.0.api_settings.max_op_datastore_value_length,
+                );
+                match deserializer.deserialize::<DeserializeError>(&v) {
+                    Ok((_, deserialized)) => Some(deserialized),
+                    Err(e) => {
+                        return Err(ApiError::InconsistencyError(format!(
+                            "Operation datastore error: {}",
+                            e
+                        ))
+                        .into())
+                    }
+                }
+            }
+            None => None,
+        };
+
+        Ok(ReadOnlyExecutionRequest {
+            max_gas,
+            target: ReadOnlyExecutionTarget::BytecodeExecution(bytecode),
+            call_stack: vec![ExecutionStackElement {
+                address,
+                coins: Default::default(),
+                owned_addresses: vec![address],
+                operation_datastore: op_datastore,
+            }],
+            coins: None,
+            fee,
+            state_overrides: state_overrides.unwrap_or_default(),
+            trace: trace.unwrap_or_default(),
+        })
+    }
+
+    /// Translate a read-only SC call into an execution request
+    fn call_request(&self, req: ReadOnlyCall) -> RpcResult<ReadOnlyExecutionRequest> {
+        let ReadOnlyCall {
+            max_gas,
+            target_address,
+            target_function,
+            parameter,
+            caller_address,
+            coins,
+            fee,
+            state_overrides,
+            trace,
+        } = req;
+
+        let caller_address = self.read_only_caller_address(caller_address)?;
+
+        Ok(ReadOnlyExecutionRequest {
+            max_gas,
+            target: ReadOnlyExecutionTarget::FunctionCall {
+                target_func: target_function,
+                target_addr: target_address,
+                parameter,
+            },
+            call_stack: vec![
+                ExecutionStackElement {
+                    address: caller_address,
+                    coins: Default::default(),
+                    owned_addresses: vec![caller_address],
+                    operation_datastore: None, // should always be None
+                },
+                ExecutionStackElement {
+                    address: target_address,
+                    coins: coins.unwrap_or(Amount::default()),
+                    owned_addresses: vec![target_address],
+                    operation_datastore: None, // should always be None
+                },
+            ],
+            coins,
+            fee,
+            state_overrides: state_overrides.unwrap_or_default(),
+            trace: trace.unwrap_or_default(),
+        })
+    }
 }
 
 #[async_trait]
@@ -130,60 +249,9 @@ impl MassaRpcServer for API<Public> {
         }
 
         let mut res: Vec<ExecuteReadOnlyResponse> = Vec::with_capacity(reqs.len());
-        for ReadOnlyBytecodeExecution {
-            max_gas,
-            address,
-            bytecode,
-            operation_datastore,
-            fee,
-        } in reqs
-        {
-            let address = if let Some(addr) = address {
-                addr
-            } else {
-                let now = MassaTime::now();
-                let keypair = self
-                    .0
-                    .keypair_factory
-                    .create(&(), FactoryStrategy::At(now))
-                    .map_err(ApiError::from)?;
-                Address::from_public_key(&keypair.get_public_key())
-            };
-
-            let op_datastore = match operation_datastore {
-                Some(v) => {
-                    let deserializer = DatastoreDeserializer::new(
-                        self.0.api_settings.max_op_datastore_entry_count,
-                        self.0.api_settings.max_op_datastore_key_length,
-                        self.0.api_settings.max_op_datastore_value_length,
-                    );
-                    match deserializer.deserialize::<DeserializeError>(&v) {
-                        Ok((_, deserialized)) => Some(deserialized),
-                        Err(e) => {
-                            return Err(ApiError::InconsistencyError(format!(
-                                "Operation datastore error: {}",
-                                e
-                            ))
-                            .into())
-                        }
-                    }
-                }
-                None => None,
-            };
-
+        for req in reqs {
             // translate request
-            let req = ReadOnlyExecutionRequest {
-                max_gas,
-                target: ReadOnlyExecutionTarget::BytecodeExecution(bytecode),
-                call_stack: vec![ExecutionStackElement {
-                    address,
-                    coins: Default::default(),
-                    owned_addresses: vec![address],
-                    operation_datastore: op_datastore,
-                }],
-                coins: None,
-                fee,
-            };
+            let req = self.bytecode_execution_request(req)?;
 
             // run
             let result = self.0.execution_controller.execute_readonly_request(req);
@@ -201,6 +269,7 @@ impl MassaRpcServer for API<Public> {
                 output_events: result
                     .as_ref()
                     .map_or_else(|_| Default::default(), |v| v.out.events.clone().0),
+                trace: result.as_ref().map_or_else(|_| None, |v| v.trace.clone()),
                 state_changes: result.map_or_else(|_| Default::default(), |v| v.out.state_changes),
             };
 
@@ -221,53 +290,9 @@ impl MassaRpcServer for API<Public> {
         }
 
         let mut res: Vec<ExecuteReadOnlyResponse> = Vec::with_capacity(reqs.len());
-        for ReadOnlyCall {
-            max_gas,
-            target_address,
-            target_function,
-            parameter,
-            caller_address,
-            coins,
-            fee,
-        } in reqs
-        {
-            let caller_address = if let Some(addr) = caller_address {
-                addr
-            } else {
-                let now = MassaTime::now();
-                let keypair = self
-                    .0
-                    .keypair_factory
-                    .create(&(), FactoryStrategy::At(now))
-                    .map_err(ApiError::from)?;
-                Address::from_public_key(&keypair.get_public_key())
-            };
-
+        for req in reqs {
             // translate request
-            let req = ReadOnlyExecutionRequest {
-                max_gas,
-                target: ReadOnlyExecutionTarget::FunctionCall {
-                    target_func: target_function,
-                    target_addr: target_address,
-                    parameter,
-                },
-                call_stack: vec![
-                    ExecutionStackElement {
-                        address: caller_address,
-                        coins: Default::default(),
-                        owned_addresses: vec![caller_address],
-                        operation_datastore: None, // should always be None
-                    },
-                    ExecutionStackElement {
-                        address: target_address,
-                        coins: coins.unwrap_or(Amount::default()),
-                        owned_addresses: vec![target_address],
-                        operation_datastore: None, // should always be None
-                    },
-                ],
-                coins,
-                fee,
-            };
+            let req = self.call_request(req)?;
 
             // run
             let result = self.0.execution_controller.execute_readonly_request(req);
@@ -285,6 +310,7 @@ impl MassaRpcServer for API<Public> {
                 output_events: result
                     .as_ref()
                     .map_or_else(|_| Default::default(), |v| v.out.events.clone().0),
+                trace: result.as_ref().map_or_else(|_| None, |v| v.trace.clone()),
                 state_changes: result.map_or_else(|_| Default::default(), |v| v.out.state_changes),
             };
 
@@ -295,6 +321,90 @@ impl MassaRpcServer for API<Public> {
         Ok(res)
     }
 
+    /// execute a sequence of read-only executions sharing a single speculative state
+    async fn execute_read_only_multicall(
+        &self,
+        req: ReadOnlyMulticall,
+    ) -> RpcResult<ExecuteReadOnlyMulticallResponse> {
+        if req.calls.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+
+        // translate request
+        let mut calls = Vec::with_capacity(req.calls.len());
+        for call in req.calls {
+            calls.push(match call {
+                ReadOnlyMulticallItem::Bytecode(execution) => {
+                    self.bytecode_execution_request(execution)?
+                }
+                ReadOnlyMulticallItem::Call(call) => self.call_request(call)?,
+            });
+        }
+
+        // run
+        let output = self
+            .0
+            .execution_controller
+            .execute_readonly_multicall(ReadOnlyMulticallRequest { calls })
+            .map_err(|e| ApiError::ExecutionError(e.to_string()))?;
+
+        // map result
+        Ok(ExecuteReadOnlyMulticallResponse {
+            executed_at: output.slot,
+            calls: output
+                .calls
+                .into_iter()
+                .map(|call| match call {
+                    Ok(call) => ReadOnlyMulticallCallResponse {
+                        result: ReadOnlyResult::Ok(call.call_result),
+                        output_events: call.events.0,
+                        gas_cost: call.gas_cost,
+                        trace: call.trace,
+                    },
+                    Err(err) => ReadOnlyMulticallCallResponse {
+                        result: ReadOnlyResult::Error(format!("readonly call failed: {}", err)),
+                        output_events: Default::default(),
+                        gas_cost: 0,
+                        trace: None,
+                    },
+                })
+                .collect(),
+            state_changes: output.state_changes,
+        })
+    }
+
+    /// estimate the costs of an unsigned operation and suggest a fee for it
+    async fn estimate_operation(
+        &self,
+        req: OperationEstimationInput,
+    ) -> RpcResult<OperationEstimation> {
+        let estimation = self
+            .0
+            .execution_controller
+            .estimate_operation(req.sender_address, req.op)
+            .map_err(|e| ApiError::ExecutionError(e.to_string()))?;
+
+        let fee = self.0.pool_command_sender.get_fee_suggestion(
+            req.sender_address
+                .get_thread(self.0.api_settings.thread_count),
+        );
+
+        Ok(OperationEstimation {
+            gas_used: estimation.gas_used,
+            max_gas: estimation.max_gas,
+            storage_cost: estimation.storage_cost,
+            fee,
+        })
+    }
+
+    async fn trace_operation(&self, _: OperationId) -> RpcResult<ExecutionTraceFrame> {
+        crate::wrong_api::<ExecutionTraceFrame>()
+    }
+
+    async fn get_pool_operations(&self, _: PoolOperationsQuery) -> RpcResult<PoolOperations> {
+        crate::wrong_api::<PoolOperations>()
+    }
+
     async fn remove_staking_addresses(&self, _: Vec<Address>) -> RpcResult<()> {
         crate::wrong_api::<()>()
     }
@@ -486,7 +596,7 @@ impl MassaRpcServer for API<Public> {
                 .collect()
         };
 
-        let storage_info: Vec<(SecureShareOperation, PreHashSet<BlockId>)> = {
+        let mut storage_info: Vec<(SecureShareOperation, PreHashSet<BlockId>)> = {
             let read_blocks = self.0.storage.read_blocks();
             secure_share_operations
                 .into_iter()
@@ -503,10 +613,36 @@ impl MassaRpcServer for API<Public> {
                 .collect()
         };
 
-        // keep only the ops id (found in storage)
+        let api_cfg = self.0.api_settings.clone();
+
+        // fall back to the archive for the final operations that were pruned from storage
+        let mut archived_ops: PreHashSet<OperationId> = PreHashSet::default();
+        {
+            let found_ops: PreHashSet<OperationId> =
+                storage_info.iter().map(|(op, _)| op.id).collect();
+            let missing_ops: Vec<OperationId> = operations_ids
+                .iter()
+                .filter(|id| !found_ops.contains(id))
+                .copied()
+                .collect();
+            if missing_ops.len() as u64 > api_cfg.max_arguments {
+                return Err(ApiError::BadRequest("too many arguments".into()).into());
+            }
+            for (operation, block_id) in self
+                .0
+                .consensus_controller
+                .get_archived_operations(&missing_ops)
+                .into_iter()
+                .flatten()
+            {
+                archived_ops.insert(operation.id);
+                storage_info.push((operation, std::iter::once(block_id).collect()));
+            }
+        }
+
+        // keep only the ops id (found in storage or in the archive)
         let ops: Vec<OperationId> = storage_info.iter().map(|(op, _)| op.id).collect();
 
-        let api_cfg = self.0.api_settings.clone();
         if ops.len() as u64 > api_cfg.max_arguments {
             return Err(ApiError::BadRequest("too many arguments".into()).into());
         }
@@ -546,7 +682,9 @@ impl MassaRpcServer for API<Public> {
             res.push(OperationInfo {
                 id,
                 in_pool,
-                is_operation_final,
+                // archived operations are in a final block
+                is_operation_final: is_operation_final
+                    .or_else(|| archived_ops.contains(&id).then_some(true)),
                 thread: operation
                     .content_creator_address
                     .get_thread(api_cfg.thread_count),
@@ -571,6 +709,7 @@ impl MassaRpcServer for API<Public> {
 
         let mut secure_share_endorsements: Vec<SecureShareEndorsement> =
             Vec::with_capacity(endorsement_ids.len());
+        let mut missing_endorsements: Vec<EndorsementId> = Vec::new();
         {
             let endorsement_storage_lock = self.0.storage.read_endorsements();
             endorsement_ids.retain(|id| {
@@ -578,11 +717,12 @@ impl MassaRpcServer for API<Public> {
                     secure_share_endorsements.push(wrapped_endorsement.clone());
                     return true;
                 };
+                missing_endorsements.push(*id);
                 false
             });
         }
 
-        let storage_info: Vec<(SecureShareEndorsement, PreHashSet<BlockId>)> = {
+        let mut storage_info: Vec<(SecureShareEndorsement, PreHashSet<BlockId>)> = {
             let read_blocks = self.0.storage.read_blocks();
             secure_share_endorsements
                 .into_iter()
@@ -599,6 +739,20 @@ impl MassaRpcServer for API<Public> {
                 .collect()
         };
 
+        // fall back to the archive for the final endorsements that were pruned from storage
+        let mut archived_endorsements: PreHashSet<EndorsementId> = PreHashSet::default();
+        for (endorsement, block_id) in self
+            .0
+            .consensus_controller
+            .get_archived_endorsements(&missing_endorsements)
+            .into_iter()
+            .flatten()
+        {
+            archived_endorsements.insert(endorsement.id);
+            endorsement_ids.push(endorsement.id);
+            storage_info.push((endorsement, std::iter::once(block_id).collect()));
+        }
+
         // ask pool whether it carries the operations
         let in_pool = self
             .0
@@ -625,9 +779,12 @@ impl MassaRpcServer for API<Public> {
                 .collect();
             storage_info
                 .iter()
-                .map(|(_ed, bs)| {
-                    bs.iter()
-                        .any(|b| block_statuses.get(b) == Some(&BlockGraphStatus::Final))
+                .map(|(ed, bs)| {
+                    // archived endorsements are in a final block
+                    archived_endorsements.contains(&ed.id)
+                        || bs
+                            .iter()
+                            .any(|b| block_statuses.get(b) == Some(&BlockGraphStatus::Final))
                 })
                 .collect()
         };
@@ -656,24 +813,28 @@ impl MassaRpcServer for API<Public> {
 
     /// get blocks
     /// Returns only active blocks are returned
-    async fn get_blocks(&self, mut ids: Vec<BlockId>) -> RpcResult<Vec<BlockInfo>> {
+    async fn get_blocks(&self, ids: Vec<BlockId>) -> RpcResult<Vec<BlockInfo>> {
+        let mut stored_ids: Vec<BlockId> = Vec::with_capacity(ids.len());
         let mut blocks: Vec<Block> = Vec::with_capacity(ids.len());
+        let mut missing_ids: Vec<BlockId> = Vec::new();
+        // whether each requested block is in storage, in request order
+        let mut is_stored: Vec<bool> = Vec::with_capacity(ids.len());
         {
             let block_storage_lock = self.0.storage.read_blocks();
-            ids.retain(|id| {
-                if let Some(wrapped_block) = block_storage_lock.get(id) {
+            for id in ids {
+                if let Some(wrapped_block) = block_storage_lock.get(&id) {
+                    stored_ids.push(id);
                     blocks.push(wrapped_block.content.clone());
-                    return true;
-                };
-                false
-            });
+                    is_stored.push(true);
+                } else {
+                    missing_ids.push(id);
+                    is_stored.push(false);
+                }
+            }
         }
-        let block_statuses = self.0.consensus_controller.get_block_statuses(&ids);
-        let res = ids
-            .into_iter()
-            .zip(blocks)
-            .zip(block_statuses)
-            .map(|((id, content), graph_status)| BlockInfo {
+        let block_statuses = self.0.consensus_controller.get_block_statuses(&stored_ids);
+        let mut stored_infos = stored_ids.into_iter().zip(blocks).zip(block_statuses).map(
+            |((id, content), graph_status)| BlockInfo {
                 id,
                 content: Some(BlockInfoContent {
                     is_final: graph_status == BlockGraphStatus::Final,
@@ -683,6 +844,39 @@ impl MassaRpcServer for API<Public> {
                     is_discarded: graph_status == BlockGraphStatus::Discarded,
                     block: content,
                 }),
+            },
+        );
+
+        // fall back to the archive for the final blocks that were pruned from storage
+        let archived_blocks = self
+            .0
+            .consensus_controller
+            .get_archived_blocks(&missing_ids);
+        let mut archived_infos = missing_ids
+            .into_iter()
+            .zip(archived_blocks)
+            .map(|(id, block)| {
+                Some(BlockInfo {
+                    id,
+                    content: Some(BlockInfoContent {
+                        is_final: true,
+                        is_in_blockclique: false,
+                        is_candidate: false,
+                        is_discarded: false,
+                        block: block?.content,
+                    }),
+                })
+            });
+
+        // merge the blocks from storage and from the archive in request order
+        let res = is_stored
+            .into_iter()
+            .filter_map(|stored| {
+                if stored {
+                    stored_infos.next()
+                } else {
+                    archived_infos.next().flatten()
+                }
             })
             .collect();
         Ok(res)
@@ -694,18 +888,23 @@ impl MassaRpcServer for API<Public> {
             .consensus_controller
             .get_blockclique_block_at_slot(slot);
 
-        let block_id = match block_id_option {
-            Some(id) => id,
-            None => return Ok(None),
-        };
+        let res = block_id_option.and_then(|block_id| {
+            self.0
+                .storage
+                .read_blocks()
+                .get(&block_id)
+                .map(|b| b.content.clone())
+        });
+        if res.is_some() {
+            return Ok(res);
+        }
 
-        let res = self
+        // fall back to the archive for the final blocks that were pruned from storage
+        Ok(self
             .0
-            .storage
-            .read_blocks()
-            .get(&block_id)
-            .map(|b| b.content.clone());
-        Ok(res)
+            .consensus_controller
+            .get_archived_block_at_slot(slot)
+            .map(|b| b.content))
     }
 
     /// gets an interval of the block graph from consensus, with time filtering
@@ -774,21 +973,133 @@ impl MassaRpcServer for API<Public> {
         &self,
         entries: Vec<DatastoreEntryInput>,
     ) -> RpcResult<Vec<DatastoreEntryOutput>> {
-        Ok(self
-            .0
-            .execution_controller
-            .get_final_and_active_data_entry(
-                entries
-                    .into_iter()
-                    .map(|input| (input.address, input.key))
-                    .collect::<Vec<_>>(),
-            )
-            .into_iter()
-            .map(|output| DatastoreEntryOutput {
-                final_value: output.0,
-                candidate_value: output.1,
-            })
-            .collect())
+        let max_arguments = self.0.api_settings.max_arguments;
+
+        // single keys are read in one batch, ranges are scanned through execution queries
+        let mut outputs: Vec<Option<DatastoreEntryOutput>> = vec![None; entries.len()];
+        let mut key_inputs = Vec::new();
+        let mut range_inputs = Vec::new();
+        for (index, input) in entries.into_iter().enumerate() {
+            let Some(range) = input.range else {
+                key_inputs.push((index, (input.address, input.key)));
+                continue;
+            };
+            let limit = range.limit.unwrap_or(max_arguments);
+            if limit > max_arguments {
+                return Err(ApiError::BadRequest(format!(
+                    "too many datastore entries requested. Only a maximum of {} entries are returned per range",
+                    max_arguments
+                ))
+                .into());
+            }
+            let range = DatastoreRange {
+                start: match (range.start, range.start_exclusive) {
+                    (Some(key), false) => Bound::Included(key),
+                    (Some(key), true) => Bound::Excluded(key),
+                    (None, _) => Bound::Unbounded,
+                },
+                end: match (range.end, range.end_inclusive) {
+                    (Some(key), true) => Bound::Included(key),
+                    (Some(key), false) => Bound::Excluded(key),
+                    (None, _) => Bound::Unbounded,
+                },
+                limit,
+                direction: range.direction,
+            };
+            range_inputs.push((index, input.address, range));
+        }
+
+        if !key_inputs.is_empty() {
+            let (indexes, keys): (Vec<usize>, Vec<(Address, Vec<u8>)>) =
+                key_inputs.into_iter().unzip();
+            let values = self
+                .0
+                .execution_controller
+                .get_final_and_active_data_entry(keys);
+            for (index, (final_value, candidate_value)) in indexes.into_iter().zip(values) {
+                outputs[index] = Some(DatastoreEntryOutput {
+                    final_value,
+                    candidate_value,
+                    range_entries: None,
+                });
+            }
+        }
+
+        if !range_inputs.is_empty() {
+            let requests = range_inputs
+                .iter()
+                .flat_map(|(_, addr, range)| {
+                    [
+                        ExecutionQueryRequestItem::AddressDatastoreRangeFinal {
+                            addr: *addr,
+                            range: range.clone(),
+                            with_values: true,
+                        },
+                        ExecutionQueryRequestItem::AddressDatastoreRangeCandidate {
+                            addr: *addr,
+                            range: range.clone(),
+                            with_values: true,
+                        },
+                    ]
+                })
+                .collect();
+            let mut responses = self
+                .0
+                .execution_controller
+                .query_state(ExecutionQueryRequest { requests })
+                .responses
+                .into_iter();
+
+            for (index, _, range) in range_inputs {
+                // each side returns the first entries of the range: merge them and select again
+                let mut merged: BTreeMap<Vec<u8>, (Option<Vec<u8>>, Option<Vec<u8>>)> =
+                    BTreeMap::new();
+                for is_final in [true, false] {
+                    match responses.next() {
+                        Some(Ok(ExecutionQueryResponseItem::DatastoreEntries(found))) => {
+                            for (key, value) in found {
+                                let entry = merged.entry(key).or_default();
+                                if is_final {
+                                    entry.0 = value;
+                                } else {
+                                    entry.1 = value;
+                                }
+                            }
+                        }
+                        // the address does not exist on this side
+                        Some(Err(ExecutionQueryError::NotFound(_))) => {}
+                        Some(Err(err)) => {
+                            return Err(ApiError::InternalServerError(err.to_string()).into())
+                        }
+                        _ => {
+                            return Err(ApiError::InternalServerError(
+                                "unexpected datastore range query response".to_string(),
+                            )
+                            .into())
+                        }
+                    }
+                }
+                outputs[index] = Some(DatastoreEntryOutput {
+                    final_value: None,
+                    candidate_value: None,
+                    range_entries: Some(
+                        range
+                            .select(merged)
+                            .into_iter()
+                            .map(
+                                |(key, (final_value, candidate_value))| DatastoreRangeEntry {
+                                    key,
+                                    final_value,
+                                    candidate_value,
+                                },
+                            )
+                            .collect(),
+                    ),
+                });
+            }
+        }
+
+        Ok(outputs.into_iter().flatten().collect())
     }
 
     /// get addresses
@@ -1002,6 +1313,87 @@ impl MassaRpcServer for API<Public> {
         Ok(res?)
     }
 
+    async fn get_address_operations(
+        &self,
+        arg: AddressOperationsQuery,
+    ) -> RpcResult<AddressOperationsPage> {
+        let max_arguments = self.0.api_settings.max_arguments;
+        let limit = arg.limit.unwrap_or(max_arguments);
+        if limit > max_arguments {
+            return Err(ApiError::BadRequest(format!(
+                "too many operations requested. Only a maximum of {} operations are returned per request",
+                max_arguments
+            ))
+            .into());
+        }
+
+        let operations = self
+            .0
+            .execution_controller
+            .get_address_operations(&arg.address, arg.cursor, limit as usize)
+            .map_err(|e| ApiError::ExecutionError(e.to_string()))?;
+        let next_cursor = if limit > 0 && operations.len() as u64 >= limit {
+            operations.last().map(|operation| operation.operation_id)
+        } else {
+            None
+        };
+
+        Ok(AddressOperationsPage {
+            operations,
+            next_cursor,
+        })
+    }
+
+    async fn get_async_pool_messages(
+        &self,
+        arg: AsyncPoolMessagesQuery,
+    ) -> RpcResult<AsyncPoolMessagesPage> {
+        let max_arguments = self.0.api_settings.max_arguments;
+        let limit = arg.limit.unwrap_or(max_arguments);
+        if limit > max_arguments {
+            return Err(ApiError::BadRequest(format!(
+                "too many messages requested. Only a maximum of {} messages are returned per request",
+                max_arguments
+            ))
+            .into());
+        }
+
+        let thread_count = self.0.api_settings.thread_count;
+        let parse_id = |id: &str| {
+            async_message_id_from_str(id, thread_count).map_err(|e| {
+                ApiError::BadRequest(format!("invalid asynchronous message id {}: {}", id, e))
+            })
+        };
+        let filter = AsyncPoolMessageFilter {
+            sender: arg.sender,
+            destination: arg.destination,
+            message_id: arg.message_id.as_deref().map(parse_id).transpose()?,
+        };
+        let cursor = arg.cursor.as_deref().map(parse_id).transpose()?;
+
+        let messages: Vec<AsyncPoolMessageInfo> = self
+            .0
+            .execution_controller
+            .get_async_pool_messages(filter, cursor, limit as usize)
+            .into_iter()
+            .map(|message| AsyncPoolMessageInfo {
+                id: async_message_id_to_string(&message.id),
+                final_message: message.final_message,
+                candidate_message: message.candidate_message,
+            })
+            .collect();
+        let next_cursor = if limit > 0 && messages.len() as u64 >= limit {
+            messages.last().map(|message| message.id.clone())
+        } else {
+            None
+        };
+
+        Ok(AsyncPoolMessagesPage {
+            messages,
+            next_cursor,
+        })
+    }
+
     /// send operations
     async fn send_operations(&self, ops: Vec<OperationInput>) -> RpcResult<Vec<OperationId>> {
         let mut cmd_sender = self.0.pool_command_sender.clone();
@@ -1048,12 +1440,61 @@ impl MassaRpcServer for API<Public> {
         Ok(ids)
     }
 
+    /// dry-run operations without sending them
+    async fn check_operations(&self, ops: Vec<OperationInput>) -> RpcResult<Vec<OperationCheck>> {
+        let api_cfg = &self.0.api_settings;
+
+        if ops.len() as u64 > api_cfg.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+
+        // the operations that cannot be deserialized or whose signature is invalid are not executed
+        let mut checks = Vec::with_capacity(ops.len());
+        let mut to_execute = Vec::new();
+        for op_input in ops {
+            let (id, failure) = match deserialize_input_operation(op_input, api_cfg) {
+                Ok(operation) => match operation.verify_signature() {
+                    Ok(()) => {
+                        let id = operation.id;
+                        to_execute.push((checks.len(), operation));
+                        (Some(id), None)
+                    }
+                    Err(e) => (
+                        Some(operation.id),
+                        Some(OperationCheckFailure::InvalidSignature(e.to_string())),
+                    ),
+                },
+                Err(e) => (
+                    None,
+                    Some(OperationCheckFailure::InvalidFormat(e.to_string())),
+                ),
+            };
+            checks.push(OperationCheck { id, failure });
+        }
+        if to_execute.is_empty() {
+            return Ok(checks);
+        }
+
+        let (indexes, operations): (Vec<usize>, Vec<SecureShareOperation>) =
+            to_execute.into_iter().unzip();
+        let verdicts = self
+            .0
+            .execution_controller
+            .check_operations(operations)
+            .map_err(|e| ApiError::ExecutionError(e.to_string()))?;
+        for (index, verdict) in indexes.into_iter().zip(verdicts) {
+            checks[index].failure = verdict.err();
+        }
+        Ok(checks)
+    }
+
     /// Get events optionally filtered by:
     /// * start slot
     /// * end slot
     /// * emitter address
     /// * original caller address
     /// * operation id
+    /// * cursor and limit
     async fn get_filtered_sc_output_event(
         &self,
         filter: EventFilter,
@@ -1067,6 +1508,23 @@ impl MassaRpcServer for API<Public> {
         Ok(events)
     }
 
+    /// Get a page of events, along with the cursor of the next page
+    async fn get_filtered_sc_output_event_page(
+        &self,
+        filter: EventFilter,
+    ) -> RpcResult<SCOutputEventsPage> {
+        let events = self
+            .0
+            .execution_controller
+            .get_filtered_sc_output_event(filter.clone());
+        let next_cursor = filter.get_next_cursor(&events);
+
+        Ok(SCOutputEventsPage {
+            events,
+            next_cursor,
+        })
+    }
+
     async fn node_peers_whitelist(&self) -> RpcResult<Vec<IpAddr>> {
         crate::wrong_api::<Vec<IpAddr>>()
     }
@@ -1153,25 +1611,7 @@ fn check_input_operation(
     api_cfg: &APIConfig,
     last_slot: Option<Slot>,
 ) -> RpcResult<SecureShareOperation> {
-    let operation_deserializer = SecureShareDeserializer::new(
-        OperationDeserializer::new(
-            api_cfg.max_datastore_value_length,
-            api_cfg.max_function_name_length,
-            api_cfg.max_parameter_size,
-            api_cfg.max_op_datastore_entry_count,
-            api_cfg.max_op_datastore_key_length,
-            api_cfg.max_op_datastore_value_length,
-        ),
-        api_cfg.chain_id,
-    );
-
-    let mut op_serialized = Vec::new();
-    op_serialized.extend(op_input.signature.to_bytes());
-    op_serialized.extend(op_input.creator_public_key.to_bytes());
-    op_serialized.extend(op_input.serialized_content);
-    let (rest, op): (&[u8], SecureShareOperation) = operation_deserializer
-        .deserialize::<DeserializeError>(&op_serialized)
-        .map_err(|err| ApiError::ModelsError(ModelsError::DeserializeError(err.to_string())))?;
+    let op = deserialize_input_operation(op_input, api_cfg).map_err(ApiError::ModelsError)?;
     match op.content.op {
         OperationType::CallSC { .. } => {
             let gas_usage =
@@ -1202,12 +1642,43 @@ fn check_input_operation(
             );
         }
     }
+    Ok(op)
+}
+
+/// Deserializes an input operation, without checking its signature.
+///
+/// # Arguments
+///
+/// * `op_input` - The input operation to be deserialized.
+/// * `api_cfg` - The API configuration providing the deserialization limits.
+fn deserialize_input_operation(
+    op_input: OperationInput,
+    api_cfg: &APIConfig,
+) -> Result<SecureShareOperation, ModelsError> {
+    let operation_deserializer = SecureShareDeserializer::new(
+        OperationDeserializer::new(
+            api_cfg.max_datastore_value_length,
+            api_cfg.max_function_name_length,
+            api_cfg.max_parameter_size,
+            api_cfg.max_op_datastore_entry_count,
+            api_cfg.max_op_datastore_key_length,
+            api_cfg.max_op_datastore_value_length,
+        ),
+        api_cfg.chain_id,
+    );
+
+    let mut op_serialized = Vec::new();
+    op_serialized.extend(op_input.signature.to_bytes());
+    op_serialized.extend(op_input.creator_public_key.to_bytes());
+    op_serialized.extend(op_input.serialized_content);
+    let (rest, op): (&[u8], SecureShareOperation) = operation_deserializer
+        .deserialize::<DeserializeError>(&op_serialized)
+        .map_err(|err| ModelsError::DeserializeError(err.to_string()))?;
     if rest.is_empty() {
         Ok(op)
     } else {
-        Err(ApiError::ModelsError(ModelsError::DeserializeError(
+        Err(ModelsError::DeserializeError(
             "There is data left after operation deserialization".to_owned(),
         ))
-        .into())
     }
 }
@@ -8,15 +8,20 @@ use jsonrpsee::{
     ws_client::WsClientBuilder,
 };
 use massa_consensus_exports::MockConsensusController;
-use massa_execution_exports::MockExecutionController;
+use massa_execution_exports::{
+    EventStore, ExecutionOutput, MockExecutionController, SlotExecutionOutput,
+};
 use massa_models::{
     address::Address,
     block::{FilledBlock, SecureShareBlock},
     block_header::BlockHeader,
     block_id::BlockId,
     config::VERSION,
+    execution::EventFilter,
     operation::SecureShareOperation,
+    output_event::{EventExecutionContext, SCOutputEvent},
     secure_share::SecureShare,
+    slot::Slot,
 };
 use massa_protocol_exports::test_exports::tools::{
     create_block, create_operation_with_expire_period,
@@ -333,3 +338,79 @@ async fn subscribe_new_operations() {
 
     api_handle.stop().await;
 }
+
+#[tokio::test]
+async fn subscribe_new_sc_events() {
+    let addr: SocketAddr = "[::]:5037".parse().unwrap();
+    let (mut api_server, api_config) = get_apiv2_server(&addr);
+
+    let uri = Url::parse(&format!(
+        "ws://localhost:{}",
+        addr.to_string().split(':').last().unwrap()
+    ))
+    .unwrap();
+    let (tx, _rx) = tokio::sync::broadcast::channel::<SlotExecutionOutput>(10);
+
+    let emitter = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+    let other = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+    let slot = Slot::new(1, 0);
+    let event = |emitter: Address, index_in_slot: u64, is_final: bool| SCOutputEvent {
+        context: EventExecutionContext {
+            slot,
+            block: None,
+            read_only: false,
+            index_in_slot,
+            call_stack: vec![emitter].into(),
+            origin_operation_id: None,
+            is_final,
+            is_error: false,
+        },
+        data: format!("event {}", index_in_slot),
+    };
+    let exec_out = |is_final: bool| ExecutionOutput {
+        slot,
+        block_info: None,
+        state_changes: Default::default(),
+        events: EventStore(vec![event(other, 0, is_final), event(emitter, 1, is_final)].into()),
+    };
+
+    api_server.0.execution_channels.slot_execution_output_sender = tx.clone();
+
+    let api_handle = api_server
+        .serve(&addr, &api_config)
+        .await
+        .expect("failed to start MASSA API V2");
+
+    let client1 = WsClientBuilder::default().build(&uri).await.unwrap();
+    let mut sub1: Subscription<Value> = client1
+        .subscribe(
+            "subscribe_new_sc_events",
+            rpc_params![EventFilter {
+                emitter_address: Some(emitter),
+                ..Default::default()
+            }],
+            "unsubscribe_new_sc_events",
+        )
+        .await
+        .unwrap();
+
+    let candidate = SlotExecutionOutput::ExecutedSlot(exec_out(false));
+    let finalized = SlotExecutionOutput::FinalizedSlot(exec_out(true));
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let _ = tx.send(candidate).unwrap();
+        let _ = tx.send(finalized).unwrap();
+    });
+
+    // only the event of the emitter is received, first as a candidate then as final
+    for is_final in [false, true] {
+        let result = tokio::time::timeout(Duration::from_secs(4), sub1.next())
+            .await
+            .unwrap();
+        let obj = result.unwrap().unwrap();
+        assert_eq!(obj["data"].as_str().unwrap(), "event 1");
+        assert_eq!(obj["context"]["is_final"].as_bool().unwrap(), is_final);
+    }
+
+    api_handle.stop().await;
+}
@@ -6,7 +6,7 @@ use std::{collections::HashMap, net::SocketAddr};
 
 use massa_api_exports::config::APIConfig;
 use massa_consensus_exports::{ConsensusBroadcasts, MockConsensusController};
-use massa_execution_exports::{GasCosts, MockExecutionController};
+use massa_execution_exports::{ExecutionChannels, GasCosts, MockExecutionController};
 use massa_models::config::CHAINID;
 use massa_models::{
     config::{
@@ -93,10 +93,15 @@ pub(crate) fn get_apiv2_server(addr: &SocketAddr) -> (API<ApiV2>, APIConfig) {
         filled_block_sender: broadcast::channel(100).0,
     };
 
+    let execution_channels = ExecutionChannels {
+        slot_execution_output_sender: broadcast::channel(100).0,
+    };
+
     let api = API::<ApiV2>::new(
         Box::new(consensus_ctrl),
         consensus_broadcasts,
         Box::new(exec_ctrl),
+        execution_channels,
         pool_broadcasts,
         api_config.clone(),
         *VERSION,
@@ -13,14 +13,20 @@ use jsonrpsee::{
     rpc_params,
 };
 use massa_api_exports::{
-    address::{AddressFilter, AddressInfo},
+    address::{AddressFilter, AddressInfo, AddressOperationsPage, AddressOperationsQuery},
     block::{BlockInfo, BlockSummary},
-    datastore::{DatastoreEntryInput, DatastoreEntryOutput},
+    datastore::{DatastoreEntryInput, DatastoreEntryOutput, DatastoreRangeInput},
     endorsement::EndorsementInfo,
-    execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall},
-    operation::{OperationInfo, OperationInput},
+    execution::{
+        AsyncPoolMessagesPage, AsyncPoolMessagesQuery, ExecuteReadOnlyMulticallResponse,
+        ExecuteReadOnlyResponse, OperationEstimation, OperationEstimationInput,
+        ReadOnlyBytecodeExecution, ReadOnlyCall, ReadOnlyMulticall, ReadOnlyMulticallItem,
+        ReadOnlyResult, SCOutputEventsPage,
+    },
+    operation::{OperationCheck, OperationInfo, OperationInput},
     TimeInterval,
 };
+use massa_async_pool::{async_message_id_to_string, AsyncMessage};
 use massa_consensus_exports::{
     block_graph_export::BlockGraphExport, block_status::ExportCompiledBlock,
     MockConsensusController,
@@ -30,8 +36,9 @@ use massa_pos_exports::MockSelectorController;
 
 use crate::{tests::mock::start_public_api, RpcServer};
 use massa_execution_exports::{
-    ExecutionAddressInfo, ExecutionQueryResponse, ExecutionQueryResponseItem,
-    MockExecutionController, ReadOnlyExecutionOutput,
+    AsyncPoolMessage, ExecutionAddressInfo, ExecutionError, ExecutionQueryResponse,
+    ExecutionQueryResponseItem, MockExecutionController, OperationCostEstimation,
+    ReadOnlyExecutionOutput, ReadOnlyMulticallCallOutput, ReadOnlyMulticallOutput,
 };
 use massa_models::{
     address::Address,
@@ -40,11 +47,15 @@ use massa_models::{
     bytecode::Bytecode,
     clique::Clique,
     endorsement::EndorsementId,
-    execution::EventFilter,
+    execution::{
+        AddressOperation, AddressOperationRole, EventCursor, EventFilter, OperationCheckFailure,
+        StateOverride,
+    },
     node::NodeId,
-    operation::OperationId,
+    operation::{OperationId, OperationType},
     output_event::SCOutputEvent,
     prehash::{CapacityAllocator, PreHashMap},
+    secure_share::Id,
     slot::Slot,
     stats::{ConsensusStats, ExecutionStats, NetworkStats},
 };
@@ -172,6 +183,9 @@ async fn get_operations() {
     let (mut api_public, config) = start_public_api(addr);
     let keypair = KeyPair::generate(0).unwrap();
     let op = create_operation_with_expire_period(&keypair, 500000);
+    // operation pruned from storage, only available in the archive
+    let archived_op = create_operation_with_expire_period(&keypair, 400000);
+    let archived_block_id = create_block(&keypair).id;
 
     api_public.0.storage.store_operations(vec![op.clone()]);
 
@@ -185,8 +199,19 @@ async fn get_operations() {
         .expect_get_ops_exec_status()
         .returning(|op| op.iter().map(|_op| (Some(true), Some(true))).collect());
 
+    let mut consensus_ctrl = MockConsensusController::new();
+    let archived = archived_op.clone();
+    consensus_ctrl
+        .expect_get_archived_operations()
+        .returning(move |ids| {
+            ids.iter()
+                .map(|id| (id == &archived.id).then(|| (archived.clone(), archived_block_id)))
+                .collect()
+        });
+
     api_public.0.execution_controller = Box::new(exec_ctrl);
     api_public.0.pool_command_sender = Box::new(pool_ctrl);
+    api_public.0.consensus_controller = Box::new(consensus_ctrl);
 
     let api_public_handle = api_public
         .serve(&addr, &config)
@@ -201,11 +226,16 @@ async fn get_operations() {
         .unwrap();
     let params = rpc_params![vec![
         OperationId::from_str("O1q4CBcuYo8YANEV34W4JRWVHrzcYns19VJfyAB7jT4qfitAnMC").unwrap(),
-        op.id
+        op.id,
+        archived_op.id
     ]];
     let response: Vec<OperationInfo> = client.request("get_operations", params).await.unwrap();
 
-    assert_eq!(response.len(), 1);
+    assert_eq!(response.len(), 2);
+    assert_eq!(response[0].id, op.id);
+    assert_eq!(response[1].id, archived_op.id);
+    assert_eq!(response[1].in_blocks, vec![archived_block_id]);
+    assert_eq!(response[1].is_operation_final, Some(true));
 
     api_public_handle.stop().await;
 }
@@ -227,6 +257,9 @@ async fn get_endorsements() {
     consensus_ctrl
         .expect_get_block_statuses()
         .returning(|param| param.iter().map(|_| BlockGraphStatus::Final).collect());
+    consensus_ctrl
+        .expect_get_archived_endorsements()
+        .returning(|ids| vec![None; ids.len()]);
 
     api_public.0.consensus_controller = Box::new(consensus_ctrl);
     api_public.0.pool_command_sender = Box::new(pool_ctrl);
@@ -275,6 +308,9 @@ async fn get_blocks() {
     let (mut api_public, config) = start_public_api(addr);
     let keypair = KeyPair::generate(0).unwrap();
     let block = create_block(&keypair);
+    // a final block pruned from storage, only available in the archive
+    let archived_block = create_block(&KeyPair::generate(0).unwrap());
+    let archived_id = archived_block.id;
 
     api_public.0.storage.store_block(block.clone());
 
@@ -282,6 +318,13 @@ async fn get_blocks() {
     consensus_ctrl
         .expect_get_block_statuses()
         .returning(|param| param.iter().map(|_| BlockGraphStatus::Final).collect());
+    consensus_ctrl
+        .expect_get_archived_blocks()
+        .returning(move |ids| {
+            ids.iter()
+                .map(|id| (id == &archived_id).then(|| archived_block.clone()))
+                .collect()
+        });
 
     api_public.0.consensus_controller = Box::new(consensus_ctrl);
 
@@ -309,6 +352,16 @@ async fn get_blocks() {
 
     assert_eq!(response[0].id, block.id);
 
+    // the blocks are returned in request order, wherever they come from
+    let response: Vec<BlockInfo> = client
+        .request("get_blocks", rpc_params![vec![archived_id, block.id]])
+        .await
+        .unwrap();
+    assert_eq!(
+        response.iter().map(|info| info.id).collect::<Vec<_>>(),
+        vec![archived_id, block.id]
+    );
+
     api_public_handle.stop().await;
 }
 
@@ -610,6 +663,30 @@ async fn get_filtered_sc_output_event() {
         .await;
 
     assert_eq!(response.unwrap().len(), 1);
+
+    // the page carries the cursor of its last event, even when it is not full
+    let response: SCOutputEventsPage = client
+        .request(
+            "get_filtered_sc_output_event_page",
+            rpc_params![EventFilter {
+                limit: Some(2),
+                ..Default::default()
+            }],
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.events.len(), 1);
+    assert_eq!(
+        response.next_cursor,
+        Some(EventCursor {
+            slot: Slot {
+                period: 1,
+                thread: 10
+            },
+            index_in_slot: 1
+        })
+    );
+
     api_public_handle.stop().await;
 }
 
@@ -633,7 +710,9 @@ async fn execute_read_only_bytecode() {
                     events: massa_execution_exports::EventStore::default(),
                 },
                 gas_cost: 100,
+                storage_cost: Amount::zero(),
                 call_result: "toto".as_bytes().to_vec(),
+                trace: None,
             })
         });
 
@@ -657,7 +736,9 @@ async fn execute_read_only_bytecode() {
             Address::from_str("AU12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x").unwrap()
         ),
         operation_datastore: None,
-        fee: None
+        fee: None,
+        state_overrides: None,
+        trace: None,
     }]];
     let response: Result<Vec<ExecuteReadOnlyResponse>, Error> = client
         .request("execute_read_only_bytecode", params.clone())
@@ -671,6 +752,8 @@ async fn execute_read_only_bytecode() {
         address: None,
         operation_datastore: None,
         fee: None,
+        state_overrides: None,
+        trace: None,
     }]];
     let response: Result<Vec<ExecuteReadOnlyResponse>, Error> = client
         .request("execute_read_only_bytecode", params.clone())
@@ -683,7 +766,9 @@ async fn execute_read_only_bytecode() {
         bytecode: "hi".as_bytes().to_vec(),
         address: None,
         operation_datastore: Some("hi".as_bytes().to_vec()),
-        fee: None
+        fee: None,
+        state_overrides: None,
+        trace: None,
     }]];
     let response: Result<Vec<ExecuteReadOnlyResponse>, Error> = client
         .request("execute_read_only_bytecode", params.clone())
@@ -713,7 +798,9 @@ async fn execute_read_only_call() {
                     events: massa_execution_exports::EventStore::default(),
                 },
                 gas_cost: 100,
+                storage_cost: Amount::zero(),
                 call_result: "toto".as_bytes().to_vec(),
+                trace: None,
             })
         });
 
@@ -746,6 +833,37 @@ async fn execute_read_only_call() {
         caller_address: None,
         fee: None,
         coins: None,
+        state_overrides: None,
+        trace: None,
+    }]];
+    let response: Vec<ExecuteReadOnlyResponse> = client
+        .request("execute_read_only_call", params.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(response.len(), 1);
+
+    // with state overrides
+    let target_address =
+        Address::from_str("AU12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x").unwrap();
+    let mut state_overrides = PreHashMap::default();
+    state_overrides.insert(
+        target_address,
+        StateOverride {
+            balance: Some(Amount::from_str("1000").unwrap()),
+            ..Default::default()
+        },
+    );
+    let params = rpc_params![vec![ReadOnlyCall {
+        max_gas: 1000000,
+        target_address,
+        target_function: "hello".to_string(),
+        parameter: vec![],
+        caller_address: None,
+        fee: None,
+        coins: None,
+        state_overrides: Some(state_overrides),
+        trace: None,
     }]];
     let response: Vec<ExecuteReadOnlyResponse> = client
         .request("execute_read_only_call", params.clone())
@@ -756,6 +874,238 @@ async fn execute_read_only_call() {
     api_public_handle.stop().await;
 }
 
+#[tokio::test]
+async fn execute_read_only_multicall() {
+    let addr: SocketAddr = "[::]:5044".parse().unwrap();
+    let (mut api_public, config) = start_public_api(addr);
+
+    let mut exec_ctrl = MockExecutionController::new();
+    exec_ctrl
+        .expect_execute_readonly_multicall()
+        .returning(|req| {
+            // the second call fails
+            Ok(ReadOnlyMulticallOutput {
+                slot: Slot {
+                    period: 1,
+                    thread: 5,
+                },
+                calls: req
+                    .calls
+                    .iter()
+                    .take(2)
+                    .enumerate()
+                    .map(|(index, _)| {
+                        if index == 0 {
+                            Ok(ReadOnlyMulticallCallOutput {
+                                events: massa_execution_exports::EventStore::default(),
+                                gas_cost: 100,
+                                call_result: "toto".as_bytes().to_vec(),
+                                trace: None,
+                            })
+                        } else {
+                            Err(ExecutionError::RuntimeError("titi".to_string()))
+                        }
+                    })
+                    .collect(),
+                state_changes: massa_final_state::StateChanges::default(),
+            })
+        });
+
+    api_public.0.execution_controller = Box::new(exec_ctrl);
+
+    let api_public_handle = api_public
+        .serve(&addr, &config)
+        .await
+        .expect("failed to start PUBLIC API");
+
+    let client = HttpClientBuilder::default()
+        .build(format!(
+            "http://localhost:{}",
+            addr.to_string().split(':').last().unwrap()
+        ))
+        .unwrap();
+
+    let address =
+        Address::from_str("AU12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x").unwrap();
+    let params = rpc_params![ReadOnlyMulticall {
+        calls: vec![
+            ReadOnlyMulticallItem::Bytecode(ReadOnlyBytecodeExecution {
+                max_gas: 100000,
+                bytecode: "hi".as_bytes().to_vec(),
+                address: Some(address),
+                operation_datastore: None,
+                fee: None,
+                state_overrides: None,
+                trace: None,
+            }),
+            ReadOnlyMulticallItem::Call(ReadOnlyCall {
+                max_gas: 1000000,
+                target_address: address,
+                target_function: "hello".to_string(),
+                parameter: vec![],
+                caller_address: Some(address),
+                fee: None,
+                coins: None,
+                state_overrides: None,
+                trace: None,
+            }),
+            ReadOnlyMulticallItem::Call(ReadOnlyCall {
+                max_gas: 1000000,
+                target_address: address,
+                target_function: "hello".to_string(),
+                parameter: vec![],
+                caller_address: Some(address),
+                fee: None,
+                coins: None,
+                state_overrides: None,
+                trace: None,
+            }),
+        ],
+    }];
+    let response: ExecuteReadOnlyMulticallResponse = client
+        .request("execute_read_only_multicall", params.clone())
+        .await
+        .unwrap();
+
+    // the sequence stops at the failed call
+    assert_eq!(response.calls.len(), 2);
+    assert_eq!(response.calls[0].gas_cost, 100);
+    assert!(matches!(response.calls[1].result, ReadOnlyResult::Error(_)));
+
+    api_public_handle.stop().await;
+}
+
+#[tokio::test]
+async fn estimate_operation() {
+    let addr: SocketAddr = "[::]:5045".parse().unwrap();
+    let (mut api_public, config) = start_public_api(addr);
+
+    let mut exec_ctrl = MockExecutionController::new();
+    exec_ctrl
+        .expect_estimate_operation()
+        .returning(|_sender_address, operation| {
+            assert!(matches!(operation, OperationType::CallSC { .. }));
+            Ok(OperationCostEstimation {
+                gas_used: 1000,
+                max_gas: 1200,
+                storage_cost: Amount::from_str("0.01").unwrap(),
+            })
+        });
+    let mut pool_ctrl = MockPoolController::new();
+    pool_ctrl
+        .expect_get_fee_suggestion()
+        .returning(|_thread| Amount::from_str("0.5").unwrap());
+
+    api_public.0.execution_controller = Box::new(exec_ctrl);
+    api_public.0.pool_command_sender = Box::new(pool_ctrl);
+
+    let api_public_handle = api_public
+        .serve(&addr, &config)
+        .await
+        .expect("failed to start PUBLIC API");
+
+    let client = HttpClientBuilder::default()
+        .build(format!(
+            "http://localhost:{}",
+            addr.to_string().split(':').last().unwrap()
+        ))
+        .unwrap();
+
+    let address =
+        Address::from_str("AU12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x").unwrap();
+    let params = rpc_params![OperationEstimationInput {
+        sender_address: address,
+        op: OperationType::CallSC {
+            target_addr: address,
+            target_func: "hello".to_string(),
+            param: vec![],
+            max_gas: 1000000,
+            coins: Amount::zero(),
+        },
+    }];
+    let response: OperationEstimation = client.request("estimate_operation", params).await.unwrap();
+
+    assert_eq!(response.gas_used, 1000);
+    assert_eq!(response.max_gas, 1200);
+    assert_eq!(response.storage_cost, Amount::from_str("0.01").unwrap());
+    assert_eq!(response.fee, Amount::from_str("0.5").unwrap());
+
+    api_public_handle.stop().await;
+}
+
+#[tokio::test]
+async fn check_operations() {
+    let addr: SocketAddr = "[::]:5046".parse().unwrap();
+    let (mut api_public, config) = start_public_api(addr);
+
+    let mut exec_ctrl = MockExecutionController::new();
+    exec_ctrl.expect_check_operations().returning(|operations| {
+        // only the well-formed and correctly signed operation is executed
+        assert_eq!(operations.len(), 1);
+        Ok(vec![Err(OperationCheckFailure::InsufficientBalance(
+            "not enough coins".to_string(),
+        ))])
+    });
+    api_public.0.execution_controller = Box::new(exec_ctrl);
+
+    let api_public_handle = api_public
+        .serve(&addr, &config)
+        .await
+        .expect("failed to start PUBLIC API");
+
+    let client = HttpClientBuilder::default()
+        .build(format!(
+            "http://localhost:{}",
+            addr.to_string().split(':').last().unwrap()
+        ))
+        .unwrap();
+    let keypair = KeyPair::generate(0).unwrap();
+    let operation = create_operation_with_expire_period(&keypair, 500000);
+    let other_operation = create_operation_with_expire_period(&keypair, 500001);
+
+    let inputs = vec![
+        OperationInput {
+            creator_public_key: keypair.get_public_key(),
+            signature: operation.signature,
+            serialized_content: operation.serialized_data.clone(),
+        },
+        // signature of another operation
+        OperationInput {
+            creator_public_key: keypair.get_public_key(),
+            signature: other_operation.signature,
+            serialized_content: operation.serialized_data.clone(),
+        },
+        // truncated content
+        OperationInput {
+            creator_public_key: keypair.get_public_key(),
+            signature: operation.signature,
+            serialized_content: operation.serialized_data[..4].to_vec(),
+        },
+    ];
+    let response: Vec<OperationCheck> = client
+        .request("check_operations", rpc_params![inputs])
+        .await
+        .unwrap();
+
+    assert_eq!(response.len(), 3);
+    assert_eq!(response[0].id, Some(operation.id));
+    assert!(matches!(
+        response[0].failure,
+        Some(OperationCheckFailure::InsufficientBalance(_))
+    ));
+    assert!(matches!(
+        response[1].failure,
+        Some(OperationCheckFailure::InvalidSignature(_))
+    ));
+    assert_eq!(response[2].id, None);
+    assert!(matches!(
+        response[2].failure,
+        Some(OperationCheckFailure::InvalidFormat(_))
+    ));
+
+    api_public_handle.stop().await;
+}
+
 #[tokio::test]
 async fn get_addresses() {
     let addr: SocketAddr = "[::]:5010".parse().unwrap();
@@ -868,6 +1218,219 @@ async fn get_addresses_bytecode() {
     api_public_handle.stop().await;
 }
 
+#[tokio::test]
+async fn get_address_operations() {
+    let addr: SocketAddr = "[::]:5043".parse().unwrap();
+    let (mut api_public, config) = start_public_api(addr);
+
+    let mut exec_ctrl = MockExecutionController::new();
+    exec_ctrl
+        .expect_get_address_operations()
+        .returning(|_address, _cursor, limit| {
+            Ok((0..limit.min(3) as u64)
+                .map(|period| AddressOperation {
+                    operation_id: OperationId::new(massa_hash::Hash::compute_from(
+                        &period.to_be_bytes(),
+                    )),
+                    slot: Slot::new(period, 0),
+                    block_id: massa_models::block_id::BlockId::generate_from_hash(
+                        massa_hash::Hash::compute_from(&period.to_be_bytes()),
+                    ),
+                    role: AddressOperationRole::Sender,
+                    success: true,
+                })
+                .collect())
+        });
+
+    api_public.0.execution_controller = Box::new(exec_ctrl);
+
+    let api_public_handle = api_public
+        .serve(&addr, &config)
+        .await
+        .expect("failed to start PUBLIC API");
+
+    let client = HttpClientBuilder::default()
+        .build(format!(
+            "http://localhost:{}",
+            addr.to_string().split(':').last().unwrap()
+        ))
+        .unwrap();
+
+    let address =
+        Address::from_str("AU12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x").unwrap();
+
+    // a full page carries the cursor of its last operation
+    let response: AddressOperationsPage = client
+        .request(
+            "get_address_operations",
+            rpc_params![AddressOperationsQuery {
+                address,
+                cursor: None,
+                limit: Some(2),
+            }],
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.operations.len(), 2);
+    assert_eq!(
+        response.next_cursor,
+        Some(response.operations[1].operation_id)
+    );
+
+    // a partial page is the last one
+    let response: AddressOperationsPage = client
+        .request(
+            "get_address_operations",
+            rpc_params![AddressOperationsQuery {
+                address,
+                cursor: response.next_cursor,
+                limit: None,
+            }],
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.operations.len(), 3);
+    assert!(response.next_cursor.is_none());
+
+    // the page size is bounded
+    let response: Result<AddressOperationsPage, Error> = client
+        .request(
+            "get_address_operations",
+            rpc_params![AddressOperationsQuery {
+                address,
+                cursor: None,
+                limit: Some(config.max_arguments + 1),
+            }],
+        )
+        .await;
+    assert!(response.is_err());
+
+    api_public_handle.stop().await;
+}
+
+#[tokio::test]
+async fn get_async_pool_messages() {
+    let addr: SocketAddr = "[::]:5047".parse().unwrap();
+    let (mut api_public, config) = start_public_api(addr);
+
+    let sender =
+        Address::from_str("AU12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x").unwrap();
+    let message = AsyncMessage::new(
+        Slot::new(1, 0),
+        0,
+        sender,
+        Address::from_str("AU12htxRWiEm8jDJpJptr6cwEhWNcCSFWstN1MLSa96DDkVM9Y42G").unwrap(),
+        String::from("receive"),
+        100_000,
+        Amount::from_raw(1),
+        Amount::zero(),
+        Slot::new(1, 0),
+        Slot::new(10, 0),
+        Vec::new(),
+        None,
+        None,
+    );
+    let message_id = message.compute_id();
+
+    let mut exec_ctrl = MockExecutionController::new();
+    exec_ctrl
+        .expect_get_async_pool_messages()
+        .withf(move |filter, cursor, limit| {
+            filter.sender == Some(sender)
+                && filter.message_id.is_none()
+                && cursor.is_none()
+                && *limit == 1
+        })
+        .returning(move |_filter, _cursor, _limit| {
+            vec![AsyncPoolMessage {
+                id: message_id,
+                final_message: None,
+                candidate_message: Some(message.clone()),
+            }]
+        });
+    exec_ctrl
+        .expect_get_async_pool_messages()
+        .withf(move |filter, cursor, _limit| {
+            filter.message_id == Some(message_id) && *cursor == Some(message_id)
+        })
+        .returning(|_filter, _cursor, _limit| Vec::new());
+
+    api_public.0.execution_controller = Box::new(exec_ctrl);
+
+    let api_public_handle = api_public
+        .serve(&addr, &config)
+        .await
+        .expect("failed to start PUBLIC API");
+
+    let client = HttpClientBuilder::default()
+        .build(format!(
+            "http://localhost:{}",
+            addr.to_string().split(':').last().unwrap()
+        ))
+        .unwrap();
+
+    // a full page carries the cursor of its last message
+    let response: AsyncPoolMessagesPage = client
+        .request(
+            "get_async_pool_messages",
+            rpc_params![AsyncPoolMessagesQuery {
+                sender: Some(sender),
+                limit: Some(1),
+                ..Default::default()
+            }],
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.messages.len(), 1);
+    assert!(response.messages[0].final_message.is_none());
+    assert!(response.messages[0].candidate_message.is_some());
+    assert_eq!(
+        response.next_cursor,
+        Some(async_message_id_to_string(&message_id))
+    );
+
+    // the ids are parsed back
+    let response: AsyncPoolMessagesPage = client
+        .request(
+            "get_async_pool_messages",
+            rpc_params![AsyncPoolMessagesQuery {
+                message_id: response.next_cursor.clone(),
+                cursor: response.next_cursor,
+                ..Default::default()
+            }],
+        )
+        .await
+        .unwrap();
+    assert!(response.messages.is_empty());
+    assert!(response.next_cursor.is_none());
+
+    // invalid ids are rejected
+    let response: Result<AsyncPoolMessagesPage, Error> = client
+        .request(
+            "get_async_pool_messages",
+            rpc_params![AsyncPoolMessagesQuery {
+                message_id: Some("invalid".to_string()),
+                ..Default::default()
+            }],
+        )
+        .await;
+    assert!(response.is_err());
+
+    // the page size is bounded
+    let response: Result<AsyncPoolMessagesPage, Error> = client
+        .request(
+            "get_async_pool_messages",
+            rpc_params![AsyncPoolMessagesQuery {
+                limit: Some(config.max_arguments + 1),
+                ..Default::default()
+            }],
+        )
+        .await;
+    assert!(response.is_err());
+
+    api_public_handle.stop().await;
+}
+
 #[tokio::test]
 async fn get_datastore_entries() {
     let addr: SocketAddr = "[::]:5009".parse().unwrap();
@@ -900,7 +1463,8 @@ async fn get_datastore_entries() {
     let params = rpc_params![vec![DatastoreEntryInput {
         address: Address::from_str("AU12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x")
             .unwrap(),
-        key: "massa".as_bytes().to_vec()
+        key: "massa".as_bytes().to_vec(),
+        range: None,
     }]];
     let response: Vec<DatastoreEntryOutput> = client
         .request("get_datastore_entries", params.clone())
@@ -920,6 +1484,87 @@ async fn get_datastore_entries() {
     api_public_handle.stop().await;
 }
 
+#[tokio::test]
+async fn get_datastore_range_entries() {
+    let addr: SocketAddr = "[::]:5048".parse().unwrap();
+    let (mut api_public, config) = start_public_api(addr);
+
+    let mut exec_ctrl = MockExecutionController::new();
+    exec_ctrl.expect_query_state().returning(|_| {
+        let entry =
+            |key: &str, value: &str| (key.as_bytes().to_vec(), Some(value.as_bytes().to_vec()));
+        ExecutionQueryResponse {
+            responses: vec![
+                Ok(ExecutionQueryResponseItem::DatastoreEntries(vec![
+                    entry("b", "final b"),
+                    entry("c", "final c"),
+                ])),
+                Ok(ExecutionQueryResponseItem::DatastoreEntries(vec![
+                    entry("c", "candidate c"),
+                    entry("d", "candidate d"),
+                ])),
+            ],
+            candidate_cursor: Slot::new(1, 2),
+            final_cursor: Slot::new(1, 7),
+            final_state_fingerprint: massa_hash::Hash::compute_from(&Vec::new()),
+        }
+    });
+
+    api_public.0.execution_controller = Box::new(exec_ctrl);
+
+    let api_public_handle = api_public
+        .serve(&addr, &config)
+        .await
+        .expect("failed to start PUBLIC API");
+
+    let client = HttpClientBuilder::default()
+        .build(format!(
+            "http://localhost:{}",
+            addr.to_string().split(':').last().unwrap()
+        ))
+        .unwrap();
+
+    let input = |limit: u64| DatastoreEntryInput {
+        address: Address::from_str("AU12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x")
+            .unwrap(),
+        key: Vec::new(),
+        range: Some(DatastoreRangeInput {
+            start: Some("a".as_bytes().to_vec()),
+            start_exclusive: true,
+            limit: Some(limit),
+            ..Default::default()
+        }),
+    };
+
+    let response: Vec<DatastoreEntryOutput> = client
+        .request("get_datastore_entries", rpc_params![vec![input(2)]])
+        .await
+        .unwrap();
+
+    let entries = response.get(0).unwrap().range_entries.as_ref().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].key, "b".as_bytes().to_vec());
+    assert_eq!(entries[0].final_value, Some("final b".as_bytes().to_vec()));
+    assert_eq!(entries[0].candidate_value, None);
+    assert_eq!(entries[1].key, "c".as_bytes().to_vec());
+    assert_eq!(entries[1].final_value, Some("final c".as_bytes().to_vec()));
+    assert_eq!(
+        entries[1].candidate_value,
+        Some("candidate c".as_bytes().to_vec())
+    );
+
+    // a limit above max_arguments is rejected
+    let response: Result<Vec<DatastoreEntryOutput>, Error> = client
+        .request(
+            "get_datastore_entries",
+            rpc_params![vec![input(config.max_arguments + 1)]],
+        )
+        .await;
+    assert!(response.is_err());
+
+    api_public_handle.stop().await;
+}
+
 #[tokio::test]
 async fn wrong_api() {
     let addr: SocketAddr = "[::]:5004".parse().unwrap();
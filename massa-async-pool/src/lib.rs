@@ -94,11 +94,16 @@ mod pool;
 pub use changes::{AsyncPoolChanges, AsyncPoolChangesDeserializer, AsyncPoolChangesSerializer};
 pub use config::AsyncPoolConfig;
 pub use message::{
-    AsyncMessage, AsyncMessageDeserializer, AsyncMessageId, AsyncMessageIdDeserializer,
-    AsyncMessageIdSerializer, AsyncMessageInfo, AsyncMessageSerializer, AsyncMessageTrigger,
-    AsyncMessageTriggerSerializer, AsyncMessageUpdate,
+    async_message_id_from_str, async_message_id_to_string, AsyncMessage, AsyncMessageDeserializer,
+    AsyncMessageId, AsyncMessageIdDeserializer, AsyncMessageIdSerializer, AsyncMessageInfo,
+    AsyncMessageSerializer, AsyncMessageTrigger, AsyncMessageTriggerSerializer, AsyncMessageUpdate,
+};
+pub use pool::{
+    AsyncPool, AsyncPoolDeserializer, AsyncPoolSerializer, CAN_BE_EXECUTED_IDENT, COINS_IDENT,
+    DESTINATION_IDENT, EMISSION_INDEX_IDENT, EMISSION_SLOT_IDENT, FEE_IDENT, FUNCTION_IDENT,
+    FUNCTION_PARAMS_IDENT, MAX_GAS_IDENT, SENDER_IDENT, TRIGGER_IDENT, VALIDITY_END_IDENT,
+    VALIDITY_START_IDENT,
 };
-pub use pool::{AsyncPool, AsyncPoolDeserializer, AsyncPoolSerializer};
 
 #[cfg(test)]
 mod tests;
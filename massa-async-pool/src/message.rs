@@ -6,6 +6,7 @@ use massa_ledger_exports::{Applicable, SetOrKeep, SetOrKeepDeserializer, SetOrKe
 use massa_models::address::{AddressDeserializer, AddressSerializer};
 use massa_models::amount::{AmountDeserializer, AmountSerializer};
 use massa_models::config::GENESIS_KEY;
+use massa_models::error::ModelsError;
 use massa_models::serialization::{StringDeserializer, StringSerializer};
 use massa_models::slot::{SlotDeserializer, SlotSerializer};
 use massa_models::{
@@ -15,9 +16,9 @@ use massa_models::{
     slot::Slot,
 };
 use massa_serialization::{
-    BoolDeserializer, BoolSerializer, Deserializer, OptionDeserializer, OptionSerializer,
-    SerializeError, Serializer, U16VarIntDeserializer, U16VarIntSerializer, U64VarIntDeserializer,
-    U64VarIntSerializer,
+    BoolDeserializer, BoolSerializer, DeserializeError, Deserializer, OptionDeserializer,
+    OptionSerializer, SerializeError, Serializer, U16VarIntDeserializer, U16VarIntSerializer,
+    U64VarIntDeserializer, U64VarIntSerializer,
 };
 use nom::error::{context, ContextError, ParseError};
 use nom::sequence::tuple;
@@ -181,6 +182,66 @@ impl Deserializer<AsyncMessageId> for AsyncMessageIdDeserializer {
     }
 }
 
+/// Encode an asynchronous message id as a base58check string, to be exposed through the APIs
+pub fn async_message_id_to_string(id: &AsyncMessageId) -> String {
+    let mut buffer = Vec::new();
+    AsyncMessageIdSerializer::new()
+        .serialize(id, &mut buffer)
+        .expect("critical: asynchronous message id serialization failed");
+    bs58::encode(buffer).with_check().into_string()
+}
+
+/// Decode an asynchronous message id encoded with `async_message_id_to_string`
+///
+/// # Arguments
+/// * `id`: the encoded message id
+/// * `thread_count`: number of threads, used to check the emission slot of the message
+///
+/// ## Example
+/// ```
+/// use massa_models::{address::Address, amount::Amount, slot::Slot};
+/// use std::str::FromStr;
+/// use massa_async_pool::{AsyncMessage, async_message_id_from_str, async_message_id_to_string};
+///
+/// let message = AsyncMessage::new(
+///     Slot::new(1, 0),
+///     0,
+///     Address::from_str("AU12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x").unwrap(),
+///     Address::from_str("AU12htxRWiEm8jDJpJptr6cwEhWNcCSFWstN1MLSa96DDkVM9Y42G").unwrap(),
+///     String::from("test"),
+///     10000000,
+///     Amount::from_str("1").unwrap(),
+///     Amount::from_str("1").unwrap(),
+///     Slot::new(2, 0),
+///     Slot::new(3, 0),
+///     vec![1, 2, 3, 4],
+///     None,
+///     None
+/// );
+/// let id = message.compute_id();
+/// let encoded = async_message_id_to_string(&id);
+/// assert_eq!(async_message_id_from_str(&encoded, 10).unwrap(), id);
+/// assert!(async_message_id_from_str("invalid", 10).is_err());
+/// ```
+pub fn async_message_id_from_str(
+    id: &str,
+    thread_count: u8,
+) -> Result<AsyncMessageId, ModelsError> {
+    let buffer = bs58::decode(id)
+        .with_check(None)
+        .into_vec()
+        .map_err(|err| ModelsError::DeserializeError(err.to_string()))?;
+    let (rest, id) = AsyncMessageIdDeserializer::new(thread_count)
+        .deserialize::<DeserializeError>(&buffer)
+        .map_err(|err| ModelsError::DeserializeError(err.to_string()))?;
+    if !rest.is_empty() {
+        return Err(ModelsError::DeserializeError(
+            "trailing bytes after the asynchronous message id".to_string(),
+        ));
+    }
+    Ok(id)
+}
+
 /// Structure defining a trigger for an asynchronous message
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct AsyncMessageTrigger {
@@ -661,6 +722,8 @@ impl Deserializer<AsyncMessage> for AsyncMessageDeserializer {
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct AsyncMessageInfo {
+    pub sender: Address,
+    pub destination: Address,
     pub validity_start: Slot,
     pub validity_end: Slot,
     pub max_gas: u64,
@@ -671,6 +734,8 @@ pub struct AsyncMessageInfo {
 impl From<AsyncMessage> for AsyncMessageInfo {
     fn from(value: AsyncMessage) -> Self {
         Self {
+            sender: value.sender,
+            destination: value.destination,
             validity_start: value.validity_start,
             validity_end: value.validity_end,
             max_gas: value.max_gas,
@@ -996,6 +1061,8 @@ impl Applicable<AsyncMessageUpdate> for AsyncMessage {
 impl Applicable<AsyncMessageUpdate> for AsyncMessageInfo {
     /// extends the `AsyncMessage` with a `AsyncMessageUpdate`
     fn apply(&mut self, update: AsyncMessageUpdate) {
+        update.sender.apply_to(&mut self.sender);
+        update.destination.apply_to(&mut self.destination);
         update.max_gas.apply_to(&mut self.max_gas);
         update.validity_start.apply_to(&mut self.validity_start);
         update.validity_end.apply_to(&mut self.validity_end);
@@ -28,19 +28,19 @@ use nom::{
 use std::collections::BTreeMap;
 use std::ops::Bound::Included;
 
-const EMISSION_SLOT_IDENT: u8 = 0u8;
-const EMISSION_INDEX_IDENT: u8 = 1u8;
-const SENDER_IDENT: u8 = 2u8;
-const DESTINATION_IDENT: u8 = 3u8;
-const FUNCTION_IDENT: u8 = 4u8;
-const MAX_GAS_IDENT: u8 = 5u8;
-const FEE_IDENT: u8 = 6u8;
-const COINS_IDENT: u8 = 7u8;
-const VALIDITY_START_IDENT: u8 = 8u8;
-const VALIDITY_END_IDENT: u8 = 9u8;
-const FUNCTION_PARAMS_IDENT: u8 = 10u8;
-const TRIGGER_IDENT: u8 = 11u8;
-const CAN_BE_EXECUTED_IDENT: u8 = 12u8;
+pub const EMISSION_SLOT_IDENT: u8 = 0u8;
+pub const EMISSION_INDEX_IDENT: u8 = 1u8;
+pub const SENDER_IDENT: u8 = 2u8;
+pub const DESTINATION_IDENT: u8 = 3u8;
+pub const FUNCTION_IDENT: u8 = 4u8;
+pub const MAX_GAS_IDENT: u8 = 5u8;
+pub const FEE_IDENT: u8 = 6u8;
+pub const COINS_IDENT: u8 = 7u8;
+pub const VALIDITY_START_IDENT: u8 = 8u8;
+pub const VALIDITY_END_IDENT: u8 = 9u8;
+pub const FUNCTION_PARAMS_IDENT: u8 = 10u8;
+pub const TRIGGER_IDENT: u8 = 11u8;
+pub const CAN_BE_EXECUTED_IDENT: u8 = 12u8;
 
 /// Emission slot key formatting macro
 #[macro_export]
@@ -66,6 +66,20 @@ impl GlobalBootstrapState {
     }
 }
 
+/// Bootstrap status of the node, as reported by its health checks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BootstrapStatus {
+    /// the node is getting its initial state from a bootstrap server or a snapshot
+    InProgress,
+    /// the node got its initial state and runs its modules from it
+    Done,
+    /// the node detected a desynchronization and is going to bootstrap again
+    NeedResync,
+}
+
+/// Bootstrap status shared between the node and the components reporting it
+pub type SharedBootstrapStatus = Arc<RwLock<BootstrapStatus>>;
+
 trait BindingReadExact: io::Read {
     /// similar to std::io::Read::read_exact, but with a timeout that is function-global instead of per-individual-read
     fn read_exact_timeout(
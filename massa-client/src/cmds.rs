@@ -187,7 +187,7 @@ pub enum Command {
     #[strum(
         ascii_case_insensitive,
         props(
-            args = "start=slot_period,slot_thread end=slot_period,slot_thread emitter_address=Address caller_address=Address operation_id=OperationId is_final=bool is_error=bool",
+            args = "start=slot_period,slot_thread end=slot_period,slot_thread emitter_address=Address caller_address=Address operation_id=OperationId is_final=bool is_error=bool cursor=EventCursor limit=u64",
             pwd_not_needed = "true"
         ),
         message = "show events emitted by smart contracts with various filters"
@@ -585,7 +585,11 @@ impl Command {
                 let key = parameters[1].as_bytes().to_vec();
                 match client
                     .public
-                    .get_datastore_entries(vec![DatastoreEntryInput { address, key }])
+                    .get_datastore_entries(vec![DatastoreEntryInput {
+                        address,
+                        key,
+                        range: None,
+                    }])
                     .await
                 {
                     Ok(result) => Ok(Box::new(result)),
@@ -621,7 +625,7 @@ impl Command {
             }
 
             Command::get_filtered_sc_output_event => {
-                let p_list: [&str; 7] = [
+                let p_list: [&str; 9] = [
                     "start",
                     "end",
                     "emitter_address",
@@ -629,6 +633,8 @@ impl Command {
                     "operation_id",
                     "is_final",
                     "is_error",
+                    "cursor",
+                    "limit",
                 ];
                 let mut p: HashMap<&str, &str> = HashMap::new();
                 for v in parameters {
@@ -647,8 +653,14 @@ impl Command {
                     original_operation_id: parse_key_value(&p, p_list[4])?,
                     is_final: parse_key_value(&p, p_list[5])?,
                     is_error: parse_key_value(&p, p_list[6])?,
+                    cursor: parse_key_value(&p, p_list[7])?,
+                    limit: parse_key_value(&p, p_list[8])?,
                 };
-                match client.public.get_filtered_sc_output_event(filter).await {
+                match client
+                    .public
+                    .get_filtered_sc_output_event_page(filter)
+                    .await
+                {
                     Ok(events) => Ok(Box::new(events)),
                     Err(e) => rpc_error!(e),
                 }
@@ -1126,6 +1138,8 @@ impl Command {
                         address,
                         operation_datastore: None, // TODO - #3072
                         fee,
+                        state_overrides: None,
+                        trace: None,
                     })
                     .await
                 {
@@ -1162,6 +1176,8 @@ impl Command {
                         max_gas,
                         coins,
                         fee,
+                        state_overrides: None,
+                        trace: None,
                     })
                     .await
                 {
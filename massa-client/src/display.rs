@@ -4,12 +4,15 @@ use crate::cmds::ExtendedWallet;
 use console::style;
 use erased_serde::{Serialize, Serializer};
 use massa_api_exports::{
-    address::AddressInfo, block::BlockInfo, datastore::DatastoreEntryOutput,
-    endorsement::EndorsementInfo, execution::ExecuteReadOnlyResponse, node::NodeStatus,
+    address::AddressInfo,
+    block::BlockInfo,
+    datastore::DatastoreEntryOutput,
+    endorsement::EndorsementInfo,
+    execution::{ExecuteReadOnlyResponse, SCOutputEventsPage},
+    node::NodeStatus,
     operation::OperationInfo,
 };
 use massa_models::composite::PubkeySig;
-use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
 use massa_models::stats::{ConsensusStats, ExecutionStats, NetworkStats};
 use massa_models::{address::Address, config::CompactConfig, operation::OperationId};
@@ -579,10 +582,13 @@ impl Output for Vec<Address> {
     }
 }
 
-impl Output for Vec<SCOutputEvent> {
+impl Output for SCOutputEventsPage {
     fn pretty_print(&self) {
-        for addr in self {
-            println!("{}", addr);
+        for event in &self.events {
+            println!("{}", event);
+        }
+        if let Some(cursor) = &self.next_cursor {
+            println!("Next cursor: {}", cursor);
         }
     }
 }
@@ -3,8 +3,15 @@ use crate::{bootstrapable_graph::BootstrapableGraph, error::ConsensusError};
 use massa_models::prehash::PreHashSet;
 use massa_models::streaming_step::StreamingStep;
 use massa_models::{
-    block::BlockGraphStatus, block_header::BlockHeader, block_id::BlockId, clique::Clique,
-    secure_share::SecureShare, slot::Slot, stats::ConsensusStats,
+    block::{BlockGraphStatus, SecureShareBlock},
+    block_header::BlockHeader,
+    block_id::BlockId,
+    clique::Clique,
+    endorsement::{EndorsementId, SecureShareEndorsement},
+    operation::{OperationId, SecureShareOperation},
+    secure_share::SecureShare,
+    slot::Slot,
+    stats::ConsensusStats,
 };
 use massa_storage::Storage;
 
@@ -98,6 +105,48 @@ pub trait ConsensusController: Send + Sync {
     /// The block id of the latest block in the thread of the given slot and before this slot
     fn get_latest_blockclique_block_at_slot(&self, slot: Slot) -> BlockId;
 
+    /// Get final blocks from the archive
+    ///
+    /// # Arguments
+    /// * `ids`: the ids of the blocks to get
+    ///
+    /// # Returns
+    /// The archived blocks sorted by the order of the input list, `None` if a block is not archived or if the archive is disabled
+    fn get_archived_blocks(&self, ids: &[BlockId]) -> Vec<Option<SecureShareBlock>>;
+
+    /// Get the final block of a slot from the archive
+    ///
+    /// # Arguments
+    /// * `slot`: the slot of the block
+    ///
+    /// # Returns
+    /// The archived block if the slot has a final block and the archive is enabled
+    fn get_archived_block_at_slot(&self, slot: Slot) -> Option<SecureShareBlock>;
+
+    /// Get final operations from the archive
+    ///
+    /// # Arguments
+    /// * `ids`: the ids of the operations to get
+    ///
+    /// # Returns
+    /// The archived operations along with the id of the final block that contains them, sorted by the order of the input list
+    fn get_archived_operations(
+        &self,
+        ids: &[OperationId],
+    ) -> Vec<Option<(SecureShareOperation, BlockId)>>;
+
+    /// Get final endorsements from the archive
+    ///
+    /// # Arguments
+    /// * `ids`: the ids of the endorsements to get
+    ///
+    /// # Returns
+    /// The archived endorsements along with the id of the final block that contains them, sorted by the order of the input list
+    fn get_archived_endorsements(
+        &self,
+        ids: &[EndorsementId],
+    ) -> Vec<Option<(SecureShareEndorsement, BlockId)>>;
+
     /// Register a block in the graph
     ///
     /// # Arguments
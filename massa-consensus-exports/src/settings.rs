@@ -1,6 +1,7 @@
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ConsensusConfig {
@@ -54,4 +55,8 @@ pub struct ConsensusConfig {
     pub last_start_period: u64,
     /// chain id
     pub chain_id: u64,
+    /// whether the final blocks, operations and endorsements are archived on disk
+    pub archive_enabled: bool,
+    /// path of the archive database
+    pub archive_path: PathBuf,
 }
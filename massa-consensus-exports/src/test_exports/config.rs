@@ -6,6 +6,7 @@ use massa_models::config::{
     CHAINID, CONSENSUS_BOOTSTRAP_PART_SIZE,
 };
 use massa_time::MassaTime;
+use std::path::PathBuf;
 
 use crate::ConsensusConfig;
 
@@ -37,6 +38,8 @@ impl Default for ConsensusConfig {
             broadcast_filled_blocks_channel_capacity: 128,
             last_start_period: 0,
             chain_id: *CHAINID,
+            archive_enabled: false,
+            archive_path: PathBuf::new(),
         }
     }
 }
@@ -0,0 +1,274 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! This module implements the optional archive of the final blocks.
+//!
+//! `Storage` only keeps the objects that are still referenced by a module, so the blocks, operations and endorsements
+//! are lost once the graph is pruned. When the archive mode is enabled, every block that becomes final is written
+//! along with its operations and endorsements to a dedicated RocksDB instance:
+//! * blocks are keyed by their id
+//! * the slot index points to the id of the final block of each slot
+//! * operations and endorsements are keyed by their id, and stored along with the id of the block that contains them
+//!
+//! The writes are made by a dedicated thread fed by consensus, so that finalization never waits for the disk.
+
+use massa_channel::{sender::MassaSender, MassaChannel};
+use massa_consensus_exports::block_status::StorageOrBlock;
+use massa_consensus_exports::ConsensusConfig;
+use massa_hash::Hash;
+use massa_models::block::{Block, BlockDeserializer, BlockDeserializerArgs, SecureShareBlock};
+use massa_models::block_id::{BlockId, BlockIdDeserializer, BlockIdSerializer};
+use massa_models::config::{
+    MAX_DATASTORE_VALUE_LENGTH, MAX_DENUNCIATIONS_PER_BLOCK_HEADER, MAX_FUNCTION_NAME_LENGTH,
+    MAX_OPERATIONS_PER_BLOCK, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+    MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH, MAX_PARAMETERS_SIZE,
+};
+use massa_models::endorsement::{
+    Endorsement, EndorsementDeserializer, EndorsementId, SecureShareEndorsement,
+};
+use massa_models::operation::{
+    Operation, OperationDeserializer, OperationId, SecureShareOperation,
+};
+use massa_models::secure_share::{
+    Id, SecureShare, SecureShareContent, SecureShareDeserializer, SecureShareSerializer,
+};
+use massa_models::slot::Slot;
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use rocksdb::{WriteBatch, DB};
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
+const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
+const ARCHIVE_SER_ERROR: &str = "critical: archive serialization failed";
+const ARCHIVE_DESER_ERROR: &str = "critical: archive deserialization failed";
+
+const BLOCK_IDENT: u8 = 0u8;
+const SLOT_IDENT: u8 = 1u8;
+const OPERATION_IDENT: u8 = 2u8;
+const ENDORSEMENT_IDENT: u8 = 3u8;
+
+/// Build an archive key from an index prefix and an object id
+fn id_key<ID: Id>(ident: u8, id: &ID) -> Vec<u8> {
+    [&[ident][..], &id.get_hash().to_bytes()[..]].concat()
+}
+
+/// Build the key of the slot index
+fn slot_key(slot: &Slot) -> Vec<u8> {
+    [&[SLOT_IDENT][..], &slot.to_bytes_key()[..]].concat()
+}
+
+/// Disk-backed archive of the final blocks, operations and endorsements
+pub(crate) struct BlockArchive {
+    /// RocksDB database
+    db: DB,
+    /// Secure share serializer
+    secure_share_ser: SecureShareSerializer,
+    /// Block id serializer
+    block_id_ser: BlockIdSerializer,
+    /// Block id deserializer
+    block_id_deser: BlockIdDeserializer,
+    /// Block deserializer
+    block_deser: SecureShareDeserializer<Block, BlockDeserializer>,
+    /// Operation deserializer
+    operation_deser: SecureShareDeserializer<Operation, OperationDeserializer>,
+    /// Endorsement deserializer
+    endorsement_deser: SecureShareDeserializer<Endorsement, EndorsementDeserializer>,
+}
+
+impl BlockArchive {
+    /// Open (or create) the archive
+    ///
+    /// # Arguments
+    /// * `path`: where to store the db
+    /// * `config`: consensus configuration, used to build the deserializers
+    pub fn new(path: PathBuf, config: &ConsensusConfig) -> Self {
+        Self {
+            db: DB::open_default(path).expect(OPEN_ERROR),
+            secure_share_ser: SecureShareSerializer::new(),
+            block_id_ser: BlockIdSerializer::new(),
+            block_id_deser: BlockIdDeserializer::new(),
+            block_deser: SecureShareDeserializer::new(
+                BlockDeserializer::new(BlockDeserializerArgs {
+                    thread_count: config.thread_count,
+                    max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
+                    endorsement_count: config.endorsement_count,
+                    max_denunciations_per_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+                    last_start_period: None,
+                    chain_id: config.chain_id,
+                }),
+                config.chain_id,
+            ),
+            operation_deser: SecureShareDeserializer::new(
+                OperationDeserializer::new(
+                    MAX_DATASTORE_VALUE_LENGTH,
+                    MAX_FUNCTION_NAME_LENGTH,
+                    MAX_PARAMETERS_SIZE,
+                    MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+                    MAX_OPERATION_DATASTORE_KEY_LENGTH,
+                    MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+                ),
+                config.chain_id,
+            ),
+            endorsement_deser: SecureShareDeserializer::new(
+                EndorsementDeserializer::new(config.thread_count, config.endorsement_count),
+                config.chain_id,
+            ),
+        }
+    }
+
+    /// Serialize an object contained in a block, prefixed with the id of the block
+    fn serialize_with_block_id<T, ID>(
+        &self,
+        block_id: &BlockId,
+        object: &SecureShare<T, ID>,
+    ) -> Vec<u8>
+    where
+        T: Display + SecureShareContent,
+        ID: Id,
+    {
+        let mut buffer = Vec::new();
+        self.block_id_ser
+            .serialize(block_id, &mut buffer)
+            .expect(ARCHIVE_SER_ERROR);
+        self.secure_share_ser
+            .serialize(object, &mut buffer)
+            .expect(ARCHIVE_SER_ERROR);
+        buffer
+    }
+
+    /// Add a block, its slot index entry and its endorsements to a write batch
+    fn put_block(&self, batch: &mut WriteBatch, block_id: &BlockId, block: &SecureShareBlock) {
+        let mut buffer = Vec::new();
+        self.secure_share_ser
+            .serialize(block, &mut buffer)
+            .expect(ARCHIVE_SER_ERROR);
+        batch.put(id_key(BLOCK_IDENT, block_id), buffer);
+        batch.put(
+            slot_key(&block.content.header.content.slot),
+            block_id.get_hash().to_bytes(),
+        );
+
+        for endorsement in block.content.header.content.endorsements.iter() {
+            batch.put(
+                id_key(ENDORSEMENT_IDENT, &endorsement.id),
+                self.serialize_with_block_id(block_id, endorsement),
+            );
+        }
+    }
+
+    /// Archive a final block along with its operations and endorsements
+    ///
+    /// # Arguments
+    /// * `block_id`: id of the final block
+    /// * `storage_or_block`: storage containing the block and its operations,
+    ///   or the block alone when consensus does not hold its operations (e.g. blocks received at bootstrap)
+    pub fn insert_final_block(&self, block_id: &BlockId, storage_or_block: &StorageOrBlock) {
+        let mut batch = WriteBatch::default();
+        match storage_or_block {
+            StorageOrBlock::Storage(storage) => {
+                let blocks = storage.read_blocks();
+                let Some(block) = blocks.get(block_id) else {
+                    return;
+                };
+                self.put_block(&mut batch, block_id, block);
+
+                let operations = storage.read_operations();
+                for operation_id in block.content.operations.iter() {
+                    if let Some(operation) = operations.get(operation_id) {
+                        batch.put(
+                            id_key(OPERATION_IDENT, operation_id),
+                            self.serialize_with_block_id(block_id, operation),
+                        );
+                    }
+                }
+            }
+            StorageOrBlock::Block(block) => self.put_block(&mut batch, block_id, block),
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+    }
+
+    /// Get an archived block
+    pub fn get_block(&self, block_id: &BlockId) -> Option<SecureShareBlock> {
+        let buffer = self
+            .db
+            .get(id_key(BLOCK_IDENT, block_id))
+            .expect(CRUD_ERROR)?;
+        let (_, block) = self
+            .block_deser
+            .deserialize::<DeserializeError>(&buffer)
+            .expect(ARCHIVE_DESER_ERROR);
+        Some(block)
+    }
+
+    /// Get the archived final block of a slot
+    pub fn get_block_at_slot(&self, slot: &Slot) -> Option<SecureShareBlock> {
+        let block_hash = self.db.get(slot_key(slot)).expect(CRUD_ERROR)?;
+        let block_id = BlockId::generate_from_hash(Hash::from_bytes(
+            block_hash[..].try_into().expect(ARCHIVE_DESER_ERROR),
+        ));
+        self.get_block(&block_id)
+    }
+
+    /// Get an archived operation along with the id of the final block that contains it
+    pub fn get_operation(
+        &self,
+        operation_id: &OperationId,
+    ) -> Option<(SecureShareOperation, BlockId)> {
+        let buffer = self
+            .db
+            .get(id_key(OPERATION_IDENT, operation_id))
+            .expect(CRUD_ERROR)?;
+        let (rest, block_id) = self
+            .block_id_deser
+            .deserialize::<DeserializeError>(&buffer)
+            .expect(ARCHIVE_DESER_ERROR);
+        let (_, operation) = self
+            .operation_deser
+            .deserialize::<DeserializeError>(rest)
+            .expect(ARCHIVE_DESER_ERROR);
+        Some((operation, block_id))
+    }
+
+    /// Get an archived endorsement along with the id of the final block that contains it
+    pub fn get_endorsement(
+        &self,
+        endorsement_id: &EndorsementId,
+    ) -> Option<(SecureShareEndorsement, BlockId)> {
+        let buffer = self
+            .db
+            .get(id_key(ENDORSEMENT_IDENT, endorsement_id))
+            .expect(CRUD_ERROR)?;
+        let (rest, block_id) = self
+            .block_id_deser
+            .deserialize::<DeserializeError>(&buffer)
+            .expect(ARCHIVE_DESER_ERROR);
+        let (_, endorsement) = self
+            .endorsement_deser
+            .deserialize::<DeserializeError>(rest)
+            .expect(ARCHIVE_DESER_ERROR);
+        Some((endorsement, block_id))
+    }
+}
+
+/// Final block handed to the archive writer thread
+pub(crate) type ArchiveRequest = (BlockId, StorageOrBlock);
+
+/// Start the thread writing the final blocks to the archive.
+///
+/// The thread stops once the returned sender is dropped and the pending blocks are written.
+pub(crate) fn start_archive_writer(
+    archive: Arc<BlockArchive>,
+) -> (MassaSender<ArchiveRequest>, JoinHandle<()>) {
+    let (tx, rx) = MassaChannel::new("consensus_archive".to_string(), None);
+    let handle = thread::Builder::new()
+        .name("consensus archive".into())
+        .spawn(move || {
+            while let Ok((block_id, storage_or_block)) = rx.recv() {
+                archive.insert_final_block(&block_id, &storage_or_block);
+            }
+        })
+        .expect("Can't spawn consensus archive thread.");
+    (tx, handle)
+}
@@ -6,11 +6,12 @@ use massa_consensus_exports::{
     export_active_block::ExportActiveBlock, ConsensusController,
 };
 use massa_models::{
-    block::{BlockGraphStatus, FilledBlock},
+    block::{BlockGraphStatus, FilledBlock, SecureShareBlock},
     block_header::BlockHeader,
     block_id::BlockId,
     clique::Clique,
-    operation::{Operation, OperationId},
+    endorsement::{EndorsementId, SecureShareEndorsement},
+    operation::{Operation, OperationId, SecureShareOperation},
     prehash::PreHashSet,
     secure_share::SecureShare,
     slot::Slot,
@@ -22,7 +23,7 @@ use parking_lot::RwLock;
 use std::sync::Arc;
 use tracing::{debug, trace, warn};
 
-use crate::{commands::ConsensusCommand, state::ConsensusState};
+use crate::{archive::BlockArchive, commands::ConsensusCommand, state::ConsensusState};
 
 /// The retrieval of data is made using a shared state and modifications are asked by sending message to a channel.
 /// This is done mostly to be able to:
@@ -38,6 +39,7 @@ pub struct ConsensusControllerImpl {
     shared_state: Arc<RwLock<ConsensusState>>,
     bootstrap_part_size: u64,
     broadcast_enabled: bool,
+    archive: Option<Arc<BlockArchive>>,
 }
 
 impl ConsensusControllerImpl {
@@ -47,6 +49,7 @@ impl ConsensusControllerImpl {
         shared_state: Arc<RwLock<ConsensusState>>,
         bootstrap_part_size: u64,
         broadcast_enabled: bool,
+        archive: Option<Arc<BlockArchive>>,
     ) -> Self {
         Self {
             command_sender,
@@ -54,6 +57,7 @@ impl ConsensusControllerImpl {
             shared_state,
             bootstrap_part_size,
             broadcast_enabled,
+            archive,
         }
     }
 }
@@ -230,6 +234,41 @@ impl ConsensusController for ConsensusControllerImpl {
             .get_latest_blockclique_block_at_slot(&slot)
     }
 
+    // The archive is read without locking the shared state: RocksDB handles concurrent accesses.
+
+    fn get_archived_blocks(&self, ids: &[BlockId]) -> Vec<Option<SecureShareBlock>> {
+        match &self.archive {
+            Some(archive) => ids.iter().map(|id| archive.get_block(id)).collect(),
+            None => vec![None; ids.len()],
+        }
+    }
+
+    fn get_archived_block_at_slot(&self, slot: Slot) -> Option<SecureShareBlock> {
+        self.archive
+            .as_ref()
+            .and_then(|archive| archive.get_block_at_slot(&slot))
+    }
+
+    fn get_archived_operations(
+        &self,
+        ids: &[OperationId],
+    ) -> Vec<Option<(SecureShareOperation, BlockId)>> {
+        match &self.archive {
+            Some(archive) => ids.iter().map(|id| archive.get_operation(id)).collect(),
+            None => vec![None; ids.len()],
+        }
+    }
+
+    fn get_archived_endorsements(
+        &self,
+        ids: &[EndorsementId],
+    ) -> Vec<Option<(SecureShareEndorsement, BlockId)>> {
+        match &self.archive {
+            Some(archive) => ids.iter().map(|id| archive.get_endorsement(id)).collect(),
+            None => vec![None; ids.len()],
+        }
+    }
+
     fn register_block(&self, block_id: BlockId, slot: Slot, block_storage: Storage, created: bool) {
         if self.broadcast_enabled {
             if let Some(verifiable_block) = block_storage.read_blocks().get(&block_id) {
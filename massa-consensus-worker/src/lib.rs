@@ -24,7 +24,13 @@
 //!    * If it's the third or more we ignore the block unless we asked for it explicitly as a dependency.
 //! If a queued block reaches the slot time at which it should be processed, the worker wakes up to check it and trigger, if necessary, the consensus algorithm.
 //! It then prunes the block graph and the caches.
+//!
+//! ## Archive
+//!
+//! If the archive mode is enabled, every block that becomes final is written to a dedicated RocksDB database
+//! along with its operations and endorsements, so that they can still be retrieved through the controller once pruned.
 
+mod archive;
 mod commands;
 mod controller;
 mod manager;
@@ -7,6 +7,9 @@ use crate::commands::ConsensusCommand;
 
 pub struct ConsensusManagerImpl {
     pub consensus_thread: Option<(MassaSender<ConsensusCommand>, JoinHandle<()>)>,
+    /// thread writing the final blocks to the archive, if enabled.
+    /// It stops once the consensus thread has dropped its sender.
+    pub archive_thread: Option<JoinHandle<()>>,
 }
 
 impl ConsensusManager for ConsensusManagerImpl {
@@ -19,6 +22,11 @@ impl ConsensusManager for ConsensusManagerImpl {
                 .join()
                 .expect("consensus thread panicked on try to join");
         }
+        if let Some(archive_thread) = self.archive_thread.take() {
+            archive_thread
+                .join()
+                .expect("consensus archive thread panicked on try to join");
+        }
         info!("consensus worker stopped");
     }
 }
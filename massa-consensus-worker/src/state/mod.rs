@@ -3,6 +3,7 @@ use std::{
     vec,
 };
 
+use massa_channel::sender::MassaSender;
 use massa_consensus_exports::{
     block_graph_export::BlockGraphExport,
     block_status::{BlockStatus, ExportCompiledBlock, HeaderOrBlock, StorageOrBlock},
@@ -26,6 +27,7 @@ use massa_time::MassaTime;
 use tracing::debug;
 
 use self::blocks_state::BlocksState;
+use crate::archive::ArchiveRequest;
 
 pub mod blocks_state;
 mod clique_computation;
@@ -91,6 +93,8 @@ pub struct ConsensusState {
     pub nonfinal_active_blocks_per_slot: HashMap<Slot, PreHashSet<BlockId>>,
     /// massa metrics
     pub(crate) massa_metrics: MassaMetrics,
+    /// sender of the final blocks to the archive writer thread, if the archive is enabled
+    pub(crate) archive_sender: Option<MassaSender<ArchiveRequest>>,
 }
 
 impl ConsensusState {
@@ -22,7 +22,7 @@ use massa_models::{
 use massa_signature::PublicKey;
 use massa_storage::Storage;
 use massa_time::MassaTime;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use crate::state::{
     clique_computation::compute_max_cliques,
@@ -722,10 +722,27 @@ impl ConsensusState {
             let mut final_block_slots = HashMap::with_capacity(finalized_blocks.len());
             let mut final_block_stats = VecDeque::with_capacity(finalized_blocks.len());
             for b_id in finalized_blocks {
-                if let Some(BlockStatus::Active { a_block, .. }) = self.blocks_state.get(&b_id) {
+                if let Some(BlockStatus::Active {
+                    a_block,
+                    storage_or_block,
+                }) = self.blocks_state.get(&b_id)
+                {
                     // add to final blocks to notify execution
                     final_block_slots.insert(a_block.slot, b_id);
 
+                    // hand the block over to the archive writer thread
+                    if let Some(archive_sender) = &self.archive_sender {
+                        if archive_sender
+                            .send((b_id, storage_or_block.clone()))
+                            .is_err()
+                        {
+                            warn!(
+                                "could not archive final block {}: archive writer stopped",
+                                b_id
+                            );
+                        }
+                    }
+
                     // add to stats
                     let block_is_from_protocol = self
                         .protocol_blocks
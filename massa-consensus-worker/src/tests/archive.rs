@@ -0,0 +1,96 @@
+use crate::archive::{start_archive_writer, BlockArchive};
+use massa_consensus_exports::block_status::StorageOrBlock;
+use massa_consensus_exports::ConsensusConfig;
+use massa_hash::Hash;
+use massa_models::block_id::BlockId;
+use massa_models::config::CHAINID;
+use massa_models::endorsement::{Endorsement, EndorsementSerializer, SecureShareEndorsement};
+use massa_models::secure_share::SecureShareContent;
+use massa_models::slot::Slot;
+use massa_protocol_exports::test_exports::tools::{
+    create_block_with_endorsements, create_block_with_operations,
+    create_operation_with_expire_period,
+};
+use massa_signature::KeyPair;
+use massa_storage::Storage;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+#[test]
+fn test_block_archive() {
+    let tmp_dir = TempDir::new().unwrap();
+    let config = ConsensusConfig {
+        thread_count: 2,
+        ..ConsensusConfig::default()
+    };
+    let keypair = KeyPair::generate(0).unwrap();
+
+    // a block with operations
+    let op_1 = create_operation_with_expire_period(&keypair, 10);
+    let op_2 = create_operation_with_expire_period(&keypair, 11);
+    let block_1 =
+        create_block_with_operations(&keypair, Slot::new(1, 0), vec![op_1.clone(), op_2.clone()]);
+
+    // a block with an endorsement of its parent in the same thread
+    let endorsement: SecureShareEndorsement = Endorsement::new_verifiable(
+        Endorsement {
+            slot: Slot::new(1, 1),
+            index: 0,
+            endorsed_block: BlockId::generate_from_hash(Hash::compute_from("Genesis 1".as_bytes())),
+        },
+        EndorsementSerializer::new(),
+        &keypair,
+        *CHAINID,
+    )
+    .unwrap();
+    let block_2 =
+        create_block_with_endorsements(&keypair, Slot::new(1, 1), vec![endorsement.clone()]);
+
+    {
+        let archive = Arc::new(BlockArchive::new(tmp_dir.path().to_path_buf(), &config));
+        let (archive_sender, archive_thread) = start_archive_writer(archive);
+        let mut storage = Storage::create_root();
+        storage.store_operations(vec![op_1.clone(), op_2.clone()]);
+        storage.store_block(block_1.clone());
+        archive_sender
+            .send((block_1.id, StorageOrBlock::Storage(storage)))
+            .unwrap();
+        // blocks held without their operations are archived too
+        archive_sender
+            .send((block_2.id, StorageOrBlock::Block(Box::new(block_2.clone()))))
+            .unwrap();
+        // the pending blocks are written before the writer stops
+        drop(archive_sender);
+        archive_thread.join().unwrap();
+    }
+
+    // the archive survives a restart and does not depend on storage anymore
+    let archive = BlockArchive::new(tmp_dir.path().to_path_buf(), &config);
+
+    let block = archive.get_block(&block_1.id).unwrap();
+    assert_eq!(block.id, block_1.id);
+    assert_eq!(block.content.operations, vec![op_1.id, op_2.id]);
+    assert_eq!(
+        archive.get_block_at_slot(&Slot::new(1, 1)).unwrap().id,
+        block_2.id
+    );
+    assert!(archive.get_block_at_slot(&Slot::new(2, 0)).is_none());
+
+    let (operation, block_id) = archive.get_operation(&op_2.id).unwrap();
+    assert_eq!(operation.id, op_2.id);
+    assert_eq!(operation.serialized_data, op_2.serialized_data);
+    assert_eq!(block_id, block_1.id);
+
+    let (archived_endorsement, block_id) = archive.get_endorsement(&endorsement.id).unwrap();
+    assert_eq!(archived_endorsement.id, endorsement.id);
+    assert_eq!(block_id, block_2.id);
+
+    // unknown objects
+    assert!(archive
+        .get_block(&BlockId::generate_from_hash(Hash::compute_from(
+            "unknown".as_bytes()
+        )))
+        .is_none());
+    let unknown_op = create_operation_with_expire_period(&keypair, 12);
+    assert!(archive.get_operation(&unknown_op.id).is_none());
+}
@@ -1,3 +1,4 @@
+mod archive;
 mod tools;
 mod universe;
 
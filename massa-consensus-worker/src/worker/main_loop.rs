@@ -169,5 +169,7 @@ impl ConsensusWorker {
                 }
             };
         }
+        // let the archive writer write the pending blocks and stop
+        self.shared_state.write().archive_sender = None;
     }
 }
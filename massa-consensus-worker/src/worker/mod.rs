@@ -16,6 +16,7 @@ use std::sync::Arc;
 use std::thread;
 use std::time::Instant;
 
+use crate::archive::{start_archive_writer, BlockArchive};
 use crate::commands::ConsensusCommand;
 use crate::controller::ConsensusControllerImpl;
 use crate::manager::ConsensusManagerImpl;
@@ -64,6 +65,10 @@ pub fn start_consensus_worker(
     let stats_desync_detection_timespan =
         config.t0.checked_mul(config.periods_per_cycle * 2).unwrap();
     let broadcasts = channels.broadcasts.clone();
+    let archive = config
+        .archive_enabled
+        .then(|| Arc::new(BlockArchive::new(config.archive_path.clone(), &config)));
+    let (archive_sender, archive_thread) = archive.clone().map(start_archive_writer).unzip();
     let shared_state = Arc::new(RwLock::new(ConsensusState {
         storage: storage.clone(),
         config: config.clone(),
@@ -97,6 +102,7 @@ pub fn start_consensus_worker(
         prev_blockclique: Default::default(),
         nonfinal_active_blocks_per_slot: Default::default(),
         massa_metrics,
+        archive_sender,
     }));
 
     let shared_state_cloned = shared_state.clone();
@@ -110,6 +116,7 @@ pub fn start_consensus_worker(
 
     let manager = ConsensusManagerImpl {
         consensus_thread: Some((tx.clone(), consensus_thread)),
+        archive_thread,
     };
 
     let controller = ConsensusControllerImpl::new(
@@ -118,6 +125,7 @@ pub fn start_consensus_worker(
         shared_state,
         bootstrap_part_size,
         config.broadcast_enabled,
+        archive,
     );
 
     (Box::new(controller), Box::new(manager))
@@ -21,4 +21,6 @@ pub enum MassaDBError {
     HashError(String),
     /// serialization error: {0}
     SerializeError(String),
+    /// state archive error: {0}
+    StateArchiveError(String),
 }
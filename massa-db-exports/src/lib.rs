@@ -3,9 +3,11 @@ mod controller;
 mod db_batch;
 mod error;
 mod settings;
+mod state_breakdown;
 
 pub use constants::*;
 pub use controller::*;
 pub use db_batch::*;
 pub use error::*;
 pub use settings::*;
+pub use state_breakdown::*;
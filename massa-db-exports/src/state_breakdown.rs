@@ -0,0 +1,188 @@
+//! Breakdown of the final state hash per key prefix.
+//!
+//! The XOF state hash is recomputed from every key of the state column family, and split by prefix.
+//! Two breakdowns, e.g. of two nodes disagreeing on their final state hash, can then be diffed:
+//! the prefixes whose hashes differ are reported, down to the diverging keys if they are listed.
+//! Listing keys is opt-in and restricted to a key prefix, as listing the whole state is expensive.
+
+use crate::{
+    MassaDBController, MassaDBError, MassaIteratorMode, ASYNC_POOL_PREFIX, CYCLE_HISTORY_PREFIX,
+    DEFERRED_CREDITS_PREFIX, EXECUTED_DENUNCIATIONS_PREFIX, EXECUTED_OPS_PREFIX,
+    EXECUTION_TRAIL_HASH_PREFIX, LEDGER_PREFIX, MIP_STORE_PREFIX, STATE_CF,
+    STATE_HASH_INITIAL_BYTES,
+};
+use massa_hash::{Hash, HashXof, HASH_XOF_SIZE_BYTES};
+use massa_models::slot::Slot;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Prefixes of the keys of the state column family
+pub const STATE_PREFIXES: [&str; 8] = [
+    ASYNC_POOL_PREFIX,
+    CYCLE_HISTORY_PREFIX,
+    DEFERRED_CREDITS_PREFIX,
+    EXECUTED_DENUNCIATIONS_PREFIX,
+    EXECUTED_OPS_PREFIX,
+    EXECUTION_TRAIL_HASH_PREFIX,
+    LEDGER_PREFIX,
+    MIP_STORE_PREFIX,
+];
+
+/// Name of the breakdown of the keys matching none of the `STATE_PREFIXES`
+pub const UNKNOWN_PREFIX: &str = "unknown";
+
+/// Part of the state hash computed from the keys of a given prefix
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrefixHashBreakdown {
+    /// number of keys
+    pub count: u64,
+    /// XOR of the hashes of the keys and their values
+    pub hash: String,
+    /// hash of the value of every listed key (hexadecimal), if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keys: Option<BTreeMap<String, String>>,
+    /// keys (hexadecimal) whose key or value could not be deserialized
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub invalid_keys: Vec<String>,
+}
+
+/// Breakdown of the final state hash per key prefix
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateHashBreakdown {
+    /// slot of the final state
+    pub slot: Slot,
+    /// state hash stored in the database
+    pub stored_hash: String,
+    /// state hash recomputed from every key
+    pub computed_hash: String,
+    /// prefix of the keys whose value hashes are listed, if keys are listed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listed_keys: Option<String>,
+    /// breakdown per prefix
+    pub prefixes: BTreeMap<String, PrefixHashBreakdown>,
+}
+
+/// Divergence between two state hash breakdowns
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StateDivergence {
+    /// the hashes of a prefix differ, but none of the keys listed in both breakdowns do
+    Prefix(String),
+    /// a key (hexadecimal) has different values
+    Value(String),
+    /// a key (hexadecimal) is only present in the first breakdown
+    OnlyInFirst(String),
+    /// a key (hexadecimal) is only present in the second breakdown
+    OnlyInSecond(String),
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+impl StateHashBreakdown {
+    /// Recompute the state hash from every key of the state column family
+    ///
+    /// # Arguments
+    /// * `db`: the database of the final state
+    /// * `listed_keys`: list the hash of the value of every key starting with this prefix, to find diverging keys
+    /// * `is_key_value_valid`: checks that a key and its value can be deserialized
+    pub fn compute(
+        db: &dyn MassaDBController,
+        listed_keys: Option<&str>,
+        is_key_value_valid: impl Fn(&[u8], &[u8]) -> bool,
+    ) -> Result<Self, MassaDBError> {
+        let slot = db
+            .get_change_id()
+            .map_err(|err| MassaDBError::InvalidChangeID(err.to_string()))?;
+
+        let initial_hash = HashXof::<HASH_XOF_SIZE_BYTES>(*STATE_HASH_INITIAL_BYTES);
+        let mut computed_hash = initial_hash;
+        let mut prefix_hashes = BTreeMap::new();
+        let mut prefixes: BTreeMap<String, PrefixHashBreakdown> = BTreeMap::new();
+        for (key, value) in db.iterator_cf(STATE_CF, MassaIteratorMode::Start) {
+            let entry_hash = HashXof::compute_from_tuple(&[&key, &value]);
+            computed_hash ^= entry_hash;
+
+            let prefix = STATE_PREFIXES
+                .into_iter()
+                .find(|prefix| key.starts_with(prefix.as_bytes()))
+                .unwrap_or(UNKNOWN_PREFIX);
+            *prefix_hashes.entry(prefix).or_insert(initial_hash) ^= entry_hash;
+            let breakdown = prefixes.entry(prefix.to_string()).or_default();
+            breakdown.count += 1;
+            if listed_keys.is_some_and(|listed| key.starts_with(listed.as_bytes())) {
+                breakdown
+                    .keys
+                    .get_or_insert_with(BTreeMap::new)
+                    .insert(to_hex(&key), Hash::compute_from(&value).to_string());
+            }
+            if !is_key_value_valid(&key, &value) {
+                breakdown.invalid_keys.push(to_hex(&key));
+            }
+        }
+        for (prefix, hash) in prefix_hashes {
+            if let Some(breakdown) = prefixes.get_mut(prefix) {
+                breakdown.hash = hash.to_string();
+            }
+        }
+
+        Ok(StateHashBreakdown {
+            slot,
+            stored_hash: db.get_xof_db_hash().to_string(),
+            computed_hash: computed_hash.to_string(),
+            listed_keys: listed_keys.map(str::to_string),
+            prefixes,
+        })
+    }
+
+    /// Whether the stored state hash matches the recomputed one, and every key is valid
+    pub fn is_valid(&self) -> bool {
+        self.stored_hash == self.computed_hash
+            && self
+                .prefixes
+                .values()
+                .all(|breakdown| breakdown.invalid_keys.is_empty())
+    }
+
+    /// List the divergences between two breakdowns
+    ///
+    /// Keys are only compared if both breakdowns list the keys of the same prefix.
+    pub fn diff(&self, other: &StateHashBreakdown) -> Vec<StateDivergence> {
+        let empty = PrefixHashBreakdown::default();
+        let empty_keys = BTreeMap::new();
+        let compare_keys = self.listed_keys.is_some() && self.listed_keys == other.listed_keys;
+        let mut divergences = Vec::new();
+        let all_prefixes: BTreeSet<&String> =
+            self.prefixes.keys().chain(other.prefixes.keys()).collect();
+        for prefix in all_prefixes {
+            let first = self.prefixes.get(prefix).unwrap_or(&empty);
+            let second = other.prefixes.get(prefix).unwrap_or(&empty);
+            if first.hash == second.hash {
+                continue;
+            }
+            let mut key_divergences = Vec::new();
+            if compare_keys {
+                let first_keys = first.keys.as_ref().unwrap_or(&empty_keys);
+                let second_keys = second.keys.as_ref().unwrap_or(&empty_keys);
+                for (key, value_hash) in first_keys {
+                    match second_keys.get(key) {
+                        Some(other_hash) if other_hash == value_hash => {}
+                        Some(_) => key_divergences.push(StateDivergence::Value(key.clone())),
+                        None => key_divergences.push(StateDivergence::OnlyInFirst(key.clone())),
+                    }
+                }
+                for key in second_keys.keys() {
+                    if !first_keys.contains_key(key) {
+                        key_divergences.push(StateDivergence::OnlyInSecond(key.clone()));
+                    }
+                }
+            }
+            // the diverging keys are not listed
+            if key_divergences.is_empty() {
+                key_divergences.push(StateDivergence::Prefix(prefix.clone()));
+            }
+            divergences.extend(key_divergences);
+        }
+        divergences
+    }
+}
@@ -0,0 +1,581 @@
+//! Decoding of the keys and values stored in a node database.
+//!
+//! Each key is dispatched on its prefix, then its key and value are decoded with the deserializers
+//! of the crate owning the prefix, following the same layouts as their `is_key_value_valid` functions.
+
+use clap::ValueEnum;
+use massa_async_pool::{
+    async_message_id_to_string, AsyncMessageDeserializer, AsyncMessageIdDeserializer,
+    CAN_BE_EXECUTED_IDENT, COINS_IDENT, DESTINATION_IDENT, EMISSION_INDEX_IDENT,
+    EMISSION_SLOT_IDENT, FEE_IDENT, FUNCTION_IDENT, FUNCTION_PARAMS_IDENT, MAX_GAS_IDENT,
+    SENDER_IDENT, TRIGGER_IDENT, VALIDITY_END_IDENT, VALIDITY_START_IDENT,
+};
+use massa_db_exports::{
+    ASYNC_POOL_PREFIX, CHANGE_ID_KEY, CYCLE_HISTORY_PREFIX, DEFERRED_CREDITS_PREFIX,
+    EXECUTED_DENUNCIATIONS_PREFIX, EXECUTED_OPS_PREFIX, EXECUTION_TRAIL_HASH_PREFIX, LEDGER_PREFIX,
+    METADATA_CF, MIP_STORE_PREFIX, MIP_STORE_STATS_PREFIX, STATE_HASH_KEY,
+};
+use massa_hash::{Hash, HashXof, HASH_XOF_SIZE_BYTES};
+use massa_ledger_exports::{KeyDeserializer, KeyType};
+use massa_models::{
+    address::Address,
+    amount::{Amount, AmountDeserializer},
+    bytecode::BytecodeDeserializer,
+    config::{
+        ENDORSEMENT_COUNT, MAX_DATASTORE_KEY_LENGTH, MAX_DATASTORE_VALUE_LENGTH,
+        MAX_DEFERRED_CREDITS_LENGTH, MAX_FUNCTION_NAME_LENGTH, MAX_PARAMETERS_SIZE,
+        MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH, THREAD_COUNT,
+    },
+    denunciation::DenunciationIndexDeserializer,
+    operation::OperationIdDeserializer,
+    slot::{Slot, SlotDeserializer, SLOT_KEY_SIZE},
+};
+use massa_pos_exports::{
+    CycleInfoDeserializer, DeferredCreditsDeserializer, COMPLETE_IDENT,
+    FINAL_STATE_HASH_SNAPSHOT_IDENT, PROD_STATS_FAIL_IDENT, PROD_STATS_IDENT,
+    PROD_STATS_SUCCESS_IDENT, RNG_SEED_IDENT, ROLL_COUNT_IDENT,
+};
+use massa_serialization::{
+    BoolDeserializer, DeserializeError, Deserializer, U64VarIntDeserializer,
+};
+use massa_versioning::versioning_ser_der::{MipInfoDeserializer, MipStateDeserializer};
+use serde_json::{json, Value as JsonValue};
+use std::ops::Bound::{Excluded, Included};
+
+/// Prefixes of the keys of the `state` and `versioning` column families
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Prefix {
+    Ledger,
+    AsyncPool,
+    CycleHistory,
+    DeferredCredits,
+    ExecutedOps,
+    ExecutedDenunciations,
+    ExecutionTrailHash,
+    MipStore,
+    MipStoreStats,
+}
+
+impl Prefix {
+    pub const ALL: [Prefix; 9] = [
+        Prefix::Ledger,
+        Prefix::AsyncPool,
+        Prefix::CycleHistory,
+        Prefix::DeferredCredits,
+        Prefix::ExecutedOps,
+        Prefix::ExecutedDenunciations,
+        Prefix::ExecutionTrailHash,
+        Prefix::MipStore,
+        Prefix::MipStoreStats,
+    ];
+
+    /// The prefix as stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Prefix::Ledger => LEDGER_PREFIX,
+            Prefix::AsyncPool => ASYNC_POOL_PREFIX,
+            Prefix::CycleHistory => CYCLE_HISTORY_PREFIX,
+            Prefix::DeferredCredits => DEFERRED_CREDITS_PREFIX,
+            Prefix::ExecutedOps => EXECUTED_OPS_PREFIX,
+            Prefix::ExecutedDenunciations => EXECUTED_DENUNCIATIONS_PREFIX,
+            Prefix::ExecutionTrailHash => EXECUTION_TRAIL_HASH_PREFIX,
+            Prefix::MipStore => MIP_STORE_PREFIX,
+            Prefix::MipStoreStats => MIP_STORE_STATS_PREFIX,
+        }
+    }
+
+    /// Get the prefix of a serialized key
+    pub fn of_key(serialized_key: &[u8]) -> Option<Prefix> {
+        Prefix::ALL
+            .into_iter()
+            .find(|prefix| serialized_key.starts_with(prefix.as_str().as_bytes()))
+    }
+}
+
+/// A decoded key and its value
+#[derive(Debug, PartialEq)]
+pub struct DecodedEntry {
+    pub key: JsonValue,
+    pub value: JsonValue,
+}
+
+/// Encode bytes that cannot be decoded further
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Deserialize the start of `buffer`, returning the remaining bytes
+fn parse<'a, T>(
+    deserializer: &impl Deserializer<T>,
+    buffer: &'a [u8],
+) -> Result<(&'a [u8], T), String> {
+    deserializer
+        .deserialize::<DeserializeError>(buffer)
+        .map_err(|err| err.to_string())
+}
+
+/// Deserialize the whole `buffer`
+fn parse_all<T>(deserializer: &impl Deserializer<T>, buffer: &[u8]) -> Result<T, String> {
+    let (rest, value) = parse(deserializer, buffer)?;
+    if !rest.is_empty() {
+        return Err(format!("{} unexpected trailing bytes", rest.len()));
+    }
+    Ok(value)
+}
+
+/// Split the fixed-size array at the start of a key suffix
+fn split_array<const N: usize>(buffer: &[u8]) -> Option<([u8; N], &[u8])> {
+    Some((buffer.get(..N)?.try_into().ok()?, &buffer[N..]))
+}
+
+/// Split the ident byte at the start of a key suffix
+fn split_ident(buffer: &[u8]) -> Result<(u8, &[u8]), String> {
+    buffer
+        .split_first()
+        .map(|(ident, rest)| (*ident, rest))
+        .ok_or_else(|| "missing ident".to_string())
+}
+
+/// Decoder of the database entries, holding the deserializers of every prefix
+pub struct Decoder {
+    ledger_key_deserializer: KeyDeserializer,
+    amount_deserializer: AmountDeserializer,
+    bytecode_deserializer: BytecodeDeserializer,
+    version_deserializer: U64VarIntDeserializer,
+    slot_deserializer: SlotDeserializer,
+    bool_deserializer: BoolDeserializer,
+    message_id_deserializer: AsyncMessageIdDeserializer,
+    message_deserializer: AsyncMessageDeserializer,
+    cycle_info_deserializer: CycleInfoDeserializer,
+    deferred_credits_deserializer: DeferredCreditsDeserializer,
+    operation_id_deserializer: OperationIdDeserializer,
+    denunciation_index_deserializer: DenunciationIndexDeserializer,
+    mip_info_deserializer: MipInfoDeserializer,
+    mip_state_deserializer: MipStateDeserializer,
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Decoder {
+    /// Creates a decoder for the databases of nodes using the current network constants
+    pub fn new() -> Self {
+        Self {
+            ledger_key_deserializer: KeyDeserializer::new(MAX_DATASTORE_KEY_LENGTH, false),
+            amount_deserializer: AmountDeserializer::new(
+                Included(Amount::MIN),
+                Included(Amount::MAX),
+            ),
+            bytecode_deserializer: BytecodeDeserializer::new(MAX_DATASTORE_VALUE_LENGTH),
+            version_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+            slot_deserializer: SlotDeserializer::new(
+                (Included(u64::MIN), Included(u64::MAX)),
+                (Included(0), Excluded(THREAD_COUNT)),
+            ),
+            bool_deserializer: BoolDeserializer::new(),
+            message_id_deserializer: AsyncMessageIdDeserializer::new(THREAD_COUNT),
+            message_deserializer: AsyncMessageDeserializer::new(
+                THREAD_COUNT,
+                MAX_FUNCTION_NAME_LENGTH,
+                MAX_PARAMETERS_SIZE as u64,
+                MAX_DATASTORE_KEY_LENGTH as u32,
+                true,
+            ),
+            cycle_info_deserializer: CycleInfoDeserializer::new(
+                MAX_ROLLS_COUNT_LENGTH,
+                MAX_PRODUCTION_STATS_LENGTH,
+            ),
+            deferred_credits_deserializer: DeferredCreditsDeserializer::new(
+                THREAD_COUNT,
+                MAX_DEFERRED_CREDITS_LENGTH,
+            ),
+            operation_id_deserializer: OperationIdDeserializer::new(),
+            denunciation_index_deserializer: DenunciationIndexDeserializer::new(
+                THREAD_COUNT,
+                ENDORSEMENT_COUNT,
+            ),
+            mip_info_deserializer: MipInfoDeserializer::new(),
+            mip_state_deserializer: MipStateDeserializer::new(),
+        }
+    }
+
+    /// Decode an entry as a JSON object, falling back to its raw bytes if it cannot be decoded
+    pub fn decode_to_json(
+        &self,
+        handle_cf: &str,
+        serialized_key: &[u8],
+        serialized_value: &[u8],
+    ) -> JsonValue {
+        let prefix = Prefix::of_key(serialized_key).map(|prefix| prefix.as_str());
+        match self.decode(handle_cf, serialized_key, serialized_value) {
+            Ok(DecodedEntry { key, value }) => json!({
+                "cf": handle_cf,
+                "prefix": prefix,
+                "key": key,
+                "value": value,
+            }),
+            Err(error) => json!({
+                "cf": handle_cf,
+                "prefix": prefix,
+                "key": to_hex(serialized_key),
+                "value": to_hex(serialized_value),
+                "error": error,
+            }),
+        }
+    }
+
+    /// Decode an entry of the given column family
+    pub fn decode(
+        &self,
+        handle_cf: &str,
+        serialized_key: &[u8],
+        serialized_value: &[u8],
+    ) -> Result<DecodedEntry, String> {
+        if handle_cf == METADATA_CF {
+            return self.decode_metadata(serialized_key, serialized_value);
+        }
+        let Some(prefix) = Prefix::of_key(serialized_key) else {
+            return Err("unknown key prefix".to_string());
+        };
+        let rest_key = &serialized_key[prefix.as_str().len()..];
+        match prefix {
+            Prefix::Ledger => self.decode_ledger(serialized_key, serialized_value),
+            Prefix::AsyncPool => self.decode_async_pool(rest_key, serialized_value),
+            Prefix::CycleHistory => self.decode_cycle_history(rest_key, serialized_value),
+            Prefix::DeferredCredits => self.decode_deferred_credits(rest_key, serialized_value),
+            Prefix::ExecutedOps => {
+                let op_id = parse_all(&self.operation_id_deserializer, rest_key)?;
+                let (rest, success) = parse(&self.bool_deserializer, serialized_value)?;
+                let expiry_slot: Slot = parse_all(&self.slot_deserializer, rest)?;
+                Ok(DecodedEntry {
+                    key: json!(op_id.to_string()),
+                    value: json!({ "success": success, "expiry_slot": expiry_slot }),
+                })
+            }
+            Prefix::ExecutedDenunciations => {
+                let index = parse_all(&self.denunciation_index_deserializer, rest_key)?;
+                if !serialized_value.is_empty() {
+                    return Err("executed denunciations have no value".to_string());
+                }
+                Ok(DecodedEntry {
+                    key: json!(index),
+                    value: JsonValue::Null,
+                })
+            }
+            Prefix::ExecutionTrailHash => {
+                let hash = Hash::try_from(serialized_value).map_err(|err| err.to_string())?;
+                Ok(DecodedEntry {
+                    key: json!(prefix.as_str()),
+                    value: json!(hash.to_string()),
+                })
+            }
+            Prefix::MipStore => {
+                let mip_info = parse_all(&self.mip_info_deserializer, rest_key)?;
+                let mip_state = parse_all(&self.mip_state_deserializer, serialized_value)?;
+                Ok(DecodedEntry {
+                    key: json!(format!("{:?}", mip_info)),
+                    value: json!(format!("{:?}", mip_state)),
+                })
+            }
+            Prefix::MipStoreStats => Ok(DecodedEntry {
+                key: json!(prefix.as_str()),
+                value: json!(to_hex(serialized_value)),
+            }),
+        }
+    }
+
+    fn decode_metadata(
+        &self,
+        serialized_key: &[u8],
+        serialized_value: &[u8],
+    ) -> Result<DecodedEntry, String> {
+        let value = if serialized_key == CHANGE_ID_KEY {
+            json!(parse_all(&self.slot_deserializer, serialized_value)?)
+        } else if serialized_key == STATE_HASH_KEY {
+            let hash: [u8; HASH_XOF_SIZE_BYTES] = serialized_value
+                .try_into()
+                .map_err(|_| "invalid state hash length".to_string())?;
+            json!(HashXof(hash).to_string())
+        } else {
+            return Err("unknown metadata key".to_string());
+        };
+        Ok(DecodedEntry {
+            key: json!(String::from_utf8_lossy(serialized_key)),
+            value,
+        })
+    }
+
+    fn decode_ledger(
+        &self,
+        serialized_key: &[u8],
+        serialized_value: &[u8],
+    ) -> Result<DecodedEntry, String> {
+        let key = parse_all(&self.ledger_key_deserializer, serialized_key)?;
+        let (key_type, value) = match key.key_type {
+            KeyType::VERSION => (
+                json!("version"),
+                json!(parse_all(&self.version_deserializer, serialized_value)?),
+            ),
+            KeyType::BALANCE => (
+                json!("balance"),
+                json!(parse_all(&self.amount_deserializer, serialized_value)?),
+            ),
+            KeyType::BYTECODE => (
+                json!("bytecode"),
+                json!(to_hex(
+                    &parse_all(&self.bytecode_deserializer, serialized_value)?.0
+                )),
+            ),
+            KeyType::DATASTORE(datastore_key) => (
+                json!({ "datastore": to_hex(&datastore_key) }),
+                json!(to_hex(serialized_value)),
+            ),
+        };
+        Ok(DecodedEntry {
+            key: json!({ "address": key.address, "type": key_type }),
+            value,
+        })
+    }
+
+    fn decode_async_pool(
+        &self,
+        rest_key: &[u8],
+        serialized_value: &[u8],
+    ) -> Result<DecodedEntry, String> {
+        let deserializer = &self.message_deserializer;
+        let (rest_key, message_id) = parse(&self.message_id_deserializer, rest_key)?;
+        let (ident, rest_key) = split_ident(rest_key)?;
+        if !rest_key.is_empty() {
+            return Err("unexpected bytes after the message field ident".to_string());
+        }
+        let (field, value) = match ident {
+            EMISSION_SLOT_IDENT => (
+                "emission_slot",
+                json!(parse_all(
+                    &deserializer.slot_deserializer,
+                    serialized_value
+                )?),
+            ),
+            EMISSION_INDEX_IDENT => (
+                "emission_index",
+                json!(parse_all(
+                    &deserializer.emission_index_deserializer,
+                    serialized_value
+                )?),
+            ),
+            SENDER_IDENT => (
+                "sender",
+                json!(parse_all::<Address>(
+                    &deserializer.address_deserializer,
+                    serialized_value
+                )?),
+            ),
+            DESTINATION_IDENT => (
+                "destination",
+                json!(parse_all::<Address>(
+                    &deserializer.address_deserializer,
+                    serialized_value
+                )?),
+            ),
+            FUNCTION_IDENT => (
+                "function",
+                json!(parse_all(
+                    &deserializer.function_deserializer,
+                    serialized_value
+                )?),
+            ),
+            MAX_GAS_IDENT => (
+                "max_gas",
+                json!(parse_all(
+                    &deserializer.max_gas_deserializer,
+                    serialized_value
+                )?),
+            ),
+            FEE_IDENT => (
+                "fee",
+                json!(parse_all(
+                    &deserializer.amount_deserializer,
+                    serialized_value
+                )?),
+            ),
+            COINS_IDENT => (
+                "coins",
+                json!(parse_all(
+                    &deserializer.amount_deserializer,
+                    serialized_value
+                )?),
+            ),
+            VALIDITY_START_IDENT => (
+                "validity_start",
+                json!(parse_all(
+                    &deserializer.slot_deserializer,
+                    serialized_value
+                )?),
+            ),
+            VALIDITY_END_IDENT => (
+                "validity_end",
+                json!(parse_all(
+                    &deserializer.slot_deserializer,
+                    serialized_value
+                )?),
+            ),
+            FUNCTION_PARAMS_IDENT => (
+                "function_params",
+                json!(to_hex(&parse_all(
+                    &deserializer.function_params_deserializer,
+                    serialized_value
+                )?)),
+            ),
+            TRIGGER_IDENT => (
+                "trigger",
+                json!(parse_all(
+                    &deserializer.trigger_deserializer,
+                    serialized_value
+                )?),
+            ),
+            CAN_BE_EXECUTED_IDENT => (
+                "can_be_executed",
+                json!(parse_all(
+                    &deserializer.bool_deserializer,
+                    serialized_value
+                )?),
+            ),
+            _ => return Err(format!("unknown message field ident {}", ident)),
+        };
+        Ok(DecodedEntry {
+            key: json!({
+                "message_id": async_message_id_to_string(&message_id),
+                "field": field,
+            }),
+            value,
+        })
+    }
+
+    fn decode_cycle_history(
+        &self,
+        rest_key: &[u8],
+        serialized_value: &[u8],
+    ) -> Result<DecodedEntry, String> {
+        let deserializer = &self.cycle_info_deserializer;
+        let (cycle, rest_key) = split_array(rest_key)
+            .map(|(cycle, rest)| (u64::from_be_bytes(cycle), rest))
+            .ok_or_else(|| "missing cycle".to_string())?;
+        let (ident, rest_key) = split_ident(rest_key)?;
+        let (field, address, value) = match ident {
+            COMPLETE_IDENT => {
+                let complete = match serialized_value {
+                    [0] => false,
+                    [1] => true,
+                    _ => return Err("invalid complete flag".to_string()),
+                };
+                ("complete", None, json!(complete))
+            }
+            RNG_SEED_IDENT => {
+                let rng_seed = parse_all(&deserializer.bitvec_deser, serialized_value)?;
+                (
+                    "rng_seed",
+                    None,
+                    json!({ "bits": rng_seed.len(), "bytes": to_hex(rng_seed.as_raw_slice()) }),
+                )
+            }
+            FINAL_STATE_HASH_SNAPSHOT_IDENT => {
+                let hash = parse_all(&deserializer.opt_hash_deser, serialized_value)?;
+                (
+                    "final_state_hash_snapshot",
+                    None,
+                    json!(hash.map(|hash| hash.to_string())),
+                )
+            }
+            ROLL_COUNT_IDENT => {
+                let address: Address =
+                    parse_all(&deserializer.rolls_deser.address_deserializer, rest_key)?;
+                let rolls =
+                    parse_all(&deserializer.rolls_deser.u64_deserializer, serialized_value)?;
+                ("roll_count", Some(address), json!(rolls))
+            }
+            PROD_STATS_IDENT => {
+                let stats_deserializer = &deserializer.production_stats_deser;
+                let (rest_key, address): (_, Address) =
+                    parse(&stats_deserializer.address_deserializer, rest_key)?;
+                let field = match rest_key {
+                    [PROD_STATS_FAIL_IDENT] => "block_failure_count",
+                    [PROD_STATS_SUCCESS_IDENT] => "block_success_count",
+                    _ => return Err("invalid production stats ident".to_string()),
+                };
+                let count = parse_all(&stats_deserializer.u64_deserializer, serialized_value)?;
+                (field, Some(address), json!(count))
+            }
+            _ => return Err(format!("unknown cycle field ident {}", ident)),
+        };
+        if address.is_none() && !rest_key.is_empty() {
+            return Err("unexpected bytes after the cycle field ident".to_string());
+        }
+        Ok(DecodedEntry {
+            key: json!({ "cycle": cycle, "field": field, "address": address }),
+            value,
+        })
+    }
+
+    fn decode_deferred_credits(
+        &self,
+        rest_key: &[u8],
+        serialized_value: &[u8],
+    ) -> Result<DecodedEntry, String> {
+        let deserializer = &self.deferred_credits_deserializer.credit_deserializer;
+        let (slot, rest_key) = split_array::<SLOT_KEY_SIZE>(rest_key)
+            .map(|(slot, rest)| (Slot::from_bytes_key(&slot), rest))
+            .ok_or_else(|| "missing slot".to_string())?;
+        let address: Address = parse_all(&deserializer.address_deserializer, rest_key)?;
+        let amount = parse_all(&deserializer.amount_deserializer, serialized_value)?;
+        Ok(DecodedEntry {
+            key: json!({ "slot": slot, "address": address }),
+            value: json!(amount),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use massa_db_exports::STATE_CF;
+    use massa_ledger_exports::{Key, KeySerializer};
+    use massa_models::amount::AmountSerializer;
+    use massa_serialization::Serializer;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_decode_ledger_balance() {
+        let address =
+            Address::from_str("AU12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x").unwrap();
+        let mut serialized_key = Vec::new();
+        KeySerializer::new(false)
+            .serialize(&Key::new(&address, KeyType::BALANCE), &mut serialized_key)
+            .unwrap();
+        let mut serialized_value = Vec::new();
+        AmountSerializer::new()
+            .serialize(&Amount::from_str("42").unwrap(), &mut serialized_value)
+            .unwrap();
+
+        let decoder = Decoder::new();
+        assert_eq!(Prefix::of_key(&serialized_key), Some(Prefix::Ledger));
+        assert_eq!(
+            decoder
+                .decode(STATE_CF, &serialized_key, &serialized_value)
+                .unwrap(),
+            DecodedEntry {
+                key: json!({ "address": address, "type": "balance" }),
+                value: json!(Amount::from_str("42").unwrap()),
+            }
+        );
+
+        // trailing bytes are reported along with the raw entry
+        serialized_value.push(0);
+        let decoded = decoder.decode_to_json(STATE_CF, &serialized_key, &serialized_value);
+        assert_eq!(decoded["prefix"], json!(LEDGER_PREFIX));
+        assert_eq!(decoded["value"], json!(to_hex(&serialized_value)));
+        assert!(decoded["error"].is_string());
+    }
+}
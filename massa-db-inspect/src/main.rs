@@ -0,0 +1,220 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Offline inspection of the database of a stopped massa node.
+//!
+//! The database is opened read-only, and its entries are decoded with the deserializers of the crates owning them.
+//!
+//! * `massa-db-inspect <path> info` prints the column families with their number of entries per prefix,
+//!   the change id (slot of the final state) and the XOF state hash.
+//! * `massa-db-inspect <path> dump [--cf <cf>] [--prefix <prefix>]` prints the decoded entries as JSON, one per line.
+//!   Entries that cannot be decoded are printed as hexadecimal along with the decoding error.
+//! * `massa-db-inspect <path> breakdown [--keys <prefix>] [--initial-rolls <path>] <output>` verifies the state hash
+//!   and writes its breakdown per key prefix as JSON (see `StateHashBreakdown`), listing the keys starting with `<prefix>`.
+//!   The entries are validated like the node does, which requires the initial rolls file of the node.
+//! * `massa-db-inspect <path> diff <first> <second>` prints the divergences between two breakdowns,
+//!   e.g. written by two nodes disagreeing on their state hash, as JSON, one per line.
+
+mod decode;
+mod validate;
+
+use crate::decode::{Decoder, Prefix};
+use crate::validate::StateValidator;
+use clap::{crate_version, Parser, Subcommand, ValueEnum};
+use massa_db_exports::{
+    MassaDBConfig, MassaDBController, MassaIteratorMode, ShareableMassaDBController,
+    StateHashBreakdown, METADATA_CF, STATE_CF, VERSIONING_CF,
+};
+use massa_db_worker::MassaDB;
+use massa_models::config::THREAD_COUNT;
+use parking_lot::RwLock;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[derive(Parser)]
+#[command(version = crate_version!())]
+struct Args {
+    /// Path of the node database (`disk_ledger_path` of the node settings)
+    path: PathBuf,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the column families, the change id and the state hash
+    Info,
+    /// Print the decoded entries of a column family as JSON, one per line
+    Dump {
+        /// Column family to dump
+        #[arg(long, value_enum, default_value_t = ColumnFamily::State)]
+        cf: ColumnFamily,
+        /// Only dump the entries whose key has this prefix
+        #[arg(long, value_enum)]
+        prefix: Option<Prefix>,
+    },
+    /// Verify the state hash, and write its breakdown per key prefix as JSON
+    Breakdown {
+        /// List the hash of the value of every key starting with this prefix (e.g. `ledger/`,
+        /// or an empty string for all keys), to find diverging keys
+        #[arg(long)]
+        keys: Option<String>,
+        /// Initial rolls file of the node (`initial_rolls_path` of the node settings),
+        /// needed to validate the entries like the node does
+        #[arg(long, default_value = "base_config/initial_rolls.json")]
+        initial_rolls: PathBuf,
+        /// Path of the breakdown to write
+        output: PathBuf,
+    },
+    /// Print the divergences between two state hash breakdowns as JSON, one per line.
+    /// The database path is ignored.
+    Diff {
+        /// Path of the first breakdown
+        first: PathBuf,
+        /// Path of the second breakdown
+        second: PathBuf,
+    },
+}
+
+/// Column families of the database
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ColumnFamily {
+    State,
+    Versioning,
+    Metadata,
+}
+
+impl ColumnFamily {
+    const ALL: [ColumnFamily; 3] = [
+        ColumnFamily::State,
+        ColumnFamily::Versioning,
+        ColumnFamily::Metadata,
+    ];
+
+    fn handle(&self) -> &'static str {
+        match self {
+            ColumnFamily::State => STATE_CF,
+            ColumnFamily::Versioning => VERSIONING_CF,
+            ColumnFamily::Metadata => METADATA_CF,
+        }
+    }
+}
+
+fn info(db: &MassaDB) -> anyhow::Result<()> {
+    println!("change id: {}", db.get_change_id()?);
+    println!("state hash: {}", db.get_xof_db_hash());
+    for cf in ColumnFamily::ALL {
+        let mut total = 0;
+        let mut count_per_prefix = BTreeMap::new();
+        for (key, _) in db.iterator_cf(cf.handle(), MassaIteratorMode::Start) {
+            total += 1;
+            let prefix = Prefix::of_key(&key).map_or("(unknown)", |prefix| prefix.as_str());
+            *count_per_prefix.entry(prefix).or_insert(0) += 1;
+        }
+        println!("column family {}: {} entries", cf.handle(), total);
+        if cf != ColumnFamily::Metadata {
+            for (prefix, count) in count_per_prefix {
+                println!("    {}: {} entries", prefix, count);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn dump(db: &MassaDB, cf: ColumnFamily, prefix: Option<Prefix>) -> anyhow::Result<()> {
+    let decoder = Decoder::new();
+    let entries: Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + '_> = match prefix {
+        Some(prefix) => Box::new(
+            db.prefix_iterator_cf(cf.handle(), prefix.as_str().as_bytes())
+                .take_while(move |(key, _)| key.starts_with(prefix.as_str().as_bytes())),
+        ),
+        None => db.iterator_cf(cf.handle(), MassaIteratorMode::Start),
+    };
+    let mut stdout = std::io::stdout().lock();
+    for (key, value) in entries {
+        writeln!(
+            stdout,
+            "{}",
+            decoder.decode_to_json(cf.handle(), &key, &value)
+        )?;
+    }
+    Ok(())
+}
+
+fn breakdown(
+    db: MassaDB,
+    keys: Option<String>,
+    initial_rolls: PathBuf,
+    output: PathBuf,
+) -> anyhow::Result<()> {
+    let db: ShareableMassaDBController = Arc::new(RwLock::new(Box::new(db)));
+    let validator = StateValidator::new(db.clone(), initial_rolls)?;
+    let breakdown = StateHashBreakdown::compute(&**db.read(), keys.as_deref(), |key, value| {
+        validator.is_key_value_valid(key, value)
+    })?;
+    let mut writer = BufWriter::new(File::create(output)?);
+    serde_json::to_writer_pretty(&mut writer, &breakdown)?;
+    writer.flush()?;
+    println!("stored state hash: {}", breakdown.stored_hash);
+    println!("computed state hash: {}", breakdown.computed_hash);
+    for (prefix, prefix_breakdown) in &breakdown.prefixes {
+        println!(
+            "    {}: {} entries, {} invalid",
+            prefix,
+            prefix_breakdown.count,
+            prefix_breakdown.invalid_keys.len()
+        );
+    }
+    if !breakdown.is_valid() {
+        anyhow::bail!("the state is not valid");
+    }
+    Ok(())
+}
+
+fn diff(first: PathBuf, second: PathBuf) -> anyhow::Result<()> {
+    let read = |path: PathBuf| -> anyhow::Result<StateHashBreakdown> {
+        Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+    };
+    let (first, second) = (read(first)?, read(second)?);
+    if first.slot != second.slot {
+        eprintln!(
+            "warning: comparing breakdowns at different slots {} and {}",
+            first.slot, second.slot
+        );
+    }
+    let mut stdout = std::io::stdout().lock();
+    for divergence in first.diff(&second) {
+        writeln!(stdout, "{}", serde_json::to_string(&divergence)?)?;
+    }
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let command = match args.command {
+        Command::Diff { first, second } => return diff(first, second),
+        command => command,
+    };
+
+    let db = MassaDB::new_read_only(MassaDBConfig {
+        path: args.path,
+        max_history_length: 0,
+        max_final_state_elements_size: 0,
+        max_versioning_elements_size: 0,
+        thread_count: THREAD_COUNT,
+    })?;
+
+    match command {
+        Command::Info => info(&db),
+        Command::Dump { cf, prefix } => dump(&db, cf, prefix),
+        Command::Breakdown {
+            keys,
+            initial_rolls,
+            output,
+        } => breakdown(db, keys, initial_rolls, output),
+        Command::Diff { .. } => unreachable!("diff does not open the database"),
+    }
+}
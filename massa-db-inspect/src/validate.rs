@@ -0,0 +1,141 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Validation of the state entries with the validators used by the node.
+
+use massa_async_pool::AsyncPoolConfig;
+use massa_db_exports::ShareableMassaDBController;
+use massa_executed_ops::{ExecutedDenunciationsConfig, ExecutedOpsConfig};
+use massa_final_state::{FinalState, FinalStateConfig};
+use massa_ledger_exports::LedgerConfig;
+use massa_ledger_worker::FinalLedger;
+use massa_models::address::Address;
+use massa_models::config::{
+    CHANNEL_SIZE, DENUNCIATION_EXPIRE_PERIODS, ENDORSEMENT_COUNT, GENESIS_KEY, GENESIS_TIMESTAMP,
+    INITIAL_DRAW_SEED, KEEP_EXECUTED_HISTORY_EXTRA_PERIODS, MAX_ASYNC_POOL_LENGTH,
+    MAX_DATASTORE_KEY_LENGTH, MAX_DATASTORE_VALUE_LENGTH, MAX_DEFERRED_CREDITS_LENGTH,
+    MAX_DENUNCIATIONS_PER_BLOCK_HEADER, MAX_DENUNCIATION_CHANGES_LENGTH, MAX_FUNCTION_NAME_LENGTH,
+    MAX_PARAMETERS_SIZE, MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH,
+    MIP_STORE_STATS_BLOCK_CONSIDERED, PERIODS_PER_CYCLE, POS_SAVED_CYCLES,
+    SELECTOR_DRAW_CACHE_SIZE, T0, THREAD_COUNT,
+};
+use massa_pos_exports::{PoSConfig, SelectorConfig, SelectorManager};
+use massa_pos_worker::start_selector_worker;
+use massa_versioning::versioning::{MipStatsConfig, MipStore};
+use num::rational::Ratio;
+use std::path::PathBuf;
+
+/// Validates the entries of the state column family with `FinalState::is_state_key_value_valid`,
+/// so that the tool and the node agree on the invalid keys.
+///
+/// The final state is built on top of the inspected database without modifying it.
+/// Building its PoS state requires a selector worker, stopped on drop, and the initial rolls of the node.
+pub struct StateValidator {
+    final_state: FinalState,
+    selector_manager: Box<dyn SelectorManager>,
+}
+
+impl StateValidator {
+    pub fn new(
+        db: ShareableMassaDBController,
+        initial_rolls_path: PathBuf,
+    ) -> anyhow::Result<Self> {
+        let ledger_config = LedgerConfig {
+            thread_count: THREAD_COUNT,
+            // only read when the initial ledger is loaded
+            initial_ledger_path: PathBuf::new(),
+            max_key_length: MAX_DATASTORE_KEY_LENGTH,
+            max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+        };
+        let final_state_config = FinalStateConfig {
+            ledger_config: ledger_config.clone(),
+            async_pool_config: AsyncPoolConfig {
+                max_length: MAX_ASYNC_POOL_LENGTH,
+                thread_count: THREAD_COUNT,
+                max_function_length: MAX_FUNCTION_NAME_LENGTH,
+                max_function_params_length: MAX_PARAMETERS_SIZE as u64,
+                max_key_length: MAX_DATASTORE_KEY_LENGTH as u32,
+            },
+            pos_config: PoSConfig {
+                periods_per_cycle: PERIODS_PER_CYCLE,
+                thread_count: THREAD_COUNT,
+                cycle_history_length: POS_SAVED_CYCLES,
+                max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
+                max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
+                max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
+                initial_deferred_credits_path: None,
+            },
+            executed_ops_config: ExecutedOpsConfig {
+                thread_count: THREAD_COUNT,
+                keep_executed_history_extra_periods: KEEP_EXECUTED_HISTORY_EXTRA_PERIODS,
+            },
+            executed_denunciations_config: ExecutedDenunciationsConfig {
+                denunciation_expire_periods: DENUNCIATION_EXPIRE_PERIODS,
+                thread_count: THREAD_COUNT,
+                endorsement_count: ENDORSEMENT_COUNT,
+                keep_executed_history_extra_periods: KEEP_EXECUTED_HISTORY_EXTRA_PERIODS,
+            },
+            // the history is not used to validate entries
+            final_history_length: 0,
+            thread_count: THREAD_COUNT,
+            periods_per_cycle: PERIODS_PER_CYCLE,
+            initial_seed_string: INITIAL_DRAW_SEED.into(),
+            initial_rolls_path,
+            endorsement_count: ENDORSEMENT_COUNT,
+            max_executed_denunciations_length: MAX_DENUNCIATION_CHANGES_LENGTH,
+            max_denunciations_per_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+            t0: T0,
+            genesis_timestamp: *GENESIS_TIMESTAMP,
+        };
+
+        let (mut selector_manager, selector_controller) = start_selector_worker(SelectorConfig {
+            max_draw_cache: SELECTOR_DRAW_CACHE_SIZE,
+            channel_size: CHANNEL_SIZE,
+            thread_count: THREAD_COUNT,
+            endorsement_count: ENDORSEMENT_COUNT,
+            periods_per_cycle: PERIODS_PER_CYCLE,
+            genesis_address: Address::from_public_key(&GENESIS_KEY.get_public_key()),
+        })?;
+        let mip_store = MipStore::try_from_db(
+            db.clone(),
+            MipStatsConfig {
+                block_count_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
+                // only used to warn about announced versions
+                warn_announced_version_ratio: Ratio::new(30, 100),
+            },
+        );
+        let final_state = mip_store
+            .map_err(anyhow::Error::from)
+            .and_then(|mip_store| {
+                Ok(FinalState::new(
+                    db.clone(),
+                    final_state_config,
+                    Box::new(FinalLedger::new(ledger_config, db)),
+                    selector_controller,
+                    mip_store,
+                    false,
+                )?)
+            });
+        match final_state {
+            Ok(final_state) => Ok(StateValidator {
+                final_state,
+                selector_manager,
+            }),
+            Err(err) => {
+                selector_manager.stop();
+                Err(err)
+            }
+        }
+    }
+
+    /// Checks that an entry of the state column family can be deserialized
+    pub fn is_key_value_valid(&self, serialized_key: &[u8], serialized_value: &[u8]) -> bool {
+        self.final_state
+            .is_state_key_value_valid(serialized_key, serialized_value)
+    }
+}
+
+impl Drop for StateValidator {
+    fn drop(&mut self) {
+        self.selector_manager.stop();
+    }
+}
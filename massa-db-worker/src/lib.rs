@@ -59,7 +59,14 @@
 //!    the updates (by querying only the cache)
 //! 3- Even after this process is finished (and as other things like consensus data are streamed),
 //!    we can send the updates
+//!
+//! # State archives
+//!
+//! The final state can also be exported to a single, compressed and versioned file (see `state_archive.rs`),
+//! and imported back after checking its hash.
 
 mod massa_db;
+mod state_archive;
 
 pub use crate::massa_db::*;
+pub use crate::state_archive::*;
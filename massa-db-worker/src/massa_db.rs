@@ -601,6 +601,34 @@ impl RawMassaDB<Slot, SlotSerializer, SlotDeserializer> {
             ],
         )?;
 
+        let massa_db = Self::from_db(db, config);
+
+        if massa_db.get_change_id().is_err() {
+            massa_db.set_initial_change_id(Slot {
+                period: 0,
+                thread: 0,
+            });
+        }
+
+        Ok(massa_db)
+    }
+
+    /// Returns a `MassaDB` instance opening an existing database in read-only mode,
+    /// e.g. to inspect the database of a stopped node. Writing to it fails.
+    pub fn new_read_only(config: MassaDBConfig) -> Result<Self, MassaDBError> {
+        let db = DB::open_cf_for_read_only(
+            &Options::default(),
+            &config.path,
+            [STATE_CF, METADATA_CF, VERSIONING_CF],
+            false,
+        )
+        .map_err(|e| MassaDBError::RocksDBError(format!("Can't open database: {}", e)))?;
+
+        Ok(Self::from_db(db, config))
+    }
+
+    /// Wraps an opened RocksDB database
+    fn from_db(db: DB, config: MassaDBConfig) -> Self {
         let db = Arc::new(db);
         let current_batch = Arc::new(Mutex::new(WriteBatch::default()));
 
@@ -609,7 +637,7 @@ impl RawMassaDB<Slot, SlotSerializer, SlotDeserializer> {
             (Included(0), Excluded(config.thread_count)),
         );
 
-        let massa_db = Self {
+        Self {
             db,
             config,
             change_history: BTreeMap::new(),
@@ -617,16 +645,7 @@ impl RawMassaDB<Slot, SlotSerializer, SlotDeserializer> {
             change_id_serializer: SlotSerializer::new(),
             change_id_deserializer,
             current_batch,
-        };
-
-        if massa_db.get_change_id().is_err() {
-            massa_db.set_initial_change_id(Slot {
-                period: 0,
-                thread: 0,
-            });
         }
-
-        Ok(massa_db)
     }
 }
 
@@ -881,6 +900,9 @@ mod test {
 
     use assert_matches::assert_matches;
     use massa_db_exports::MassaDBError::TimeError;
+    use massa_db_exports::{
+        StateDivergence, StateHashBreakdown, ASYNC_POOL_PREFIX, LEDGER_PREFIX, UNKNOWN_PREFIX,
+    };
     use parking_lot::RwLock;
     use tempfile::tempdir;
 
@@ -1801,4 +1823,101 @@ mod test {
         assert!(stream_batch_.is_err());
         assert!(stream_batch_.unwrap_err().to_string().contains("all our changes are strictly after last_change_id, we can't be sure we did not miss any"));
     }
+
+    #[test]
+    fn test_state_hash_breakdown() {
+        // Recompute the state hash per prefix, then diff the breakdowns of two diverging dbs
+
+        let new_db = |temp_dir: &std::path::Path| {
+            MassaDB::new(MassaDBConfig {
+                path: temp_dir.to_path_buf(),
+                max_history_length: 100,
+                max_final_state_elements_size: 100,
+                max_versioning_elements_size: 100,
+                thread_count: THREAD_COUNT,
+            })
+        };
+        let key = |prefix: &str, suffix: u8| [prefix.as_bytes(), &[suffix]].concat();
+        let temp_dir_db_1 = tempdir().expect("Unable to create a temp folder");
+        let temp_dir_db_2 = tempdir().expect("Unable to create a temp folder");
+        let mut db_1 = new_db(temp_dir_db_1.path());
+        let mut db_2 = new_db(temp_dir_db_2.path());
+
+        let batch = DBBatch::from([
+            (key(LEDGER_PREFIX, 1), Some(vec![1])),
+            (key(LEDGER_PREFIX, 2), Some(vec![2])),
+            (key(ASYNC_POOL_PREFIX, 1), Some(vec![3])),
+        ]);
+        db_1.write_batch(batch.clone(), DBBatch::new(), Some(Slot::new(1, 0)));
+        db_2.write_batch(batch, DBBatch::new(), Some(Slot::new(1, 0)));
+        db_2.write_batch(
+            DBBatch::from([
+                (key(LEDGER_PREFIX, 2), Some(vec![4])),
+                (key(LEDGER_PREFIX, 3), Some(vec![5])),
+                (vec![0], Some(vec![6])),
+            ]),
+            DBBatch::new(),
+            Some(Slot::new(1, 0)),
+        );
+
+        let breakdown_1 = StateHashBreakdown::compute(&db_1, Some(""), |_, _| true).unwrap();
+        assert!(breakdown_1.is_valid());
+        assert_eq!(breakdown_1.slot, Slot::new(1, 0));
+        assert_eq!(
+            breakdown_1.computed_hash,
+            db_1.get_xof_db_hash().to_string()
+        );
+        assert_eq!(breakdown_1.prefixes[LEDGER_PREFIX].count, 2);
+        assert_eq!(breakdown_1.prefixes[ASYNC_POOL_PREFIX].count, 1);
+
+        let breakdown_2 =
+            StateHashBreakdown::compute(&db_2, Some(""), |key, _| key != [0].as_slice()).unwrap();
+        assert!(!breakdown_2.is_valid());
+        assert_eq!(
+            breakdown_2.prefixes[UNKNOWN_PREFIX].invalid_keys,
+            vec!["00"]
+        );
+
+        let ledger_key = |suffix: u8| {
+            let prefix: String = LEDGER_PREFIX
+                .bytes()
+                .map(|byte| format!("{:02x}", byte))
+                .collect();
+            format!("{}{:02x}", prefix, suffix)
+        };
+        assert_eq!(
+            breakdown_1.diff(&breakdown_2),
+            vec![
+                StateDivergence::Value(ledger_key(2)),
+                StateDivergence::OnlyInSecond(ledger_key(3)),
+                StateDivergence::OnlyInSecond("00".to_string()),
+            ]
+        );
+
+        // keys are only compared within the listed prefix
+        let breakdown_1 =
+            StateHashBreakdown::compute(&db_1, Some(LEDGER_PREFIX), |_, _| true).unwrap();
+        assert!(breakdown_1.prefixes[ASYNC_POOL_PREFIX].keys.is_none());
+        let breakdown_2 =
+            StateHashBreakdown::compute(&db_2, Some(LEDGER_PREFIX), |_, _| true).unwrap();
+        assert_eq!(
+            breakdown_1.diff(&breakdown_2),
+            vec![
+                StateDivergence::Value(ledger_key(2)),
+                StateDivergence::OnlyInSecond(ledger_key(3)),
+                StateDivergence::Prefix(UNKNOWN_PREFIX.to_string()),
+            ]
+        );
+
+        // without keys, only the diverging prefixes are known
+        let breakdown_2 = StateHashBreakdown::compute(&db_2, None, |_, _| true).unwrap();
+        assert!(breakdown_2.is_valid());
+        assert_eq!(
+            breakdown_1.diff(&breakdown_2),
+            vec![
+                StateDivergence::Prefix(LEDGER_PREFIX.to_string()),
+                StateDivergence::Prefix(UNKNOWN_PREFIX.to_string()),
+            ]
+        );
+    }
 }
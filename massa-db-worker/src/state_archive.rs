@@ -0,0 +1,354 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! A state archive is a single, self-describing file holding the final state of a node,
+//! that can be shared between operators and imported to start a node from it.
+//!
+//! Layout of an archive:
+//! * a header: magic bytes, format version, slot of the final state, its hash (see `get_xof_db_hash`)
+//!   and the hash of the versioning entries
+//! * a sequence of chunks, each one holding:
+//!   * a tag telling the column family of its entries (`STATE_CF` or `VERSIONING_CF`)
+//!   * the number of entries, the lengths of the uncompressed and compressed payloads and the hash of the uncompressed one
+//!   * the compressed payload: length-prefixed keys and values
+//! * an end tag
+//!
+//! On import, every chunk is checked against its hash,
+//! and the hashes of the imported state and versioning entries are checked against the ones of the header.
+
+use lz4_flex::block::{compress, decompress_into, get_maximum_output_size};
+use massa_db_exports::{
+    MassaDBController, MassaDBError, MassaIteratorMode, StreamBatch, STATE_CF, VERSIONING_CF,
+};
+use massa_hash::{Hash, HashXof, HASH_SIZE_BYTES, HASH_XOF_SIZE_BYTES};
+use massa_models::slot::Slot;
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+/// Magic bytes starting every state archive
+const STATE_ARCHIVE_MAGIC: &[u8; 19] = b"MASSA-STATE-ARCHIVE";
+/// Current format version of state archives
+pub const STATE_ARCHIVE_VERSION: u32 = 1;
+/// Maximum size of the uncompressed payload of a chunk, above the size of any single entry
+const STATE_ARCHIVE_CHUNK_SIZE: usize = 16 * 1024 * 1024;
+
+const END_TAG: u8 = 0u8;
+const STATE_CHUNK_TAG: u8 = 1u8;
+const VERSIONING_CHUNK_TAG: u8 = 2u8;
+
+/// Description of the final state held by a state archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateArchiveHeader {
+    /// format version of the archive
+    pub version: u32,
+    /// slot of the final state
+    pub slot: Slot,
+    /// hash of the final state
+    pub state_hash: HashXof<HASH_XOF_SIZE_BYTES>,
+    /// hash of the versioning entries, which are not covered by `state_hash`
+    pub versioning_hash: Hash,
+}
+
+fn archive_error(message: impl std::fmt::Display) -> MassaDBError {
+    MassaDBError::StateArchiveError(message.to_string())
+}
+
+fn read_array<const N: usize, R: Read>(reader: &mut R) -> Result<[u8; N], MassaDBError> {
+    let mut buffer = [0u8; N];
+    reader.read_exact(&mut buffer).map_err(archive_error)?;
+    Ok(buffer)
+}
+
+/// Append a length-prefixed `(key, value)` entry to a chunk payload
+fn push_entry(payload: &mut Vec<u8>, key: &[u8], value: &[u8]) {
+    for field in [key, value] {
+        payload.extend((field.len() as u32).to_be_bytes());
+        payload.extend(field);
+    }
+}
+
+/// Hash of the versioning entries of a database, in key order
+fn versioning_hash(db: &dyn MassaDBController) -> Hash {
+    let mut payload = Vec::new();
+    for (key, value) in db.iterator_cf(VERSIONING_CF, MassaIteratorMode::Start) {
+        push_entry(&mut payload, &key, &value);
+    }
+    Hash::compute_from(&payload)
+}
+
+/// Split a chunk payload into its `(key, value)` entries
+fn parse_entries(
+    mut payload: &[u8],
+    entry_count: u32,
+) -> Result<BTreeMap<Vec<u8>, Vec<u8>>, MassaDBError> {
+    let mut next_field = || -> Result<Vec<u8>, MassaDBError> {
+        let len = read_array::<4, _>(&mut payload)?;
+        let len = u32::from_be_bytes(len) as usize;
+        if payload.len() < len {
+            return Err(archive_error("truncated chunk entry"));
+        }
+        let (field, rest) = payload.split_at(len);
+        payload = rest;
+        Ok(field.to_vec())
+    };
+    let mut entries = BTreeMap::new();
+    for _ in 0..entry_count {
+        let key = next_field()?;
+        let value = next_field()?;
+        entries.insert(key, value);
+    }
+    if !payload.is_empty() {
+        return Err(archive_error("unexpected data after the chunk entries"));
+    }
+    Ok(entries)
+}
+
+/// Read and check the header of a state archive
+pub fn read_state_archive_header<R: Read>(
+    reader: &mut R,
+) -> Result<StateArchiveHeader, MassaDBError> {
+    if &read_array::<19, _>(reader)? != STATE_ARCHIVE_MAGIC {
+        return Err(archive_error("not a state archive"));
+    }
+    let version = u32::from_be_bytes(read_array(reader)?);
+    if version != STATE_ARCHIVE_VERSION {
+        return Err(archive_error(format!(
+            "unsupported state archive version {}",
+            version
+        )));
+    }
+    let period = u64::from_be_bytes(read_array(reader)?);
+    let [thread] = read_array::<1, _>(reader)?;
+    Ok(StateArchiveHeader {
+        version,
+        slot: Slot::new(period, thread),
+        state_hash: HashXof(read_array(reader)?),
+        versioning_hash: Hash::from_bytes(&read_array::<HASH_SIZE_BYTES, _>(reader)?),
+    })
+}
+
+/// Export the final state of a database as a state archive
+///
+/// # Returns
+/// The header of the written archive
+pub fn export_state_archive<W: Write>(
+    db: &dyn MassaDBController,
+    writer: &mut W,
+) -> Result<StateArchiveHeader, MassaDBError> {
+    let header = StateArchiveHeader {
+        version: STATE_ARCHIVE_VERSION,
+        slot: db.get_change_id().map_err(archive_error)?,
+        state_hash: db.get_xof_db_hash(),
+        versioning_hash: versioning_hash(db),
+    };
+    let mut write = |bytes: &[u8]| writer.write_all(bytes).map_err(archive_error);
+
+    write(STATE_ARCHIVE_MAGIC)?;
+    write(&header.version.to_be_bytes())?;
+    write(&header.slot.period.to_be_bytes())?;
+    write(&[header.slot.thread])?;
+    write(&header.state_hash.0)?;
+    write(header.versioning_hash.to_bytes())?;
+
+    for (tag, handle_cf) in [
+        (STATE_CHUNK_TAG, STATE_CF),
+        (VERSIONING_CHUNK_TAG, VERSIONING_CF),
+    ] {
+        let mut payload = Vec::new();
+        let mut entry_count = 0u32;
+        let mut write_chunk = |payload: &mut Vec<u8>, entry_count: &mut u32| {
+            let compressed = compress(payload);
+            write(&[tag])?;
+            write(&entry_count.to_be_bytes())?;
+            write(&(payload.len() as u32).to_be_bytes())?;
+            write(&(compressed.len() as u32).to_be_bytes())?;
+            write(Hash::compute_from(payload).to_bytes())?;
+            write(&compressed)?;
+            payload.clear();
+            *entry_count = 0;
+            Ok::<(), MassaDBError>(())
+        };
+        for (key, value) in db.iterator_cf(handle_cf, MassaIteratorMode::Start) {
+            let entry_len = 8 + key.len() + value.len();
+            if entry_len > STATE_ARCHIVE_CHUNK_SIZE {
+                return Err(archive_error(
+                    "an entry is too large for a state archive chunk",
+                ));
+            }
+            if payload.len() + entry_len > STATE_ARCHIVE_CHUNK_SIZE {
+                write_chunk(&mut payload, &mut entry_count)?;
+            }
+            push_entry(&mut payload, &key, &value);
+            entry_count += 1;
+        }
+        if entry_count > 0 {
+            write_chunk(&mut payload, &mut entry_count)?;
+        }
+    }
+    write(&[END_TAG])?;
+
+    Ok(header)
+}
+
+/// Import a state archive into an empty database
+///
+/// Every chunk is checked against its hash, and the hash of the imported state against the one of the header.
+/// On failure, the database must be discarded.
+///
+/// # Returns
+/// The header of the imported archive
+pub fn import_state_archive<R: Read>(
+    db: &mut dyn MassaDBController,
+    reader: &mut R,
+) -> Result<StateArchiveHeader, MassaDBError> {
+    let header = read_state_archive_header(reader)?;
+    if db
+        .iterator_cf(STATE_CF, MassaIteratorMode::Start)
+        .next()
+        .is_some()
+    {
+        return Err(archive_error(
+            "a state archive can only be imported into an empty database",
+        ));
+    }
+    db.reset(header.slot);
+
+    let empty_batch = || StreamBatch {
+        new_elements: BTreeMap::new(),
+        updates_on_previous_elements: BTreeMap::new(),
+        change_id: header.slot,
+    };
+    loop {
+        let [tag] = read_array::<1, _>(reader)?;
+        if tag == END_TAG {
+            break;
+        }
+        let entry_count = u32::from_be_bytes(read_array(reader)?);
+        let payload_len = u32::from_be_bytes(read_array(reader)?) as usize;
+        let compressed_len = u32::from_be_bytes(read_array(reader)?) as usize;
+        let payload_hash = Hash::from_bytes(&read_array::<HASH_SIZE_BYTES, _>(reader)?);
+        // bound the allocations before trusting the lengths of the archive
+        if payload_len > STATE_ARCHIVE_CHUNK_SIZE
+            || compressed_len > get_maximum_output_size(STATE_ARCHIVE_CHUNK_SIZE)
+        {
+            return Err(archive_error("chunk larger than the maximum chunk size"));
+        }
+        let mut compressed = vec![0u8; compressed_len];
+        reader.read_exact(&mut compressed).map_err(archive_error)?;
+        let mut payload = vec![0u8; payload_len];
+        if decompress_into(&compressed, &mut payload).map_err(archive_error)? != payload_len {
+            return Err(archive_error("truncated chunk"));
+        }
+        if Hash::compute_from(&payload) != payload_hash {
+            return Err(archive_error("corrupted chunk"));
+        }
+        let batch = StreamBatch {
+            new_elements: parse_entries(&payload, entry_count)?,
+            ..empty_batch()
+        };
+        match tag {
+            STATE_CHUNK_TAG => db.write_batch_bootstrap_client(batch, empty_batch())?,
+            VERSIONING_CHUNK_TAG => db.write_batch_bootstrap_client(empty_batch(), batch)?,
+            _ => return Err(archive_error(format!("unknown chunk tag {}", tag))),
+        };
+    }
+    db.flush()?;
+
+    let state_hash = db.get_xof_db_hash();
+    if state_hash != header.state_hash {
+        return Err(archive_error(format!(
+            "the imported state hash {} does not match the archive one {}",
+            state_hash, header.state_hash
+        )));
+    }
+    if versioning_hash(db) != header.versioning_hash {
+        return Err(archive_error(
+            "the imported versioning entries do not match the archive ones",
+        ));
+    }
+    Ok(header)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MassaDB;
+    use massa_db_exports::{DBBatch, MassaDBConfig};
+    use massa_models::config::THREAD_COUNT;
+    use tempfile::{tempdir, TempDir};
+
+    fn new_db() -> (MassaDB, TempDir) {
+        let temp_dir = tempdir().expect("Unable to create a temp folder");
+        let db = MassaDB::new(MassaDBConfig {
+            path: temp_dir.path().to_path_buf(),
+            max_history_length: 100,
+            max_final_state_elements_size: 100,
+            max_versioning_elements_size: 100,
+            thread_count: THREAD_COUNT,
+        });
+        (db, temp_dir)
+    }
+
+    fn dump_column(db: &MassaDB, handle_cf: &str) -> BTreeMap<Vec<u8>, Vec<u8>> {
+        db.iterator_cf(handle_cf, MassaIteratorMode::Start)
+            .collect()
+    }
+
+    #[test]
+    fn test_state_archive() {
+        let (mut db, _dir) = new_db();
+        let mut batch = DBBatch::new();
+        let mut versioning_batch = DBBatch::new();
+        for i in 0..100u8 {
+            batch.insert(vec![1, i], Some(vec![i; 10]));
+        }
+        versioning_batch.insert(vec![2], Some(vec![3]));
+        db.write_batch(batch, versioning_batch, Some(Slot::new(3, 1)));
+
+        let mut archive = Vec::new();
+        let header = export_state_archive(&db, &mut archive).unwrap();
+        assert_eq!(header.slot, Slot::new(3, 1));
+        assert_eq!(
+            read_state_archive_header(&mut archive.as_slice()).unwrap(),
+            header
+        );
+
+        // the imported state is identical
+        let (mut imported, _imported_dir) = new_db();
+        assert_eq!(
+            import_state_archive(&mut imported, &mut archive.as_slice()).unwrap(),
+            header
+        );
+        assert_eq!(imported.get_change_id().unwrap(), Slot::new(3, 1));
+        assert_eq!(imported.get_xof_db_hash(), db.get_xof_db_hash());
+        for handle_cf in [STATE_CF, VERSIONING_CF] {
+            assert_eq!(
+                dump_column(&imported, handle_cf),
+                dump_column(&db, handle_cf)
+            );
+        }
+
+        // an archive cannot be imported twice
+        assert!(import_state_archive(&mut imported, &mut archive.as_slice()).is_err());
+
+        // corrupted archives are rejected
+        let (mut corrupted_db, _corrupted_dir) = new_db();
+        let mut corrupted = archive.clone();
+        let last = corrupted.len() - 2;
+        corrupted[last] ^= 1;
+        assert!(import_state_archive(&mut corrupted_db, &mut corrupted.as_slice()).is_err());
+
+        // archives with tampered versioning entries are rejected
+        let header_len =
+            STATE_ARCHIVE_MAGIC.len() + 4 + 8 + 1 + HASH_XOF_SIZE_BYTES + HASH_SIZE_BYTES;
+        let (mut tampered_db, _tampered_dir) = new_db();
+        let mut tampered = archive.clone();
+        tampered[header_len - 1] ^= 1;
+        assert!(import_state_archive(&mut tampered_db, &mut tampered.as_slice()).is_err());
+
+        // chunks announcing a payload above the maximum chunk size are rejected before allocating it
+        let (mut oversized_db, _oversized_dir) = new_db();
+        let mut oversized = archive.clone();
+        oversized[header_len + 5..header_len + 9].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(import_state_archive(&mut oversized_db, &mut oversized.as_slice()).is_err());
+    }
+}
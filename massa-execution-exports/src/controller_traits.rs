@@ -3,16 +3,21 @@
 //! This module exports generic traits representing interfaces for interacting with the Execution worker
 
 use crate::types::{
-    ExecutionBlockMetadata, ExecutionQueryRequest, ExecutionQueryResponse, ReadOnlyExecutionRequest,
+    AsyncPoolMessage, AsyncPoolMessageFilter, ExecutionBlockMetadata, ExecutionQueryRequest,
+    ExecutionQueryResponse, OperationCostEstimation, ReadOnlyExecutionRequest,
+    ReadOnlyMulticallOutput, ReadOnlyMulticallRequest,
 };
 use crate::ExecutionError;
 use crate::{ExecutionAddressInfo, ReadOnlyExecutionOutput};
+use massa_async_pool::AsyncMessageId;
 use massa_models::address::Address;
 use massa_models::amount::Amount;
 use massa_models::block_id::BlockId;
 use massa_models::denunciation::DenunciationIndex;
-use massa_models::execution::EventFilter;
-use massa_models::operation::OperationId;
+use massa_models::execution::{
+    AddressOperation, EventFilter, ExecutionTraceFrame, OperationCheckFailure,
+};
+use massa_models::operation::{OperationId, OperationType, SecureShareOperation};
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashMap;
 use massa_models::slot::Slot;
@@ -47,6 +52,28 @@ pub trait ExecutionController: Send + Sync {
     /// * operation id
     fn get_filtered_sc_output_event(&self, filter: EventFilter) -> Vec<SCOutputEvent>;
 
+    /// Get at most `limit` final operations sent or received by an address,
+    /// from the most recent to the oldest one.
+    /// If `cursor` is set, only the operations older than it are returned.
+    ///
+    /// Fails if the operation history index is disabled.
+    fn get_address_operations(
+        &self,
+        address: &Address,
+        cursor: Option<OperationId>,
+        limit: usize,
+    ) -> Result<Vec<AddressOperation>, ExecutionError>;
+
+    /// Get at most `limit` messages of the asynchronous pool matching a filter,
+    /// in the final and candidate states, ordered by id (highest priority first).
+    /// If `cursor` is set, only the messages after it are returned.
+    fn get_async_pool_messages(
+        &self,
+        filter: AsyncPoolMessageFilter,
+        cursor: Option<AsyncMessageId>,
+        limit: usize,
+    ) -> Vec<AsyncPoolMessage>;
+
     /// Get the final and active values of balance.
     ///
     /// # Return value
@@ -94,6 +121,67 @@ pub trait ExecutionController: Send + Sync {
         req: ReadOnlyExecutionRequest,
     ) -> Result<ReadOnlyExecutionOutput, ExecutionError>;
 
+    /// Execute a sequence of read-only executions in a single speculative context,
+    /// without causing modifications to the consensus state.
+    /// Each execution sees the state changes of the previous ones.
+    ///
+    /// # arguments
+    /// * `req`: the executions to run, in order
+    ///
+    /// # returns
+    /// The output of each execution along with the state changes of the whole sequence,
+    /// or an error if the sequence could not be started.
+    fn execute_readonly_multicall(
+        &self,
+        req: ReadOnlyMulticallRequest,
+    ) -> Result<ReadOnlyMulticallOutput, ExecutionError>;
+
+    /// Estimate the gas and the storage costs of an unsigned operation,
+    /// by executing it in read-only mode on top of the current speculative state.
+    ///
+    /// # arguments
+    /// * `sender_address`: address that would send the operation
+    /// * `operation`: content of the operation
+    ///
+    /// # returns
+    /// The estimated costs of the operation, or an error if its execution failed.
+    fn estimate_operation(
+        &self,
+        sender_address: Address,
+        operation: OperationType,
+    ) -> Result<OperationCostEstimation, ExecutionError>;
+
+    /// Dry-run a batch of signed operations: run the checks done before including them in a block,
+    /// then execute them in order on top of the current speculative state, without causing modifications to it.
+    /// Each operation sees the state changes of the previous ones.
+    ///
+    /// # arguments
+    /// * `operations`: the operations to check, in order
+    ///
+    /// # returns
+    /// For each operation, the reason why it would be rejected or would fail, if any,
+    /// or an error if the check could not be run.
+    fn check_operations(
+        &self,
+        operations: Vec<SecureShareOperation>,
+    ) -> Result<Vec<Result<(), OperationCheckFailure>>, ExecutionError>;
+
+    /// Execute again an operation of the active history with the call tracer enabled.
+    /// The operation sees the state it was originally executed on: the asynchronous messages of its slot
+    /// and the operations before it in its block are executed again first.
+    /// Nothing is applied to the consensus state.
+    /// The request fails with `ExecutionError::ReadOnlyTimeout` if the trace is not ready within the read-only request timeout.
+    ///
+    /// # arguments
+    /// * `operation_id`: id of an operation included in a block of the active history
+    ///
+    /// # returns
+    /// The call trace of the operation, or an error if the operation could not be executed again.
+    fn trace_operation(
+        &self,
+        operation_id: OperationId,
+    ) -> Result<ExecutionTraceFrame, ExecutionError>;
+
     /// Check if a denunciation has been executed given a `DenunciationIndex`
     /// (speculative, final)
     fn get_denunciation_execution_status(
@@ -67,6 +67,15 @@ pub enum ExecutionError {
 
     /// Factory error: {0}
     FactoryError(#[from] FactoryError),
+
+    /// Operation history index is disabled
+    OperationHistoryDisabled,
+
+    /// Read-only execution timeout: {0}
+    ReadOnlyTimeout(String),
+
+    /// Read-only execution snapshot expired: {0}
+    ReadOnlySnapshotExpired(String),
 }
 
 /// Execution query errors
@@ -74,4 +83,6 @@ pub enum ExecutionError {
 pub enum ExecutionQueryError {
     /// Not found: {0}
     NotFound(String),
+    /// Not available: {0}
+    NotAvailable(String),
 }
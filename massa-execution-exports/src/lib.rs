@@ -58,15 +58,17 @@ pub use channels::ExecutionChannels;
 pub use controller_traits::MockExecutionController;
 pub use controller_traits::{ExecutionController, ExecutionManager};
 pub use error::{ExecutionError, ExecutionQueryError};
-pub use event_store::EventStore;
+pub use event_store::{is_event_matching_filter, EventStore};
 pub use massa_sc_runtime::GasCosts;
 pub use settings::{ExecutionConfig, StorageCostsConstants};
 pub use types::{
-    ExecutedBlockInfo, ExecutionAddressInfo, ExecutionBlockMetadata, ExecutionOutput,
-    ExecutionQueryCycleInfos, ExecutionQueryExecutionStatus, ExecutionQueryRequest,
-    ExecutionQueryRequestItem, ExecutionQueryResponse, ExecutionQueryResponseItem,
-    ExecutionQueryStakerInfo, ExecutionStackElement, ReadOnlyCallRequest, ReadOnlyExecutionOutput,
-    ReadOnlyExecutionRequest, ReadOnlyExecutionTarget, SlotExecutionOutput,
+    AsyncPoolMessage, AsyncPoolMessageFilter, ExecutedBlockInfo, ExecutedBlockOperation,
+    ExecutionAddressInfo, ExecutionBlockMetadata, ExecutionOutput, ExecutionQueryCycleInfos,
+    ExecutionQueryExecutionStatus, ExecutionQueryRequest, ExecutionQueryRequestItem,
+    ExecutionQueryResponse, ExecutionQueryResponseItem, ExecutionQueryStakerInfo,
+    ExecutionStackElement, OperationCostEstimation, ReadOnlyCallRequest, ReadOnlyExecutionOutput,
+    ReadOnlyExecutionRequest, ReadOnlyExecutionTarget, ReadOnlyMulticallCallOutput,
+    ReadOnlyMulticallOutput, ReadOnlyMulticallRequest, SlotExecutionOutput,
 };
 
 #[cfg(any(feature = "test-exports", feature = "gas_calibration"))]
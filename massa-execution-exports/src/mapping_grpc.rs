@@ -226,6 +226,13 @@ fn to_execution_query_result(
                 },
             )
         }
+        ExecutionQueryResponseItem::DatastoreEntries(result) => {
+            grpc_api::execution_query_response_item::ResponseItem::VecBytes(
+                grpc_model::ArrayOfBytesWrapper {
+                    items: result.into_iter().map(|(key, _value)| key).collect(),
+                },
+            )
+        }
         ExecutionQueryResponseItem::DeferredCredits(result) => {
             grpc_api::execution_query_response_item::ResponseItem::DeferredCredits(
                 grpc_api::DeferredCreditsEntryWrapper {
@@ -349,6 +356,10 @@ impl From<ExecutionQueryError> for grpc_model::Error {
                 code: 404,
                 message: error,
             },
+            ExecutionQueryError::NotAvailable(error) => grpc_model::Error {
+                code: 410,
+                message: error,
+            },
         }
     }
 }
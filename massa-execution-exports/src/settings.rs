@@ -24,8 +24,26 @@ pub struct StorageCostsConstants {
 pub struct ExecutionConfig {
     /// read-only execution request queue length
     pub readonly_queue_length: usize,
-    /// maximum number of SC output events kept in cache
+    /// number of threads executing read-only requests, at least 1
+    pub readonly_executor_count: usize,
+    /// maximum time a read-only request can spend queued and running before its caller stops waiting for it
+    pub readonly_request_timeout: MassaTime,
+    /// maximum number of SC output events kept in the final event store
     pub max_final_events: usize,
+    /// number of periods during which final SC output events are kept in the final event store
+    pub event_store_retention_periods: u64,
+    /// Path to the final event store
+    pub event_store_path: PathBuf,
+    /// whether to index the final operations involving each address
+    pub operation_history_enabled: bool,
+    /// Path to the operation history index
+    pub operation_history_path: PathBuf,
+    /// whether to keep the final values overwritten by each final slot, to answer point-in-time queries
+    pub state_history_enabled: bool,
+    /// number of periods during which the overwritten final values are kept
+    pub state_history_retention_periods: u64,
+    /// Path to the final state history
+    pub state_history_path: PathBuf,
     /// maximum available gas for asynchronous messages execution
     pub max_async_gas: u64,
     /// constant cost for async messages
@@ -68,6 +86,8 @@ pub struct ExecutionConfig {
     pub storage_costs_constants: StorageCostsConstants,
     /// Max gas for read only executions
     pub max_read_only_gas: u64,
+    /// Safety margin, in percent of the gas used, added to the max gas suggested by operation estimations
+    pub gas_estimation_margin_percent: u64,
     /// Gas costs
     pub gas_costs: GasCosts,
     /// Gas used by a transaction, a roll buy or a roll sell)
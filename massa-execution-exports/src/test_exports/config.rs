@@ -21,7 +21,16 @@ impl Default for ExecutionConfig {
 
         Self {
             readonly_queue_length: 100,
+            readonly_executor_count: 2,
+            readonly_request_timeout: MassaTime::from_millis(60000),
             max_final_events: 1000,
+            event_store_retention_periods: 1000,
+            event_store_path: TempDir::new().unwrap().path().to_path_buf(),
+            operation_history_enabled: false,
+            operation_history_path: TempDir::new().unwrap().path().to_path_buf(),
+            state_history_enabled: false,
+            state_history_retention_periods: 1000,
+            state_history_path: TempDir::new().unwrap().path().to_path_buf(),
             max_async_gas: MAX_ASYNC_GAS,
             async_msg_cst_gas_cost: ASYNC_MSG_CST_GAS_COST,
             thread_count: THREAD_COUNT,
@@ -42,6 +51,7 @@ impl Default for ExecutionConfig {
             max_datastore_value_size: MAX_DATASTORE_VALUE_LENGTH,
             storage_costs_constants,
             max_read_only_gas: 1_000_000_000,
+            gas_estimation_margin_percent: 20,
             gas_costs: GasCosts::new(
                 concat!(
                     env!("CARGO_MANIFEST_DIR"),
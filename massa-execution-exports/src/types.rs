@@ -2,18 +2,19 @@
 
 //! This file exports useful types used to interact with the execution worker
 
-use crate::error::ExecutionQueryError;
+use crate::error::{ExecutionError, ExecutionQueryError};
 use crate::event_store::EventStore;
+use massa_async_pool::{AsyncMessage, AsyncMessageId};
 use massa_final_state::StateChanges;
 use massa_hash::Hash;
 use massa_models::block_id::BlockId;
 use massa_models::bytecode::Bytecode;
-use massa_models::datastore::Datastore;
+use massa_models::datastore::{Datastore, DatastoreRange};
 use massa_models::denunciation::DenunciationIndex;
-use massa_models::execution::EventFilter;
+use massa_models::execution::{EventFilter, ExecutionTraceFrame, StateOverride};
 use massa_models::operation::OperationId;
 use massa_models::output_event::SCOutputEvent;
-use massa_models::prehash::PreHashSet;
+use massa_models::prehash::{PreHashMap, PreHashSet};
 use massa_models::{
     address::Address, address::ExecutionAddressCycleInfo, amount::Amount, slot::Slot,
 };
@@ -58,6 +59,14 @@ pub enum ExecutionQueryRequestItem {
     AddressBalanceCandidate(Address),
     /// gets the balance (final) of an address, returns ExecutionQueryResponseItem::Amount(balance) or an error if the address is not found
     AddressBalanceFinal(Address),
+    /// gets the balance of an address at the output of a slot, returns ExecutionQueryResponseItem::Amount(balance) or an error if the address is not found
+    /// or if the slot is not in the active history nor in the retained final state history
+    AddressBalanceAtSlot {
+        /// Address for which to query the balance
+        addr: Address,
+        /// Slot at the output of which the balance is queried
+        slot: Slot,
+    },
     /// gets the bytecode (candidate) of an address, returns ExecutionQueryResponseItem::Bytecode(bytecode) or an error if the address is not found
     AddressBytecodeCandidate(Address),
     /// gets the bytecode (final) of an address, returns ExecutionQueryResponseItem::Bytecode(bytecode) or an error if the address is not found
@@ -76,6 +85,24 @@ pub enum ExecutionQueryRequestItem {
         /// Filter only entries whose key starts with a prefix
         prefix: Vec<u8>,
     },
+    /// gets a range of datastore entries (candidate) of an address, returns ExecutionQueryResponseItem::DatastoreEntries(entries) or an error if the address is not found
+    AddressDatastoreRangeCandidate {
+        /// Address for which to query the datastore
+        addr: Address,
+        /// Range of keys to scan
+        range: DatastoreRange,
+        /// Whether to return the values along with the keys
+        with_values: bool,
+    },
+    /// gets a range of datastore entries (final) of an address, returns ExecutionQueryResponseItem::DatastoreEntries(entries) or an error if the address is not found
+    AddressDatastoreRangeFinal {
+        /// Address for which to query the datastore
+        addr: Address,
+        /// Range of keys to scan
+        range: DatastoreRange,
+        /// Whether to return the values along with the keys
+        with_values: bool,
+    },
     /// gets a datastore value (candidate) for an address, returns ExecutionQueryResponseItem::DatastoreValue(keys) or an error if the address or key is not found
     AddressDatastoreValueCandidate {
         /// Address for which to query the datastore
@@ -90,6 +117,16 @@ pub enum ExecutionQueryRequestItem {
         /// Key of the entry
         key: Vec<u8>,
     },
+    /// gets a datastore value for an address at the output of a slot, returns ExecutionQueryResponseItem::DatastoreValue(keys) or an error if the address or key is not found
+    /// or if the slot is not in the active history nor in the retained final state history
+    AddressDatastoreValueAtSlot {
+        /// Address for which to query the datastore
+        addr: Address,
+        /// Key of the entry
+        key: Vec<u8>,
+        /// Slot at the output of which the value is queried
+        slot: Slot,
+    },
 
     /// gets the execution status (candidate) for an operation, returns ExecutionQueryResponseItem::ExecutionStatus(status)
     OpExecutionStatusCandidate(OperationId),
@@ -104,6 +141,14 @@ pub enum ExecutionQueryRequestItem {
     AddressRollsCandidate(Address),
     /// gets the roll count (final) of an address, returns ExecutionQueryResponseItem::RollCount(rolls) or an error if the address is not found
     AddressRollsFinal(Address),
+    /// gets the roll count of an address at the output of a slot, returns ExecutionQueryResponseItem::RollCount(rolls)
+    /// or an error if the slot is not in the active history nor in the retained final state history
+    AddressRollsAtSlot {
+        /// Address for which to query the roll count
+        addr: Address,
+        /// Slot at the output of which the roll count is queried
+        slot: Slot,
+    },
     /// gets the deferred credits (candidate) of an address, returns ExecutionQueryResponseItem::DeferredCredits(deferred_credits) or an error if the address is not found
     AddressDeferredCreditsCandidate(Address),
     /// gets the deferred credits (final) of an address, returns ExecutionQueryResponseItem::DeferredCredits(deferred_credits) or an error if the address is not found
@@ -135,6 +180,8 @@ pub enum ExecutionQueryResponseItem {
     DatastoreValue(Vec<u8>),
     /// list of keys
     KeyList(BTreeSet<Vec<u8>>),
+    /// datastore entries in scan order, with their values if they were requested
+    DatastoreEntries(Vec<(Vec<u8>, Option<Vec<u8>>)>),
     /// deferred credits value
     DeferredCredits(BTreeMap<Slot, Amount>),
     /// execution status value
@@ -203,6 +250,29 @@ pub struct ExecutionAddressInfo {
     pub cycle_infos: Vec<ExecutionAddressCycleInfo>,
 }
 
+/// Filter of the asynchronous messages of the pool.
+/// A message matches if it satisfies all the criteria that are set.
+#[derive(Clone, Debug, Default)]
+pub struct AsyncPoolMessageFilter {
+    /// address that sent the message
+    pub sender: Option<Address>,
+    /// address the message is sent to
+    pub destination: Option<Address>,
+    /// id of the message
+    pub message_id: Option<AsyncMessageId>,
+}
+
+/// An asynchronous message of the pool, as seen by the final and the candidate states
+#[derive(Clone, Debug)]
+pub struct AsyncPoolMessage {
+    /// id of the message
+    pub id: AsyncMessageId,
+    /// the message in the final state, if it is there
+    pub final_message: Option<AsyncMessage>,
+    /// the message in the candidate state, if it is there
+    pub candidate_message: Option<AsyncMessage>,
+}
+
 /// structure describing the output of the execution of a slot
 #[derive(Debug, Clone)]
 pub enum SlotExecutionOutput {
@@ -222,6 +292,19 @@ pub struct ExecutedBlockInfo {
     pub current_version: u32,
     /// Announced network version (see Versioning doc)
     pub announced_version: Option<u32>,
+    /// Operations of the block, in the order in which they appear in the block
+    pub operations: Vec<ExecutedBlockOperation>,
+}
+
+/// structure storing an operation of an executed block along with the addresses it involves
+#[derive(Debug, Clone)]
+pub struct ExecutedBlockOperation {
+    /// Operation id
+    pub id: OperationId,
+    /// Address of the creator of the operation
+    pub sender: Address,
+    /// Recipient of a transaction or target of a smart contract call
+    pub recipient: Option<Address>,
 }
 
 /// structure describing the output of a single execution
@@ -244,8 +327,12 @@ pub struct ReadOnlyExecutionOutput {
     pub out: ExecutionOutput,
     /// Gas cost for this execution, with needed adjustments
     pub gas_cost: u64,
+    /// Net storage costs paid by the execution
+    pub storage_cost: Amount,
     /// Returned value from the module call
     pub call_result: Vec<u8>,
+    /// Call trace of the execution, if it was requested
+    pub trace: Option<ExecutionTraceFrame>,
 }
 
 /// structure describing different types of read-only execution request
@@ -261,6 +348,54 @@ pub struct ReadOnlyExecutionRequest {
     pub coins: Option<Amount>,
     /// Fee
     pub fee: Option<Amount>,
+    /// Overrides of the state of some addresses, only applied to this execution
+    pub state_overrides: PreHashMap<Address, StateOverride>,
+    /// Whether to record the call trace of the execution
+    pub trace: bool,
+}
+
+/// structure describing a sequence of read-only executions sharing a single speculative state
+#[derive(Debug, Clone)]
+pub struct ReadOnlyMulticallRequest {
+    /// Executions to run in order, each one seeing the state changes of the previous ones.
+    /// The state overrides of all the executions are applied before the first one.
+    pub calls: Vec<ReadOnlyExecutionRequest>,
+}
+
+/// structure describing the output of one of the executions of a read-only multicall
+#[derive(Debug, Clone)]
+pub struct ReadOnlyMulticallCallOutput {
+    /// Events emitted by the execution
+    pub events: EventStore,
+    /// Gas cost for this execution, with needed adjustments
+    pub gas_cost: u64,
+    /// Returned value from the module call
+    pub call_result: Vec<u8>,
+    /// Call trace of the execution, if it was requested
+    pub trace: Option<ExecutionTraceFrame>,
+}
+
+/// structure describing the estimated costs of an operation, obtained by executing it in read-only mode
+#[derive(Debug, Clone)]
+pub struct OperationCostEstimation {
+    /// Gas used by the operation in the current speculative state
+    pub gas_used: u64,
+    /// Gas to set as the max gas of the operation: the gas used plus a safety margin
+    pub max_gas: u64,
+    /// Net storage costs the operation would pay
+    pub storage_cost: Amount,
+}
+
+/// structure describing the output of a read-only multicall
+#[derive(Debug, Clone)]
+pub struct ReadOnlyMulticallOutput {
+    /// Slot at which the executions were run
+    pub slot: Slot,
+    /// Outputs of the executions, in order.
+    /// The sequence stops at the first failed execution, whose state changes are dropped.
+    pub calls: Vec<Result<ReadOnlyMulticallCallOutput, ExecutionError>>,
+    /// State changes caused by the whole sequence of successful executions
+    pub state_changes: StateChanges,
 }
 
 /// structure describing different possible targets of a read-only execution request
@@ -10,11 +10,13 @@ use massa_models::{
 };
 use massa_pos_exports::DeferredCredits;
 use std::collections::VecDeque;
+use std::sync::Arc;
 
 #[derive(Default)]
 /// History of the outputs of recently executed slots.
 /// Slots should be consecutive, oldest at the beginning and latest at the back.
-pub(crate) struct ActiveHistory(pub VecDeque<ExecutionOutput>);
+/// Outputs are shared, so that copies of the history handed to read-only executions only clone pointers.
+pub(crate) struct ActiveHistory(pub VecDeque<Arc<ExecutionOutput>>);
 
 /// Result of a lazy, active history search
 pub enum HistorySearchResult<T> {
@@ -46,6 +48,18 @@ impl ActiveHistory {
         }
     }
 
+    /// Get the slot of the block including an operation, if that block is in the active history
+    pub fn fetch_operation_slot(&self, op_id: &OperationId) -> Option<Slot> {
+        self.0
+            .iter()
+            .find(|output| {
+                output.block_info.as_ref().map_or(false, |block_info| {
+                    block_info.operations.iter().any(|op| &op.id == op_id)
+                })
+            })
+            .map(|output| output.slot)
+    }
+
     /// Lazily query (from end to beginning) the active list of executed ops to check if an op was executed.
     ///
     /// Returns a `HistorySearchResult`.
@@ -116,18 +130,14 @@ impl ActiveHistory {
     ///
     /// Returns a `HistorySearchResult`.
     pub fn fetch_balance(&self, addr: &Address) -> HistorySearchResult<Amount> {
-        for output in self.0.iter().rev() {
-            match output.state_changes.ledger_changes.0.get(addr) {
-                Some(SetUpdateOrDelete::Set(v)) => return HistorySearchResult::Present(v.balance),
-                Some(SetUpdateOrDelete::Update(LedgerEntryUpdate {
-                    balance: SetOrKeep::Set(v),
-                    ..
-                })) => return HistorySearchResult::Present(*v),
-                Some(SetUpdateOrDelete::Delete) => return HistorySearchResult::Absent,
-                _ => (),
-            }
-        }
-        HistorySearchResult::NoInfo
+        fetch_balance_in(self.0.iter().rev().map(Arc::as_ref), addr)
+    }
+
+    /// Lazily query (from `slot` to beginning) the active balance of an address at the output of `slot`.
+    ///
+    /// Returns a `HistorySearchResult`.
+    pub fn fetch_balance_at(&self, addr: &Address, slot: &Slot) -> HistorySearchResult<Amount> {
+        fetch_balance_in(self.outputs_until(slot), addr)
     }
 
     /// Lazily query (from end to beginning) the active bytecode of an address after a given index.
@@ -158,28 +168,19 @@ impl ActiveHistory {
         addr: &Address,
         key: &[u8],
     ) -> HistorySearchResult<Vec<u8>> {
-        for output in self.0.iter().rev() {
-            match output.state_changes.ledger_changes.0.get(addr) {
-                Some(SetUpdateOrDelete::Set(LedgerEntry { datastore, .. })) => {
-                    match datastore.get(key) {
-                        Some(value) => return HistorySearchResult::Present(value.to_vec()),
-                        None => return HistorySearchResult::Absent,
-                    }
-                }
-                Some(SetUpdateOrDelete::Update(LedgerEntryUpdate { datastore, .. })) => {
-                    match datastore.get(key) {
-                        Some(SetOrDelete::Set(value)) => {
-                            return HistorySearchResult::Present(value.to_vec())
-                        }
-                        Some(SetOrDelete::Delete) => return HistorySearchResult::Absent,
-                        None => (),
-                    }
-                }
-                Some(SetUpdateOrDelete::Delete) => return HistorySearchResult::Absent,
-                None => (),
-            }
-        }
-        HistorySearchResult::NoInfo
+        fetch_data_entry_in(self.0.iter().rev().map(Arc::as_ref), addr, key)
+    }
+
+    /// Lazily query (from `slot` to beginning) the active datastore entry of an address at the output of `slot`.
+    ///
+    /// Returns a `HistorySearchResult`.
+    pub fn fetch_data_entry_at(
+        &self,
+        addr: &Address,
+        key: &[u8],
+        slot: &Slot,
+    ) -> HistorySearchResult<Vec<u8>> {
+        fetch_data_entry_in(self.outputs_until(slot), addr, key)
     }
 
     /// Starting from the newest element in history, return the first existing roll change of `addr`.
@@ -187,14 +188,28 @@ impl ActiveHistory {
     /// # Arguments
     /// * `addr`: address to fetch the rolls from
     pub fn fetch_roll_count(&self, addr: &Address) -> Option<u64> {
-        self.0.iter().rev().find_map(|output| {
-            output
-                .state_changes
-                .pos_changes
-                .roll_changes
-                .get(addr)
-                .cloned()
-        })
+        fetch_roll_count_in(self.0.iter().rev().map(Arc::as_ref), addr)
+    }
+
+    /// Starting from `slot` in history, return the first existing roll change of `addr`.
+    ///
+    /// # Arguments
+    /// * `addr`: address to fetch the rolls from
+    /// * `slot`: slot at the output of which the rolls are fetched
+    pub fn fetch_roll_count_at(&self, addr: &Address, slot: &Slot) -> Option<u64> {
+        fetch_roll_count_in(self.outputs_until(slot), addr)
+    }
+
+    /// Iterate (from `slot` to beginning) over the outputs of the slots up to `slot`
+    fn outputs_until<'a>(
+        &'a self,
+        slot: &'a Slot,
+    ) -> impl Iterator<Item = &'a ExecutionOutput> + 'a {
+        self.0
+            .iter()
+            .rev()
+            .skip_while(move |output| &output.slot > slot)
+            .map(Arc::as_ref)
     }
 
     /// Gets all the deferred credits that will be credited until a given slot (included)
@@ -348,3 +363,67 @@ impl ActiveHistory {
             .collect()
     }
 }
+
+/// Lazily query the balance of an address in a sequence of outputs, from the newest one to the oldest one
+fn fetch_balance_in<'a>(
+    outputs: impl Iterator<Item = &'a ExecutionOutput>,
+    addr: &Address,
+) -> HistorySearchResult<Amount> {
+    for output in outputs {
+        match output.state_changes.ledger_changes.0.get(addr) {
+            Some(SetUpdateOrDelete::Set(v)) => return HistorySearchResult::Present(v.balance),
+            Some(SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                balance: SetOrKeep::Set(v),
+                ..
+            })) => return HistorySearchResult::Present(*v),
+            Some(SetUpdateOrDelete::Delete) => return HistorySearchResult::Absent,
+            _ => (),
+        }
+    }
+    HistorySearchResult::NoInfo
+}
+
+/// Lazily query a datastore entry of an address in a sequence of outputs, from the newest one to the oldest one
+fn fetch_data_entry_in<'a>(
+    outputs: impl Iterator<Item = &'a ExecutionOutput>,
+    addr: &Address,
+    key: &[u8],
+) -> HistorySearchResult<Vec<u8>> {
+    for output in outputs {
+        match output.state_changes.ledger_changes.0.get(addr) {
+            Some(SetUpdateOrDelete::Set(LedgerEntry { datastore, .. })) => {
+                match datastore.get(key) {
+                    Some(value) => return HistorySearchResult::Present(value.to_vec()),
+                    None => return HistorySearchResult::Absent,
+                }
+            }
+            Some(SetUpdateOrDelete::Update(LedgerEntryUpdate { datastore, .. })) => {
+                match datastore.get(key) {
+                    Some(SetOrDelete::Set(value)) => {
+                        return HistorySearchResult::Present(value.to_vec())
+                    }
+                    Some(SetOrDelete::Delete) => return HistorySearchResult::Absent,
+                    None => (),
+                }
+            }
+            Some(SetUpdateOrDelete::Delete) => return HistorySearchResult::Absent,
+            None => (),
+        }
+    }
+    HistorySearchResult::NoInfo
+}
+
+/// Find the newest roll change of an address in a sequence of outputs, from the newest one to the oldest one
+fn fetch_roll_count_in<'a>(
+    mut outputs: impl Iterator<Item = &'a ExecutionOutput>,
+    addr: &Address,
+) -> Option<u64> {
+    outputs.find_map(|output| {
+        output
+            .state_changes
+            .pos_changes
+            .roll_changes
+            .get(addr)
+            .cloned()
+    })
+}
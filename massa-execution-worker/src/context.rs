@@ -8,10 +8,12 @@
 //! and does not write anything persistent to the consensus state.
 
 use crate::active_history::HistorySearchResult;
+use crate::readonly_executor::ReadOnlyDeadline;
 use crate::speculative_async_pool::SpeculativeAsyncPool;
 use crate::speculative_executed_denunciations::SpeculativeExecutedDenunciations;
 use crate::speculative_executed_ops::SpeculativeExecutedOps;
 use crate::speculative_ledger::SpeculativeLedger;
+use crate::tracer::ExecutionTracer;
 use crate::{active_history::ActiveHistory, speculative_roll_state::SpeculativeRollState};
 use massa_async_pool::{AsyncMessage, AsyncPoolChanges};
 use massa_async_pool::{AsyncMessageId, AsyncMessageInfo};
@@ -27,6 +29,8 @@ use massa_models::address::ExecutionAddressCycleInfo;
 use massa_models::block_id::BlockIdSerializer;
 use massa_models::bytecode::Bytecode;
 use massa_models::denunciation::DenunciationIndex;
+use massa_models::execution::{ExecutionTraceStep, StateOverride};
+use massa_models::prehash::PreHashMap;
 use massa_models::timeslots::get_block_slot_timestamp;
 use massa_models::{
     address::Address,
@@ -55,6 +59,9 @@ pub struct ExecutionContextSnapshot {
     /// speculative ledger changes caused so far in the context
     pub ledger_changes: LedgerChanges,
 
+    /// storage costs paid and refunded so far in the context
+    pub storage_costs: (Amount, Amount),
+
     /// speculative asynchronous pool messages emitted so far in the context
     pub async_pool_changes: AsyncPoolChanges,
 
@@ -179,6 +186,14 @@ pub struct ExecutionContext {
     /// The gas remaining before the last subexecution.
     /// so *excluding* the gas used by the last sc call.
     pub gas_remaining_before_subexecution: Option<u64>,
+
+    /// Optional tracer recording the calls made during the execution.
+    /// It is not part of the snapshots, so that the steps of failed calls are kept.
+    pub(crate) tracer: Option<ExecutionTracer>,
+
+    /// Deadline of the read-only request executed in this context, if any.
+    /// The execution is interrupted at its next call once the deadline is reached.
+    pub(crate) readonly_deadline: Option<ReadOnlyDeadline>,
 }
 
 impl ExecutionContext {
@@ -243,6 +258,8 @@ impl ExecutionContext {
             address_factory: AddressFactory { mip_store },
             execution_trail_hash,
             gas_remaining_before_subexecution: None,
+            tracer: None,
+            readonly_deadline: None,
         }
     }
 
@@ -250,8 +267,11 @@ impl ExecutionContext {
     /// Note that the snapshot does not include slot-level information such as the slot number or block ID.
     pub(crate) fn get_snapshot(&self) -> ExecutionContextSnapshot {
         let (async_pool_changes, message_infos) = self.speculative_async_pool.get_snapshot();
+        let (ledger_changes, storage_costs_paid, storage_costs_refunded) =
+            self.speculative_ledger.get_snapshot();
         ExecutionContextSnapshot {
-            ledger_changes: self.speculative_ledger.get_snapshot(),
+            ledger_changes,
+            storage_costs: (storage_costs_paid, storage_costs_refunded),
             async_pool_changes,
             message_infos,
             pos_changes: self.speculative_roll_state.get_snapshot(),
@@ -276,8 +296,11 @@ impl ExecutionContext {
     /// * `error`: an execution error to emit as an event conserved after snapshot reset.
     pub fn reset_to_snapshot(&mut self, snapshot: ExecutionContextSnapshot, error: ExecutionError) {
         // Reset context to snapshot.
-        self.speculative_ledger
-            .reset_to_snapshot(snapshot.ledger_changes);
+        self.speculative_ledger.reset_to_snapshot((
+            snapshot.ledger_changes,
+            snapshot.storage_costs.0,
+            snapshot.storage_costs.1,
+        ));
         self.speculative_async_pool
             .reset_to_snapshot((snapshot.async_pool_changes, snapshot.message_infos));
         self.speculative_roll_state
@@ -535,6 +558,15 @@ impl ExecutionContext {
         Ok(address)
     }
 
+    /// Sets the state overrides of a read-only execution in the speculative ledger.
+    /// They are visible to the execution but never part of its output.
+    pub fn set_state_overrides(
+        &mut self,
+        overrides: &PreHashMap<Address, StateOverride>,
+    ) -> Result<(), ExecutionError> {
+        self.speculative_ledger.set_state_overrides(overrides)
+    }
+
     /// gets the bytecode of an address if it exists in the speculative ledger, or returns None
     pub fn get_bytecode(&self, address: &Address) -> Option<Bytecode> {
         self.speculative_ledger.get_bytecode(address)
@@ -696,6 +728,11 @@ impl ExecutionContext {
             .transfer_coins(from_addr, to_addr, amount)
     }
 
+    /// Get the net storage costs paid so far in the context
+    pub fn get_storage_costs(&self) -> Amount {
+        self.speculative_ledger.get_storage_costs()
+    }
+
     /// Add a new asynchronous message to speculative pool
     ///
     /// # Arguments
@@ -982,6 +1019,25 @@ impl ExecutionContext {
         self.events.push(event);
     }
 
+    /// Records a step in the execution trace, if the execution is traced.
+    /// The step is only built when needed.
+    pub fn trace(&mut self, step: impl FnOnce() -> ExecutionTraceStep) {
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer.record(step());
+        }
+    }
+
+    /// Fails if the read-only request executed in this context reached its deadline
+    /// or if its caller stopped waiting for its output
+    pub fn check_readonly_deadline(&self) -> Result<(), ExecutionError> {
+        match &self.readonly_deadline {
+            Some(deadline) if deadline.is_expired() => Err(ExecutionError::ReadOnlyTimeout(
+                "execution interrupted after its deadline".into(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
     /// Check if an operation was previously executed (to prevent reuse)
     pub fn is_op_executed(&self, op_id: &OperationId) -> bool {
         self.speculative_executed_ops.is_op_executed(op_id)
@@ -4,20 +4,28 @@
 //! See `massa-execution-exports/controller_traits.rs` for functional details.
 
 use crate::execution::ExecutionState;
+use crate::readonly_executor::{ReadOnlyDeadline, ReadOnlyInputData};
 use crate::request_queue::{RequestQueue, RequestWithResponseSender};
+use massa_async_pool::AsyncMessageId;
 use massa_channel::MassaChannel;
 use massa_execution_exports::{
-    ExecutionAddressInfo, ExecutionBlockMetadata, ExecutionConfig, ExecutionController,
-    ExecutionError, ExecutionManager, ExecutionQueryError, ExecutionQueryExecutionStatus,
-    ExecutionQueryRequest, ExecutionQueryRequestItem, ExecutionQueryResponse,
-    ExecutionQueryResponseItem, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
+    AsyncPoolMessage, AsyncPoolMessageFilter, ExecutionAddressInfo, ExecutionBlockMetadata,
+    ExecutionConfig, ExecutionController, ExecutionError, ExecutionManager, ExecutionQueryError,
+    ExecutionQueryExecutionStatus, ExecutionQueryRequest, ExecutionQueryRequestItem,
+    ExecutionQueryResponse, ExecutionQueryResponseItem, ExecutionStackElement,
+    OperationCostEstimation, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
+    ReadOnlyExecutionTarget, ReadOnlyMulticallOutput, ReadOnlyMulticallRequest,
 };
+use massa_metrics::MassaMetrics;
 use massa_models::denunciation::DenunciationIndex;
-use massa_models::execution::EventFilter;
+use massa_models::execution::{
+    AddressOperation, EventFilter, ExecutionTraceFrame, OperationCheckFailure,
+};
+use massa_models::operation::{OperationId, OperationType, SecureShareOperation};
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashMap;
 use massa_models::stats::ExecutionStats;
-use massa_models::{address::Address, amount::Amount, operation::OperationId};
+use massa_models::{address::Address, amount::Amount};
 use massa_models::{block_id::BlockId, slot::Slot};
 use parking_lot::{Condvar, Mutex, RwLock};
 use std::collections::{BTreeMap, HashMap};
@@ -35,15 +43,15 @@ pub(crate) struct ExecutionInputData {
     pub new_blockclique: Option<HashMap<Slot, BlockId>>,
     /// storage instances for previously unprocessed blocks
     pub block_metadata: PreHashMap<BlockId, ExecutionBlockMetadata>,
-    /// queue for read-only execution requests and response MPSCs to send back their outputs
-    pub readonly_requests: RequestQueue<ReadOnlyExecutionRequest, ReadOnlyExecutionOutput>,
+    /// queue for operation trace requests, along with their deadline, and response MPSCs to send back their traces
+    pub trace_requests: RequestQueue<(OperationId, ReadOnlyDeadline), ExecutionTraceFrame>,
 }
 
 impl Display for ExecutionInputData {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "stop={:?}, finalized={:?}, blockclique={:?}, readonly={:?}, storage={:?}",
+            "stop={:?}, finalized={:?}, blockclique={:?}, trace={:?}, storage={:?}",
             self.stop,
             self.finalized_blocks
                 .iter()
@@ -53,7 +61,7 @@ impl Display for ExecutionInputData {
                 .iter()
                 .map(|(slot, id)| (*slot, *id))
                 .collect::<BTreeMap<Slot, BlockId>>()),
-            self.readonly_requests,
+            self.trace_requests,
             self.block_metadata.keys().collect::<Vec<&BlockId>>(),
         )
     }
@@ -67,21 +75,21 @@ impl ExecutionInputData {
             finalized_blocks: Default::default(),
             new_blockclique: Default::default(),
             block_metadata: Default::default(),
-            readonly_requests: RequestQueue::new(config.max_final_events),
+            trace_requests: RequestQueue::new(config.max_final_events),
         }
     }
 
     /// Takes the current input data into a clone that is returned,
     /// and resets self.
     pub fn take(&mut self) -> Self {
-        let max_final_events = self.readonly_requests.capacity();
+        let max_final_events = self.trace_requests.capacity();
         ExecutionInputData {
             stop: std::mem::take(&mut self.stop),
             finalized_blocks: std::mem::take(&mut self.finalized_blocks),
             new_blockclique: std::mem::take(&mut self.new_blockclique),
             block_metadata: std::mem::take(&mut self.block_metadata),
-            readonly_requests: std::mem::replace(
-                &mut self.readonly_requests,
+            trace_requests: std::mem::replace(
+                &mut self.trace_requests,
                 RequestQueue::new(max_final_events),
             ),
         }
@@ -96,6 +104,13 @@ pub struct ExecutionControllerImpl {
     pub(crate) input_data: Arc<(Condvar, Mutex<ExecutionInputData>)>,
     /// current execution state (see execution.rs for details)
     pub(crate) execution_state: Arc<RwLock<ExecutionState>>,
+    /// input data of the read-only executors (see readonly_executor.rs for details)
+    /// with a wake-up condition variable that needs to be triggered when the data changes
+    pub(crate) readonly_input_data: Arc<(Condvar, Mutex<ReadOnlyInputData>)>,
+    /// execution configuration
+    pub(crate) config: ExecutionConfig,
+    /// prometheus metrics
+    pub(crate) massa_metrics: MassaMetrics,
 }
 
 impl ExecutionController for ExecutionControllerImpl {
@@ -172,6 +187,16 @@ impl ExecutionController for ExecutionControllerImpl {
                         None => Err(ExecutionQueryError::NotFound(format!("Account {}", addr))),
                     }
                 }
+                ExecutionQueryRequestItem::AddressBalanceAtSlot { addr, slot } => {
+                    match execution_lock.get_balance_at_slot(&addr, &slot) {
+                        Ok(Some(balance)) => Ok(ExecutionQueryResponseItem::Amount(balance)),
+                        Ok(None) => Err(ExecutionQueryError::NotFound(format!(
+                            "Account {} at slot {}",
+                            addr, slot
+                        ))),
+                        Err(err) => Err(err),
+                    }
+                }
                 ExecutionQueryRequestItem::AddressBytecodeCandidate(addr) => {
                     let (_final_v, speculative_v) =
                         execution_lock.get_final_and_active_bytecode(&addr);
@@ -204,6 +229,40 @@ impl ExecutionController for ExecutionControllerImpl {
                         None => Err(ExecutionQueryError::NotFound(format!("Account {}", addr))),
                     }
                 }
+                ExecutionQueryRequestItem::AddressDatastoreRangeCandidate {
+                    addr,
+                    range,
+                    with_values,
+                } => {
+                    let (_final_v, speculative_v) =
+                        execution_lock.get_final_and_candidate_datastore_range(&addr, &range);
+                    match speculative_v {
+                        Some(entries) => Ok(ExecutionQueryResponseItem::DatastoreEntries(
+                            entries
+                                .into_iter()
+                                .map(|(key, value)| (key, with_values.then_some(value)))
+                                .collect(),
+                        )),
+                        None => Err(ExecutionQueryError::NotFound(format!("Account {}", addr))),
+                    }
+                }
+                ExecutionQueryRequestItem::AddressDatastoreRangeFinal {
+                    addr,
+                    range,
+                    with_values,
+                } => {
+                    let (final_v, _speculative_v) =
+                        execution_lock.get_final_and_candidate_datastore_range(&addr, &range);
+                    match final_v {
+                        Some(entries) => Ok(ExecutionQueryResponseItem::DatastoreEntries(
+                            entries
+                                .into_iter()
+                                .map(|(key, value)| (key, with_values.then_some(value)))
+                                .collect(),
+                        )),
+                        None => Err(ExecutionQueryError::NotFound(format!("Account {}", addr))),
+                    }
+                }
                 ExecutionQueryRequestItem::AddressDatastoreValueCandidate { addr, key } => {
                     let (_final_v, speculative_v) =
                         execution_lock.get_final_and_active_data_entry(&addr, &key);
@@ -226,6 +285,16 @@ impl ExecutionController for ExecutionControllerImpl {
                         ))),
                     }
                 }
+                ExecutionQueryRequestItem::AddressDatastoreValueAtSlot { addr, key, slot } => {
+                    match execution_lock.get_data_entry_at_slot(&addr, &key, &slot) {
+                        Ok(Some(value)) => Ok(ExecutionQueryResponseItem::DatastoreValue(value)),
+                        Ok(None) => Err(ExecutionQueryError::NotFound(format!(
+                            "Account {} datastore entry {:?} at slot {}",
+                            addr, key, slot
+                        ))),
+                        Err(err) => Err(err),
+                    }
+                }
                 ExecutionQueryRequestItem::OpExecutionStatusCandidate(id) => {
                     let (speculative_v, _final_v) = execution_lock
                         .get_ops_exec_status(&[id])
@@ -296,6 +365,9 @@ impl ExecutionController for ExecutionControllerImpl {
                         execution_lock.get_final_and_candidate_rolls(&addr);
                     Ok(ExecutionQueryResponseItem::RollCount(final_rolls))
                 }
+                ExecutionQueryRequestItem::AddressRollsAtSlot { addr, slot } => execution_lock
+                    .get_rolls_at_slot(&addr, &slot)
+                    .map(ExecutionQueryResponseItem::RollCount),
                 ExecutionQueryRequestItem::AddressDeferredCreditsCandidate(addr) => {
                     let (candidate_v, _final_v) =
                         execution_lock.get_address_deferred_credits(&addr);
@@ -342,6 +414,30 @@ impl ExecutionController for ExecutionControllerImpl {
             .get_filtered_sc_output_event(filter)
     }
 
+    /// Get the final operations involving an address, from the most recent to the oldest one
+    fn get_address_operations(
+        &self,
+        address: &Address,
+        cursor: Option<OperationId>,
+        limit: usize,
+    ) -> Result<Vec<AddressOperation>, ExecutionError> {
+        self.execution_state
+            .read()
+            .get_address_operations(address, cursor, limit)
+    }
+
+    /// Get the messages of the asynchronous pool matching a filter, in the final and candidate states
+    fn get_async_pool_messages(
+        &self,
+        filter: AsyncPoolMessageFilter,
+        cursor: Option<AsyncMessageId>,
+        limit: usize,
+    ) -> Vec<AsyncPoolMessage> {
+        self.execution_state
+            .read()
+            .get_async_pool_messages(&filter, cursor, limit)
+    }
+
     /// Get the final and candidate values of balance.
     ///
     /// # Return value
@@ -385,8 +481,9 @@ impl ExecutionController for ExecutionControllerImpl {
         &self,
         req: ReadOnlyExecutionRequest,
     ) -> Result<ReadOnlyExecutionOutput, ExecutionError> {
+        let deadline = ReadOnlyDeadline::new(self.config.readonly_request_timeout.to_duration());
         let resp_rx = {
-            let mut input_data = self.input_data.1.lock();
+            let mut input_data = self.readonly_input_data.1.lock();
 
             // if the read-only queue is already full, return an error
             if input_data.readonly_requests.is_full() {
@@ -401,17 +498,30 @@ impl ExecutionController for ExecutionControllerImpl {
             // append the request to the queue of input read-only requests
             input_data
                 .readonly_requests
-                .push(RequestWithResponseSender::new(req, resp_tx));
+                .push(RequestWithResponseSender::new(
+                    (req, deadline.clone()),
+                    resp_tx,
+                ));
+            self.massa_metrics
+                .set_readonly_queue_depth(input_data.queue_depth());
 
-            // wake up the execution main loop
-            self.input_data.0.notify_one();
+            // wake up a read-only executor
+            self.readonly_input_data.0.notify_one();
 
             resp_rx
         };
 
-        // Wait for the result of the execution
-        match resp_rx.recv() {
+        // Wait for the result of the execution, until the deadline
+        match resp_rx.recv_deadline(deadline.instant()) {
             Ok(result) => result,
+            Err(err) if err.is_timeout() => {
+                // stop the execution, the output will not be read
+                deadline.cancel();
+                Err(ExecutionError::ReadOnlyTimeout(format!(
+                    "no result after {} ms",
+                    self.config.readonly_request_timeout.as_millis()
+                )))
+            }
             Err(err) => Err(ExecutionError::ChannelError(format!(
                 "readonly execution response channel readout failed: {}",
                 err
@@ -419,6 +529,263 @@ impl ExecutionController for ExecutionControllerImpl {
         }
     }
 
+    /// Executes a sequence of read-only requests in a single speculative context
+    /// Read-only requests do not modify consensus state
+    fn execute_readonly_multicall(
+        &self,
+        req: ReadOnlyMulticallRequest,
+    ) -> Result<ReadOnlyMulticallOutput, ExecutionError> {
+        let deadline = ReadOnlyDeadline::new(self.config.readonly_request_timeout.to_duration());
+        let resp_rx = {
+            let mut input_data = self.readonly_input_data.1.lock();
+
+            // if the read-only multicall queue is already full, return an error
+            if input_data.readonly_multicall_requests.is_full() {
+                return Err(ExecutionError::ChannelError(
+                    "too many queued readonly multicall requests".into(),
+                ));
+            }
+
+            // prepare the channel to send back the result of the read-only multicall
+            let (resp_tx, resp_rx) =
+                MassaChannel::new("read_only_multicall_request".to_string(), None);
+
+            // append the request to the queue of input read-only multicall requests
+            input_data
+                .readonly_multicall_requests
+                .push(RequestWithResponseSender::new(
+                    (req, deadline.clone()),
+                    resp_tx,
+                ));
+            self.massa_metrics
+                .set_readonly_queue_depth(input_data.queue_depth());
+
+            // wake up a read-only executor
+            self.readonly_input_data.0.notify_one();
+
+            resp_rx
+        };
+
+        // Wait for the result of the executions, until the deadline
+        match resp_rx.recv_deadline(deadline.instant()) {
+            Ok(result) => result,
+            Err(err) if err.is_timeout() => {
+                // stop the execution, the output will not be read
+                deadline.cancel();
+                Err(ExecutionError::ReadOnlyTimeout(format!(
+                    "no result after {} ms",
+                    self.config.readonly_request_timeout.as_millis()
+                )))
+            }
+            Err(err) => Err(ExecutionError::ChannelError(format!(
+                "readonly multicall response channel readout failed: {}",
+                err
+            ))),
+        }
+    }
+
+    /// Estimates the costs of an operation by executing it as a read-only request
+    fn estimate_operation(
+        &self,
+        sender_address: Address,
+        operation: OperationType,
+    ) -> Result<OperationCostEstimation, ExecutionError> {
+        let (call_stack, target, coins) = match operation {
+            OperationType::ExecuteSC {
+                data, datastore, ..
+            } => (
+                vec![ExecutionStackElement {
+                    address: sender_address,
+                    coins: Amount::zero(),
+                    owned_addresses: vec![sender_address],
+                    operation_datastore: Some(datastore),
+                }],
+                ReadOnlyExecutionTarget::BytecodeExecution(data),
+                None,
+            ),
+            OperationType::CallSC {
+                target_addr,
+                target_func,
+                param,
+                coins,
+                ..
+            } => (
+                vec![
+                    ExecutionStackElement {
+                        address: sender_address,
+                        coins: Amount::zero(),
+                        owned_addresses: vec![sender_address],
+                        operation_datastore: None,
+                    },
+                    ExecutionStackElement {
+                        address: target_addr,
+                        coins,
+                        owned_addresses: vec![target_addr],
+                        operation_datastore: None,
+                    },
+                ],
+                ReadOnlyExecutionTarget::FunctionCall {
+                    target_addr,
+                    target_func,
+                    parameter: param,
+                },
+                Some(coins),
+            ),
+            OperationType::Transaction {
+                recipient_address, ..
+            } => {
+                // crediting an address that does not exist yet creates it, which costs a ledger entry
+                let (_, candidate_balance) = self
+                    .execution_state
+                    .read()
+                    .get_final_and_candidate_balance(&recipient_address);
+                return Ok(OperationCostEstimation {
+                    gas_used: self.config.base_operation_gas_cost,
+                    max_gas: self.config.base_operation_gas_cost,
+                    storage_cost: match candidate_balance {
+                        Some(_) => Amount::zero(),
+                        None => self.config.storage_costs_constants.ledger_entry_base_cost,
+                    },
+                });
+            }
+            OperationType::RollBuy { .. } | OperationType::RollSell { .. } => {
+                return Ok(OperationCostEstimation {
+                    gas_used: self.config.base_operation_gas_cost,
+                    max_gas: self.config.base_operation_gas_cost,
+                    storage_cost: Amount::zero(),
+                });
+            }
+        };
+
+        let output = self.execute_readonly_request(ReadOnlyExecutionRequest {
+            max_gas: std::cmp::min(self.config.max_read_only_gas, self.config.max_gas_per_block),
+            call_stack,
+            target,
+            coins,
+            fee: None,
+            state_overrides: Default::default(),
+            trace: false,
+        })?;
+
+        // add the safety margin to the gas used, without exceeding the gas of a block
+        let margin = output
+            .gas_cost
+            .saturating_mul(self.config.gas_estimation_margin_percent)
+            / 100;
+        Ok(OperationCostEstimation {
+            gas_used: output.gas_cost,
+            max_gas: std::cmp::min(
+                output.gas_cost.saturating_add(margin),
+                self.config.max_gas_per_block,
+            ),
+            storage_cost: output.storage_cost,
+        })
+    }
+
+    /// Dry-runs a batch of signed operations on top of the current speculative state
+    fn check_operations(
+        &self,
+        operations: Vec<SecureShareOperation>,
+    ) -> Result<Vec<Result<(), OperationCheckFailure>>, ExecutionError> {
+        let deadline = ReadOnlyDeadline::new(self.config.readonly_request_timeout.to_duration());
+        let resp_rx = {
+            let mut input_data = self.readonly_input_data.1.lock();
+
+            // if the operation check queue is already full, return an error
+            if input_data.operation_check_requests.is_full() {
+                return Err(ExecutionError::ChannelError(
+                    "too many queued operation check requests".into(),
+                ));
+            }
+
+            // prepare the channel to send back the verdicts
+            let (resp_tx, resp_rx) = MassaChannel::new("operation_check_request".to_string(), None);
+
+            // append the request to the queue of input operation check requests
+            input_data
+                .operation_check_requests
+                .push(RequestWithResponseSender::new(
+                    (operations, deadline.clone()),
+                    resp_tx,
+                ));
+            self.massa_metrics
+                .set_readonly_queue_depth(input_data.queue_depth());
+
+            // wake up a read-only executor
+            self.readonly_input_data.0.notify_one();
+
+            resp_rx
+        };
+
+        // Wait for the verdicts, until the deadline
+        match resp_rx.recv_deadline(deadline.instant()) {
+            Ok(result) => result,
+            Err(err) if err.is_timeout() => {
+                // stop the execution, the output will not be read
+                deadline.cancel();
+                Err(ExecutionError::ReadOnlyTimeout(format!(
+                    "no result after {} ms",
+                    self.config.readonly_request_timeout.as_millis()
+                )))
+            }
+            Err(err) => Err(ExecutionError::ChannelError(format!(
+                "operation check response channel readout failed: {}",
+                err
+            ))),
+        }
+    }
+
+    /// Executes again an operation of the active history to get its call trace
+    fn trace_operation(
+        &self,
+        operation_id: OperationId,
+    ) -> Result<ExecutionTraceFrame, ExecutionError> {
+        let deadline = ReadOnlyDeadline::new(self.config.readonly_request_timeout.to_duration());
+        let resp_rx = {
+            let mut input_data = self.input_data.1.lock();
+
+            // if the trace queue is already full, return an error
+            if input_data.trace_requests.is_full() {
+                return Err(ExecutionError::ChannelError(
+                    "too many queued trace requests".into(),
+                ));
+            }
+
+            // prepare the channel to send back the trace
+            let (resp_tx, resp_rx) = MassaChannel::new("trace_request".to_string(), None);
+
+            // append the request to the queue of input trace requests
+            input_data
+                .trace_requests
+                .push(RequestWithResponseSender::new(
+                    (operation_id, deadline.clone()),
+                    resp_tx,
+                ));
+
+            // wake up the execution main loop, which hands the request over to a read-only executor
+            self.input_data.0.notify_one();
+
+            resp_rx
+        };
+
+        // Wait for the trace, until the deadline
+        match resp_rx.recv_deadline(deadline.instant()) {
+            Ok(result) => result,
+            Err(err) if err.is_timeout() => {
+                // stop the execution, the output will not be read
+                deadline.cancel();
+                Err(ExecutionError::ReadOnlyTimeout(format!(
+                    "no result after {} ms",
+                    self.config.readonly_request_timeout.as_millis()
+                )))
+            }
+            Err(err) => Err(ExecutionError::ChannelError(format!(
+                "trace response channel readout failed: {}",
+                err
+            ))),
+        }
+    }
+
     /// Check if a denunciation has been executed given a `DenunciationIndex`
     /// Returns a tuple of booleans: `(speculative_execution_status, final_execution_status)`
     fn get_denunciation_execution_status(
@@ -487,6 +854,11 @@ pub struct ExecutionManagerImpl {
     pub(crate) input_data: Arc<(Condvar, Mutex<ExecutionInputData>)>,
     /// handle used to join the worker thread
     pub(crate) thread_handle: Option<std::thread::JoinHandle<()>>,
+    /// input data of the read-only executors
+    /// with a wake-up condition variable that needs to be triggered when the data changes
+    pub(crate) readonly_input_data: Arc<(Condvar, Mutex<ReadOnlyInputData>)>,
+    /// handles used to join the read-only executor threads
+    pub(crate) readonly_thread_handles: Vec<std::thread::JoinHandle<()>>,
 }
 
 impl ExecutionManager for ExecutionManagerImpl {
@@ -503,6 +875,17 @@ impl ExecutionManager for ExecutionManagerImpl {
         if let Some(join_handle) = self.thread_handle.take() {
             join_handle.join().expect("VM controller thread panicked");
         }
+        // notify the read-only executor threads to stop, and join them
+        {
+            let mut readonly_input_wlock = self.readonly_input_data.1.lock();
+            readonly_input_wlock.stop = true;
+            self.readonly_input_data.0.notify_all();
+        }
+        for join_handle in self.readonly_thread_handles.drain(..) {
+            join_handle
+                .join()
+                .expect("readonly execution thread panicked");
+        }
         info!("execution controller stopped");
     }
 }
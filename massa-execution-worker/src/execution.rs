@@ -1,6 +1,7 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-//! This module deals with executing final and active slots, as well as read-only requests.
+//! This module deals with executing final and active slots.
+//! Read-only requests are executed by the read-only executors (see `readonly_executor.rs`) on top of snapshots of its state.
 //! It also keeps a history of executed slots, thus holding the speculative state of the ledger.
 //!
 //! Execution usually happens in the following way:
@@ -9,24 +10,28 @@
 //! * the output of the execution is extracted from the context
 
 use crate::active_history::{ActiveHistory, HistorySearchResult};
-use crate::context::{ExecutionContext, ExecutionContextSnapshot};
-use crate::interface_impl::InterfaceImpl;
+use crate::context::ExecutionContext;
+use crate::final_event_store::FinalEventStore;
+use crate::operation_executor::OperationExecutor;
+use crate::operation_history_store::OperationHistoryStore;
+use crate::readonly_executor::{ReadOnlyExecutor, ReadOnlySnapshot};
+use crate::state_history_store::{HistoricalValue, StateHistoryStore};
 use crate::stats::ExecutionStatsCounter;
-use massa_async_pool::AsyncMessage;
+use massa_async_pool::AsyncMessageId;
 use massa_execution_exports::{
-    EventStore, ExecutedBlockInfo, ExecutionBlockMetadata, ExecutionChannels, ExecutionConfig,
-    ExecutionError, ExecutionOutput, ExecutionQueryCycleInfos, ExecutionQueryStakerInfo,
-    ExecutionStackElement, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
-    ReadOnlyExecutionTarget, SlotExecutionOutput,
+    AsyncPoolMessage, AsyncPoolMessageFilter, ExecutedBlockInfo, ExecutedBlockOperation,
+    ExecutionBlockMetadata, ExecutionChannels, ExecutionConfig, ExecutionError, ExecutionOutput,
+    ExecutionQueryCycleInfos, ExecutionQueryError, ExecutionQueryStakerInfo, ExecutionStackElement,
+    SlotExecutionOutput,
 };
 use massa_final_state::FinalStateController;
-use massa_ledger_exports::{SetOrDelete, SetUpdateOrDelete};
+use massa_ledger_exports::{Applicable, SetOrDelete, SetUpdateOrDelete};
 use massa_metrics::MassaMetrics;
 use massa_models::address::ExecutionAddressCycleInfo;
 use massa_models::bytecode::Bytecode;
-use massa_models::datastore::get_prefix_bounds;
+use massa_models::datastore::{get_prefix_bounds, DatastoreRange};
 use massa_models::denunciation::{Denunciation, DenunciationIndex};
-use massa_models::execution::EventFilter;
+use massa_models::execution::{AddressOperation, EventFilter};
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
 use massa_models::stats::ExecutionStats;
@@ -40,11 +45,11 @@ use massa_models::{amount::Amount, slot::Slot};
 use massa_module_cache::config::ModuleCacheConfig;
 use massa_module_cache::controller::ModuleCache;
 use massa_pos_exports::SelectorController;
-use massa_sc_runtime::{Interface, Response, VMError};
 use massa_versioning::versioning::MipStore;
 use massa_wallet::Wallet;
 use parking_lot::{Mutex, RwLock};
 use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound;
 use std::sync::Arc;
 use tracing::{debug, info, trace, warn};
 
@@ -70,14 +75,18 @@ pub(crate) struct ExecutionState {
     pub active_cursor: Slot,
     // a cursor pointing to the highest executed final slot
     pub final_cursor: Slot,
-    // store containing execution events that became final
-    final_events: EventStore,
+    // disk-backed store containing execution events that became final
+    final_events: FinalEventStore,
+    // optional disk-backed index of the final operations involving each address
+    operation_history: Option<OperationHistoryStore>,
+    // optional disk-backed history of the final values overwritten by each final slot
+    state_history: Option<StateHistoryStore>,
     // final state with atomic R/W access
     final_state: Arc<RwLock<dyn FinalStateController>>,
     // execution context (see documentation in context.rs)
     execution_context: Arc<Mutex<ExecutionContext>>,
-    // execution interface allowing the VM runtime to access the Massa context
-    execution_interface: Box<dyn Interface>,
+    // executor of the operations and asynchronous messages, running on the execution context
+    executor: OperationExecutor,
     // execution statistics
     stats_counter: ExecutionStatsCounter,
     // cache of pre compiled sc modules
@@ -122,6 +131,37 @@ impl ExecutionState {
             execution_trail_hash = final_state_read.get_execution_trail_hash();
         }
 
+        // Open the final event store, and drop the events of slots that are not final anymore
+        let mut final_events = FinalEventStore::new(
+            config.event_store_path.clone(),
+            config.max_final_events,
+            config.event_store_retention_periods,
+            config.thread_count,
+            config.max_event_size,
+        );
+        final_events.truncate_after(&last_final_slot);
+
+        // Open the operation history index if it is enabled, and drop the operations of slots that are not final anymore
+        let operation_history = config.operation_history_enabled.then(|| {
+            let mut operation_history = OperationHistoryStore::new(
+                config.operation_history_path.clone(),
+                config.thread_count,
+            );
+            operation_history.truncate_after(&last_final_slot);
+            operation_history
+        });
+
+        // Open the final state history if it is enabled, and drop it if it does not end at the last final slot
+        let state_history = config.state_history_enabled.then(|| {
+            let mut state_history = StateHistoryStore::new(
+                config.state_history_path.clone(),
+                config.state_history_retention_periods,
+                config.thread_count,
+            );
+            state_history.reset_if_not_contiguous(&last_final_slot);
+            state_history
+        });
+
         // Create default active history
         let active_history: Arc<RwLock<ActiveHistory>> = Default::default();
 
@@ -145,21 +185,24 @@ impl ExecutionState {
             execution_trail_hash,
         )));
 
-        // Instantiate the interface providing ABI access to the VM, share the execution context with it
-        let execution_interface = Box::new(InterfaceImpl::new(
+        // Create the executor of the operations of the executed slots, sharing the execution context
+        let executor = OperationExecutor::new(
             config.clone(),
+            module_cache.clone(),
             execution_context.clone(),
-        ));
+        );
 
         // build the execution state
         ExecutionState {
             final_state,
             execution_context,
-            execution_interface,
+            executor,
             // empty execution output history: it is not recovered through bootstrap
             active_history,
-            // empty final event store: it is not recovered through bootstrap
-            final_events: Default::default(),
+            // final event store: it is persisted on disk but not recovered through bootstrap
+            final_events,
+            operation_history,
+            state_history,
             // no active slots executed yet: set active_cursor to the last final block
             active_cursor: last_final_slot,
             final_cursor: last_final_slot,
@@ -211,7 +254,20 @@ impl ExecutionState {
         // as it will also write the MIP store on disk
         self.update_versioning_stats(&exec_out.block_info, &exec_out.slot);
 
+        // set the generated events as final
+        exec_out.events.finalize();
+
         let exec_out_2 = exec_out.clone();
+
+        // keep the final values that are about to be overwritten
+        if let Some(state_history) = self.state_history.as_mut() {
+            state_history.insert_final_slot(
+                &exec_out.slot,
+                &exec_out.state_changes,
+                &*self.final_state.read(),
+            );
+        }
+
         // apply state changes to the final ledger
         self.final_state
             .write()
@@ -227,9 +283,17 @@ impl ExecutionState {
         }
 
         // append generated events to the final event store
-        exec_out.events.finalize();
-        self.final_events.extend(exec_out.events);
-        self.final_events.prune(self.config.max_final_events);
+        self.final_events
+            .insert_final_events(&exec_out.slot, exec_out.events.0.iter());
+
+        // index the final operations of the slot by address
+        if let Some(operation_history) = self.operation_history.as_mut() {
+            operation_history.insert_final_slot(
+                &exec_out_2.slot,
+                exec_out_2.block_info.as_ref(),
+                &exec_out_2.state_changes.executed_ops_changes,
+            );
+        }
 
         // update the prometheus metrics
         self.massa_metrics
@@ -292,202 +356,13 @@ impl ExecutionState {
         self.active_cursor = exec_out.slot;
 
         // add the execution output at the end of the output history
-        self.active_history.write().0.push_back(exec_out);
+        self.active_history.write().0.push_back(Arc::new(exec_out));
 
         // update the prometheus metrics
         self.massa_metrics
             .set_active_history(self.active_history.read().0.len())
     }
 
-    /// Helper function.
-    /// Within a locked execution context (lock is taken at the beginning of the function then released at the end):
-    /// - if not yet executed then transfer fee and add the operation to the context then return a context snapshot
-    ///
-    /// # Arguments
-    /// * `operation`: operation to be schedule
-    /// * `sender_addr`: sender address for the operation (for fee transfer)
-    fn prepare_operation_for_execution(
-        &self,
-        operation: &SecureShareOperation,
-        sender_addr: Address,
-    ) -> Result<ExecutionContextSnapshot, ExecutionError> {
-        let operation_id = operation.id;
-
-        // lock execution context
-        let mut context = context_guard!(self);
-
-        // ignore the operation if it was already executed
-        if context.is_op_executed(&operation_id) {
-            return Err(ExecutionError::IncludeOperationError(
-                "operation was executed previously".to_string(),
-            ));
-        }
-
-        // Compute the minimal amount of coins the sender is allowed to have after the execution of this op based on `op.max_spending`.
-        // Note that the max spending might exceed the sender's balance.
-        let creator_initial_balance = context
-            .get_balance(&sender_addr)
-            .unwrap_or_else(Amount::zero);
-        context.creator_min_balance = Some(
-            creator_initial_balance
-                .saturating_sub(operation.get_max_spending(self.config.roll_price)),
-        );
-
-        // debit the fee from the operation sender
-        if let Err(err) =
-            context.transfer_coins(Some(sender_addr), None, operation.content.fee, false)
-        {
-            let error = format!("could not spend fees: {}", err);
-            let event = context.event_create(error.clone(), true);
-            context.event_emit(event);
-            return Err(ExecutionError::IncludeOperationError(error));
-        }
-
-        // from here, fees have been transferred.
-        // Op will be executed just after in the context of a snapshot.
-
-        // save a snapshot of the context to revert any further changes on error
-        let context_snapshot = context.get_snapshot();
-
-        // set the creator address
-        context.creator_address = Some(operation.content_creator_address);
-
-        // set the context origin operation ID
-        context.origin_operation_id = Some(operation_id);
-
-        Ok(context_snapshot)
-    }
-
-    /// Execute an operation in the context of a block.
-    /// Assumes the execution context was initialized at the beginning of the slot.
-    ///
-    /// # Arguments
-    /// * `operation`: operation to execute
-    /// * `block_slot`: slot of the block in which the op is included
-    /// * `remaining_block_gas`: mutable reference towards the remaining gas in the block
-    /// * `block_credits`: mutable reference towards the total block reward/fee credits
-    pub fn execute_operation(
-        &self,
-        operation: &SecureShareOperation,
-        block_slot: Slot,
-        remaining_block_gas: &mut u64,
-        block_credits: &mut Amount,
-    ) -> Result<(), ExecutionError> {
-        // check validity period
-        if !(operation
-            .get_validity_range(self.config.operation_validity_period)
-            .contains(&block_slot.period))
-        {
-            return Err(ExecutionError::InvalidSlotRange);
-        }
-
-        // check remaining block gas
-        let op_gas = operation.get_gas_usage(
-            self.config.base_operation_gas_cost,
-            self.config.gas_costs.sp_compilation_cost,
-        );
-        let new_remaining_block_gas = remaining_block_gas.checked_sub(op_gas).ok_or_else(|| {
-            ExecutionError::NotEnoughGas(
-                "not enough remaining block gas to execute operation".to_string(),
-            )
-        })?;
-
-        // get the operation's sender address
-        let sender_addr = operation.content_creator_address;
-
-        // get the thread to which the operation belongs
-        let op_thread = sender_addr.get_thread(self.config.thread_count);
-
-        // check block/op thread compatibility
-        if op_thread != block_slot.thread {
-            return Err(ExecutionError::IncludeOperationError(
-                "operation vs block thread mismatch".to_string(),
-            ));
-        }
-
-        // get operation ID
-        let operation_id = operation.id;
-
-        // Add fee from operation.
-        let new_block_credits = block_credits.saturating_add(operation.content.fee);
-
-        let context_snapshot = self.prepare_operation_for_execution(operation, sender_addr)?;
-
-        // update block gas
-        *remaining_block_gas = new_remaining_block_gas;
-
-        // update block credits
-        *block_credits = new_block_credits;
-
-        // Call the execution process specific to the operation type.
-        let mut execution_result = match &operation.content.op {
-            OperationType::ExecuteSC { .. } => {
-                self.execute_executesc_op(&operation.content.op, sender_addr)
-            }
-            OperationType::CallSC { .. } => {
-                self.execute_callsc_op(&operation.content.op, sender_addr)
-            }
-            OperationType::RollBuy { .. } => {
-                self.execute_roll_buy_op(&operation.content.op, sender_addr)
-            }
-            OperationType::RollSell { .. } => {
-                self.execute_roll_sell_op(&operation.content.op, sender_addr)
-            }
-            OperationType::Transaction { .. } => {
-                self.execute_transaction_op(&operation.content.op, sender_addr)
-            }
-        };
-
-        {
-            // lock execution context
-            let mut context = context_guard!(self);
-
-            if execution_result.is_ok() {
-                // check that the `max_coins` spending limit was respected by the sender
-                if let Some(creator_min_balance) = &context.creator_min_balance {
-                    let creator_balance = context
-                        .get_balance(&sender_addr)
-                        .unwrap_or_else(Amount::zero);
-                    if &creator_balance < creator_min_balance {
-                        execution_result = Err(ExecutionError::RuntimeError(format!(
-                            "at the end of the execution of the operation, the sender {} was expected to have at least {} coins according to the operation's max spending, but has only {}.",
-                            sender_addr, creator_min_balance, creator_balance
-                        )));
-                    }
-                }
-            }
-
-            // check execution results
-            match execution_result {
-                Ok(_) => {
-                    context.insert_executed_op(
-                        operation_id,
-                        true,
-                        Slot::new(operation.content.expire_period, op_thread),
-                    );
-                }
-                Err(err) => {
-                    // an error occurred: emit error event and reset context to snapshot
-                    let err = ExecutionError::RuntimeError(format!(
-                        "runtime error when executing operation {}: {}",
-                        operation_id, &err
-                    ));
-                    debug!("{}", &err);
-                    context.reset_to_snapshot(context_snapshot, err);
-
-                    // Insert op AFTER the context has been restored (otherwise it would be overwritten)
-                    context.insert_executed_op(
-                        operation_id,
-                        false,
-                        Slot::new(operation.content.expire_period, op_thread),
-                    )
-                }
-            }
-        }
-
-        Ok(())
-    }
-
     /// Execute a denunciation in the context of a block.
     ///
     /// # Arguments
@@ -633,411 +508,6 @@ impl ExecutionState {
         Ok(())
     }
 
-    /// Execute an operation of type `RollSell`
-    /// Will panic if called with another operation type
-    ///
-    /// # Arguments
-    /// * `operation`: the `WrappedOperation` to process, must be an `RollSell`
-    /// * `sender_addr`: address of the sender
-    pub fn execute_roll_sell_op(
-        &self,
-        operation: &OperationType,
-        seller_addr: Address,
-    ) -> Result<(), ExecutionError> {
-        // process roll sell operations only
-        let roll_count = match operation {
-            OperationType::RollSell { roll_count } => roll_count,
-            _ => panic!("unexpected operation type"),
-        };
-
-        // acquire write access to the context
-        let mut context = context_guard!(self);
-
-        // Set call stack
-        // This needs to be defined before anything can fail, so that the emitted event contains the right stack
-        context.stack = vec![ExecutionStackElement {
-            address: seller_addr,
-            coins: Amount::default(),
-            owned_addresses: vec![seller_addr],
-            operation_datastore: None,
-        }];
-
-        // try to sell the rolls
-        if let Err(err) = context.try_sell_rolls(&seller_addr, *roll_count) {
-            return Err(ExecutionError::RollSellError(format!(
-                "{} failed to sell {} rolls: {}",
-                seller_addr, roll_count, err
-            )));
-        }
-        Ok(())
-    }
-
-    /// Execute an operation of type `RollBuy`
-    /// Will panic if called with another operation type
-    ///
-    /// # Arguments
-    /// * `operation`: the `WrappedOperation` to process, must be an `RollBuy`
-    /// * `buyer_addr`: address of the buyer
-    pub fn execute_roll_buy_op(
-        &self,
-        operation: &OperationType,
-        buyer_addr: Address,
-    ) -> Result<(), ExecutionError> {
-        // process roll buy operations only
-        let roll_count = match operation {
-            OperationType::RollBuy { roll_count } => roll_count,
-            _ => panic!("unexpected operation type"),
-        };
-
-        // acquire write access to the context
-        let mut context = context_guard!(self);
-
-        // Set call stack
-        // This needs to be defined before anything can fail, so that the emitted event contains the right stack
-        context.stack = vec![ExecutionStackElement {
-            address: buyer_addr,
-            coins: Default::default(),
-            owned_addresses: vec![buyer_addr],
-            operation_datastore: None,
-        }];
-
-        // compute the amount of coins to spend
-        let spend_coins = match self.config.roll_price.checked_mul_u64(*roll_count) {
-            Some(v) => v,
-            None => {
-                return Err(ExecutionError::RollBuyError(format!(
-                    "{} failed to buy {} rolls: overflow on the required coin amount",
-                    buyer_addr, roll_count
-                )));
-            }
-        };
-
-        // spend `roll_price` * `roll_count` coins from the buyer
-        if let Err(err) = context.transfer_coins(Some(buyer_addr), None, spend_coins, false) {
-            return Err(ExecutionError::RollBuyError(format!(
-                "{} failed to buy {} rolls: {}",
-                buyer_addr, roll_count, err
-            )));
-        }
-
-        // add rolls to the buyer within the context
-        context.add_rolls(&buyer_addr, *roll_count);
-
-        Ok(())
-    }
-
-    /// Execute an operation of type `Transaction`
-    /// Will panic if called with another operation type
-    ///
-    /// # Arguments
-    /// * `operation`: the `WrappedOperation` to process, must be a `Transaction`
-    /// * `operation_id`: ID of the operation
-    /// * `sender_addr`: address of the sender
-    pub fn execute_transaction_op(
-        &self,
-        operation: &OperationType,
-        sender_addr: Address,
-    ) -> Result<(), ExecutionError> {
-        // process transaction operations only
-        let (recipient_address, amount) = match operation {
-            OperationType::Transaction {
-                recipient_address,
-                amount,
-            } => (recipient_address, amount),
-            _ => panic!("unexpected operation type"),
-        };
-
-        // acquire write access to the context
-        let mut context = context_guard!(self);
-
-        // Set call stack
-        // This needs to be defined before anything can fail, so that the emitted event contains the right stack
-        context.stack = vec![ExecutionStackElement {
-            address: sender_addr,
-            coins: *amount,
-            owned_addresses: vec![sender_addr],
-            operation_datastore: None,
-        }];
-
-        // transfer coins from sender to destination
-        if let Err(err) =
-            context.transfer_coins(Some(sender_addr), Some(*recipient_address), *amount, true)
-        {
-            return Err(ExecutionError::TransactionError(format!(
-                "transfer of {} coins from {} to {} failed: {}",
-                amount, sender_addr, recipient_address, err
-            )));
-        }
-
-        Ok(())
-    }
-
-    /// Execute an operation of type `ExecuteSC`
-    /// Will panic if called with another operation type
-    ///
-    /// # Arguments
-    /// * `operation`: the `WrappedOperation` to process, must be an `ExecuteSC`
-    /// * `sender_addr`: address of the sender
-    pub fn execute_executesc_op(
-        &self,
-        operation: &OperationType,
-        sender_addr: Address,
-    ) -> Result<(), ExecutionError> {
-        // process ExecuteSC operations only
-        let (bytecode, max_gas, datastore) = match &operation {
-            OperationType::ExecuteSC {
-                data,
-                max_gas,
-                datastore,
-                ..
-            } => (data, max_gas, datastore),
-            _ => panic!("unexpected operation type"),
-        };
-
-        {
-            // acquire write access to the context
-            let mut context = context_guard!(self);
-
-            // Set the call stack to a single element:
-            // * the execution will happen in the context of the address of the operation's sender
-            // * the context will give the operation's sender write access to its own ledger entry
-            // This needs to be defined before anything can fail, so that the emitted event
-            // contains the right stack
-            context.stack = vec![ExecutionStackElement {
-                address: sender_addr,
-                coins: Amount::zero(),
-                owned_addresses: vec![sender_addr],
-                operation_datastore: Some(datastore.clone()),
-            }];
-        };
-
-        // load the tmp module
-        let module = self
-            .module_cache
-            .read()
-            .load_tmp_module(bytecode, *max_gas)?;
-        // run the VM
-        massa_sc_runtime::run_main(
-            &*self.execution_interface,
-            module,
-            *max_gas,
-            self.config.gas_costs.clone(),
-        )
-        .map_err(|error| ExecutionError::VMError {
-            context: "ExecuteSC".to_string(),
-            error,
-        })?;
-
-        Ok(())
-    }
-
-    /// Execute an operation of type `CallSC`
-    /// Will panic if called with another operation type
-    ///
-    /// # Arguments
-    /// * `operation`: the `WrappedOperation` to process, must be an `CallSC`
-    /// * `block_creator_addr`: address of the block creator
-    /// * `operation_id`: ID of the operation
-    /// * `sender_addr`: address of the sender
-    pub fn execute_callsc_op(
-        &self,
-        operation: &OperationType,
-        sender_addr: Address,
-    ) -> Result<(), ExecutionError> {
-        // process CallSC operations only
-        let (max_gas, target_addr, target_func, param, coins) = match &operation {
-            OperationType::CallSC {
-                max_gas,
-                target_addr,
-                target_func,
-                param,
-                coins,
-                ..
-            } => (*max_gas, *target_addr, target_func, param, *coins),
-            _ => panic!("unexpected operation type"),
-        };
-
-        // prepare the current slot context for executing the operation
-        let bytecode;
-        {
-            // acquire write access to the context
-            let mut context = context_guard!(self);
-
-            // Set the call stack
-            // This needs to be defined before anything can fail, so that the emitted event contains the right stack
-            context.stack = vec![
-                ExecutionStackElement {
-                    address: sender_addr,
-                    coins: Default::default(),
-                    owned_addresses: vec![sender_addr],
-                    operation_datastore: None,
-                },
-                ExecutionStackElement {
-                    address: target_addr,
-                    coins,
-                    owned_addresses: vec![target_addr],
-                    operation_datastore: None,
-                },
-            ];
-
-            // Ensure that the target address is an SC address
-            // Ensure that the target address exists
-            context.check_target_sc_address(target_addr)?;
-
-            // Transfer coins from the sender to the target
-            if let Err(err) =
-                context.transfer_coins(Some(sender_addr), Some(target_addr), coins, false)
-            {
-                return Err(ExecutionError::RuntimeError(format!(
-                    "failed to transfer {} operation coins from {} to {}: {}",
-                    coins, sender_addr, target_addr, err
-                )));
-            }
-
-            // quit if there is no function to be called
-            if target_func.is_empty() {
-                return Ok(());
-            }
-
-            // Load bytecode. Assume empty bytecode if not found.
-            bytecode = context.get_bytecode(&target_addr).unwrap_or_default().0;
-        }
-
-        // load and execute the compiled module
-        // IMPORTANT: do not keep a lock here as `run_function` uses the `get_module` interface
-        let module = self.module_cache.write().load_module(&bytecode, max_gas)?;
-        let response = massa_sc_runtime::run_function(
-            &*self.execution_interface,
-            module,
-            target_func,
-            param,
-            max_gas,
-            self.config.gas_costs.clone(),
-        );
-        match response {
-            Ok(Response { init_gas_cost, .. })
-            | Err(VMError::ExecutionError { init_gas_cost, .. }) => {
-                self.module_cache
-                    .write()
-                    .set_init_cost(&bytecode, init_gas_cost);
-            }
-            _ => (),
-        }
-        response.map_err(|error| ExecutionError::VMError {
-            context: "CallSC".to_string(),
-            error,
-        })?;
-        Ok(())
-    }
-
-    /// Tries to execute an asynchronous message
-    /// If the execution failed reimburse the message sender.
-    ///
-    /// # Arguments
-    /// * message: message information
-    /// * bytecode: executable target bytecode, or None if unavailable
-    pub fn execute_async_message(
-        &self,
-        message: AsyncMessage,
-        bytecode: Option<Bytecode>,
-    ) -> Result<(), ExecutionError> {
-        // prepare execution context
-        let context_snapshot;
-        let bytecode = {
-            let mut context = context_guard!(self);
-            context_snapshot = context.get_snapshot();
-            context.creator_address = None;
-            context.creator_min_balance = None;
-            context.stack = vec![
-                ExecutionStackElement {
-                    address: message.sender,
-                    coins: message.coins,
-                    owned_addresses: vec![message.sender],
-                    operation_datastore: None,
-                },
-                ExecutionStackElement {
-                    address: message.destination,
-                    coins: message.coins,
-                    owned_addresses: vec![message.destination],
-                    operation_datastore: None,
-                },
-            ];
-
-            // check the target address
-            if let Err(err) = context.check_target_sc_address(message.destination) {
-                context.reset_to_snapshot(context_snapshot, err.clone());
-                context.cancel_async_message(&message);
-                return Err(err);
-            }
-
-            // if there is no bytecode: fail
-            let bytecode = match bytecode {
-                Some(bytecode) => bytecode,
-                None => {
-                    let err = ExecutionError::RuntimeError("no target bytecode found".into());
-                    context.reset_to_snapshot(context_snapshot, err.clone());
-                    context.cancel_async_message(&message);
-                    return Err(err);
-                }
-            };
-
-            // credit coins to the target address
-            if let Err(err) =
-                context.transfer_coins(None, Some(message.destination), message.coins, false)
-            {
-                // coin crediting failed: reset context to snapshot and reimburse sender
-                let err = ExecutionError::RuntimeError(format!(
-                    "could not credit coins to target of async execution: {}",
-                    err
-                ));
-                context.reset_to_snapshot(context_snapshot, err.clone());
-                context.cancel_async_message(&message);
-                return Err(err);
-            }
-
-            bytecode.0
-        };
-
-        // load and execute the compiled module
-        // IMPORTANT: do not keep a lock here as `run_function` uses the `get_module` interface
-        let module = self
-            .module_cache
-            .write()
-            .load_module(&bytecode, message.max_gas)?;
-        let response = massa_sc_runtime::run_function(
-            &*self.execution_interface,
-            module,
-            &message.function,
-            &message.function_params,
-            message.max_gas,
-            self.config.gas_costs.clone(),
-        );
-        match response {
-            Ok(Response { init_gas_cost, .. }) => {
-                self.module_cache
-                    .write()
-                    .set_init_cost(&bytecode, init_gas_cost);
-                Ok(())
-            }
-            Err(error) => {
-                if let VMError::ExecutionError { init_gas_cost, .. } = error {
-                    self.module_cache
-                        .write()
-                        .set_init_cost(&bytecode, init_gas_cost);
-                }
-                // execution failed: reset context to snapshot and reimburse sender
-                let err = ExecutionError::VMError {
-                    context: "Asynchronous Message".to_string(),
-                    error,
-                };
-                let mut context = context_guard!(self);
-                context.reset_to_snapshot(context_snapshot, err.clone());
-                context.cancel_async_message(&message);
-                Err(err)
-            }
-        }
-    }
-
     /// Executes a full slot (with or without a block inside) without causing any changes to the state,
     /// just yielding the execution output.
     ///
@@ -1077,7 +547,7 @@ impl ExecutionState {
         // Try executing asynchronous messages.
         // Effects are cancelled on failure and the sender is reimbursed.
         for (opt_bytecode, message) in messages {
-            if let Err(err) = self.execute_async_message(message, opt_bytecode) {
+            if let Err(err) = self.executor.execute_async_message(message, opt_bytecode) {
                 debug!("failed executing async message: {}", err);
             }
         }
@@ -1098,12 +568,6 @@ impl ExecutionState {
                 .expect("Missing block in storage.")
                 .clone();
 
-            block_info = Some(ExecutedBlockInfo {
-                block_id: *block_id,
-                current_version: stored_block.content.header.content.current_version,
-                announced_version: stored_block.content.header.content.announced_version,
-            });
-
             // gather all operations
             let operations = {
                 let ops = block_store.read_operations();
@@ -1119,6 +583,26 @@ impl ExecutionState {
                     .collect::<Vec<_>>()
             };
 
+            block_info = Some(ExecutedBlockInfo {
+                block_id: *block_id,
+                current_version: stored_block.content.header.content.current_version,
+                announced_version: stored_block.content.header.content.announced_version,
+                operations: operations
+                    .iter()
+                    .map(|operation| ExecutedBlockOperation {
+                        id: operation.id,
+                        sender: operation.content_creator_address,
+                        recipient: match &operation.content.op {
+                            OperationType::Transaction {
+                                recipient_address, ..
+                            } => Some(*recipient_address),
+                            OperationType::CallSC { target_addr, .. } => Some(*target_addr),
+                            _ => None,
+                        },
+                    })
+                    .collect(),
+            });
+
             debug!("executing {} operations at slot {}", operations.len(), slot);
 
             // gather all available endorsement creators and target blocks
@@ -1143,7 +627,7 @@ impl ExecutionState {
             // Try executing the operations of this block in the order in which they appear in the block.
             // Errors are logged but do not interrupt the execution of the slot.
             for operation in operations.into_iter() {
-                if let Err(err) = self.execute_operation(
+                if let Err(err) = self.executor.execute_operation(
                     &operation,
                     stored_block.content.header.content.slot,
                     &mut remaining_block_gas,
@@ -1330,14 +814,16 @@ impl ExecutionState {
                 && exec_out.block_info.as_ref().map(|i| i.block_id) == target_id
             {
                 // speculative execution front result matches what we want to compute
-                // apply the cached output and return
-                self.apply_final_execution_output(exec_out);
+                // apply the cached output and return, copying it only if a read-only execution still holds it
+                self.apply_final_execution_output(
+                    Arc::try_unwrap(exec_out).unwrap_or_else(|exec_out| (*exec_out).clone()),
+                );
                 return;
             } else {
                 // speculative cache mismatch
                 warn!(
                     "speculative execution cache mismatch (final slot={}/block={:?}, front speculative slot={}/block={:?}). Resetting the cache.",
-                    slot, target_id, exec_out.slot, exec_out.block_info.map(|i| i.block_id)
+                    slot, target_id, exec_out.slot, exec_out.block_info.as_ref().map(|i| i.block_id)
                 );
             }
         } else {
@@ -1364,177 +850,64 @@ impl ExecutionState {
         );
     }
 
-    /// Runs a read-only execution request.
-    /// The executed bytecode appears to be able to read and write the consensus state,
-    /// but all accumulated changes are simply returned as an `ExecutionOutput` object,
-    /// and not actually applied to the consensus state.
-    ///
-    /// # Arguments
-    /// * `req`: a read-only execution request
+    /// Get a view of the execution state at the start of an active slot,
+    /// on top of which the operations of its block can be executed again
     ///
     /// # Returns
-    ///  `ExecutionOutput` describing the output of the execution, or an error
-    pub(crate) fn execute_readonly_request(
-        &self,
-        req: ReadOnlyExecutionRequest,
-    ) -> Result<ReadOnlyExecutionOutput, ExecutionError> {
-        // TODO ensure that speculative things are reset after every execution ends (incl. on error and readonly)
-        // otherwise, on prod stats accumulation etc... from the API we might be counting the remainder of this speculative execution
-
-        // check if read only request max gas is above the threshold
-        if req.max_gas > self.config.max_read_only_gas {
-            return Err(ExecutionError::TooMuchGas(format!(
-                "execution gas for read-only call is {} which is above the maximum allowed {}",
-                req.max_gas, self.config.max_read_only_gas
-            )));
+    /// A snapshot at `slot` holding a copy of the active history before it.
+    /// The final state is pinned at the slot before `slot`, after which it would include changes of `slot`.
+    pub(crate) fn get_trace_snapshot(&self, slot: &Slot) -> ReadOnlySnapshot {
+        let active_history = ActiveHistory(
+            self.active_history
+                .read()
+                .0
+                .iter()
+                .take_while(|exec_out| &exec_out.slot < slot)
+                .cloned()
+                .collect(),
+        );
+        ReadOnlySnapshot {
+            slot: *slot,
+            active_history: Arc::new(RwLock::new(active_history)),
+            pinned_final_slot: slot
+                .get_prev_slot(self.config.thread_count)
+                .expect("active slot has no previous slot"),
         }
+    }
 
-        // set the execution slot to be the one after the latest executed active slot
+    /// Get the slot of the block including an operation, if that block is in the active history
+    pub fn get_active_operation_slot(&self, operation_id: &OperationId) -> Option<Slot> {
+        self.active_history
+            .read()
+            .fetch_operation_slot(operation_id)
+    }
+
+    /// Get a consistent view of the speculative state for a read-only execution
+    ///
+    /// # Returns
+    /// The slot after the latest executed active slot, a copy of the active history sharing its outputs,
+    /// and the latest executed active slot, at which the final state is pinned for the execution
+    pub(crate) fn get_readonly_snapshot(&self) -> ReadOnlySnapshot {
         let slot = self
             .active_cursor
             .get_next_slot(self.config.thread_count)
             .expect("slot overflow in readonly execution from active slot");
+        let active_history = ActiveHistory(self.active_history.read().0.clone());
+        ReadOnlySnapshot {
+            slot,
+            active_history: Arc::new(RwLock::new(active_history)),
+            pinned_final_slot: self.active_cursor,
+        }
+    }
 
-        // create a readonly execution context
-        let execution_context = ExecutionContext::readonly(
+    /// Create a read-only executor sharing the final state and the module cache of this execution state
+    pub(crate) fn new_readonly_executor(&self) -> ReadOnlyExecutor {
+        ReadOnlyExecutor::new(
             self.config.clone(),
-            slot,
-            req.call_stack,
             self.final_state.clone(),
-            self.active_history.clone(),
             self.module_cache.clone(),
             self.mip_store.clone(),
-        );
-
-        // run the interpreter according to the target type
-        let exec_response = match req.target {
-            ReadOnlyExecutionTarget::BytecodeExecution(bytecode) => {
-                {
-                    let mut context = context_guard!(self);
-                    *context = execution_context;
-
-                    let call_stack_addr = context.get_call_stack();
-
-                    // transfer fee
-                    if let (Some(fee), Some(addr)) = (req.fee, call_stack_addr.get(0)) {
-                        context.transfer_coins(Some(*addr), None, fee, false)?;
-                    }
-                }
-
-                // load the tmp module
-                let module = self
-                    .module_cache
-                    .read()
-                    .load_tmp_module(&bytecode, req.max_gas)?;
-
-                // run the VM
-                massa_sc_runtime::run_main(
-                    &*self.execution_interface,
-                    module,
-                    req.max_gas,
-                    self.config.gas_costs.clone(),
-                )
-                .map_err(|error| ExecutionError::VMError {
-                    context: "ReadOnlyExecutionTarget::BytecodeExecution".to_string(),
-                    error,
-                })?
-            }
-
-            ReadOnlyExecutionTarget::FunctionCall {
-                target_addr,
-                target_func,
-                parameter,
-            } => {
-                // get the bytecode, default to an empty vector
-                let bytecode = execution_context
-                    .get_bytecode(&target_addr)
-                    .unwrap_or_default()
-                    .0;
-
-                {
-                    let mut context = context_guard!(self);
-                    *context = execution_context;
-
-                    // Ensure that the target address is an SC address and exists
-                    context.check_target_sc_address(target_addr)?;
-
-                    let call_stack_addr = context.get_call_stack();
-
-                    // transfer fee
-                    if let (Some(fee), Some(addr)) = (req.fee, call_stack_addr.get(0)) {
-                        context.transfer_coins(Some(*addr), None, fee, false)?;
-                    }
-
-                    // transfer coins
-                    if let (Some(coins), Some(from), Some(to)) =
-                        (req.coins, call_stack_addr.get(0), call_stack_addr.get(1))
-                    {
-                        context.transfer_coins(Some(*from), Some(*to), coins, false)?;
-                    }
-                }
-
-                // load and execute the compiled module
-                // IMPORTANT: do not keep a lock here as `run_function` uses the `get_module` interface
-                let module = self
-                    .module_cache
-                    .write()
-                    .load_module(&bytecode, req.max_gas)?;
-
-                let response = massa_sc_runtime::run_function(
-                    &*self.execution_interface,
-                    module,
-                    &target_func,
-                    &parameter,
-                    req.max_gas,
-                    self.config.gas_costs.clone(),
-                );
-
-                match response {
-                    Ok(Response { init_gas_cost, .. })
-                    | Err(VMError::ExecutionError { init_gas_cost, .. }) => {
-                        self.module_cache
-                            .write()
-                            .set_init_cost(&bytecode, init_gas_cost);
-                    }
-                    _ => (),
-                }
-
-                response.map_err(|error| ExecutionError::VMError {
-                    context: "ReadOnlyExecutionTarget::FunctionCall".to_string(),
-                    error,
-                })?
-            }
-        };
-
-        // return the execution output
-        let execution_output = context_guard!(self).settle_slot(None);
-        let exact_exec_cost = req.max_gas.saturating_sub(exec_response.remaining_gas);
-
-        // compute a gas cost, estimating the gas of the last SC call to be max_instance_cost
-        let corrected_cost = match (context_guard!(self)).gas_remaining_before_subexecution {
-            Some(gas_remaining) => req
-                .max_gas
-                .saturating_sub(gas_remaining) // yield gas used until last subexecution
-                .saturating_add(self.config.gas_costs.max_instance_cost),
-            None => self.config.gas_costs.max_instance_cost, // no subexecution, just max_instance_cost
-        };
-
-        // keep the max of the two so the last SC call has at least max_instance_cost of gas
-        let estimated_cost = u64::max(exact_exec_cost, corrected_cost);
-        debug!(
-            "execute_readonly_request:
-            exec_response.remaining_gas: {}
-            exact_exec_cost: {}
-            corrected_cost: {}
-            estimated_cost: {}",
-            exec_response.remaining_gas, exact_exec_cost, corrected_cost, estimated_cost
-        );
-
-        Ok(ReadOnlyExecutionOutput {
-            out: execution_output,
-            gas_cost: estimated_cost,
-            call_result: exec_response.ret,
-        })
+        )
     }
 
     /// Gets a balance both at the latest final and candidate executed slots
@@ -1609,6 +982,109 @@ impl ExecutionState {
         )
     }
 
+    /// Answer a query about the state at the output of a slot:
+    /// * from the active history if the slot is not final yet
+    /// * from the final state if the slot is the last final one
+    /// * from the final state history if the slot is older
+    fn query_at_slot<T>(
+        &self,
+        slot: &Slot,
+        final_value: impl FnOnce() -> Option<T>,
+        active_value: impl FnOnce(&ActiveHistory) -> HistorySearchResult<T>,
+        historical_value: impl FnOnce(&StateHistoryStore) -> HistoricalValue<T>,
+    ) -> Result<Option<T>, ExecutionQueryError> {
+        if slot > &self.active_cursor {
+            return Err(ExecutionQueryError::NotAvailable(format!(
+                "slot {} is not executed yet",
+                slot
+            )));
+        }
+        if slot > &self.final_cursor {
+            return Ok(match active_value(&self.active_history.read()) {
+                HistorySearchResult::Present(value) => Some(value),
+                HistorySearchResult::NoInfo => final_value(),
+                HistorySearchResult::Absent => None,
+            });
+        }
+        if slot == &self.final_cursor {
+            return Ok(final_value());
+        }
+        let state_history = self.state_history.as_ref().ok_or_else(|| {
+            ExecutionQueryError::NotAvailable(format!(
+                "slot {} is older than the last final slot and the final state history is disabled",
+                slot
+            ))
+        })?;
+        match historical_value(state_history) {
+            HistoricalValue::Changed(value) => Ok(value),
+            HistoricalValue::Unchanged => Ok(final_value()),
+            HistoricalValue::NotRetained => Err(ExecutionQueryError::NotAvailable(format!(
+                "slot {} is older than the retained final state history",
+                slot
+            ))),
+        }
+    }
+
+    /// Gets the balance of an address at the output of a slot
+    pub fn get_balance_at_slot(
+        &self,
+        address: &Address,
+        slot: &Slot,
+    ) -> Result<Option<Amount>, ExecutionQueryError> {
+        self.query_at_slot(
+            slot,
+            || self.final_state.read().get_ledger().get_balance(address),
+            |active_history| active_history.fetch_balance_at(address, slot),
+            |state_history| state_history.get_balance_at(address, slot),
+        )
+    }
+
+    /// Gets a data entry of an address at the output of a slot
+    pub fn get_data_entry_at_slot(
+        &self,
+        address: &Address,
+        key: &[u8],
+        slot: &Slot,
+    ) -> Result<Option<Vec<u8>>, ExecutionQueryError> {
+        self.query_at_slot(
+            slot,
+            || {
+                self.final_state
+                    .read()
+                    .get_ledger()
+                    .get_data_entry(address, key)
+            },
+            |active_history| active_history.fetch_data_entry_at(address, key, slot),
+            |state_history| state_history.get_data_entry_at(address, key, slot),
+        )
+    }
+
+    /// Gets the roll count of an address at the output of a slot
+    pub fn get_rolls_at_slot(
+        &self,
+        address: &Address,
+        slot: &Slot,
+    ) -> Result<u64, ExecutionQueryError> {
+        self.query_at_slot(
+            slot,
+            || {
+                Some(
+                    self.final_state
+                        .read()
+                        .get_pos_state()
+                        .get_rolls_for(address),
+                )
+            },
+            |active_history| {
+                active_history
+                    .fetch_roll_count_at(address, slot)
+                    .map_or(HistorySearchResult::NoInfo, HistorySearchResult::Present)
+            },
+            |state_history| state_history.get_rolls_at(address, slot),
+        )
+        .map(Option::unwrap_or_default)
+    }
+
     /// Get every final and active datastore key of the given address
     #[allow(clippy::type_complexity)]
     pub fn get_final_and_candidate_datastore_keys(
@@ -1670,6 +1146,106 @@ impl ExecutionState {
         (final_keys, candidate_keys)
     }
 
+    /// Gets a range of datastore entries of an address both at the latest final and candidate executed slots
+    ///
+    /// # Returns
+    /// The final and candidate entries of the range in scan order, or `None` where the address does not exist
+    #[allow(clippy::type_complexity)]
+    pub fn get_final_and_candidate_datastore_range(
+        &self,
+        addr: &Address,
+        range: &DatastoreRange,
+    ) -> (
+        Option<Vec<(Vec<u8>, Vec<u8>)>>,
+        Option<Vec<(Vec<u8>, Vec<u8>)>>,
+    ) {
+        // traverse the history from oldest to newest, gathering the changes of the entries of the range
+        // (`None` values are deletions), whether the final datastore was replaced and whether the address exists
+        let mut changes: BTreeMap<Vec<u8>, Option<Vec<u8>>> = BTreeMap::new();
+        let mut replaced = false;
+        let mut exists = None;
+        for output in &self.active_history.read().0 {
+            match output.state_changes.ledger_changes.get(addr) {
+                // address absent from the changes
+                None => (),
+
+                // address ledger entry being reset to an absolute new datastore
+                Some(SetUpdateOrDelete::Set(new_ledger_entry)) => {
+                    changes = new_ledger_entry
+                        .datastore
+                        .iter()
+                        .filter(|(key, _)| range.contains(key))
+                        .map(|(key, value)| (key.clone(), Some(value.clone())))
+                        .collect();
+                    replaced = true;
+                    exists = Some(true);
+                }
+
+                // address ledger entry being updated
+                Some(SetUpdateOrDelete::Update(entry_updates)) => {
+                    for (ds_key, ds_update) in entry_updates.datastore.iter() {
+                        if !range.contains(ds_key) {
+                            continue;
+                        }
+                        match ds_update {
+                            SetOrDelete::Set(value) => {
+                                changes.insert(ds_key.clone(), Some(value.clone()))
+                            }
+                            SetOrDelete::Delete => changes.insert(ds_key.clone(), None),
+                        };
+                    }
+                    exists = Some(true);
+                }
+
+                // address ledger entry being deleted
+                Some(SetUpdateOrDelete::Delete) => {
+                    changes.clear();
+                    replaced = true;
+                    exists = Some(false);
+                }
+            }
+        }
+
+        // each deletion may hide a final entry: fetch as many extra final entries to fill the limit
+        let deleted_count = changes.values().filter(|value| value.is_none()).count() as u64;
+        let extended_range = DatastoreRange {
+            limit: range.limit.saturating_add(deleted_count),
+            ..range.clone()
+        };
+        let extended_final_entries = self
+            .final_state
+            .read()
+            .get_ledger()
+            .get_datastore_range(addr, &extended_range);
+        let final_entries = extended_final_entries.as_ref().map(|entries| {
+            entries
+                .iter()
+                .take(usize::try_from(range.limit).unwrap_or(usize::MAX))
+                .cloned()
+                .collect()
+        });
+
+        if !exists.unwrap_or(extended_final_entries.is_some()) {
+            return (final_entries, None);
+        }
+        let mut candidate_entries: BTreeMap<Vec<u8>, Vec<u8>> = if replaced {
+            BTreeMap::new()
+        } else {
+            extended_final_entries
+                .unwrap_or_default()
+                .into_iter()
+                .collect()
+        };
+        for (key, change) in changes {
+            match change {
+                Some(value) => candidate_entries.insert(key, value),
+                None => candidate_entries.remove(&key),
+            };
+        }
+
+        (final_entries, Some(range.select(candidate_entries)))
+    }
+
     pub fn get_address_cycle_infos(&self, address: &Address) -> Vec<ExecutionAddressCycleInfo> {
         context_guard!(self).get_address_cycle_infos(address, self.config.periods_per_cycle)
     }
@@ -1692,33 +1268,140 @@ impl ExecutionState {
     /// * original caller address
     /// * operation id
     /// * event state (final, candidate or both)
+    /// * cursor
+    ///
+    /// Events are ordered by slot then index in slot, and at most `filter.limit` of them are returned.
     pub fn get_filtered_sc_output_event(&self, filter: EventFilter) -> Vec<SCOutputEvent> {
-        match filter.is_final {
-            Some(true) => self
-                .final_events
-                .get_filtered_sc_output_events(&filter)
-                .into_iter()
-                .collect(),
-            Some(false) => self
-                .active_history
-                .read()
-                .0
+        let limit = filter
+            .limit
+            .map_or(usize::MAX, |limit| limit.try_into().unwrap_or(usize::MAX));
+        let final_events = match filter.is_final {
+            Some(false) => Vec::new(),
+            _ => self.final_events.get_filtered_sc_output_events(&filter),
+        };
+        if final_events.len() >= limit || filter.is_final == Some(true) {
+            return final_events;
+        }
+        let candidate_events_limit = limit - final_events.len();
+        final_events
+            .into_iter()
+            .chain(
+                self.active_history
+                    .read()
+                    .0
+                    .iter()
+                    .flat_map(|item| item.events.get_filtered_sc_output_events(&filter))
+                    .take(candidate_events_limit),
+            )
+            .collect()
+    }
+
+    /// Gets at most `limit` final operations involving an address, from the most recent to the oldest one.
+    /// If `cursor` is set, only the operations older than it are returned.
+    ///
+    /// Fails if the operation history index is disabled.
+    pub fn get_address_operations(
+        &self,
+        address: &Address,
+        cursor: Option<OperationId>,
+        limit: usize,
+    ) -> Result<Vec<AddressOperation>, ExecutionError> {
+        let operation_history = self
+            .operation_history
+            .as_ref()
+            .ok_or(ExecutionError::OperationHistoryDisabled)?;
+        Ok(operation_history.get_address_operations(address, cursor.as_ref(), limit))
+    }
+
+    /// Gets at most `limit` messages of the asynchronous pool matching a filter,
+    /// in the final and candidate states, ordered by id (highest priority first).
+    /// If `cursor` is set, only the messages after it are returned.
+    ///
+    /// A message matches if either its final or its candidate version matches the filter.
+    pub fn get_async_pool_messages(
+        &self,
+        filter: &AsyncPoolMessageFilter,
+        cursor: Option<AsyncMessageId>,
+        limit: usize,
+    ) -> Vec<AsyncPoolMessage> {
+        let is_matching = |sender: &Address, destination: &Address| {
+            filter.sender.map_or(true, |filter| *sender == filter)
+                && filter
+                    .destination
+                    .map_or(true, |filter| *destination == filter)
+        };
+
+        // both locks are held for the whole lookup so that the final and candidate versions are consistent
+        let final_state = self.final_state.read();
+        let async_pool = final_state.get_async_pool();
+        let active_history = self.active_history.read();
+
+        // the messages changed by the active history, whose candidate version needs to be looked up
+        let active_ids: BTreeSet<AsyncMessageId> = active_history
+            .0
+            .iter()
+            .flat_map(|history_item| history_item.state_changes.async_pool_changes.0.keys())
+            .copied()
+            .collect();
+
+        // the sender and destination of the final messages are indexed in the message info cache,
+        // so that only the matching final messages are read from the database
+        let ids: BTreeSet<AsyncMessageId> = match filter.message_id {
+            Some(message_id) => BTreeSet::from([message_id]),
+            None => async_pool
+                .message_info_cache
                 .iter()
-                .flat_map(|item| item.events.get_filtered_sc_output_events(&filter))
-                .collect(),
-            None => self
-                .final_events
-                .get_filtered_sc_output_events(&filter)
-                .into_iter()
-                .chain(
-                    self.active_history
-                        .read()
-                        .0
-                        .iter()
-                        .flat_map(|item| item.events.get_filtered_sc_output_events(&filter)),
-                )
+                .filter(|(_, info)| is_matching(&info.sender, &info.destination))
+                .map(|(id, _)| *id)
+                .chain(active_ids.iter().copied())
                 .collect(),
+        };
+        let range_start = match cursor {
+            Some(cursor) => Bound::Excluded(cursor),
+            None => Bound::Unbounded,
+        };
+
+        let mut messages = Vec::new();
+        for id in ids.range((range_start, Bound::Unbounded)) {
+            if messages.len() >= limit {
+                break;
+            }
+            let final_matches = async_pool
+                .message_info_cache
+                .get(id)
+                .map_or(false, |info| is_matching(&info.sender, &info.destination));
+            let is_active = active_ids.contains(id);
+            if !final_matches && !is_active {
+                continue;
+            }
+            let final_message = async_pool.fetch_message(id);
+            let candidate_message = if is_active {
+                match active_history.fetch_message(id, Default::default()) {
+                    HistorySearchResult::Present(SetUpdateOrDelete::Set(message)) => Some(message),
+                    HistorySearchResult::Present(SetUpdateOrDelete::Update(update)) => {
+                        final_message.clone().map(|mut message| {
+                            message.apply(update);
+                            message
+                        })
+                    }
+                    _ => None,
+                }
+            } else {
+                None
+            };
+            if final_matches
+                || candidate_message.as_ref().map_or(false, |message| {
+                    is_matching(&message.sender, &message.destination)
+                })
+            {
+                messages.push(AsyncPoolMessage {
+                    id: *id,
+                    final_message,
+                    candidate_message,
+                });
+            }
         }
+        messages
     }
 
     /// Check if a denunciation has been executed given a `DenunciationIndex`
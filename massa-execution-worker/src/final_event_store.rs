@@ -0,0 +1,325 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! This module implements a disk-backed store for final execution events.
+//!
+//! Events are stored in a dedicated RocksDB instance and are indexed by:
+//! * slot and index in slot (primary key)
+//! * emitter address (last address of the call stack)
+//! * original caller address (first address of the call stack)
+//! * origin operation id
+//!
+//! The primary key of an event is its slot (sortable binary representation) followed by its index in the slot.
+//! Secondary index keys are made of an index prefix, the indexed value and the primary key of the event.
+//! This allows answering `EventFilter` queries with range scans instead of a full scan of the store.
+
+use massa_execution_exports::is_event_matching_filter;
+use massa_models::address::{Address, AddressSerializer};
+use massa_models::execution::EventFilter;
+use massa_models::operation::{OperationId, OperationIdSerializer};
+use massa_models::output_event::{
+    SCOutputEvent, SCOutputEventDeserializer, SCOutputEventSerializer,
+};
+use massa_models::slot::{Slot, SLOT_KEY_SIZE};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use rocksdb::{Direction, IteratorMode, WriteBatch, DB};
+use std::path::PathBuf;
+use tracing::debug;
+
+const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
+const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
+const EVENT_SER_ERROR: &str = "critical: event serialization failed";
+const EVENT_DESER_ERROR: &str = "critical: event deserialization failed";
+
+const EVENT_IDENT: u8 = 0u8;
+const EMITTER_IDENT: u8 = 1u8;
+const CALLER_IDENT: u8 = 2u8;
+const OPERATION_IDENT: u8 = 3u8;
+
+/// Size of the primary key suffix of an event: slot key followed by the index in slot
+const EVENT_KEY_SUFFIX_SIZE: usize = SLOT_KEY_SIZE + 8;
+
+/// Build the primary key of an event from its slot and its index in the slot
+fn event_key(slot: &Slot, index_in_slot: u64) -> Vec<u8> {
+    [
+        &[EVENT_IDENT][..],
+        &slot.to_bytes_key()[..],
+        &index_in_slot.to_be_bytes()[..],
+    ]
+    .concat()
+}
+
+/// Disk-backed store of final execution events, indexed by slot, emitter, caller and operation id
+pub(crate) struct FinalEventStore {
+    /// RocksDB database
+    db: DB,
+    /// How many events are in the db. Count is initialized at creation time by iterating
+    /// over all the events in the db then it is maintained in memory
+    entry_count: usize,
+    /// Maximum number of events we want to keep in the db
+    max_entry_count: usize,
+    /// Number of periods during which final events are kept
+    retention_periods: u64,
+    /// Number of threads
+    thread_count: u8,
+    /// Event serializer
+    event_ser: SCOutputEventSerializer,
+    /// Event deserializer
+    event_deser: SCOutputEventDeserializer,
+    /// Address serializer, used to build index keys
+    address_ser: AddressSerializer,
+    /// Operation id serializer, used to build index keys
+    operation_id_ser: OperationIdSerializer,
+}
+
+impl FinalEventStore {
+    /// Create a new `FinalEventStore`
+    ///
+    /// # Arguments
+    /// * `path`: where to store the db
+    /// * `max_entry_count`: maximum number of events we want to keep in the db
+    /// * `retention_periods`: number of periods during which final events are kept
+    /// * `thread_count`: number of threads
+    /// * `max_event_size`: maximum size of the data of an event, in bytes
+    pub fn new(
+        path: PathBuf,
+        max_entry_count: usize,
+        retention_periods: u64,
+        thread_count: u8,
+        max_event_size: usize,
+    ) -> Self {
+        let db = DB::open_default(path).expect(OPEN_ERROR);
+        let entry_count = db
+            .prefix_iterator([EVENT_IDENT])
+            .flatten()
+            .take_while(|(key, _)| key.first() == Some(&EVENT_IDENT))
+            .count();
+
+        Self {
+            db,
+            entry_count,
+            max_entry_count,
+            retention_periods,
+            thread_count,
+            event_ser: SCOutputEventSerializer::new(),
+            event_deser: SCOutputEventDeserializer::new(
+                thread_count,
+                u64::MAX,
+                max_event_size as u64,
+            ),
+            address_ser: AddressSerializer::new(),
+            operation_id_ser: OperationIdSerializer::new(),
+        }
+    }
+
+    /// Build the prefix of the index keys pointing to the events emitted by an address
+    fn emitter_prefix(&self, address: &Address) -> Vec<u8> {
+        let mut prefix = vec![EMITTER_IDENT];
+        self.address_ser
+            .serialize(address, &mut prefix)
+            .expect(EVENT_SER_ERROR);
+        prefix
+    }
+
+    /// Build the prefix of the index keys pointing to the events originally called by an address
+    fn caller_prefix(&self, address: &Address) -> Vec<u8> {
+        let mut prefix = vec![CALLER_IDENT];
+        self.address_ser
+            .serialize(address, &mut prefix)
+            .expect(EVENT_SER_ERROR);
+        prefix
+    }
+
+    /// Build the prefix of the index keys pointing to the events generated by an operation
+    fn operation_prefix(&self, operation_id: &OperationId) -> Vec<u8> {
+        let mut prefix = vec![OPERATION_IDENT];
+        self.operation_id_ser
+            .serialize(operation_id, &mut prefix)
+            .expect(EVENT_SER_ERROR);
+        prefix
+    }
+
+    /// Get all the index keys of an event
+    fn index_keys(&self, event: &SCOutputEvent) -> Vec<Vec<u8>> {
+        let event_key = event_key(&event.context.slot, event.context.index_in_slot);
+        let suffix = &event_key[1..];
+        let mut keys = Vec::new();
+        if let Some(emitter) = event.context.call_stack.back() {
+            keys.push([&self.emitter_prefix(emitter)[..], suffix].concat());
+        }
+        if let Some(caller) = event.context.call_stack.front() {
+            keys.push([&self.caller_prefix(caller)[..], suffix].concat());
+        }
+        if let Some(operation_id) = &event.context.origin_operation_id {
+            keys.push([&self.operation_prefix(operation_id)[..], suffix].concat());
+        }
+        keys
+    }
+
+    /// Add the events of a final slot to the store, then prune the events that are out of the retention window
+    ///
+    /// # Arguments
+    /// * `slot`: the final slot that was just executed
+    /// * `events`: events that became final at that slot
+    pub fn insert_final_events<'a>(
+        &mut self,
+        slot: &Slot,
+        events: impl Iterator<Item = &'a SCOutputEvent>,
+    ) {
+        let mut batch = WriteBatch::default();
+        for event in events {
+            let mut ser_event = Vec::new();
+            self.event_ser
+                .serialize(event, &mut ser_event)
+                .expect(EVENT_SER_ERROR);
+            batch.put(
+                event_key(&event.context.slot, event.context.index_in_slot),
+                ser_event,
+            );
+            for index_key in self.index_keys(event) {
+                batch.put(index_key, b"");
+            }
+            self.entry_count = self.entry_count.saturating_add(1);
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+        self.prune(slot);
+    }
+
+    /// Remove the events that are strictly after the given slot.
+    /// Used at startup so that slots that are executed again do not produce duplicated events.
+    pub fn truncate_after(&mut self, slot: &Slot) {
+        let Ok(next_slot) = slot.get_next_slot(self.thread_count) else {
+            return;
+        };
+        let mut batch = WriteBatch::default();
+        let mut deleted_count = 0usize;
+        let start_key = event_key(&next_slot, 0);
+        for (key, ser_event) in self
+            .db
+            .iterator(IteratorMode::From(&start_key, Direction::Forward))
+            .flatten()
+        {
+            if key.first() != Some(&EVENT_IDENT) {
+                break;
+            }
+            self.delete_event(&key, &ser_event, &mut batch);
+            deleted_count += 1;
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+        self.entry_count = self.entry_count.saturating_sub(deleted_count);
+    }
+
+    /// Delete the oldest events while the store is over capacity
+    /// or the events are older than the retention window
+    fn prune(&mut self, slot: &Slot) {
+        let mut batch = WriteBatch::default();
+        let min_period = slot.period.saturating_sub(self.retention_periods);
+        let mut pruned_count = 0usize;
+        for (key, ser_event) in self.db.prefix_iterator([EVENT_IDENT]).flatten() {
+            if key.first() != Some(&EVENT_IDENT) {
+                break;
+            }
+            let event_slot =
+                Slot::from_bytes_key(key[1..=SLOT_KEY_SIZE].try_into().expect(EVENT_DESER_ERROR));
+            if self.entry_count.saturating_sub(pruned_count) <= self.max_entry_count
+                && event_slot.period >= min_period
+            {
+                break;
+            }
+            self.delete_event(&key, &ser_event, &mut batch);
+            pruned_count += 1;
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+        self.entry_count = self.entry_count.saturating_sub(pruned_count);
+        if pruned_count > 0 {
+            debug!(
+                "(final event store) pruned {} events, entry_count is: {}",
+                pruned_count, self.entry_count
+            );
+        }
+    }
+
+    /// Add the deletion of an event and of its index keys to the batch
+    fn delete_event(&self, key: &[u8], ser_event: &[u8], batch: &mut WriteBatch) {
+        let (_, event) = self
+            .event_deser
+            .deserialize::<DeserializeError>(ser_event)
+            .expect(EVENT_DESER_ERROR);
+        for index_key in self.index_keys(&event) {
+            batch.delete(index_key);
+        }
+        batch.delete(key);
+    }
+
+    /// Get the final events matching the filter.
+    ///
+    /// The most selective available index is used (operation id, then emitter, then caller),
+    /// and the scan is restricted to the slot range and to the cursor of the filter.
+    /// Events are returned ordered by slot then index in slot, and at most `filter.limit` of them are returned.
+    pub fn get_filtered_sc_output_events(&self, filter: &EventFilter) -> Vec<SCOutputEvent> {
+        if filter.is_final == Some(false) {
+            return Vec::new();
+        }
+
+        let index_prefix = if let Some(operation_id) = &filter.original_operation_id {
+            self.operation_prefix(operation_id)
+        } else if let Some(emitter) = &filter.emitter_address {
+            self.emitter_prefix(emitter)
+        } else if let Some(caller) = &filter.original_caller_address {
+            self.caller_prefix(caller)
+        } else {
+            vec![EVENT_IDENT]
+        };
+        let mut start_key = match filter.start {
+            Some(start) => [&index_prefix[..], &event_key(&start, 0)[1..]].concat(),
+            None => index_prefix.clone(),
+        };
+        if let Some(cursor) = filter.cursor {
+            // the event pointed by the cursor is skipped by the filter check below
+            let cursor_key = [
+                &index_prefix[..],
+                &event_key(&cursor.slot, cursor.index_in_slot)[1..],
+            ]
+            .concat();
+            start_key = std::cmp::max(start_key, cursor_key);
+        }
+        let end_key = filter
+            .end
+            .map(|end| [&index_prefix[..], &event_key(&end, 0)[1..]].concat());
+        let limit = filter
+            .limit
+            .map_or(usize::MAX, |limit| limit.try_into().unwrap_or(usize::MAX));
+
+        self.db
+            .iterator(IteratorMode::From(&start_key, Direction::Forward))
+            .flatten()
+            .take_while(|(key, _)| {
+                key.starts_with(&index_prefix)
+                    && end_key
+                        .as_ref()
+                        .map_or(true, |end_key| key[..] < end_key[..])
+            })
+            .filter_map(|(key, value)| {
+                if index_prefix[0] == EVENT_IDENT {
+                    Some(value.into_vec())
+                } else if key.len() >= EVENT_KEY_SUFFIX_SIZE {
+                    let event_key = [
+                        &[EVENT_IDENT][..],
+                        &key[key.len() - EVENT_KEY_SUFFIX_SIZE..],
+                    ]
+                    .concat();
+                    self.db.get(event_key).expect(CRUD_ERROR)
+                } else {
+                    None
+                }
+            })
+            .map(|ser_event| {
+                self.event_deser
+                    .deserialize::<DeserializeError>(&ser_event)
+                    .expect(EVENT_DESER_ERROR)
+                    .1
+            })
+            .filter(|event| is_event_matching_filter(event, filter))
+            .take(limit)
+            .collect()
+    }
+}
@@ -12,6 +12,7 @@ use massa_execution_exports::ExecutionConfig;
 use massa_execution_exports::ExecutionStackElement;
 use massa_models::bytecode::Bytecode;
 use massa_models::datastore::get_prefix_bounds;
+use massa_models::execution::ExecutionTraceStep;
 use massa_models::{
     address::{Address, SCAddress, UserAddress},
     amount::Amount,
@@ -281,6 +282,11 @@ impl Interface for InterfaceImpl {
             operation_datastore: None,
         });
 
+        // start a new call frame in the trace, its gas is known when the module is loaded
+        if let Some(tracer) = context.tracer.as_mut() {
+            tracer.enter_call(to_address, coins, None);
+        }
+
         // return the target bytecode
         Ok(bytecode.0)
     }
@@ -294,6 +300,10 @@ impl Interface for InterfaceImpl {
             bail!("call stack out of bounds")
         }
 
+        if let Some(tracer) = context.tracer.as_mut() {
+            tracer.exit_call();
+        }
+
         Ok(())
     }
 
@@ -302,7 +312,14 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// A `massa-sc-runtime` CL compiled module & the remaining gas after loading the module
     fn get_module(&self, bytecode: &[u8], gas_limit: u64) -> Result<RuntimeModule> {
-        Ok((context_guard!(self))
+        let mut context = context_guard!(self);
+        // stop read-only executions whose output will not be read before loading the called module
+        context.check_readonly_deadline()?;
+        // the module of a call is loaded with the gas available for that call
+        if let Some(tracer) = context.tracer.as_mut() {
+            tracer.set_gas_available(gas_limit);
+        }
+        Ok(context
             .module_cache
             .write()
             .load_module(bytecode, gas_limit)?)
@@ -313,7 +330,9 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// A `massa-sc-runtime` SP compiled module & the remaining gas after loading the module
     fn get_tmp_module(&self, bytecode: &[u8], gas_limit: u64) -> Result<RuntimeModule> {
-        Ok((context_guard!(self))
+        let context = context_guard!(self);
+        context.check_readonly_deadline()?;
+        Ok(context
             .module_cache
             .write()
             .load_tmp_module(bytecode, gas_limit)?)
@@ -441,9 +460,15 @@ impl Interface for InterfaceImpl {
     ///
     /// [DeprecatedByNewRuntime] Replaced by `raw_get_data_wasmv1`
     fn raw_get_data(&self, key: &[u8]) -> Result<Vec<u8>> {
-        let context = context_guard!(self);
+        let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
-        match context.get_data_entry(&addr, key) {
+        let value = context.get_data_entry(&addr, key);
+        context.trace(|| ExecutionTraceStep::DatastoreRead {
+            address: addr,
+            key: key.to_vec(),
+            value: value.clone(),
+        });
+        match value {
             Some(value) => Ok(value),
             _ => bail!("data entry not found"),
         }
@@ -460,9 +485,15 @@ impl Interface for InterfaceImpl {
     ///
     /// [DeprecatedByNewRuntime] Replaced by `raw_get_data_wasmv1`
     fn raw_get_data_for(&self, address: &str, key: &[u8]) -> Result<Vec<u8>> {
-        let addr = &massa_models::address::Address::from_str(address)?;
-        let context = context_guard!(self);
-        match context.get_data_entry(addr, key) {
+        let addr = massa_models::address::Address::from_str(address)?;
+        let mut context = context_guard!(self);
+        let value = context.get_data_entry(&addr, key);
+        context.trace(|| ExecutionTraceStep::DatastoreRead {
+            address: addr,
+            key: key.to_vec(),
+            value: value.clone(),
+        });
+        match value {
             Some(value) => Ok(value),
             _ => bail!("data entry not found"),
         }
@@ -477,10 +508,16 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// The datastore value matching the provided key, if found, otherwise an error.
     fn get_ds_value_wasmv1(&self, key: &[u8], address: Option<String>) -> Result<Vec<u8>> {
-        let context = context_guard!(self);
+        let mut context = context_guard!(self);
         let address = get_address_from_opt_or_context(&context, address)?;
 
-        match context.get_data_entry(&address, key) {
+        let value = context.get_data_entry(&address, key);
+        context.trace(|| ExecutionTraceStep::DatastoreRead {
+            address,
+            key: key.to_vec(),
+            value: value.clone(),
+        });
+        match value {
             Some(value) => Ok(value),
             _ => bail!("data entry not found"),
         }
@@ -500,6 +537,11 @@ impl Interface for InterfaceImpl {
         let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
         context.set_data_entry(&addr, key.to_vec(), value.to_vec())?;
+        context.trace(|| ExecutionTraceStep::DatastoreWrite {
+            address: addr,
+            key: key.to_vec(),
+            value: value.to_vec(),
+        });
         Ok(())
     }
 
@@ -517,6 +559,11 @@ impl Interface for InterfaceImpl {
         let addr = massa_models::address::Address::from_str(address)?;
         let mut context = context_guard!(self);
         context.set_data_entry(&addr, key.to_vec(), value.to_vec())?;
+        context.trace(|| ExecutionTraceStep::DatastoreWrite {
+            address: addr,
+            key: key.to_vec(),
+            value: value.to_vec(),
+        });
         Ok(())
     }
 
@@ -525,6 +572,11 @@ impl Interface for InterfaceImpl {
         let address = get_address_from_opt_or_context(&context, address)?;
 
         context.set_data_entry(&address, key.to_vec(), value.to_vec())?;
+        context.trace(|| ExecutionTraceStep::DatastoreWrite {
+            address,
+            key: key.to_vec(),
+            value: value.to_vec(),
+        });
         Ok(())
     }
 
@@ -540,6 +592,11 @@ impl Interface for InterfaceImpl {
         let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
         context.append_data_entry(&addr, key.to_vec(), value.to_vec())?;
+        context.trace(|| ExecutionTraceStep::DatastoreAppend {
+            address: addr,
+            key: key.to_vec(),
+            value: value.to_vec(),
+        });
         Ok(())
     }
 
@@ -554,7 +611,13 @@ impl Interface for InterfaceImpl {
     /// [DeprecatedByNewRuntime] Replaced by `raw_append_data_wasmv1`
     fn raw_append_data_for(&self, address: &str, key: &[u8], value: &[u8]) -> Result<()> {
         let addr = massa_models::address::Address::from_str(address)?;
-        context_guard!(self).append_data_entry(&addr, key.to_vec(), value.to_vec())?;
+        let mut context = context_guard!(self);
+        context.append_data_entry(&addr, key.to_vec(), value.to_vec())?;
+        context.trace(|| ExecutionTraceStep::DatastoreAppend {
+            address: addr,
+            key: key.to_vec(),
+            value: value.to_vec(),
+        });
         Ok(())
     }
 
@@ -575,6 +638,11 @@ impl Interface for InterfaceImpl {
         let address = get_address_from_opt_or_context(&context, address)?;
 
         context.append_data_entry(&address, key.to_vec(), value.to_vec())?;
+        context.trace(|| ExecutionTraceStep::DatastoreAppend {
+            address,
+            key: key.to_vec(),
+            value: value.to_vec(),
+        });
         Ok(())
     }
 
@@ -589,6 +657,10 @@ impl Interface for InterfaceImpl {
         let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
         context.delete_data_entry(&addr, key)?;
+        context.trace(|| ExecutionTraceStep::DatastoreDelete {
+            address: addr,
+            key: key.to_vec(),
+        });
         Ok(())
     }
 
@@ -601,8 +673,13 @@ impl Interface for InterfaceImpl {
     ///
     /// [DeprecatedByNewRuntime] Replaced by `raw_delete_data_wasmv1`
     fn raw_delete_data_for(&self, address: &str, key: &[u8]) -> Result<()> {
-        let addr = &massa_models::address::Address::from_str(address)?;
-        context_guard!(self).delete_data_entry(addr, key)?;
+        let addr = massa_models::address::Address::from_str(address)?;
+        let mut context = context_guard!(self);
+        context.delete_data_entry(&addr, key)?;
+        context.trace(|| ExecutionTraceStep::DatastoreDelete {
+            address: addr,
+            key: key.to_vec(),
+        });
         Ok(())
     }
 
@@ -617,6 +694,10 @@ impl Interface for InterfaceImpl {
         let address = get_address_from_opt_or_context(&context, address)?;
 
         context.delete_data_entry(&address, key)?;
+        context.trace(|| ExecutionTraceStep::DatastoreDelete {
+            address,
+            key: key.to_vec(),
+        });
         Ok(())
     }
 
@@ -973,6 +1054,11 @@ impl Interface for InterfaceImpl {
         let mut context = context_guard!(self);
         let from_address = context.get_current_address()?;
         context.transfer_coins(Some(from_address), Some(to_address), amount, true)?;
+        context.trace(|| ExecutionTraceStep::Transfer {
+            from: from_address,
+            to: to_address,
+            amount,
+        });
         Ok(())
     }
 
@@ -995,6 +1081,11 @@ impl Interface for InterfaceImpl {
         let amount = Amount::from_raw(raw_amount);
         let mut context = context_guard!(self);
         context.transfer_coins(Some(from_address), Some(to_address), amount, true)?;
+        context.trace(|| ExecutionTraceStep::Transfer {
+            from: from_address,
+            to: to_address,
+            amount,
+        });
         Ok(())
     }
 
@@ -1019,6 +1110,11 @@ impl Interface for InterfaceImpl {
             None => context.get_current_address()?,
         };
         context.transfer_coins(Some(from_address), Some(to_address), amount, true)?;
+        context.trace(|| ExecutionTraceStep::Transfer {
+            from: from_address,
+            to: to_address,
+            amount,
+        });
         Ok(())
     }
 
@@ -1084,6 +1180,9 @@ impl Interface for InterfaceImpl {
 
         let mut context = context_guard!(self);
         let event = context.event_create(data, false);
+        context.trace(|| ExecutionTraceStep::Event {
+            data: event.data.clone(),
+        });
         context.event_emit(event);
         Ok(())
     }
@@ -1100,6 +1199,9 @@ impl Interface for InterfaceImpl {
         let data_str = String::from_utf8(data.clone()).unwrap_or(format!("{:?}", data));
         let mut context = context_guard!(self);
         let event = context.event_create(data_str, false);
+        context.trace(|| ExecutionTraceStep::Event {
+            data: event.data.clone(),
+        });
         context.event_emit(event);
 
         Ok(())
@@ -1234,6 +1336,15 @@ impl Interface for InterfaceImpl {
             None,
         ));
         execution_context.created_message_index += 1;
+        execution_context.trace(|| ExecutionTraceStep::AsyncMessage {
+            destination: target_addr,
+            function: target_function.to_string(),
+            max_gas,
+            fee,
+            coins,
+            validity_start: Slot::new(validity_start.0, validity_start.1),
+            validity_end: Slot::new(validity_end.0, validity_end.1),
+        });
         Ok(())
     }
 
@@ -1377,6 +1488,11 @@ impl Interface for InterfaceImpl {
             operation_datastore: None,
         });
 
+        // start a new call frame in the trace, its gas is known when the module is loaded
+        if let Some(tracer) = context.tracer.as_mut() {
+            tracer.enter_call(to_address, coins, None);
+        }
+
         // return the target bytecode
         Ok(bytecode.0)
     }
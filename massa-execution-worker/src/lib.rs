@@ -40,10 +40,14 @@
 //!
 //! ## worker.rs
 //! This module runs the main loop of the worker thread.
-//! It contains the logic to process incoming blockclique change notifications and read-only execution requests.
+//! It contains the logic to process incoming blockclique change notifications.
 //! It sequences the blocks according to their slot number into queues,
 //! and requests the execution of active and final slots to execution.rs.
 //!
+//! ## `readonly_executor.rs`
+//! Runs the pool of threads executing read-only requests,
+//! each of them on top of a snapshot of the execution state and in its own execution context.
+//!
 //! ## slot_sequencer.rs
 //! Implements `SlotSequencer`
 //! that allows sequencing slots for execution.
@@ -56,12 +60,16 @@
 //! ## execution.rs
 //! Contains the machinery to execute final and non-final slots,
 //! and track the state and results of those executions.
-//! This module initializes and holds a reference to the interface from `interface_impl.rs`
-//! that allows the crate to provide execution state access
-//! to the virtual machine runtime (massa-sc-runtime crate).
+//! Operations and asynchronous messages are executed by the executor from `operation_executor.rs`.
 //! It also serves as an access point to the current execution state and speculative ledger
 //! as defined in `speculative_ledger.rs`.
 //!
+//! ## `operation_executor.rs`
+//! Executes operations and asynchronous messages within an execution context.
+//! It initializes and holds a reference to the interface from `interface_impl.rs`
+//! that allows the crate to provide execution state access
+//! to the virtual machine runtime (massa-sc-runtime crate).
+//!
 //! ## `speculative_ledger.rs`
 //! A speculative (non-final) ledger that supports canceling already-executed operations
 //! in the case of some blockclique changes.
@@ -69,6 +77,16 @@
 //! ## `speculative_executed_ops.rs`
 //! A speculative (non-final) list of previously executed operations to prevent reuse.
 //!
+//! ## `final_event_store.rs`
+//! A disk-backed store of final execution events, indexed by slot, emitter, caller and operation id.
+//!
+//! ## `operation_history_store.rs`
+//! An optional disk-backed index of the final operations sent or received by each address.
+//!
+//! ## `state_history_store.rs`
+//! An optional disk-backed history of the final balances, datastore entries and roll counts,
+//! used to answer point-in-time state queries.
+//!
 //! ## `request_queue.rs`
 //! This module contains the implementation of a generic finite-size execution request queue.
 //! It handles requests that come with an MPSC to send back the result of their execution once it's done.
@@ -83,7 +101,11 @@ mod active_history;
 mod context;
 mod controller;
 mod execution;
+mod final_event_store;
 mod interface_impl;
+mod operation_executor;
+mod operation_history_store;
+mod readonly_executor;
 mod request_queue;
 mod slot_sequencer;
 mod speculative_async_pool;
@@ -91,7 +113,9 @@ mod speculative_executed_denunciations;
 mod speculative_executed_ops;
 mod speculative_ledger;
 mod speculative_roll_state;
+mod state_history_store;
 mod stats;
+mod tracer;
 mod worker;
 
 use massa_db_exports as _;
@@ -0,0 +1,713 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! This module implements the execution of operations and asynchronous messages within an execution context.
+//!
+//! An `OperationExecutor` owns an execution context and the VM interface bound to it.
+//! The execution state uses one to execute slots, and other executors are created on separate contexts
+//! to execute operations again without touching the context of the slot executions (e.g. to trace them).
+
+use crate::context::{ExecutionContext, ExecutionContextSnapshot};
+use crate::interface_impl::InterfaceImpl;
+use massa_async_pool::AsyncMessage;
+use massa_execution_exports::{ExecutionConfig, ExecutionError, ExecutionStackElement};
+use massa_models::bytecode::Bytecode;
+use massa_models::{
+    address::Address,
+    amount::Amount,
+    operation::{OperationType, SecureShareOperation},
+    slot::Slot,
+};
+use massa_module_cache::controller::ModuleCache;
+use massa_sc_runtime::{Interface, Response, VMError};
+use parking_lot::{Mutex, RwLock};
+use std::sync::Arc;
+use tracing::debug;
+
+/// Used to acquire a lock on the execution context
+macro_rules! context_guard {
+    ($self:ident) => {
+        $self.execution_context.lock()
+    };
+}
+
+/// Executes operations and asynchronous messages within its execution context
+pub(crate) struct OperationExecutor {
+    // execution config
+    config: ExecutionConfig,
+    // cache of pre compiled sc modules
+    module_cache: Arc<RwLock<ModuleCache>>,
+    // execution context (see documentation in context.rs)
+    execution_context: Arc<Mutex<ExecutionContext>>,
+    // execution interface allowing the VM runtime to access the Massa context
+    execution_interface: Box<dyn Interface>,
+}
+
+impl OperationExecutor {
+    /// Create a new operation executor running on the given execution context
+    ///
+    /// # Arguments
+    /// * `config`: execution configuration
+    /// * `module_cache`: cache of pre compiled sc modules
+    /// * `execution_context`: execution context the operations are executed in
+    pub fn new(
+        config: ExecutionConfig,
+        module_cache: Arc<RwLock<ModuleCache>>,
+        execution_context: Arc<Mutex<ExecutionContext>>,
+    ) -> Self {
+        // Instantiate the interface providing ABI access to the VM, share the execution context with it
+        let execution_interface = Box::new(InterfaceImpl::new(
+            config.clone(),
+            execution_context.clone(),
+        ));
+        OperationExecutor {
+            config,
+            module_cache,
+            execution_context,
+            execution_interface,
+        }
+    }
+
+    /// Get the execution context of this executor
+    pub fn context(&self) -> &Arc<Mutex<ExecutionContext>> {
+        &self.execution_context
+    }
+
+    /// Get the execution interface bound to the execution context of this executor
+    pub fn interface(&self) -> &dyn Interface {
+        &*self.execution_interface
+    }
+
+    /// Helper function.
+    /// Within a locked execution context (lock is taken at the beginning of the function then released at the end):
+    /// - if not yet executed then transfer fee and add the operation to the context then return a context snapshot
+    ///
+    /// # Arguments
+    /// * `operation`: operation to be schedule
+    /// * `sender_addr`: sender address for the operation (for fee transfer)
+    pub fn prepare_operation_for_execution(
+        &self,
+        operation: &SecureShareOperation,
+        sender_addr: Address,
+    ) -> Result<ExecutionContextSnapshot, ExecutionError> {
+        let operation_id = operation.id;
+
+        // lock execution context
+        let mut context = context_guard!(self);
+
+        // ignore the operation if it was already executed
+        if context.is_op_executed(&operation_id) {
+            return Err(ExecutionError::IncludeOperationError(
+                "operation was executed previously".to_string(),
+            ));
+        }
+
+        // Compute the minimal amount of coins the sender is allowed to have after the execution of this op based on `op.max_spending`.
+        // Note that the max spending might exceed the sender's balance.
+        let creator_initial_balance = context
+            .get_balance(&sender_addr)
+            .unwrap_or_else(Amount::zero);
+        context.creator_min_balance = Some(
+            creator_initial_balance
+                .saturating_sub(operation.get_max_spending(self.config.roll_price)),
+        );
+
+        // debit the fee from the operation sender
+        if let Err(err) =
+            context.transfer_coins(Some(sender_addr), None, operation.content.fee, false)
+        {
+            let error = format!("could not spend fees: {}", err);
+            let event = context.event_create(error.clone(), true);
+            context.event_emit(event);
+            return Err(ExecutionError::IncludeOperationError(error));
+        }
+
+        // from here, fees have been transferred.
+        // Op will be executed just after in the context of a snapshot.
+
+        // save a snapshot of the context to revert any further changes on error
+        let context_snapshot = context.get_snapshot();
+
+        // set the creator address
+        context.creator_address = Some(operation.content_creator_address);
+
+        // set the context origin operation ID
+        context.origin_operation_id = Some(operation_id);
+
+        Ok(context_snapshot)
+    }
+
+    /// Execute an operation in the context of a block.
+    /// Assumes the execution context was initialized at the beginning of the slot.
+    ///
+    /// # Arguments
+    /// * `operation`: operation to execute
+    /// * `block_slot`: slot of the block in which the op is included
+    /// * `remaining_block_gas`: mutable reference towards the remaining gas in the block
+    /// * `block_credits`: mutable reference towards the total block reward/fee credits
+    pub fn execute_operation(
+        &self,
+        operation: &SecureShareOperation,
+        block_slot: Slot,
+        remaining_block_gas: &mut u64,
+        block_credits: &mut Amount,
+    ) -> Result<(), ExecutionError> {
+        // check validity period
+        if !(operation
+            .get_validity_range(self.config.operation_validity_period)
+            .contains(&block_slot.period))
+        {
+            return Err(ExecutionError::InvalidSlotRange);
+        }
+
+        // check remaining block gas
+        let op_gas = operation.get_gas_usage(
+            self.config.base_operation_gas_cost,
+            self.config.gas_costs.sp_compilation_cost,
+        );
+        let new_remaining_block_gas = remaining_block_gas.checked_sub(op_gas).ok_or_else(|| {
+            ExecutionError::NotEnoughGas(
+                "not enough remaining block gas to execute operation".to_string(),
+            )
+        })?;
+
+        // get the operation's sender address
+        let sender_addr = operation.content_creator_address;
+
+        // get the thread to which the operation belongs
+        let op_thread = sender_addr.get_thread(self.config.thread_count);
+
+        // check block/op thread compatibility
+        if op_thread != block_slot.thread {
+            return Err(ExecutionError::IncludeOperationError(
+                "operation vs block thread mismatch".to_string(),
+            ));
+        }
+
+        // Add fee from operation.
+        let new_block_credits = block_credits.saturating_add(operation.content.fee);
+
+        let context_snapshot = self.prepare_operation_for_execution(operation, sender_addr)?;
+
+        // update block gas
+        *remaining_block_gas = new_remaining_block_gas;
+
+        // update block credits
+        *block_credits = new_block_credits;
+
+        // the operation is included whether its execution succeeds or not
+        if let Err(err) = self.execute_prepared_operation(operation, context_snapshot) {
+            debug!("{}", &err);
+        }
+
+        Ok(())
+    }
+
+    /// Executes an operation whose fee was spent by `prepare_operation_for_execution`, and marks it as executed.
+    /// If the execution fails, the context is reset to the snapshot taken after spending the fee.
+    ///
+    /// # Arguments
+    /// * `operation`: operation to execute
+    /// * `context_snapshot`: snapshot returned by `prepare_operation_for_execution`
+    ///
+    /// # Returns
+    /// The error that made the execution fail, if any
+    pub fn execute_prepared_operation(
+        &self,
+        operation: &SecureShareOperation,
+        context_snapshot: ExecutionContextSnapshot,
+    ) -> Result<(), ExecutionError> {
+        let operation_id = operation.id;
+        let sender_addr = operation.content_creator_address;
+        let op_thread = sender_addr.get_thread(self.config.thread_count);
+
+        // Call the execution process specific to the operation type.
+        let mut execution_result = match &operation.content.op {
+            OperationType::ExecuteSC { .. } => {
+                self.execute_executesc_op(&operation.content.op, sender_addr)
+            }
+            OperationType::CallSC { .. } => {
+                self.execute_callsc_op(&operation.content.op, sender_addr)
+            }
+            OperationType::RollBuy { .. } => {
+                self.execute_roll_buy_op(&operation.content.op, sender_addr)
+            }
+            OperationType::RollSell { .. } => {
+                self.execute_roll_sell_op(&operation.content.op, sender_addr)
+            }
+            OperationType::Transaction { .. } => {
+                self.execute_transaction_op(&operation.content.op, sender_addr)
+            }
+        };
+
+        // lock execution context
+        let mut context = context_guard!(self);
+
+        if execution_result.is_ok() {
+            // check that the `max_coins` spending limit was respected by the sender
+            if let Some(creator_min_balance) = &context.creator_min_balance {
+                let creator_balance = context
+                    .get_balance(&sender_addr)
+                    .unwrap_or_else(Amount::zero);
+                if &creator_balance < creator_min_balance {
+                    execution_result = Err(ExecutionError::RuntimeError(format!(
+                        "at the end of the execution of the operation, the sender {} was expected to have at least {} coins according to the operation's max spending, but has only {}.",
+                        sender_addr, creator_min_balance, creator_balance
+                    )));
+                }
+            }
+        }
+
+        // check execution results
+        match execution_result {
+            Ok(_) => {
+                context.insert_executed_op(
+                    operation_id,
+                    true,
+                    Slot::new(operation.content.expire_period, op_thread),
+                );
+                Ok(())
+            }
+            Err(err) => {
+                // an error occurred: emit error event and reset context to snapshot
+                let err = ExecutionError::RuntimeError(format!(
+                    "runtime error when executing operation {}: {}",
+                    operation_id, &err
+                ));
+                context.reset_to_snapshot(context_snapshot, err.clone());
+
+                // Insert op AFTER the context has been restored (otherwise it would be overwritten)
+                context.insert_executed_op(
+                    operation_id,
+                    false,
+                    Slot::new(operation.content.expire_period, op_thread),
+                );
+                Err(err)
+            }
+        }
+    }
+
+    /// Execute an operation of type `RollSell`
+    /// Will panic if called with another operation type
+    ///
+    /// # Arguments
+    /// * `operation`: the `WrappedOperation` to process, must be an `RollSell`
+    /// * `sender_addr`: address of the sender
+    pub fn execute_roll_sell_op(
+        &self,
+        operation: &OperationType,
+        seller_addr: Address,
+    ) -> Result<(), ExecutionError> {
+        // process roll sell operations only
+        let roll_count = match operation {
+            OperationType::RollSell { roll_count } => roll_count,
+            _ => panic!("unexpected operation type"),
+        };
+
+        // acquire write access to the context
+        let mut context = context_guard!(self);
+
+        // Set call stack
+        // This needs to be defined before anything can fail, so that the emitted event contains the right stack
+        context.stack = vec![ExecutionStackElement {
+            address: seller_addr,
+            coins: Amount::default(),
+            owned_addresses: vec![seller_addr],
+            operation_datastore: None,
+        }];
+
+        // try to sell the rolls
+        if let Err(err) = context.try_sell_rolls(&seller_addr, *roll_count) {
+            return Err(ExecutionError::RollSellError(format!(
+                "{} failed to sell {} rolls: {}",
+                seller_addr, roll_count, err
+            )));
+        }
+        Ok(())
+    }
+
+    /// Execute an operation of type `RollBuy`
+    /// Will panic if called with another operation type
+    ///
+    /// # Arguments
+    /// * `operation`: the `WrappedOperation` to process, must be an `RollBuy`
+    /// * `buyer_addr`: address of the buyer
+    pub fn execute_roll_buy_op(
+        &self,
+        operation: &OperationType,
+        buyer_addr: Address,
+    ) -> Result<(), ExecutionError> {
+        // process roll buy operations only
+        let roll_count = match operation {
+            OperationType::RollBuy { roll_count } => roll_count,
+            _ => panic!("unexpected operation type"),
+        };
+
+        // acquire write access to the context
+        let mut context = context_guard!(self);
+
+        // Set call stack
+        // This needs to be defined before anything can fail, so that the emitted event contains the right stack
+        context.stack = vec![ExecutionStackElement {
+            address: buyer_addr,
+            coins: Default::default(),
+            owned_addresses: vec![buyer_addr],
+            operation_datastore: None,
+        }];
+
+        // compute the amount of coins to spend
+        let spend_coins = match self.config.roll_price.checked_mul_u64(*roll_count) {
+            Some(v) => v,
+            None => {
+                return Err(ExecutionError::RollBuyError(format!(
+                    "{} failed to buy {} rolls: overflow on the required coin amount",
+                    buyer_addr, roll_count
+                )));
+            }
+        };
+
+        // spend `roll_price` * `roll_count` coins from the buyer
+        if let Err(err) = context.transfer_coins(Some(buyer_addr), None, spend_coins, false) {
+            return Err(ExecutionError::RollBuyError(format!(
+                "{} failed to buy {} rolls: {}",
+                buyer_addr, roll_count, err
+            )));
+        }
+
+        // add rolls to the buyer within the context
+        context.add_rolls(&buyer_addr, *roll_count);
+
+        Ok(())
+    }
+
+    /// Execute an operation of type `Transaction`
+    /// Will panic if called with another operation type
+    ///
+    /// # Arguments
+    /// * `operation`: the `WrappedOperation` to process, must be a `Transaction`
+    /// * `operation_id`: ID of the operation
+    /// * `sender_addr`: address of the sender
+    pub fn execute_transaction_op(
+        &self,
+        operation: &OperationType,
+        sender_addr: Address,
+    ) -> Result<(), ExecutionError> {
+        // process transaction operations only
+        let (recipient_address, amount) = match operation {
+            OperationType::Transaction {
+                recipient_address,
+                amount,
+            } => (recipient_address, amount),
+            _ => panic!("unexpected operation type"),
+        };
+
+        // acquire write access to the context
+        let mut context = context_guard!(self);
+
+        // Set call stack
+        // This needs to be defined before anything can fail, so that the emitted event contains the right stack
+        context.stack = vec![ExecutionStackElement {
+            address: sender_addr,
+            coins: *amount,
+            owned_addresses: vec![sender_addr],
+            operation_datastore: None,
+        }];
+
+        // transfer coins from sender to destination
+        if let Err(err) =
+            context.transfer_coins(Some(sender_addr), Some(*recipient_address), *amount, true)
+        {
+            return Err(ExecutionError::TransactionError(format!(
+                "transfer of {} coins from {} to {} failed: {}",
+                amount, sender_addr, recipient_address, err
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Execute an operation of type `ExecuteSC`
+    /// Will panic if called with another operation type
+    ///
+    /// # Arguments
+    /// * `operation`: the `WrappedOperation` to process, must be an `ExecuteSC`
+    /// * `sender_addr`: address of the sender
+    pub fn execute_executesc_op(
+        &self,
+        operation: &OperationType,
+        sender_addr: Address,
+    ) -> Result<(), ExecutionError> {
+        // process ExecuteSC operations only
+        let (bytecode, max_gas, datastore) = match &operation {
+            OperationType::ExecuteSC {
+                data,
+                max_gas,
+                datastore,
+                ..
+            } => (data, max_gas, datastore),
+            _ => panic!("unexpected operation type"),
+        };
+
+        {
+            // acquire write access to the context
+            let mut context = context_guard!(self);
+
+            // Set the call stack to a single element:
+            // * the execution will happen in the context of the address of the operation's sender
+            // * the context will give the operation's sender write access to its own ledger entry
+            // This needs to be defined before anything can fail, so that the emitted event
+            // contains the right stack
+            context.stack = vec![ExecutionStackElement {
+                address: sender_addr,
+                coins: Amount::zero(),
+                owned_addresses: vec![sender_addr],
+                operation_datastore: Some(datastore.clone()),
+            }];
+
+            if let Some(tracer) = context.tracer.as_mut() {
+                tracer.set_gas_available(*max_gas);
+            }
+        };
+
+        // load the tmp module
+        let module = self
+            .module_cache
+            .read()
+            .load_tmp_module(bytecode, *max_gas)?;
+        // run the VM
+        let response = massa_sc_runtime::run_main(
+            &*self.execution_interface,
+            module,
+            *max_gas,
+            self.config.gas_costs.clone(),
+        )
+        .map_err(|error| ExecutionError::VMError {
+            context: "ExecuteSC".to_string(),
+            error,
+        })?;
+
+        // the gas consumed by the root call of the trace is known once the VM returns
+        if let Some(tracer) = context_guard!(self).tracer.as_mut() {
+            tracer.set_gas_remaining(response.remaining_gas);
+        }
+
+        Ok(())
+    }
+
+    /// Execute an operation of type `CallSC`
+    /// Will panic if called with another operation type
+    ///
+    /// # Arguments
+    /// * `operation`: the `WrappedOperation` to process, must be an `CallSC`
+    /// * `block_creator_addr`: address of the block creator
+    /// * `operation_id`: ID of the operation
+    /// * `sender_addr`: address of the sender
+    pub fn execute_callsc_op(
+        &self,
+        operation: &OperationType,
+        sender_addr: Address,
+    ) -> Result<(), ExecutionError> {
+        // process CallSC operations only
+        let (max_gas, target_addr, target_func, param, coins) = match &operation {
+            OperationType::CallSC {
+                max_gas,
+                target_addr,
+                target_func,
+                param,
+                coins,
+                ..
+            } => (*max_gas, *target_addr, target_func, param, *coins),
+            _ => panic!("unexpected operation type"),
+        };
+
+        // prepare the current slot context for executing the operation
+        let bytecode;
+        {
+            // acquire write access to the context
+            let mut context = context_guard!(self);
+
+            // Set the call stack
+            // This needs to be defined before anything can fail, so that the emitted event contains the right stack
+            context.stack = vec![
+                ExecutionStackElement {
+                    address: sender_addr,
+                    coins: Default::default(),
+                    owned_addresses: vec![sender_addr],
+                    operation_datastore: None,
+                },
+                ExecutionStackElement {
+                    address: target_addr,
+                    coins,
+                    owned_addresses: vec![target_addr],
+                    operation_datastore: None,
+                },
+            ];
+
+            // Ensure that the target address is an SC address
+            // Ensure that the target address exists
+            context.check_target_sc_address(target_addr)?;
+
+            // Transfer coins from the sender to the target
+            if let Err(err) =
+                context.transfer_coins(Some(sender_addr), Some(target_addr), coins, false)
+            {
+                return Err(ExecutionError::RuntimeError(format!(
+                    "failed to transfer {} operation coins from {} to {}: {}",
+                    coins, sender_addr, target_addr, err
+                )));
+            }
+
+            // start the call frame of the target in the trace
+            if let Some(tracer) = context.tracer.as_mut() {
+                tracer.enter_call(target_addr, coins, Some(max_gas));
+            }
+
+            // quit if there is no function to be called
+            if target_func.is_empty() {
+                return Ok(());
+            }
+
+            // Load bytecode. Assume empty bytecode if not found.
+            bytecode = context.get_bytecode(&target_addr).unwrap_or_default().0;
+        }
+
+        // load and execute the compiled module
+        // IMPORTANT: do not keep a lock here as `run_function` uses the `get_module` interface
+        let module = self.module_cache.write().load_module(&bytecode, max_gas)?;
+        let response = massa_sc_runtime::run_function(
+            &*self.execution_interface,
+            module,
+            target_func,
+            param,
+            max_gas,
+            self.config.gas_costs.clone(),
+        );
+        match response {
+            Ok(Response { init_gas_cost, .. })
+            | Err(VMError::ExecutionError { init_gas_cost, .. }) => {
+                self.module_cache
+                    .write()
+                    .set_init_cost(&bytecode, init_gas_cost);
+            }
+            _ => (),
+        }
+        let response = response.map_err(|error| ExecutionError::VMError {
+            context: "CallSC".to_string(),
+            error,
+        })?;
+
+        // the gas consumed by the call of the target is known once the VM returns
+        if let Some(tracer) = context_guard!(self).tracer.as_mut() {
+            tracer.set_gas_remaining(response.remaining_gas);
+        }
+
+        Ok(())
+    }
+
+    /// Tries to execute an asynchronous message
+    /// If the execution failed reimburse the message sender.
+    ///
+    /// # Arguments
+    /// * message: message information
+    /// * bytecode: executable target bytecode, or None if unavailable
+    pub fn execute_async_message(
+        &self,
+        message: AsyncMessage,
+        bytecode: Option<Bytecode>,
+    ) -> Result<(), ExecutionError> {
+        // prepare execution context
+        let context_snapshot;
+        let bytecode = {
+            let mut context = context_guard!(self);
+            context_snapshot = context.get_snapshot();
+            context.creator_address = None;
+            context.creator_min_balance = None;
+            context.stack = vec![
+                ExecutionStackElement {
+                    address: message.sender,
+                    coins: message.coins,
+                    owned_addresses: vec![message.sender],
+                    operation_datastore: None,
+                },
+                ExecutionStackElement {
+                    address: message.destination,
+                    coins: message.coins,
+                    owned_addresses: vec![message.destination],
+                    operation_datastore: None,
+                },
+            ];
+
+            // check the target address
+            if let Err(err) = context.check_target_sc_address(message.destination) {
+                context.reset_to_snapshot(context_snapshot, err.clone());
+                context.cancel_async_message(&message);
+                return Err(err);
+            }
+
+            // if there is no bytecode: fail
+            let bytecode = match bytecode {
+                Some(bytecode) => bytecode,
+                None => {
+                    let err = ExecutionError::RuntimeError("no target bytecode found".into());
+                    context.reset_to_snapshot(context_snapshot, err.clone());
+                    context.cancel_async_message(&message);
+                    return Err(err);
+                }
+            };
+
+            // credit coins to the target address
+            if let Err(err) =
+                context.transfer_coins(None, Some(message.destination), message.coins, false)
+            {
+                // coin crediting failed: reset context to snapshot and reimburse sender
+                let err = ExecutionError::RuntimeError(format!(
+                    "could not credit coins to target of async execution: {}",
+                    err
+                ));
+                context.reset_to_snapshot(context_snapshot, err.clone());
+                context.cancel_async_message(&message);
+                return Err(err);
+            }
+
+            bytecode.0
+        };
+
+        // load and execute the compiled module
+        // IMPORTANT: do not keep a lock here as `run_function` uses the `get_module` interface
+        let module = self
+            .module_cache
+            .write()
+            .load_module(&bytecode, message.max_gas)?;
+        let response = massa_sc_runtime::run_function(
+            &*self.execution_interface,
+            module,
+            &message.function,
+            &message.function_params,
+            message.max_gas,
+            self.config.gas_costs.clone(),
+        );
+        match response {
+            Ok(Response { init_gas_cost, .. }) => {
+                self.module_cache
+                    .write()
+                    .set_init_cost(&bytecode, init_gas_cost);
+                Ok(())
+            }
+            Err(error) => {
+                if let VMError::ExecutionError { init_gas_cost, .. } = error {
+                    self.module_cache
+                        .write()
+                        .set_init_cost(&bytecode, init_gas_cost);
+                }
+                // execution failed: reset context to snapshot and reimburse sender
+                let err = ExecutionError::VMError {
+                    context: "Asynchronous Message".to_string(),
+                    error,
+                };
+                let mut context = context_guard!(self);
+                context.reset_to_snapshot(context_snapshot, err.clone());
+                context.cancel_async_message(&message);
+                Err(err)
+            }
+        }
+    }
+}
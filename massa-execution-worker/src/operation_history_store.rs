@@ -0,0 +1,305 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! This module implements an optional disk-backed index of the final operations involving each address.
+//!
+//! It is fed with the outputs of the final slots and stored in a dedicated RocksDB instance:
+//! * operation records are keyed by the slot of their block (sortable binary representation)
+//!   followed by their index in the block
+//! * address index keys are made of an index prefix, the address and the key of the operation record
+//! * operation id index keys are made of an index prefix and the operation id, and point to the key of the operation record
+//!
+//! The operations of an address are listed from the most recent to the oldest one.
+
+use massa_executed_ops::ExecutedOpsChanges;
+use massa_execution_exports::ExecutedBlockInfo;
+use massa_models::address::{Address, AddressDeserializer, AddressSerializer};
+use massa_models::block_id::{BlockId, BlockIdDeserializer, BlockIdSerializer};
+use massa_models::execution::{AddressOperation, AddressOperationRole};
+use massa_models::operation::{OperationId, OperationIdDeserializer, OperationIdSerializer};
+use massa_models::slot::{Slot, SLOT_KEY_SIZE};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use rocksdb::{Direction, IteratorMode, WriteBatch, DB};
+use std::path::PathBuf;
+
+const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
+const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
+const OPERATION_SER_ERROR: &str = "critical: operation record serialization failed";
+const OPERATION_DESER_ERROR: &str = "critical: operation record deserialization failed";
+
+const OPERATION_IDENT: u8 = 0u8;
+const ADDRESS_IDENT: u8 = 1u8;
+const OPERATION_ID_IDENT: u8 = 2u8;
+
+/// Size of the key suffix of an operation record: slot key followed by the index in the block
+const OPERATION_KEY_SUFFIX_SIZE: usize = SLOT_KEY_SIZE + 4;
+
+/// Build the key of an operation record from its slot and its index in the block
+fn operation_key(slot: &Slot, index_in_block: u32) -> Vec<u8> {
+    [
+        &[OPERATION_IDENT][..],
+        &slot.to_bytes_key()[..],
+        &index_in_block.to_be_bytes()[..],
+    ]
+    .concat()
+}
+
+/// Final operation as stored in the index
+struct OperationRecord {
+    operation_id: OperationId,
+    block_id: BlockId,
+    sender: Address,
+    recipient: Option<Address>,
+    success: bool,
+}
+
+/// Disk-backed index of the final operations sent or received by each address
+pub(crate) struct OperationHistoryStore {
+    /// RocksDB database
+    db: DB,
+    /// Number of threads
+    thread_count: u8,
+    /// Address serializer
+    address_ser: AddressSerializer,
+    /// Address deserializer
+    address_deser: AddressDeserializer,
+    /// Block id serializer
+    block_id_ser: BlockIdSerializer,
+    /// Block id deserializer
+    block_id_deser: BlockIdDeserializer,
+    /// Operation id serializer
+    operation_id_ser: OperationIdSerializer,
+    /// Operation id deserializer
+    operation_id_deser: OperationIdDeserializer,
+}
+
+impl OperationHistoryStore {
+    /// Create a new `OperationHistoryStore`
+    ///
+    /// # Arguments
+    /// * `path`: where to store the db
+    /// * `thread_count`: number of threads
+    pub fn new(path: PathBuf, thread_count: u8) -> Self {
+        Self {
+            db: DB::open_default(path).expect(OPEN_ERROR),
+            thread_count,
+            address_ser: AddressSerializer::new(),
+            address_deser: AddressDeserializer::new(),
+            block_id_ser: BlockIdSerializer::new(),
+            block_id_deser: BlockIdDeserializer::new(),
+            operation_id_ser: OperationIdSerializer::new(),
+            operation_id_deser: OperationIdDeserializer::new(),
+        }
+    }
+
+    /// Build the prefix of the index keys pointing to the operations involving an address
+    fn address_prefix(&self, address: &Address) -> Vec<u8> {
+        let mut prefix = vec![ADDRESS_IDENT];
+        self.address_ser
+            .serialize(address, &mut prefix)
+            .expect(OPERATION_SER_ERROR);
+        prefix
+    }
+
+    /// Build the index key pointing to the record of an operation
+    fn operation_id_key(&self, operation_id: &OperationId) -> Vec<u8> {
+        let mut key = vec![OPERATION_ID_IDENT];
+        self.operation_id_ser
+            .serialize(operation_id, &mut key)
+            .expect(OPERATION_SER_ERROR);
+        key
+    }
+
+    /// Get the address index keys of an operation record
+    fn address_keys(&self, key: &[u8], record: &OperationRecord) -> Vec<Vec<u8>> {
+        let suffix = &key[1..];
+        let mut keys = vec![[&self.address_prefix(&record.sender)[..], suffix].concat()];
+        if let Some(recipient) = &record.recipient {
+            if recipient != &record.sender {
+                keys.push([&self.address_prefix(recipient)[..], suffix].concat());
+            }
+        }
+        keys
+    }
+
+    fn serialize_record(&self, record: &OperationRecord) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        self.operation_id_ser
+            .serialize(&record.operation_id, &mut buffer)
+            .expect(OPERATION_SER_ERROR);
+        self.block_id_ser
+            .serialize(&record.block_id, &mut buffer)
+            .expect(OPERATION_SER_ERROR);
+        self.address_ser
+            .serialize(&record.sender, &mut buffer)
+            .expect(OPERATION_SER_ERROR);
+        match &record.recipient {
+            Some(recipient) => {
+                buffer.push(1u8);
+                self.address_ser
+                    .serialize(recipient, &mut buffer)
+                    .expect(OPERATION_SER_ERROR);
+            }
+            None => buffer.push(0u8),
+        }
+        buffer.push(u8::from(record.success));
+        buffer
+    }
+
+    fn deserialize_record(&self, buffer: &[u8]) -> OperationRecord {
+        let (rest, operation_id) = self
+            .operation_id_deser
+            .deserialize::<DeserializeError>(buffer)
+            .expect(OPERATION_DESER_ERROR);
+        let (rest, block_id) = self
+            .block_id_deser
+            .deserialize::<DeserializeError>(rest)
+            .expect(OPERATION_DESER_ERROR);
+        let (rest, sender) = self
+            .address_deser
+            .deserialize::<DeserializeError>(rest)
+            .expect(OPERATION_DESER_ERROR);
+        let (rest, recipient) = match rest.split_first() {
+            Some((1u8, rest)) => {
+                let (rest, recipient) = self
+                    .address_deser
+                    .deserialize::<DeserializeError>(rest)
+                    .expect(OPERATION_DESER_ERROR);
+                (rest, Some(recipient))
+            }
+            Some((0u8, rest)) => (rest, None),
+            _ => panic!("{}", OPERATION_DESER_ERROR),
+        };
+        OperationRecord {
+            operation_id,
+            block_id,
+            sender,
+            recipient,
+            success: rest.first().expect(OPERATION_DESER_ERROR) == &1u8,
+        }
+    }
+
+    /// Index the operations of a final slot
+    ///
+    /// # Arguments
+    /// * `slot`: the final slot that was just executed
+    /// * `block_info`: the block executed at that slot, if any
+    /// * `executed_ops_changes`: the operations executed at that slot along with their execution status.
+    ///    Operations of the block that were not executed are not indexed.
+    pub fn insert_final_slot(
+        &mut self,
+        slot: &Slot,
+        block_info: Option<&ExecutedBlockInfo>,
+        executed_ops_changes: &ExecutedOpsChanges,
+    ) {
+        let Some(block_info) = block_info else {
+            return;
+        };
+        let mut batch = WriteBatch::default();
+        for (index_in_block, operation) in block_info.operations.iter().enumerate() {
+            let Some((success, _)) = executed_ops_changes.get(&operation.id) else {
+                continue;
+            };
+            let record = OperationRecord {
+                operation_id: operation.id,
+                block_id: block_info.block_id,
+                sender: operation.sender,
+                recipient: operation.recipient,
+                success: *success,
+            };
+            let key = operation_key(slot, index_in_block as u32);
+            for address_key in self.address_keys(&key, &record) {
+                batch.put(address_key, b"");
+            }
+            batch.put(self.operation_id_key(&record.operation_id), &key);
+            batch.put(&key, self.serialize_record(&record));
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+    }
+
+    /// Remove the operations that are strictly after the given slot.
+    /// Used at startup so that slots that are executed again are not indexed twice.
+    pub fn truncate_after(&mut self, slot: &Slot) {
+        let Ok(next_slot) = slot.get_next_slot(self.thread_count) else {
+            return;
+        };
+        let mut batch = WriteBatch::default();
+        let start_key = operation_key(&next_slot, 0);
+        for (key, value) in self
+            .db
+            .iterator(IteratorMode::From(&start_key, Direction::Forward))
+            .flatten()
+        {
+            if key.first() != Some(&OPERATION_IDENT) {
+                break;
+            }
+            let record = self.deserialize_record(&value);
+            for address_key in self.address_keys(&key, &record) {
+                batch.delete(address_key);
+            }
+            batch.delete(self.operation_id_key(&record.operation_id));
+            batch.delete(key);
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+    }
+
+    /// Get the final operations involving an address, from the most recent to the oldest one
+    ///
+    /// # Arguments
+    /// * `address`: the address whose operations are listed
+    /// * `cursor`: if set, only the operations older than this one are returned
+    /// * `limit`: maximum number of operations to return
+    pub fn get_address_operations(
+        &self,
+        address: &Address,
+        cursor: Option<&OperationId>,
+        limit: usize,
+    ) -> Vec<AddressOperation> {
+        let prefix = self.address_prefix(address);
+        let start_key = match cursor {
+            Some(cursor) => {
+                // the operation pointed by the cursor is skipped below
+                match self
+                    .db
+                    .get(self.operation_id_key(cursor))
+                    .expect(CRUD_ERROR)
+                {
+                    Some(cursor_key) => [&prefix[..], &cursor_key[1..]].concat(),
+                    None => return Vec::new(),
+                }
+            }
+            None => [&prefix[..], &[u8::MAX; OPERATION_KEY_SUFFIX_SIZE][..]].concat(),
+        };
+
+        self.db
+            .iterator(IteratorMode::From(&start_key, Direction::Reverse))
+            .flatten()
+            .skip_while(|(key, _)| key[..] >= start_key[..])
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .filter_map(|(key, _)| {
+                if key.len() < prefix.len() + OPERATION_KEY_SUFFIX_SIZE {
+                    return None;
+                }
+                let suffix = &key[key.len() - OPERATION_KEY_SUFFIX_SIZE..];
+                let record_key = [&[OPERATION_IDENT][..], suffix].concat();
+                let value = self.db.get(record_key).expect(CRUD_ERROR)?;
+                let record = self.deserialize_record(&value);
+                Some(AddressOperation {
+                    operation_id: record.operation_id,
+                    slot: Slot::from_bytes_key(
+                        suffix[..SLOT_KEY_SIZE]
+                            .try_into()
+                            .expect(OPERATION_DESER_ERROR),
+                    ),
+                    block_id: record.block_id,
+                    role: if &record.sender == address {
+                        AddressOperationRole::Sender
+                    } else {
+                        AddressOperationRole::Recipient
+                    },
+                    success: record.success,
+                })
+            })
+            .take(limit)
+            .collect()
+    }
+}
@@ -0,0 +1,969 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! This module implements the pool of read-only executor threads.
+//!
+//! Read-only requests are not executed by the main execution thread: they are queued in a shared input
+//! and picked up by a configurable number of executor threads.
+//! Each executor owns its own execution context and operation executor,
+//! so that several read-only requests can run in parallel without blocking slot executions.
+//!
+//! When it starts a request, an executor briefly takes read access to the execution state
+//! to get the slot after the latest executed active slot and a copy of the active history,
+//! whose outputs are shared with the execution state.
+//! The final state is shared: slots only become final after having been executed as active,
+//! so the changes of the copied history keep taking precedence over the final state while the request runs.
+//! The final state is pinned at the latest slot of the copied history:
+//! the output of a request during which the final state moved past that slot is discarded.
+//!
+//! Operation traces are requested to the main execution thread, which owns the blocks of the active slots:
+//! it looks up the block of the operation and hands it over to the executors, along with a snapshot of the
+//! execution state at the start of the slot of the block, on top of which the operation is executed again.
+//!
+//! Each request has a deadline: requests that are still queued at their deadline are dropped,
+//! and running requests are interrupted at their next call once the deadline is reached
+//! or once their caller stopped waiting for them.
+
+use crate::active_history::ActiveHistory;
+use crate::context::ExecutionContext;
+use crate::execution::ExecutionState;
+use crate::operation_executor::OperationExecutor;
+use crate::request_queue::{RequestQueue, RequestWithResponseSender};
+use crate::tracer::ExecutionTracer;
+use massa_execution_exports::{
+    ExecutionBlockMetadata, ExecutionConfig, ExecutionError, ExecutionStackElement,
+    ReadOnlyExecutionOutput, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    ReadOnlyMulticallCallOutput, ReadOnlyMulticallOutput, ReadOnlyMulticallRequest,
+};
+use massa_final_state::FinalStateController;
+use massa_metrics::MassaMetrics;
+use massa_models::address::Address;
+use massa_models::amount::Amount;
+use massa_models::block_id::BlockId;
+use massa_models::execution::{ExecutionTraceFrame, OperationCheckFailure, StateOverride};
+use massa_models::operation::{OperationId, SecureShareOperation};
+use massa_models::prehash::PreHashMap;
+use massa_models::slot::Slot;
+use massa_module_cache::controller::ModuleCache;
+use massa_sc_runtime::{Response, VMError};
+use massa_versioning::versioning::MipStore;
+use parking_lot::{Condvar, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Number of request queues of `ReadOnlyInputData`
+const READONLY_QUEUE_COUNT: usize = 4;
+
+/// Used to acquire a lock on the execution context
+macro_rules! context_guard {
+    ($self:ident) => {
+        $self.executor.context().lock()
+    };
+}
+
+/// Deadline of a read-only request, along with the flag raised when its caller stops waiting for the output
+#[derive(Clone, Debug)]
+pub(crate) struct ReadOnlyDeadline {
+    /// instant after which the caller does not wait for the output anymore
+    instant: Instant,
+    /// raised by the caller when it stopped waiting for the output
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ReadOnlyDeadline {
+    /// Create a deadline `timeout` from now
+    pub fn new(timeout: Duration) -> Self {
+        ReadOnlyDeadline {
+            instant: Instant::now() + timeout,
+            cancelled: Default::default(),
+        }
+    }
+
+    /// Instant after which the caller does not wait for the output anymore
+    pub fn instant(&self) -> Instant {
+        self.instant
+    }
+
+    /// Tell the executor that the output of the request will not be read
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the request should not be executed any further
+    pub fn is_expired(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed) || Instant::now() >= self.instant
+    }
+}
+
+/// Consistent view of the execution state on top of which a read-only request runs
+pub(crate) struct ReadOnlySnapshot {
+    /// slot at which the execution happens, right after the latest executed active slot
+    pub slot: Slot,
+    /// copy of the active history, sharing its outputs with the execution state
+    pub active_history: Arc<RwLock<ActiveHistory>>,
+    /// latest slot the final state may reach while the request runs.
+    /// The changes of the slots finalized up to it are also in the copied active history.
+    pub pinned_final_slot: Slot,
+}
+
+/// Operation of the active history to execute again to get its call trace
+pub(crate) struct TraceRequest {
+    /// id of the operation to trace
+    pub operation_id: OperationId,
+    /// the block including the operation and its metadata
+    pub block: (BlockId, ExecutionBlockMetadata),
+    /// snapshot of the execution state at the start of the slot of the block
+    pub snapshot: ReadOnlySnapshot,
+}
+
+/// Structure used to communicate with the read-only executor threads
+pub(crate) struct ReadOnlyInputData {
+    /// set stop to true to stop the threads
+    pub stop: bool,
+    /// queue for read-only execution requests, along with their deadline, and response MPSCs to send back their outputs
+    pub readonly_requests:
+        RequestQueue<(ReadOnlyExecutionRequest, ReadOnlyDeadline), ReadOnlyExecutionOutput>,
+    /// queue for read-only multicall requests, along with their deadline, and response MPSCs to send back their outputs
+    pub readonly_multicall_requests:
+        RequestQueue<(ReadOnlyMulticallRequest, ReadOnlyDeadline), ReadOnlyMulticallOutput>,
+    /// queue for operation check requests, along with their deadline, and response MPSCs to send back their verdicts
+    pub operation_check_requests: RequestQueue<
+        (Vec<SecureShareOperation>, ReadOnlyDeadline),
+        Vec<Result<(), OperationCheckFailure>>,
+    >,
+    /// queue for operation trace requests, along with their deadline, and response MPSCs to send back their traces
+    pub trace_requests: RequestQueue<(TraceRequest, ReadOnlyDeadline), ExecutionTraceFrame>,
+    /// index of the queue the next request is taken from, so that the queues are served in turn
+    next_queue: usize,
+}
+
+impl ReadOnlyInputData {
+    /// Creates a new empty `ReadOnlyInputData`
+    pub fn new(config: &ExecutionConfig) -> Self {
+        ReadOnlyInputData {
+            stop: false,
+            readonly_requests: RequestQueue::new(config.readonly_queue_length),
+            readonly_multicall_requests: RequestQueue::new(config.readonly_queue_length),
+            operation_check_requests: RequestQueue::new(config.readonly_queue_length),
+            trace_requests: RequestQueue::new(config.readonly_queue_length),
+            next_queue: 0,
+        }
+    }
+
+    /// Number of requests waiting for an executor
+    pub fn queue_depth(&self) -> usize {
+        self.readonly_requests.len()
+            + self.readonly_multicall_requests.len()
+            + self.operation_check_requests.len()
+            + self.trace_requests.len()
+    }
+
+    /// Takes the oldest request of the next non-empty queue, serving the queues in turn
+    /// so that a busy queue does not starve the others
+    fn pop_task(&mut self) -> Option<ReadOnlyTask> {
+        for _ in 0..READONLY_QUEUE_COUNT {
+            let queue = self.next_queue;
+            self.next_queue = (queue + 1) % READONLY_QUEUE_COUNT;
+            let task = match queue {
+                0 => self.readonly_requests.pop().map(ReadOnlyTask::Request),
+                1 => self
+                    .readonly_multicall_requests
+                    .pop()
+                    .map(ReadOnlyTask::Multicall),
+                2 => self
+                    .operation_check_requests
+                    .pop()
+                    .map(ReadOnlyTask::OperationCheck),
+                _ => self.trace_requests.pop().map(ReadOnlyTask::Trace),
+            };
+            if task.is_some() {
+                return task;
+            }
+        }
+        None
+    }
+}
+
+/// Read-only request taken from the queues by an executor thread
+enum ReadOnlyTask {
+    /// read-only execution
+    Request(
+        RequestWithResponseSender<
+            (ReadOnlyExecutionRequest, ReadOnlyDeadline),
+            ReadOnlyExecutionOutput,
+        >,
+    ),
+    /// read-only multicall
+    Multicall(
+        RequestWithResponseSender<
+            (ReadOnlyMulticallRequest, ReadOnlyDeadline),
+            ReadOnlyMulticallOutput,
+        >,
+    ),
+    /// dry-run check of signed operations
+    OperationCheck(
+        RequestWithResponseSender<
+            (Vec<SecureShareOperation>, ReadOnlyDeadline),
+            Vec<Result<(), OperationCheckFailure>>,
+        >,
+    ),
+    /// trace of an operation of the active history
+    Trace(RequestWithResponseSender<(TraceRequest, ReadOnlyDeadline), ExecutionTraceFrame>),
+}
+
+/// Executes read-only requests in its own execution context
+pub(crate) struct ReadOnlyExecutor {
+    /// execution config
+    config: ExecutionConfig,
+    /// final state with atomic R/W access
+    final_state: Arc<RwLock<dyn FinalStateController>>,
+    /// cache of pre compiled sc modules
+    module_cache: Arc<RwLock<ModuleCache>>,
+    /// MipStore (Versioning)
+    mip_store: MipStore,
+    /// executor running on the execution context of the read-only executions
+    executor: OperationExecutor,
+}
+
+impl ReadOnlyExecutor {
+    /// Create a new read-only executor
+    ///
+    /// # Arguments
+    /// * `config`: execution configuration
+    /// * `final_state`: atomic access to the final state
+    /// * `module_cache`: cache of pre compiled sc modules, shared with the execution state
+    /// * `mip_store`: versioning store
+    pub fn new(
+        config: ExecutionConfig,
+        final_state: Arc<RwLock<dyn FinalStateController>>,
+        module_cache: Arc<RwLock<ModuleCache>>,
+        mip_store: MipStore,
+    ) -> Self {
+        // Create an empty placeholder execution context, replaced at each request
+        let execution_trail_hash = final_state.read().get_execution_trail_hash();
+        let execution_context = Arc::new(Mutex::new(ExecutionContext::new(
+            config.clone(),
+            final_state.clone(),
+            Default::default(),
+            module_cache.clone(),
+            mip_store.clone(),
+            execution_trail_hash,
+        )));
+
+        let executor =
+            OperationExecutor::new(config.clone(), module_cache.clone(), execution_context);
+
+        ReadOnlyExecutor {
+            config,
+            final_state,
+            module_cache,
+            mip_store,
+            executor,
+        }
+    }
+
+    /// Creates a read-only execution context
+    ///
+    /// # Arguments
+    /// * `snapshot`: snapshot of the execution state the execution is run on top of
+    /// * `deadline`: deadline of the request, checked by the context at each call
+    /// * `call_stack`: call stack of the execution
+    fn new_readonly_context(
+        &self,
+        snapshot: ReadOnlySnapshot,
+        deadline: &ReadOnlyDeadline,
+        call_stack: Vec<ExecutionStackElement>,
+    ) -> ExecutionContext {
+        let mut context = ExecutionContext::readonly(
+            self.config.clone(),
+            snapshot.slot,
+            call_stack,
+            self.final_state.clone(),
+            snapshot.active_history,
+            self.module_cache.clone(),
+            self.mip_store.clone(),
+        );
+        context.readonly_deadline = Some(deadline.clone());
+        context
+    }
+
+    /// Runs a read-only execution on top of the current execution context.
+    /// The call stack of the context is replaced by the one of the request,
+    /// and the accumulated changes are left in the context.
+    ///
+    /// # Arguments
+    /// * `req`: a read-only execution request. Its state overrides are ignored.
+    ///
+    /// # Returns
+    /// The estimated gas cost of the execution and the value it returned, or an error
+    fn run_readonly_call(
+        &self,
+        req: ReadOnlyExecutionRequest,
+    ) -> Result<(u64, Vec<u8>), ExecutionError> {
+        // check if read only request max gas is above the threshold
+        if req.max_gas > self.config.max_read_only_gas {
+            return Err(ExecutionError::TooMuchGas(format!(
+                "execution gas for read-only call is {} which is above the maximum allowed {}",
+                req.max_gas, self.config.max_read_only_gas
+            )));
+        }
+
+        {
+            let mut context = context_guard!(self);
+            context.stack = req.call_stack;
+            context.gas_remaining_before_subexecution = None;
+        }
+
+        // run the interpreter according to the target type
+        let exec_response = match req.target {
+            ReadOnlyExecutionTarget::BytecodeExecution(bytecode) => {
+                {
+                    let mut context = context_guard!(self);
+                    let call_stack_addr = context.get_call_stack();
+
+                    // transfer fee
+                    if let (Some(fee), Some(addr)) = (req.fee, call_stack_addr.get(0)) {
+                        context.transfer_coins(Some(*addr), None, fee, false)?;
+                    }
+
+                    // start tracing the execution if requested
+                    if let (true, Some(addr)) = (req.trace, call_stack_addr.get(0)) {
+                        context.tracer = Some(ExecutionTracer::new(*addr, Some(req.max_gas)));
+                    }
+                }
+
+                // load the tmp module
+                let module = self
+                    .module_cache
+                    .read()
+                    .load_tmp_module(&bytecode, req.max_gas)?;
+
+                // run the VM
+                massa_sc_runtime::run_main(
+                    self.executor.interface(),
+                    module,
+                    req.max_gas,
+                    self.config.gas_costs.clone(),
+                )
+                .map_err(|error| ExecutionError::VMError {
+                    context: "ReadOnlyExecutionTarget::BytecodeExecution".to_string(),
+                    error,
+                })?
+            }
+
+            ReadOnlyExecutionTarget::FunctionCall {
+                target_addr,
+                target_func,
+                parameter,
+            } => {
+                let bytecode = {
+                    let mut context = context_guard!(self);
+
+                    // get the bytecode, default to an empty vector
+                    let bytecode = context.get_bytecode(&target_addr).unwrap_or_default().0;
+
+                    // Ensure that the target address is an SC address and exists
+                    context.check_target_sc_address(target_addr)?;
+
+                    let call_stack_addr = context.get_call_stack();
+
+                    // transfer fee
+                    if let (Some(fee), Some(addr)) = (req.fee, call_stack_addr.get(0)) {
+                        context.transfer_coins(Some(*addr), None, fee, false)?;
+                    }
+
+                    // transfer coins
+                    if let (Some(coins), Some(from), Some(to)) =
+                        (req.coins, call_stack_addr.get(0), call_stack_addr.get(1))
+                    {
+                        context.transfer_coins(Some(*from), Some(*to), coins, false)?;
+                    }
+
+                    // start tracing the execution if requested, with the call of the target
+                    if let (true, Some(addr)) = (req.trace, call_stack_addr.get(0)) {
+                        let mut tracer = ExecutionTracer::new(*addr, Some(req.max_gas));
+                        tracer.enter_call(
+                            target_addr,
+                            req.coins.unwrap_or_default(),
+                            Some(req.max_gas),
+                        );
+                        context.tracer = Some(tracer);
+                    }
+
+                    bytecode
+                };
+
+                // load and execute the compiled module
+                // IMPORTANT: do not keep a lock here as `run_function` uses the `get_module` interface
+                let module = self
+                    .module_cache
+                    .write()
+                    .load_module(&bytecode, req.max_gas)?;
+
+                let response = massa_sc_runtime::run_function(
+                    self.executor.interface(),
+                    module,
+                    &target_func,
+                    &parameter,
+                    req.max_gas,
+                    self.config.gas_costs.clone(),
+                );
+
+                match response {
+                    Ok(Response { init_gas_cost, .. })
+                    | Err(VMError::ExecutionError { init_gas_cost, .. }) => {
+                        self.module_cache
+                            .write()
+                            .set_init_cost(&bytecode, init_gas_cost);
+                    }
+                    _ => (),
+                }
+
+                response.map_err(|error| ExecutionError::VMError {
+                    context: "ReadOnlyExecutionTarget::FunctionCall".to_string(),
+                    error,
+                })?
+            }
+        };
+
+        let exact_exec_cost = req.max_gas.saturating_sub(exec_response.remaining_gas);
+
+        // the gas consumed by the traced call is known once the VM returns
+        if let Some(tracer) = context_guard!(self).tracer.as_mut() {
+            tracer.set_gas_remaining(exec_response.remaining_gas);
+        }
+
+        // compute a gas cost, estimating the gas of the last SC call to be max_instance_cost
+        let corrected_cost = match (context_guard!(self)).gas_remaining_before_subexecution {
+            Some(gas_remaining) => req
+                .max_gas
+                .saturating_sub(gas_remaining) // yield gas used until last subexecution
+                .saturating_add(self.config.gas_costs.max_instance_cost),
+            None => self.config.gas_costs.max_instance_cost, // no subexecution, just max_instance_cost
+        };
+
+        // keep the max of the two so the last SC call has at least max_instance_cost of gas
+        let estimated_cost = u64::max(exact_exec_cost, corrected_cost);
+        debug!(
+            "execute_readonly_request:
+            exec_response.remaining_gas: {}
+            exact_exec_cost: {}
+            corrected_cost: {}
+            estimated_cost: {}",
+            exec_response.remaining_gas, exact_exec_cost, corrected_cost, estimated_cost
+        );
+
+        Ok((estimated_cost, exec_response.ret))
+    }
+
+    /// Runs a read-only execution request.
+    /// The executed bytecode appears to be able to read and write the consensus state,
+    /// but all accumulated changes are simply returned as an `ExecutionOutput` object,
+    /// and not actually applied to the consensus state.
+    ///
+    /// # Arguments
+    /// * `snapshot`: snapshot of the execution state the execution is run on top of
+    /// * `deadline`: deadline of the request
+    /// * `req`: a read-only execution request
+    ///
+    /// # Returns
+    ///  `ExecutionOutput` describing the output of the execution, or an error
+    pub fn execute_readonly_request(
+        &self,
+        snapshot: ReadOnlySnapshot,
+        deadline: &ReadOnlyDeadline,
+        req: ReadOnlyExecutionRequest,
+    ) -> Result<ReadOnlyExecutionOutput, ExecutionError> {
+        // TODO ensure that speculative things are reset after every execution ends (incl. on error and readonly)
+        // otherwise, on prod stats accumulation etc... from the API we might be counting the remainder of this speculative execution
+
+        // create a readonly execution context
+        let mut execution_context =
+            self.new_readonly_context(snapshot, deadline, req.call_stack.clone());
+        execution_context.set_state_overrides(&req.state_overrides)?;
+        *context_guard!(self) = execution_context;
+
+        // run the execution
+        let (gas_cost, call_result) = self.run_readonly_call(req)?;
+
+        // return the execution output
+        let mut context = context_guard!(self);
+        Ok(ReadOnlyExecutionOutput {
+            out: context.settle_slot(None),
+            gas_cost,
+            storage_cost: context.get_storage_costs(),
+            call_result,
+            trace: context.tracer.take().map(ExecutionTracer::finish),
+        })
+    }
+
+    /// Runs a sequence of read-only executions in a single read-only execution context.
+    /// Each execution sees the changes of the previous ones, and the sequence stops at the first failed execution.
+    /// The accumulated changes are returned and not actually applied to the consensus state.
+    ///
+    /// # Arguments
+    /// * `snapshot`: snapshot of the execution state the executions are run on top of
+    /// * `deadline`: deadline of the request
+    /// * `req`: the executions to run, in order
+    ///
+    /// # Returns
+    ///  `ReadOnlyMulticallOutput` describing the output of each execution and the changes of the whole sequence,
+    /// or an error if the sequence could not be started
+    pub fn execute_readonly_multicall(
+        &self,
+        snapshot: ReadOnlySnapshot,
+        deadline: &ReadOnlyDeadline,
+        req: ReadOnlyMulticallRequest,
+    ) -> Result<ReadOnlyMulticallOutput, ExecutionError> {
+        // the overrides of all the executions are applied before the first one
+        let state_overrides: PreHashMap<Address, StateOverride> = req
+            .calls
+            .iter()
+            .flat_map(|call| call.state_overrides.clone())
+            .collect();
+
+        // create a readonly execution context shared by all the executions
+        let mut execution_context = self.new_readonly_context(snapshot, deadline, Vec::new());
+        execution_context.set_state_overrides(&state_overrides)?;
+        *context_guard!(self) = execution_context;
+
+        let mut calls = Vec::with_capacity(req.calls.len());
+        for call in req.calls {
+            let snapshot = context_guard!(self).get_snapshot();
+            let result = self.run_readonly_call(call);
+            let mut context = context_guard!(self);
+            let trace = context.tracer.take().map(ExecutionTracer::finish);
+            match result {
+                Ok((gas_cost, call_result)) => calls.push(Ok(ReadOnlyMulticallCallOutput {
+                    events: std::mem::take(&mut context.events),
+                    gas_cost,
+                    call_result,
+                    trace,
+                })),
+                Err(err) => {
+                    // drop the changes and the events of the failed execution, and stop there
+                    context.reset_to_snapshot(snapshot, err.clone());
+                    context.events = Default::default();
+                    calls.push(Err(err));
+                    break;
+                }
+            }
+        }
+
+        let execution_output = context_guard!(self).settle_slot(None);
+        Ok(ReadOnlyMulticallOutput {
+            slot: execution_output.slot,
+            calls,
+            state_changes: execution_output.state_changes,
+        })
+    }
+
+    /// Checks a batch of signed operations as if they were included in blocks at the slot after the latest executed active slot.
+    /// The operations are executed in order in a single read-only execution context,
+    /// so that each one sees the changes of the previous ones, and nothing is applied to the consensus state.
+    ///
+    /// # Arguments
+    /// * `snapshot`: snapshot of the execution state on top of which the operations are checked
+    /// * `deadline`: deadline of the request
+    /// * `operations`: the operations to check, in order
+    ///
+    /// # Returns
+    /// For each operation, the reason why it would not be included or why its execution would fail, if any
+    pub fn check_operations(
+        &self,
+        snapshot: ReadOnlySnapshot,
+        deadline: &ReadOnlyDeadline,
+        operations: Vec<SecureShareOperation>,
+    ) -> Result<Vec<Result<(), OperationCheckFailure>>, ExecutionError> {
+        let slot = snapshot.slot;
+        *context_guard!(self) = self.new_readonly_context(snapshot, deadline, Vec::new());
+        Ok(operations
+            .iter()
+            .map(|operation| self.check_operation(slot, operation))
+            .collect())
+    }
+
+    /// Runs the checks done before including an operation in a block, then executes it
+    /// on top of the current read-only execution context, the same way as the operations of a block.
+    ///
+    /// # Arguments
+    /// * `slot`: slot at which the operation is checked
+    /// * `operation`: the operation to check
+    fn check_operation(
+        &self,
+        slot: Slot,
+        operation: &SecureShareOperation,
+    ) -> Result<(), OperationCheckFailure> {
+        // check validity period
+        let validity_range = operation.get_validity_range(self.config.operation_validity_period);
+        if !validity_range.contains(&slot.period) {
+            return Err(OperationCheckFailure::InvalidPeriod(format!(
+                "the operation can only be included from period {} to period {}, the current period is {}",
+                validity_range.start(),
+                validity_range.end(),
+                slot.period
+            )));
+        }
+
+        // check that the operation fits in a block
+        let op_gas = operation.get_gas_usage(
+            self.config.base_operation_gas_cost,
+            self.config.gas_costs.sp_compilation_cost,
+        );
+        if op_gas > self.config.max_gas_per_block {
+            return Err(OperationCheckFailure::TooMuchGas(format!(
+                "the operation uses {} gas but a block can only hold {}",
+                op_gas, self.config.max_gas_per_block
+            )));
+        }
+
+        let sender_addr = operation.content_creator_address;
+        {
+            let context = context_guard!(self);
+
+            if context.is_op_executed(&operation.id) {
+                return Err(OperationCheckFailure::AlreadyExecuted);
+            }
+
+            // the pool only keeps the operations whose max spending is covered by the sender's balance
+            let balance = context
+                .get_balance(&sender_addr)
+                .unwrap_or_else(Amount::zero);
+            let max_spending = operation.get_max_spending(self.config.roll_price);
+            if balance < max_spending {
+                return Err(OperationCheckFailure::InsufficientBalance(format!(
+                    "the sender {} has {} coins but the operation can spend up to {}",
+                    sender_addr, balance, max_spending
+                )));
+            }
+        }
+
+        // spend the fee and execute the operation like the operations of a block,
+        // marking it as executed so that it cannot be included again later in the batch
+        let context_snapshot = self
+            .executor
+            .prepare_operation_for_execution(operation, sender_addr)
+            .map_err(|err| OperationCheckFailure::InsufficientBalance(err.to_string()))?;
+        self.executor
+            .execute_prepared_operation(operation, context_snapshot)
+            .map_err(|err| OperationCheckFailure::ExecutionFailed(err.to_string()))
+    }
+
+    /// Executes again an operation of a block of the active history, and returns its call trace.
+    ///
+    /// The slot of the block is executed again in a separate execution context, following the sequence of
+    /// `ExecutionState::execute_slot` up to the operation: the asynchronous messages of the slot,
+    /// then the operations of the block before the traced one.
+    /// The rest of the slot (denunciations, block rewards and production stats) is executed after all the operations
+    /// of the block and cannot change the outcome of the traced operation, so it is not executed again.
+    /// Nothing is applied to the consensus state.
+    ///
+    /// # Arguments
+    /// * `snapshot`: snapshot of the execution state at the start of the slot of the block
+    /// * `deadline`: deadline of the request, checked by the context at each call
+    /// * `operation_id`: id of the operation to trace
+    /// * `block`: the block including the operation and its metadata
+    pub fn trace_operation(
+        &self,
+        snapshot: ReadOnlySnapshot,
+        deadline: &ReadOnlyDeadline,
+        operation_id: &OperationId,
+        block: &(BlockId, ExecutionBlockMetadata),
+    ) -> Result<ExecutionTraceFrame, ExecutionError> {
+        let (block_id, block_metadata) = block;
+
+        // create an execution context for the slot, and take its asynchronous messages
+        let mut execution_context = ExecutionContext::active_slot(
+            self.config.clone(),
+            snapshot.slot,
+            Some(*block_id),
+            self.final_state.clone(),
+            snapshot.active_history,
+            self.module_cache.clone(),
+            self.mip_store.clone(),
+        );
+        execution_context.readonly_deadline = Some(deadline.clone());
+        let messages = execution_context.take_async_batch(
+            self.config.max_async_gas,
+            self.config.async_msg_cst_gas_cost,
+        );
+        *context_guard!(self) = execution_context;
+
+        for (opt_bytecode, message) in messages {
+            if let Err(err) = self.executor.execute_async_message(message, opt_bytecode) {
+                debug!("failed executing async message: {}", err);
+            }
+        }
+
+        // gather the operations of the block
+        let block_store = block_metadata.storage.as_ref().ok_or_else(|| {
+            ExecutionError::RuntimeError(format!("storage of block {} is missing", block_id))
+        })?;
+        let (block_slot, operations) = {
+            let blocks = block_store.read_blocks();
+            let block = blocks.get(block_id).ok_or_else(|| {
+                ExecutionError::RuntimeError(format!("block {} is missing from storage", block_id))
+            })?;
+            let ops = block_store.read_operations();
+            let operations = block
+                .content
+                .operations
+                .iter()
+                .map(|op_id| {
+                    ops.get(op_id).cloned().ok_or_else(|| {
+                        ExecutionError::RuntimeError(format!(
+                            "operation {} is missing from storage",
+                            op_id
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            (block.content.header.content.slot, operations)
+        };
+
+        // execute the operations of the block until the traced one
+        let mut remaining_block_gas = self.config.max_gas_per_block;
+        let mut block_credits = self.config.block_reward;
+        for operation in operations {
+            if &operation.id != operation_id {
+                if let Err(err) = self.executor.execute_operation(
+                    &operation,
+                    block_slot,
+                    &mut remaining_block_gas,
+                    &mut block_credits,
+                ) {
+                    debug!(
+                        "failed executing operation {} in block {}: {}",
+                        operation.id, block_id, err
+                    );
+                }
+                continue;
+            }
+
+            context_guard!(self).tracer = Some(ExecutionTracer::new(
+                operation.content_creator_address,
+                None,
+            ));
+            let result = self.executor.execute_operation(
+                &operation,
+                block_slot,
+                &mut remaining_block_gas,
+                &mut block_credits,
+            );
+            let tracer = context_guard!(self).tracer.take();
+            result?;
+            return tracer.map(ExecutionTracer::finish).ok_or_else(|| {
+                ExecutionError::RuntimeError(format!(
+                    "trace of operation {} is missing",
+                    operation_id
+                ))
+            });
+        }
+
+        Err(ExecutionError::RuntimeError(format!(
+            "operation {} not found in block {}",
+            operation_id, block_id
+        )))
+    }
+}
+
+/// Structure gathering all elements needed by a read-only executor thread
+struct ReadOnlyExecutorThread {
+    /// shared input data allowing access to incoming read-only requests
+    input_data: Arc<(Condvar, Mutex<ReadOnlyInputData>)>,
+    /// execution state on top of which the requests are executed
+    execution_state: Arc<RwLock<ExecutionState>>,
+    /// executor running the requests
+    executor: ReadOnlyExecutor,
+    /// prometheus metrics
+    massa_metrics: MassaMetrics,
+}
+
+impl ReadOnlyExecutorThread {
+    /// Waits for a read-only request to execute.
+    ///
+    /// # Returns
+    /// The oldest request of the next queue to serve, or None if the thread needs to stop
+    fn wait_task(&self) -> Option<ReadOnlyTask> {
+        let mut input_data = self.input_data.1.lock();
+        loop {
+            if input_data.stop {
+                return None;
+            }
+            if let Some(task) = input_data.pop_task() {
+                self.massa_metrics
+                    .set_readonly_queue_depth(input_data.queue_depth());
+                return Some(task);
+            }
+            self.input_data.0.wait(&mut input_data);
+        }
+    }
+
+    /// Runs a read-only request on top of a snapshot of the current execution state, if its deadline is not reached yet
+    ///
+    /// # Arguments
+    /// * `deadline`: deadline of the request
+    /// * `run`: the execution to run, given the snapshot of the execution state
+    fn run_before_deadline<R>(
+        &self,
+        deadline: &ReadOnlyDeadline,
+        run: impl FnOnce(ReadOnlySnapshot) -> Result<R, ExecutionError>,
+    ) -> Result<R, ExecutionError> {
+        self.run_on_snapshot_before_deadline(
+            deadline,
+            || self.execution_state.read().get_readonly_snapshot(),
+            run,
+        )
+    }
+
+    /// Runs a read-only request on top of a snapshot of the execution state, if its deadline is not reached yet
+    ///
+    /// # Arguments
+    /// * `deadline`: deadline of the request
+    /// * `get_snapshot`: provides the snapshot of the execution state, only called if the deadline is not reached
+    /// * `run`: the execution to run, given the snapshot of the execution state
+    fn run_on_snapshot_before_deadline<R>(
+        &self,
+        deadline: &ReadOnlyDeadline,
+        get_snapshot: impl FnOnce() -> ReadOnlySnapshot,
+        run: impl FnOnce(ReadOnlySnapshot) -> Result<R, ExecutionError>,
+    ) -> Result<R, ExecutionError> {
+        if deadline.is_expired() {
+            self.massa_metrics.inc_readonly_timeouts();
+            return Err(ExecutionError::ReadOnlyTimeout(
+                "request expired before being executed".into(),
+            ));
+        }
+
+        let snapshot = get_snapshot();
+        let pinned_final_slot = snapshot.pinned_final_slot;
+        let outcome = run(snapshot);
+
+        // the caller stopped waiting for the output of an execution that ended after the deadline
+        if deadline.is_expired() {
+            self.massa_metrics.inc_readonly_timeouts();
+        }
+
+        // the final state moving past the snapshot exposed changes the copied active history does not hold
+        let final_slot = self.execution_state.read().final_cursor;
+        if outcome.is_ok() && final_slot > pinned_final_slot {
+            return Err(ExecutionError::ReadOnlySnapshotExpired(format!(
+                "the final state moved to slot {} while the request was running on top of slot {}",
+                final_slot, pinned_final_slot
+            )));
+        }
+        outcome
+    }
+
+    /// Main loop of the read-only executor thread
+    fn main_loop(&self) {
+        while let Some(task) = self.wait_task() {
+            // Send the outputs through the response channels.
+            // Ignore errors because they just mean that the request emitter dropped the received
+            // because it doesn't need the response anymore.
+            match task {
+                ReadOnlyTask::Request(req_resp) => {
+                    let ((req, deadline), resp_tx) = req_resp.into_request_sender_pair();
+                    let outcome = self.run_before_deadline(&deadline, |snapshot| {
+                        self.executor
+                            .execute_readonly_request(snapshot, &deadline, req)
+                    });
+                    let _ = resp_tx.send(outcome);
+                }
+                ReadOnlyTask::Multicall(req_resp) => {
+                    let ((req, deadline), resp_tx) = req_resp.into_request_sender_pair();
+                    let outcome = self.run_before_deadline(&deadline, |snapshot| {
+                        self.executor
+                            .execute_readonly_multicall(snapshot, &deadline, req)
+                    });
+                    let _ = resp_tx.send(outcome);
+                }
+                ReadOnlyTask::OperationCheck(req_resp) => {
+                    let ((operations, deadline), resp_tx) = req_resp.into_request_sender_pair();
+                    let outcome = self.run_before_deadline(&deadline, |snapshot| {
+                        self.executor
+                            .check_operations(snapshot, &deadline, operations)
+                    });
+                    let _ = resp_tx.send(outcome);
+                }
+                ReadOnlyTask::Trace(req_resp) => {
+                    let ((req, deadline), resp_tx) = req_resp.into_request_sender_pair();
+                    let TraceRequest {
+                        operation_id,
+                        block,
+                        snapshot,
+                    } = req;
+                    let outcome = self.run_on_snapshot_before_deadline(
+                        &deadline,
+                        || snapshot,
+                        |snapshot| {
+                            self.executor.trace_operation(
+                                snapshot,
+                                &deadline,
+                                &operation_id,
+                                &block,
+                            )
+                        },
+                    );
+                    let _ = resp_tx.send(outcome);
+                }
+            }
+        }
+
+        // Cancel the pending requests
+        let cancel_err = ExecutionError::ChannelError(
+            "readonly execution cancelled because the execution worker is closing".into(),
+        );
+        let mut input_data = self.input_data.1.lock();
+        input_data.readonly_requests.cancel(cancel_err.clone());
+        input_data
+            .readonly_multicall_requests
+            .cancel(cancel_err.clone());
+        input_data
+            .operation_check_requests
+            .cancel(cancel_err.clone());
+        input_data.trace_requests.cancel(cancel_err);
+        self.massa_metrics.set_readonly_queue_depth(0);
+    }
+}
+
+/// Launches the read-only executor threads
+///
+/// # Arguments
+/// * `config`: execution configuration
+/// * `input_data`: shared input data through which the read-only requests are received
+/// * `execution_state`: execution state on top of which the requests are executed
+/// * `massa_metrics`: prometheus metrics
+///
+/// # Returns
+/// The handles of the launched threads
+///
+/// # Panics
+/// If `config.readonly_executor_count` is 0, as the queued requests would never be executed
+pub(crate) fn start_readonly_executors(
+    config: &ExecutionConfig,
+    input_data: Arc<(Condvar, Mutex<ReadOnlyInputData>)>,
+    execution_state: Arc<RwLock<ExecutionState>>,
+    massa_metrics: MassaMetrics,
+) -> Vec<thread::JoinHandle<()>> {
+    assert!(
+        config.readonly_executor_count >= 1,
+        "at least one read-only executor thread is needed"
+    );
+    (0..config.readonly_executor_count)
+        .map(|index| {
+            let executor_thread = ReadOnlyExecutorThread {
+                input_data: input_data.clone(),
+                executor: execution_state.read().new_readonly_executor(),
+                execution_state: execution_state.clone(),
+                massa_metrics: massa_metrics.clone(),
+            };
+            thread::Builder::new()
+                .name(format!("readonly_execution_{}", index))
+                .spawn(move || executor_thread.main_loop())
+                .expect("failed to spawn thread : readonly_execution")
+        })
+        .collect()
+}
@@ -154,6 +154,11 @@ impl<T, R> RequestQueue<T, R> {
         self.queue.len() >= self.max_items
     }
 
+    /// Returns the number of items in the queue
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
     /// Checks whether the queue is empty
     ///
     /// # Returns
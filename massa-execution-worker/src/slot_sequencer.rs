@@ -564,6 +564,12 @@ impl SlotSequencer {
             .and_then(|idx| self.sequence.get(idx))
     }
 
+    /// Gets the content (block ID and metadata) of a slot of the sequence, if any
+    pub fn get_slot_content(&self, slot: &Slot) -> Option<&(BlockId, ExecutionBlockMetadata)> {
+        self.get_slot(slot)
+            .and_then(|slot_info| slot_info.content.as_ref())
+    }
+
     /// Returns true if there is a queued slot that needs to be executed now.
     pub fn is_task_available(&self) -> bool {
         // The sequence is empty => nothing to do.
@@ -12,6 +12,8 @@ use massa_final_state::FinalStateController;
 use massa_ledger_exports::{Applicable, LedgerChanges, SetOrDelete, SetUpdateOrDelete};
 use massa_models::bytecode::Bytecode;
 use massa_models::datastore::get_prefix_bounds;
+use massa_models::execution::StateOverride;
+use massa_models::prehash::PreHashMap;
 use massa_models::{address::Address, amount::Amount};
 use parking_lot::RwLock;
 use std::cmp::Ordering;
@@ -49,6 +51,9 @@ pub(crate) struct SpeculativeLedger {
     ))]
     pub added_changes: LedgerChanges,
 
+    /// state overrides of a read-only execution, applied on top of the history but not part of the added changes
+    state_overrides: LedgerChanges,
+
     /// max datastore key length
     max_datastore_key_length: u8,
 
@@ -60,6 +65,12 @@ pub(crate) struct SpeculativeLedger {
 
     /// storage cost constants
     storage_costs_constants: StorageCostsConstants,
+
+    /// storage costs charged since the creation of this `SpeculativeLedger`
+    storage_costs_paid: Amount,
+
+    /// storage costs refunded since the creation of this `SpeculativeLedger`
+    storage_costs_refunded: Amount,
 }
 
 impl SpeculativeLedger {
@@ -79,11 +90,14 @@ impl SpeculativeLedger {
         SpeculativeLedger {
             final_state,
             added_changes: Default::default(),
+            state_overrides: Default::default(),
             active_history,
             max_datastore_key_length,
             max_datastore_value_size,
             max_bytecode_size,
             storage_costs_constants,
+            storage_costs_paid: Amount::zero(),
+            storage_costs_refunded: Amount::zero(),
         }
     }
 
@@ -93,14 +107,94 @@ impl SpeculativeLedger {
         std::mem::take(&mut self.added_changes)
     }
 
-    /// Takes a snapshot (clone) of the changes caused to the `SpeculativeLedger` since its creation
-    pub fn get_snapshot(&self) -> LedgerChanges {
-        self.added_changes.clone()
+    /// Takes a snapshot (clone) of the changes caused to the `SpeculativeLedger` since its creation,
+    /// along with the storage costs paid and refunded so far
+    pub fn get_snapshot(&self) -> (LedgerChanges, Amount, Amount) {
+        (
+            self.added_changes.clone(),
+            self.storage_costs_paid,
+            self.storage_costs_refunded,
+        )
     }
 
     /// Resets the `SpeculativeLedger` to a snapshot (see `get_snapshot` method)
-    pub fn reset_to_snapshot(&mut self, snapshot: LedgerChanges) {
-        self.added_changes = snapshot;
+    pub fn reset_to_snapshot(&mut self, snapshot: (LedgerChanges, Amount, Amount)) {
+        (
+            self.added_changes,
+            self.storage_costs_paid,
+            self.storage_costs_refunded,
+        ) = snapshot;
+    }
+
+    /// Returns the net storage costs paid since the creation of the `SpeculativeLedger`,
+    /// i.e. the storage costs charged minus the storage costs refunded, saturating at zero
+    pub fn get_storage_costs(&self) -> Amount {
+        self.storage_costs_paid
+            .saturating_sub(self.storage_costs_refunded)
+    }
+
+    /// Charges storage costs to an address by destroying the corresponding coins
+    fn pay_storage_costs(&mut self, addr: Address, amount: Amount) -> Result<(), ExecutionError> {
+        self.transfer_coins(Some(addr), None, amount)?;
+        self.storage_costs_paid = self.storage_costs_paid.saturating_add(amount);
+        Ok(())
+    }
+
+    /// Refunds storage costs to an address by creating the corresponding coins
+    fn refund_storage_costs(
+        &mut self,
+        addr: Address,
+        amount: Amount,
+    ) -> Result<(), ExecutionError> {
+        self.transfer_coins(None, Some(addr), amount)?;
+        self.storage_costs_refunded = self.storage_costs_refunded.saturating_add(amount);
+        Ok(())
+    }
+
+    /// Sets the state overrides of a read-only execution.
+    /// Addresses that do not exist are created.
+    ///
+    /// # Arguments
+    /// * `overrides`: overrides of the balance, bytecode and datastore entries of each address
+    pub fn set_state_overrides(
+        &mut self,
+        overrides: &PreHashMap<Address, StateOverride>,
+    ) -> Result<(), ExecutionError> {
+        let mut changes = LedgerChanges::default();
+        for (addr, state_override) in overrides.iter() {
+            if !self.entry_exists(addr) {
+                changes.create_address(addr);
+            }
+            if let Some(balance) = state_override.balance {
+                changes.set_balance(*addr, balance);
+            }
+            if let Some(bytecode) = &state_override.bytecode {
+                if bytecode.0.len() > self.max_bytecode_size as usize {
+                    return Err(ExecutionError::RuntimeError(format!(
+                        "could not override bytecode for address {}: bytecode size exceeds maximum allowed size",
+                        addr
+                    )));
+                }
+                changes.set_bytecode(*addr, bytecode.clone());
+            }
+            for (key, value) in state_override.datastore.iter() {
+                if key.len() > self.max_datastore_key_length as usize {
+                    return Err(ExecutionError::RuntimeError(format!(
+                        "could not override data for address {}: datastore key is too long",
+                        addr
+                    )));
+                }
+                if value.len() as u64 > self.max_datastore_value_size {
+                    return Err(ExecutionError::RuntimeError(format!(
+                        "could not override data for address {}: datastore value is too big",
+                        addr
+                    )));
+                }
+                changes.set_data_entry(*addr, key.clone(), value.clone());
+            }
+        }
+        self.state_overrides = changes;
+        Ok(())
     }
 
     /// Gets the effective balance of an address
@@ -111,15 +205,17 @@ impl SpeculativeLedger {
     /// # Returns
     /// Some(Amount) if the address was found, otherwise None
     pub fn get_balance(&self, addr: &Address) -> Option<Amount> {
-        // try to read from added changes > history > final_state
+        // try to read from added changes > state overrides > history > final_state
         self.added_changes.get_balance_or_else(addr, || {
-            match self.active_history.read().fetch_balance(addr) {
-                HistorySearchResult::Present(par_balance) => Some(par_balance),
-                HistorySearchResult::NoInfo => {
-                    self.final_state.read().get_ledger().get_balance(addr)
+            self.state_overrides.get_balance_or_else(addr, || {
+                match self.active_history.read().fetch_balance(addr) {
+                    HistorySearchResult::Present(par_balance) => Some(par_balance),
+                    HistorySearchResult::NoInfo => {
+                        self.final_state.read().get_ledger().get_balance(addr)
+                    }
+                    HistorySearchResult::Absent => None,
                 }
-                HistorySearchResult::Absent => None,
-            }
+            })
         })
     }
 
@@ -131,15 +227,17 @@ impl SpeculativeLedger {
     /// # Returns
     /// `Some(Bytecode)` if the address was found, otherwise None
     pub fn get_bytecode(&self, addr: &Address) -> Option<Bytecode> {
-        // try to read from added changes > history > final_state
+        // try to read from added changes > state overrides > history > final_state
         self.added_changes.get_bytecode_or_else(addr, || {
-            match self.active_history.read().fetch_bytecode(addr) {
-                HistorySearchResult::Present(bytecode) => Some(bytecode),
-                HistorySearchResult::NoInfo => {
-                    self.final_state.read().get_ledger().get_bytecode(addr)
+            self.state_overrides.get_bytecode_or_else(addr, || {
+                match self.active_history.read().fetch_bytecode(addr) {
+                    HistorySearchResult::Present(bytecode) => Some(bytecode),
+                    HistorySearchResult::NoInfo => {
+                        self.final_state.read().get_ledger().get_bytecode(addr)
+                    }
+                    HistorySearchResult::Absent => None,
                 }
-                HistorySearchResult::Absent => None,
-            }
+            })
         })
     }
 
@@ -159,6 +257,7 @@ impl SpeculativeLedger {
     ) -> Result<(), ExecutionError> {
         // init empty ledger changes
         let mut changes = LedgerChanges::default();
+        let mut created_address = false;
 
         // simulate spending coins from sender address (if any)
         if let Some(from_addr) = from_addr {
@@ -197,6 +296,7 @@ impl SpeculativeLedger {
                 debug!("Creating address {} from coins", to_addr);
                 changes.create_address(&to_addr);
                 changes.set_balance(to_addr, remaining_coins);
+                created_address = true;
             } else {
                 // `to_addr` does not exist and we don't have the money to create it
                 return Err(ExecutionError::RuntimeError(format!(
@@ -209,6 +309,13 @@ impl SpeculativeLedger {
         // apply the simulated changes to the speculative ledger
         self.added_changes.apply(changes);
 
+        // the coins used to create the credited address pay for its storage
+        if created_address {
+            self.storage_costs_paid = self
+                .storage_costs_paid
+                .saturating_add(self.storage_costs_constants.ledger_entry_base_cost);
+        }
+
         Ok(())
     }
 
@@ -220,15 +327,17 @@ impl SpeculativeLedger {
     /// # Returns
     /// true if the address was found, otherwise false
     pub fn entry_exists(&self, addr: &Address) -> bool {
-        // try to read from added changes > history > final_state
+        // try to read from added changes > state overrides > history > final_state
         self.added_changes.entry_exists_or_else(addr, || {
-            match self.active_history.read().fetch_balance(addr) {
-                HistorySearchResult::Present(_balance) => true,
-                HistorySearchResult::NoInfo => {
-                    self.final_state.read().get_ledger().entry_exists(addr)
+            self.state_overrides.entry_exists_or_else(addr, || {
+                match self.active_history.read().fetch_balance(addr) {
+                    HistorySearchResult::Present(_balance) => true,
+                    HistorySearchResult::NoInfo => {
+                        self.final_state.read().get_ledger().entry_exists(addr)
+                    }
+                    HistorySearchResult::Absent => false,
                 }
-                HistorySearchResult::Absent => false,
-            }
+            })
         })
     }
 
@@ -290,7 +399,7 @@ impl SpeculativeLedger {
                 ExecutionError::RuntimeError("overflow in ledger cost for bytecode".to_string())
             })?;
 
-        self.transfer_coins(Some(creator_address), None, address_storage_cost)?;
+        self.pay_storage_costs(creator_address, address_storage_cost)?;
         self.added_changes.create_address(&addr);
         self.added_changes.set_bytecode(addr, bytecode);
         Ok(())
@@ -337,8 +446,8 @@ impl SpeculativeLedger {
                 })?;
 
             match diff_size_storage.signum() {
-                1 => self.transfer_coins(Some(*caller_addr), None, storage_cost_bytecode)?,
-                -1 => self.transfer_coins(None, Some(*caller_addr), storage_cost_bytecode)?,
+                1 => self.pay_storage_costs(*caller_addr, storage_cost_bytecode)?,
+                -1 => self.refund_storage_costs(*caller_addr, storage_cost_bytecode)?,
                 _ => {}
             };
         } else {
@@ -351,7 +460,7 @@ impl SpeculativeLedger {
                         "overflow when calculating storage cost of bytecode".to_string(),
                     )
                 })?;
-            self.transfer_coins(Some(*caller_addr), None, bytecode_storage_cost)?;
+            self.pay_storage_costs(*caller_addr, bytecode_storage_cost)?;
         }
         // set the bytecode of that address
         self.added_changes.set_bytecode(*addr, bytecode);
@@ -379,12 +488,13 @@ impl SpeculativeLedger {
             .get_ledger()
             .get_datastore_keys(addr, prefix);
 
-        // here, traverse the history from oldest to newest with added_changes at the end, applying additions and deletions
+        // here, traverse the history from oldest to newest with state_overrides then added_changes at the end, applying additions and deletions
         let active_history = self.active_history.read();
         let changes_iterator = active_history
             .0
             .iter()
             .map(|item| &item.state_changes.ledger_changes)
+            .chain(std::iter::once(&self.state_overrides))
             .chain(std::iter::once(&self.added_changes));
         for ledger_changes in changes_iterator {
             match ledger_changes.get(addr) {
@@ -434,21 +544,23 @@ impl SpeculativeLedger {
     /// # Returns
     /// `Some(Vec<u8>)` if the value was found, `None` if the address does not exist or if the key is not in its datastore.
     pub fn get_data_entry(&self, addr: &Address, key: &[u8]) -> Option<Vec<u8>> {
-        // try to read from added changes > history > final_state
+        // try to read from added changes > state overrides > history > final_state
         self.added_changes.get_data_entry_or_else(addr, key, || {
-            match self
-                .active_history
-                .read()
-                .fetch_active_history_data_entry(addr, key)
-            {
-                HistorySearchResult::Present(entry) => Some(entry),
-                HistorySearchResult::NoInfo => self
-                    .final_state
+            self.state_overrides.get_data_entry_or_else(addr, key, || {
+                match self
+                    .active_history
                     .read()
-                    .get_ledger()
-                    .get_data_entry(addr, key),
-                HistorySearchResult::Absent => None,
-            }
+                    .fetch_active_history_data_entry(addr, key)
+                {
+                    HistorySearchResult::Present(entry) => Some(entry),
+                    HistorySearchResult::NoInfo => self
+                        .final_state
+                        .read()
+                        .get_ledger()
+                        .get_data_entry(addr, key),
+                    HistorySearchResult::Absent => None,
+                }
+            })
         })
     }
 
@@ -461,22 +573,24 @@ impl SpeculativeLedger {
     /// # Returns
     /// true if the key exists in the address datastore, false otherwise
     pub fn has_data_entry(&self, addr: &Address, key: &[u8]) -> bool {
-        // try to read from added changes > history > final_state
+        // try to read from added changes > state overrides > history > final_state
         self.added_changes.has_data_entry_or_else(addr, key, || {
-            match self
-                .active_history
-                .read()
-                .fetch_active_history_data_entry(addr, key)
-            {
-                HistorySearchResult::Present(_entry) => true,
-                HistorySearchResult::NoInfo => self
-                    .final_state
+            self.state_overrides.has_data_entry_or_else(addr, key, || {
+                match self
+                    .active_history
                     .read()
-                    .get_ledger()
-                    .get_data_entry(addr, key)
-                    .is_some(),
-                HistorySearchResult::Absent => false,
-            }
+                    .fetch_active_history_data_entry(addr, key)
+                {
+                    HistorySearchResult::Present(_entry) => true,
+                    HistorySearchResult::NoInfo => self
+                        .final_state
+                        .read()
+                        .get_ledger()
+                        .get_data_entry(addr, key)
+                        .is_some(),
+                    HistorySearchResult::Absent => false,
+                }
+            })
         })
     }
 
@@ -552,17 +666,15 @@ impl SpeculativeLedger {
         match new_storage_cost.cmp(&old_storage_cost) {
             Ordering::Greater => {
                 // more bytes are now occupied
-                self.transfer_coins(
-                    Some(*caller_addr),
-                    None,
+                self.pay_storage_costs(
+                    *caller_addr,
                     new_storage_cost.saturating_sub(old_storage_cost),
                 )
             }
             Ordering::Less => {
                 // some bytes have been freed
-                self.transfer_coins(
-                    None,
-                    Some(*caller_addr),
+                self.refund_storage_costs(
+                    *caller_addr,
                     old_storage_cost.saturating_sub(new_storage_cost),
                 )
             }
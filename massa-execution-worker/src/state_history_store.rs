@@ -0,0 +1,493 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! This module implements an optional disk-backed history of the final balances, datastore entries and roll counts.
+//!
+//! Before the state changes of a final slot are applied to the final state,
+//! the values they are about to overwrite are read and stored in a dedicated RocksDB instance:
+//! * balance, datastore entry and roll count records are keyed by an index prefix, the address
+//!   (and the length-prefixed datastore key) followed by the slot of the change
+//! * entry records mark the slots at which a whole ledger entry was replaced or deleted:
+//!   only the datastore keys set by the change are recorded, so the other values it dropped are not retained
+//! * slot keys point to the list of the records written at that slot, so that they can be pruned together
+//!
+//! The value of an entry at the output of a retained slot is the value replaced by its first change after that slot,
+//! or its final value if it was not changed since.
+//!
+//! The records are written and pruned by a dedicated thread, so that finalization never waits for the disk.
+//! The records that are not written yet are kept in memory and looked up along with the database.
+
+use massa_channel::{sender::MassaSender, MassaChannel};
+use massa_final_state::{FinalStateController, StateChanges};
+use massa_ledger_exports::{LedgerEntryUpdate, SetOrKeep, SetUpdateOrDelete};
+use massa_models::address::{Address, AddressSerializer};
+use massa_models::amount::Amount;
+use massa_models::slot::{Slot, SLOT_KEY_SIZE};
+use massa_serialization::Serializer;
+use parking_lot::Mutex;
+use rocksdb::{Direction, IteratorMode, WriteBatch, DB};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::ops::Bound;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use tracing::debug;
+
+const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
+const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
+const STATE_HISTORY_SER_ERROR: &str = "critical: state history serialization failed";
+const STATE_HISTORY_DESER_ERROR: &str = "critical: state history deserialization failed";
+
+const SLOT_IDENT: u8 = 0u8;
+const BALANCE_IDENT: u8 = 1u8;
+const DATASTORE_IDENT: u8 = 2u8;
+const ROLLS_IDENT: u8 = 3u8;
+const ENTRY_IDENT: u8 = 4u8;
+
+/// Build the key listing the records written at a slot
+fn slot_key(slot: &Slot) -> Vec<u8> {
+    [&[SLOT_IDENT][..], &slot.to_bytes_key()[..]].concat()
+}
+
+/// Get the slot of a slot key
+fn slot_of_key(key: &[u8]) -> Slot {
+    Slot::from_bytes_key(
+        key[1..=SLOT_KEY_SIZE]
+            .try_into()
+            .expect(STATE_HISTORY_DESER_ERROR),
+    )
+}
+
+/// Get the slot suffix of a record key
+fn record_slot(key: &[u8]) -> Slot {
+    Slot::from_bytes_key(
+        key[key.len() - SLOT_KEY_SIZE..]
+            .try_into()
+            .expect(STATE_HISTORY_DESER_ERROR),
+    )
+}
+
+/// Serialize an optional value: a presence byte followed by the value
+fn serialize_optional(value: Option<&[u8]>) -> Vec<u8> {
+    match value {
+        Some(value) => [&[1u8][..], value].concat(),
+        None => vec![0u8],
+    }
+}
+
+/// Deserialize an optional value serialized with `serialize_optional`
+fn deserialize_optional(buffer: &[u8]) -> Option<&[u8]> {
+    match buffer.split_first() {
+        Some((1u8, value)) => Some(value),
+        Some((0u8, _)) => None,
+        _ => panic!("{}", STATE_HISTORY_DESER_ERROR),
+    }
+}
+
+/// Value of a final state entry at the output of a slot, as known from the retained history
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum HistoricalValue<T> {
+    /// the slot is older than the retained history
+    NotRetained,
+    /// the entry was changed after the slot, and this was its value (`None` if it did not exist)
+    Changed(Option<T>),
+    /// the entry was not changed after the slot: its value is the current final one
+    Unchanged,
+}
+
+impl<T> HistoricalValue<T> {
+    fn map<U>(self, f: impl FnOnce(T) -> U) -> HistoricalValue<U> {
+        match self {
+            HistoricalValue::NotRetained => HistoricalValue::NotRetained,
+            HistoricalValue::Changed(value) => HistoricalValue::Changed(value.map(f)),
+            HistoricalValue::Unchanged => HistoricalValue::Unchanged,
+        }
+    }
+}
+
+/// Records of a final slot, as handed to the writer thread
+type SlotRecords = (Slot, Arc<BTreeMap<Vec<u8>, Vec<u8>>>);
+
+/// In-memory state shared by the store and its writer thread
+#[derive(Default)]
+struct StateHistoryShared {
+    /// records of the final slots that are not written to the database yet, from the oldest to the most recent
+    pending: VecDeque<SlotRecords>,
+    /// first retained slot, if any
+    first_slot: Option<Slot>,
+    /// last retained slot, if any
+    last_slot: Option<Slot>,
+    /// records of the periods before this one are pruned or about to be
+    min_period: u64,
+}
+
+/// Disk-backed history of the values overwritten by the final state changes
+pub(crate) struct StateHistoryStore {
+    /// RocksDB database
+    db: Arc<DB>,
+    /// Number of periods during which the overwritten values are kept
+    retention_periods: u64,
+    /// Number of threads
+    thread_count: u8,
+    /// Address serializer, used to build record keys
+    address_ser: AddressSerializer,
+    /// Pending records and retained slot bounds, shared with the writer thread
+    shared: Arc<Mutex<StateHistoryShared>>,
+    /// Sender of the records to the writer thread, along with the handle of the thread
+    writer: Option<(MassaSender<()>, JoinHandle<()>)>,
+}
+
+impl StateHistoryStore {
+    /// Create a new `StateHistoryStore` and start its writer thread
+    ///
+    /// # Arguments
+    /// * `path`: where to store the db
+    /// * `retention_periods`: number of periods during which the overwritten values are kept
+    /// * `thread_count`: number of threads
+    pub fn new(path: PathBuf, retention_periods: u64, thread_count: u8) -> Self {
+        let db = Arc::new(DB::open_default(path).expect(OPEN_ERROR));
+        let (first_slot, last_slot) = match Self::scan_retained_slots(&db) {
+            Some((first, last)) => (Some(first), Some(last)),
+            None => (None, None),
+        };
+        let shared = Arc::new(Mutex::new(StateHistoryShared {
+            first_slot,
+            last_slot,
+            ..Default::default()
+        }));
+        let writer = Self::start_writer(db.clone(), shared.clone());
+        Self {
+            db,
+            retention_periods,
+            thread_count,
+            address_ser: AddressSerializer::new(),
+            shared,
+            writer: Some(writer),
+        }
+    }
+
+    /// Start the thread writing the pending records to the database and pruning the old ones.
+    /// Each message tells the thread that the records of one more slot are pending.
+    fn start_writer(
+        db: Arc<DB>,
+        shared: Arc<Mutex<StateHistoryShared>>,
+    ) -> (MassaSender<()>, JoinHandle<()>) {
+        let (tx, rx) = MassaChannel::new("execution_state_history".to_string(), None);
+        let handle = thread::Builder::new()
+            .name("execution state history".into())
+            .spawn(move || {
+                while rx.recv().is_ok() {
+                    let Some((slot, records)) = shared.lock().pending.front().cloned() else {
+                        continue;
+                    };
+                    let mut batch = WriteBatch::default();
+                    let mut record_keys = Vec::new();
+                    for (key, value) in records.iter() {
+                        record_keys.extend((key.len() as u32).to_be_bytes());
+                        record_keys.extend(key);
+                        batch.put(key, value);
+                    }
+                    batch.put(slot_key(&slot), record_keys);
+                    db.write(batch).expect(CRUD_ERROR);
+                    // the records are only dropped from memory once they can be read from the database
+                    let min_period = {
+                        let mut shared = shared.lock();
+                        shared.pending.pop_front();
+                        shared.min_period
+                    };
+                    Self::prune(&db, min_period);
+                }
+            })
+            .expect("Can't spawn execution state history thread.");
+        (tx, handle)
+    }
+
+    /// Build the prefix of the records of an address
+    fn address_prefix(&self, ident: u8, address: &Address) -> Vec<u8> {
+        let mut prefix = vec![ident];
+        self.address_ser
+            .serialize(address, &mut prefix)
+            .expect(STATE_HISTORY_SER_ERROR);
+        prefix
+    }
+
+    /// Build the prefix of the records of a datastore entry
+    fn datastore_prefix(&self, address: &Address, key: &[u8]) -> Vec<u8> {
+        let mut prefix = self.address_prefix(DATASTORE_IDENT, address);
+        prefix.extend((key.len() as u32).to_be_bytes());
+        prefix.extend(key);
+        prefix
+    }
+
+    /// Get the first slot written in the database and the last one
+    fn scan_retained_slots(db: &DB) -> Option<(Slot, Slot)> {
+        let first = db
+            .prefix_iterator([SLOT_IDENT])
+            .flatten()
+            .next()
+            .filter(|(key, _)| key.first() == Some(&SLOT_IDENT))?;
+        let last = db
+            .iterator(IteratorMode::From(&[BALANCE_IDENT], Direction::Reverse))
+            .flatten()
+            .find(|(key, _)| key.first() == Some(&SLOT_IDENT))?;
+        Some((slot_of_key(&first.0), slot_of_key(&last.0)))
+    }
+
+    /// Drop the whole history if it does not end at the given slot.
+    /// Used at startup: the history must follow the final state without gaps to be meaningful.
+    pub fn reset_if_not_contiguous(&mut self, last_final_slot: &Slot) {
+        let Some(last) = self.shared.lock().last_slot else {
+            return;
+        };
+        if &last == last_final_slot {
+            return;
+        }
+        let mut batch = WriteBatch::default();
+        for (key, _) in self.db.iterator(IteratorMode::Start).flatten() {
+            batch.delete(key);
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+        let mut shared = self.shared.lock();
+        shared.first_slot = None;
+        shared.last_slot = None;
+        drop(shared);
+        debug!(
+            "(state history store) history ending at slot {} dropped, final slot is {}",
+            last, last_final_slot
+        );
+    }
+
+    /// Record the values that the state changes of a final slot are about to overwrite,
+    /// and hand them to the writer thread along with the pruning of the records that are out of the retention window.
+    /// Must be called before the changes are applied to the final state.
+    ///
+    /// # Arguments
+    /// * `slot`: the final slot whose changes are about to be applied
+    /// * `changes`: the state changes of that slot
+    /// * `final_state`: the final state, still at the output of the previous slot
+    pub fn insert_final_slot(
+        &mut self,
+        slot: &Slot,
+        changes: &StateChanges,
+        final_state: &dyn FinalStateController,
+    ) {
+        let slot_suffix = slot.to_bytes_key();
+        let ledger = final_state.get_ledger();
+        let mut records: BTreeMap<Vec<u8>, Vec<u8>> = BTreeMap::new();
+
+        for (address, change) in changes.ledger_changes.0.iter() {
+            // balance
+            if matches!(
+                change,
+                SetUpdateOrDelete::Set(_)
+                    | SetUpdateOrDelete::Delete
+                    | SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                        balance: SetOrKeep::Set(_),
+                        ..
+                    })
+            ) {
+                let balance = ledger
+                    .get_balance(address)
+                    .map(|balance| balance.to_raw().to_be_bytes());
+                // replacing or deleting an existing entry drops its whole datastore
+                if balance.is_some() && !matches!(change, SetUpdateOrDelete::Update(_)) {
+                    records.insert(
+                        [
+                            &self.address_prefix(ENTRY_IDENT, address)[..],
+                            &slot_suffix[..],
+                        ]
+                        .concat(),
+                        Vec::new(),
+                    );
+                }
+                records.insert(
+                    [
+                        &self.address_prefix(BALANCE_IDENT, address)[..],
+                        &slot_suffix[..],
+                    ]
+                    .concat(),
+                    serialize_optional(balance.as_ref().map(|balance| &balance[..])),
+                );
+            }
+
+            // datastore entries set or deleted by the change
+            let keys: BTreeSet<&Vec<u8>> = match change {
+                SetUpdateOrDelete::Set(entry) => entry.datastore.keys().collect(),
+                SetUpdateOrDelete::Update(update) => update.datastore.keys().collect(),
+                SetUpdateOrDelete::Delete => BTreeSet::new(),
+            };
+            for key in keys {
+                let value = ledger.get_data_entry(address, key);
+                records.insert(
+                    [&self.datastore_prefix(address, key)[..], &slot_suffix[..]].concat(),
+                    serialize_optional(value.as_deref()),
+                );
+            }
+        }
+
+        // roll counts
+        for address in changes.pos_changes.roll_changes.keys() {
+            let rolls = final_state.get_pos_state().get_rolls_for(address);
+            records.insert(
+                [
+                    &self.address_prefix(ROLLS_IDENT, address)[..],
+                    &slot_suffix[..],
+                ]
+                .concat(),
+                serialize_optional(Some(&rolls.to_be_bytes())),
+            );
+        }
+
+        {
+            let mut shared = self.shared.lock();
+            shared.pending.push_back((*slot, Arc::new(records)));
+            shared.first_slot.get_or_insert(*slot);
+            shared.last_slot = Some(*slot);
+            shared.min_period = slot.period.saturating_sub(self.retention_periods);
+        }
+        if let Some((tx, _)) = self.writer.as_ref() {
+            tx.send(()).expect("state history writer thread stopped");
+        }
+    }
+
+    /// Delete the records of the slots whose period is older than `min_period`
+    fn prune(db: &DB, min_period: u64) {
+        let mut batch = WriteBatch::default();
+        let mut pruned_count = 0usize;
+        for (key, record_keys) in db.prefix_iterator([SLOT_IDENT]).flatten() {
+            if key.first() != Some(&SLOT_IDENT) {
+                break;
+            }
+            if slot_of_key(&key).period >= min_period {
+                break;
+            }
+            let mut rest = &record_keys[..];
+            while !rest.is_empty() {
+                let (len, tail) = rest.split_at(4);
+                let len = u32::from_be_bytes(len.try_into().expect(STATE_HISTORY_DESER_ERROR));
+                let (record_key, tail) = tail.split_at(len as usize);
+                batch.delete(record_key);
+                rest = tail;
+            }
+            batch.delete(key);
+            pruned_count += 1;
+        }
+        if pruned_count > 0 {
+            db.write(batch).expect(CRUD_ERROR);
+            debug!("(state history store) pruned {} slots", pruned_count);
+        }
+    }
+
+    /// Get the first change of an entry at or after a slot, along with the slot of the change.
+    /// The database is searched first as it holds the oldest records, then the pending ones.
+    /// The caller must hold the lock of the shared state so that no record moves from memory to disk meanwhile.
+    ///
+    /// # Arguments
+    /// * `shared`: the locked shared state
+    /// * `prefix`: prefix of the records of the entry
+    /// * `from_slot`: first slot at which changes are searched
+    fn first_change_from(
+        &self,
+        shared: &StateHistoryShared,
+        prefix: &[u8],
+        from_slot: &Slot,
+    ) -> Option<(Slot, Vec<u8>)> {
+        let start_key = [prefix, &from_slot.to_bytes_key()[..]].concat();
+        let is_record =
+            |key: &[u8]| key.starts_with(prefix) && key.len() == prefix.len() + SLOT_KEY_SIZE;
+        if let Some((key, value)) = self
+            .db
+            .iterator(IteratorMode::From(&start_key, Direction::Forward))
+            .flatten()
+            .next()
+            .filter(|(key, _)| is_record(key))
+        {
+            return Some((record_slot(&key), value.to_vec()));
+        }
+        shared.pending.iter().find_map(|(_, records)| {
+            records
+                .range::<Vec<u8>, _>((Bound::Included(&start_key), Bound::Unbounded))
+                .next()
+                .filter(|(key, _)| is_record(key))
+                .map(|(key, value)| (record_slot(key), value.clone()))
+        })
+    }
+
+    /// Get the value replaced by the first change of an entry after a slot
+    ///
+    /// # Arguments
+    /// * `prefix`: prefix of the records of the entry
+    /// * `slot`: the slot at the output of which the value is queried. It must be older than the last final slot.
+    /// * `entry_address`: for datastore entries, the address whose whole entry replacements also change the value
+    fn get_at(
+        &self,
+        prefix: &[u8],
+        slot: &Slot,
+        entry_address: Option<&Address>,
+    ) -> HistoricalValue<Vec<u8>> {
+        let Ok(next_slot) = slot.get_next_slot(self.thread_count) else {
+            return HistoricalValue::Unchanged;
+        };
+        let shared = self.shared.lock();
+        match shared.first_slot {
+            Some(first) if first <= next_slot && next_slot.period >= shared.min_period => {}
+            _ => return HistoricalValue::NotRetained,
+        }
+        let change = self.first_change_from(&shared, prefix, &next_slot);
+        if let Some(address) = entry_address {
+            let entry_prefix = self.address_prefix(ENTRY_IDENT, address);
+            if let Some((entry_slot, _)) =
+                self.first_change_from(&shared, &entry_prefix, &next_slot)
+            {
+                // the value dropped along with the entry was not recorded
+                if change
+                    .as_ref()
+                    .map_or(true, |(change_slot, _)| entry_slot < *change_slot)
+                {
+                    return HistoricalValue::NotRetained;
+                }
+            }
+        }
+        match change {
+            Some((_, value)) => {
+                HistoricalValue::Changed(deserialize_optional(&value).map(|v| v.to_vec()))
+            }
+            None => HistoricalValue::Unchanged,
+        }
+    }
+
+    /// Get the balance of an address at the output of a final slot
+    pub fn get_balance_at(&self, address: &Address, slot: &Slot) -> HistoricalValue<Amount> {
+        self.get_at(&self.address_prefix(BALANCE_IDENT, address), slot, None)
+            .map(|value| {
+                Amount::from_raw(u64::from_be_bytes(
+                    value[..].try_into().expect(STATE_HISTORY_DESER_ERROR),
+                ))
+            })
+    }
+
+    /// Get a datastore entry of an address at the output of a final slot
+    pub fn get_data_entry_at(
+        &self,
+        address: &Address,
+        key: &[u8],
+        slot: &Slot,
+    ) -> HistoricalValue<Vec<u8>> {
+        self.get_at(&self.datastore_prefix(address, key), slot, Some(address))
+    }
+
+    /// Get the roll count of an address at the output of a final slot
+    pub fn get_rolls_at(&self, address: &Address, slot: &Slot) -> HistoricalValue<u64> {
+        self.get_at(&self.address_prefix(ROLLS_IDENT, address), slot, None)
+            .map(|value| u64::from_be_bytes(value[..].try_into().expect(STATE_HISTORY_DESER_ERROR)))
+    }
+}
+
+impl Drop for StateHistoryStore {
+    /// Stop the writer thread once the pending records are written
+    fn drop(&mut self) {
+        if let Some((tx, handle)) = self.writer.take() {
+            drop(tx);
+            let _ = handle.join();
+        }
+    }
+}
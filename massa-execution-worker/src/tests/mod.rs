@@ -9,4 +9,16 @@ mod universe;
 #[cfg(test)]
 mod tests_active_history;
 
+#[cfg(test)]
+mod tests_final_event_store;
+
+#[cfg(test)]
+mod tests_operation_history_store;
+
+#[cfg(test)]
+mod tests_state_history_store;
+
+#[cfg(test)]
+mod tools;
+
 mod interface;
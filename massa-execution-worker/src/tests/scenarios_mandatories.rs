@@ -4,8 +4,9 @@ use massa_async_pool::{AsyncMessage, AsyncPool, AsyncPoolChanges, AsyncPoolConfi
 use massa_db_exports::{DBBatch, ShareableMassaDBController};
 use massa_executed_ops::{ExecutedDenunciations, ExecutedDenunciationsConfig};
 use massa_execution_exports::{
-    ExecutionConfig, ExecutionQueryRequest, ExecutionQueryRequestItem, ExecutionStackElement,
-    ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    AsyncPoolMessageFilter, ExecutionBlockMetadata, ExecutionConfig, ExecutionError,
+    ExecutionQueryRequest, ExecutionQueryRequestItem, ExecutionStackElement,
+    ReadOnlyExecutionRequest, ReadOnlyExecutionTarget, ReadOnlyMulticallRequest,
 };
 use massa_final_state::test_exports::get_initials;
 use massa_final_state::MockFinalStateController;
@@ -13,6 +14,7 @@ use massa_hash::Hash;
 use massa_ledger_exports::{
     LedgerEntryUpdate, MockLedgerControllerWrapper, SetOrKeep, SetUpdateOrDelete,
 };
+use massa_models::block_id::BlockId;
 use massa_models::bytecode::Bytecode;
 use massa_models::config::{
     CHAINID, ENDORSEMENT_COUNT, LEDGER_ENTRY_DATASTORE_BASE_SIZE, THREAD_COUNT,
@@ -22,7 +24,7 @@ use massa_models::test_exports::gen_endorsements_for_denunciation;
 use massa_models::{address::Address, amount::Amount, slot::Slot};
 use massa_models::{
     denunciation::Denunciation,
-    execution::EventFilter,
+    execution::{EventFilter, ExecutionTraceStep, OperationCheckFailure, StateOverride},
     operation::{Operation, OperationSerializer, OperationType},
     secure_share::SecureShareContent,
 };
@@ -31,11 +33,17 @@ use massa_pos_exports::{
 };
 use massa_signature::KeyPair;
 use massa_test_framework::{TestUniverse, WaitPoint};
+use massa_time::MassaTime;
 use mockall::predicate;
 use num::rational::Ratio;
 use parking_lot::RwLock;
 use std::sync::Arc;
-use std::{cmp::Reverse, collections::BTreeMap, str::FromStr, time::Duration};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+    time::Duration,
+};
 
 use super::universe::{ExecutionForeignControllers, ExecutionTestUniverse};
 
@@ -272,6 +280,8 @@ fn test_readonly_execution() {
             ),
             coins: None,
             fee: Some(Amount::from_str("40").unwrap()),
+            state_overrides: Default::default(),
+            trace: false,
         })
         .expect("readonly execution failed");
 
@@ -317,6 +327,8 @@ fn test_readonly_execution() {
             },
             coins: Some(Amount::from_str("20").unwrap()),
             fee: Some(Amount::from_str("30").unwrap()),
+            state_overrides: Default::default(),
+            trace: false,
         })
         .expect("readonly execution failed");
 
@@ -333,6 +345,600 @@ fn test_readonly_execution() {
     );
 }
 
+#[test]
+fn test_readonly_execution_with_state_overrides() {
+    let exec_cfg = ExecutionConfig::default();
+    let mut foreign_controllers = ExecutionForeignControllers::new_with_mocks();
+    selector_boilerplate(&mut foreign_controllers.selector_controller);
+
+    foreign_controllers
+        .ledger_controller
+        .set_expectations(|ledger_controller| {
+            ledger_controller
+                .expect_get_balance()
+                .returning(move |_| Some(Amount::from_str("100").unwrap()));
+            ledger_controller
+                .expect_entry_exists()
+                .returning(move |_| true);
+        });
+    final_state_boilerplate(
+        &mut foreign_controllers.final_state,
+        foreign_controllers.db.clone(),
+        &foreign_controllers.selector_controller,
+        &mut foreign_controllers.ledger_controller,
+        None,
+        None,
+        None,
+    );
+    let universe = ExecutionTestUniverse::new(foreign_controllers, exec_cfg);
+
+    let addr = Address::from_str("AU1LQrXPJ3DVL8SFRqACk31E9MVxBcmCATFiRdpEmgztGxWAx48D").unwrap();
+    let other = Address::from_str("AU1DHJY6zd6oKJPos8gQ6KYqmsTR669wes4ZhttLD9gE7PYUF3Rs").unwrap();
+
+    let mut state_overrides = PreHashMap::default();
+    state_overrides.insert(
+        addr,
+        StateOverride {
+            balance: Some(Amount::from_str("1000").unwrap()),
+            ..Default::default()
+        },
+    );
+    state_overrides.insert(
+        other,
+        StateOverride {
+            datastore: vec![(b"key".to_vec(), b"value".to_vec())],
+            ..Default::default()
+        },
+    );
+
+    let mut res = universe
+        .module_controller
+        .execute_readonly_request(ReadOnlyExecutionRequest {
+            max_gas: 100_000_000,
+            call_stack: vec![ExecutionStackElement {
+                address: addr,
+                coins: Amount::zero(),
+                owned_addresses: vec![],
+                operation_datastore: None,
+            }],
+            target: ReadOnlyExecutionTarget::BytecodeExecution(
+                include_bytes!("./wasm/event_test.wasm").to_vec(),
+            ),
+            coins: None,
+            fee: Some(Amount::from_str("40").unwrap()),
+            state_overrides,
+            trace: false,
+        })
+        .expect("readonly execution failed");
+
+    // the fee is paid from the overridden balance
+    assert_eq!(res.out.events.take().len(), 1, "wrong number of events");
+    assert_eq!(
+        res.out.state_changes.ledger_changes.0.get(&addr).unwrap(),
+        &SetUpdateOrDelete::Update(LedgerEntryUpdate {
+            balance: massa_ledger_exports::SetOrKeep::Set(Amount::from_str("960").unwrap()),
+            bytecode: massa_ledger_exports::SetOrKeep::Keep,
+            datastore: BTreeMap::new()
+        })
+    );
+    // the overrides themselves are not part of the output
+    assert!(res.out.state_changes.ledger_changes.0.get(&other).is_none());
+}
+
+#[test]
+fn test_readonly_multicall() {
+    let exec_cfg = ExecutionConfig::default();
+    let mut foreign_controllers = ExecutionForeignControllers::new_with_mocks();
+    selector_boilerplate(&mut foreign_controllers.selector_controller);
+
+    foreign_controllers
+        .ledger_controller
+        .set_expectations(|ledger_controller| {
+            ledger_controller
+                .expect_get_balance()
+                .returning(move |_| Some(Amount::from_str("100").unwrap()));
+            ledger_controller
+                .expect_entry_exists()
+                .returning(move |_| true);
+        });
+    final_state_boilerplate(
+        &mut foreign_controllers.final_state,
+        foreign_controllers.db.clone(),
+        &foreign_controllers.selector_controller,
+        &mut foreign_controllers.ledger_controller,
+        None,
+        None,
+        None,
+    );
+    let universe = ExecutionTestUniverse::new(foreign_controllers, exec_cfg);
+
+    let addr = Address::from_str("AU1LQrXPJ3DVL8SFRqACk31E9MVxBcmCATFiRdpEmgztGxWAx48D").unwrap();
+    let call = ReadOnlyExecutionRequest {
+        max_gas: 100_000_000,
+        call_stack: vec![ExecutionStackElement {
+            address: addr,
+            coins: Amount::zero(),
+            owned_addresses: vec![],
+            operation_datastore: None,
+        }],
+        target: ReadOnlyExecutionTarget::BytecodeExecution(
+            include_bytes!("./wasm/event_test.wasm").to_vec(),
+        ),
+        coins: None,
+        fee: Some(Amount::from_str("40").unwrap()),
+        state_overrides: Default::default(),
+        trace: false,
+    };
+
+    // the third call cannot pay its fee: the sequence stops there
+    let res = universe
+        .module_controller
+        .execute_readonly_multicall(ReadOnlyMulticallRequest {
+            calls: vec![call.clone(), call.clone(), call],
+        })
+        .expect("readonly multicall failed");
+
+    assert_eq!(res.calls.len(), 3);
+    for call in res.calls[..2].iter() {
+        let call = call.as_ref().expect("readonly call failed");
+        assert!(call.gas_cost > 0);
+        assert_eq!(call.events.0.len(), 1, "wrong number of events");
+    }
+    assert!(res.calls[2].is_err());
+
+    // the second call sees the balance left by the first one
+    assert_eq!(
+        res.state_changes.ledger_changes.0.get(&addr).unwrap(),
+        &SetUpdateOrDelete::Update(LedgerEntryUpdate {
+            balance: massa_ledger_exports::SetOrKeep::Set(Amount::from_str("20").unwrap()),
+            bytecode: massa_ledger_exports::SetOrKeep::Keep,
+            datastore: BTreeMap::new()
+        })
+    );
+}
+
+#[test]
+fn test_readonly_execution_with_trace() {
+    let exec_cfg = ExecutionConfig::default();
+    let mut foreign_controllers = ExecutionForeignControllers::new_with_mocks();
+    selector_boilerplate(&mut foreign_controllers.selector_controller);
+
+    foreign_controllers
+        .ledger_controller
+        .set_expectations(|ledger_controller| {
+            ledger_controller
+                .expect_get_balance()
+                .returning(move |_| Some(Amount::from_str("100").unwrap()));
+            ledger_controller
+                .expect_entry_exists()
+                .returning(move |_| true);
+        });
+    final_state_boilerplate(
+        &mut foreign_controllers.final_state,
+        foreign_controllers.db.clone(),
+        &foreign_controllers.selector_controller,
+        &mut foreign_controllers.ledger_controller,
+        None,
+        None,
+        None,
+    );
+    let universe = ExecutionTestUniverse::new(foreign_controllers, exec_cfg);
+
+    let addr = Address::from_str("AU1LQrXPJ3DVL8SFRqACk31E9MVxBcmCATFiRdpEmgztGxWAx48D").unwrap();
+    let request = ReadOnlyExecutionRequest {
+        max_gas: 100_000_000,
+        call_stack: vec![ExecutionStackElement {
+            address: addr,
+            coins: Amount::zero(),
+            owned_addresses: vec![],
+            operation_datastore: None,
+        }],
+        target: ReadOnlyExecutionTarget::BytecodeExecution(
+            include_bytes!("./wasm/event_test.wasm").to_vec(),
+        ),
+        coins: None,
+        fee: None,
+        state_overrides: Default::default(),
+        trace: false,
+    };
+
+    // no trace unless requested
+    let res = universe
+        .module_controller
+        .execute_readonly_request(request.clone())
+        .expect("readonly execution failed");
+    assert!(res.trace.is_none());
+
+    let res = universe
+        .module_controller
+        .execute_readonly_request(ReadOnlyExecutionRequest {
+            trace: true,
+            ..request
+        })
+        .expect("readonly execution failed");
+    let trace = res.trace.expect("missing execution trace");
+    assert_eq!(trace.address, addr);
+    assert_eq!(trace.gas_available, Some(100_000_000));
+    assert!(matches!(trace.gas_used, Some(gas) if gas > 0 && gas <= 100_000_000));
+    assert_eq!(
+        trace
+            .steps
+            .iter()
+            .filter(|step| matches!(step, ExecutionTraceStep::Event { .. }))
+            .count(),
+        1,
+        "wrong number of traced events"
+    );
+}
+
+#[test]
+fn test_trace_operation() {
+    let exec_cfg = ExecutionConfig::default();
+    let keypair = KeyPair::from_str(TEST_SK_1).unwrap();
+    let mut foreign_controllers = ExecutionForeignControllers::new_with_mocks();
+    selector_boilerplate(&mut foreign_controllers.selector_controller);
+    foreign_controllers
+        .ledger_controller
+        .set_expectations(|ledger_controller| {
+            ledger_controller
+                .expect_get_balance()
+                .returning(move |_| Some(Amount::from_str("100").unwrap()));
+            ledger_controller
+                .expect_entry_exists()
+                .returning(move |_| true);
+        });
+    final_state_boilerplate(
+        &mut foreign_controllers.final_state,
+        foreign_controllers.db.clone(),
+        &foreign_controllers.selector_controller,
+        &mut foreign_controllers.ledger_controller,
+        None,
+        None,
+        None,
+    );
+    let mut universe = ExecutionTestUniverse::new(foreign_controllers, exec_cfg.clone());
+
+    // add a block executing a smart contract to the blockclique, without finalizing it
+    let operation = ExecutionTestUniverse::create_execute_sc_operation(
+        &keypair,
+        include_bytes!("./wasm/event_test.wasm"),
+        BTreeMap::default(),
+    )
+    .unwrap();
+    universe.storage.store_operations(vec![operation.clone()]);
+    let block = ExecutionTestUniverse::create_block(
+        &keypair,
+        Slot::new(1, 0),
+        vec![operation.clone()],
+        vec![],
+        vec![],
+    );
+    universe.storage.store_block(block.clone());
+    let mut block_metadata: PreHashMap<BlockId, ExecutionBlockMetadata> = Default::default();
+    block_metadata.insert(
+        block.id,
+        ExecutionBlockMetadata {
+            same_thread_parent_creator: Some(Address::from_public_key(&keypair.get_public_key())),
+            storage: Some(universe.storage.clone()),
+        },
+    );
+    universe.module_controller.update_blockclique_status(
+        Default::default(),
+        Some(HashMap::from([(Slot::new(1, 0), block.id)])),
+        block_metadata,
+    );
+
+    // Sleep to wait (1,0) candidate slot to be executed
+    std::thread::sleep(Duration::from_millis(exec_cfg.t0.as_millis() * 2));
+
+    // the operation is executed again on top of the state it was executed on
+    let trace = universe
+        .module_controller
+        .trace_operation(operation.id)
+        .expect("operation trace failed");
+    assert_eq!(trace.address, operation.content_creator_address);
+    assert_eq!(
+        trace
+            .steps
+            .iter()
+            .filter(|step| matches!(step, ExecutionTraceStep::Event { .. }))
+            .count(),
+        1,
+        "wrong number of traced events"
+    );
+
+    // only the operations of the active history can be traced
+    let other_operation = ExecutionTestUniverse::create_execute_sc_operation(
+        &KeyPair::from_str(TEST_SK_2).unwrap(),
+        include_bytes!("./wasm/event_test.wasm"),
+        BTreeMap::default(),
+    )
+    .unwrap();
+    assert!(matches!(
+        universe
+            .module_controller
+            .trace_operation(other_operation.id),
+        Err(ExecutionError::RuntimeError(_))
+    ));
+}
+
+#[test]
+fn test_readonly_execution_timeout() {
+    // requests that are not executed before their deadline are abandoned
+    let exec_cfg = ExecutionConfig {
+        readonly_request_timeout: MassaTime::from_millis(0),
+        ..ExecutionConfig::default()
+    };
+    let mut foreign_controllers = ExecutionForeignControllers::new_with_mocks();
+    selector_boilerplate(&mut foreign_controllers.selector_controller);
+    final_state_boilerplate(
+        &mut foreign_controllers.final_state,
+        foreign_controllers.db.clone(),
+        &foreign_controllers.selector_controller,
+        &mut foreign_controllers.ledger_controller,
+        None,
+        None,
+        None,
+    );
+    let universe = ExecutionTestUniverse::new(foreign_controllers, exec_cfg);
+
+    let addr = Address::from_str("AU1LQrXPJ3DVL8SFRqACk31E9MVxBcmCATFiRdpEmgztGxWAx48D").unwrap();
+    let res = universe
+        .module_controller
+        .execute_readonly_request(ReadOnlyExecutionRequest {
+            max_gas: 100_000_000,
+            call_stack: vec![ExecutionStackElement {
+                address: addr,
+                coins: Amount::zero(),
+                owned_addresses: vec![],
+                operation_datastore: None,
+            }],
+            target: ReadOnlyExecutionTarget::BytecodeExecution(
+                include_bytes!("./wasm/event_test.wasm").to_vec(),
+            ),
+            coins: None,
+            fee: None,
+            state_overrides: Default::default(),
+            trace: false,
+        });
+    assert!(matches!(res, Err(ExecutionError::ReadOnlyTimeout(_))));
+}
+
+#[test]
+fn test_estimate_operation() {
+    let exec_cfg = ExecutionConfig::default();
+    let mut foreign_controllers = ExecutionForeignControllers::new_with_mocks();
+    selector_boilerplate(&mut foreign_controllers.selector_controller);
+    final_state_boilerplate(
+        &mut foreign_controllers.final_state,
+        foreign_controllers.db.clone(),
+        &foreign_controllers.selector_controller,
+        &mut foreign_controllers.ledger_controller,
+        None,
+        None,
+        None,
+    );
+    let universe = ExecutionTestUniverse::new(foreign_controllers, exec_cfg.clone());
+
+    let addr = Address::from_str("AU1LQrXPJ3DVL8SFRqACk31E9MVxBcmCATFiRdpEmgztGxWAx48D").unwrap();
+    let estimation = universe
+        .module_controller
+        .estimate_operation(
+            addr,
+            OperationType::ExecuteSC {
+                data: include_bytes!("./wasm/event_test.wasm").to_vec(),
+                max_gas: 0,
+                max_coins: Amount::zero(),
+                datastore: Default::default(),
+            },
+        )
+        .expect("operation estimation failed");
+    assert!(estimation.gas_used > 0);
+    assert_eq!(
+        estimation.max_gas,
+        estimation.gas_used + estimation.gas_used * exec_cfg.gas_estimation_margin_percent / 100
+    );
+    // the execution does not store anything
+    assert_eq!(estimation.storage_cost, Amount::zero());
+}
+
+#[test]
+fn test_check_operations() {
+    let exec_cfg = ExecutionConfig::default();
+    let mut foreign_controllers = ExecutionForeignControllers::new_with_mocks();
+    selector_boilerplate(&mut foreign_controllers.selector_controller);
+    final_state_boilerplate(
+        &mut foreign_controllers.final_state,
+        foreign_controllers.db.clone(),
+        &foreign_controllers.selector_controller,
+        &mut foreign_controllers.ledger_controller,
+        None,
+        None,
+        None,
+    );
+    let universe = ExecutionTestUniverse::new(foreign_controllers, exec_cfg.clone());
+
+    let keypair = KeyPair::from_str(TEST_SK_1).unwrap();
+    let recipient_address =
+        Address::from_str("AU1LQrXPJ3DVL8SFRqACk31E9MVxBcmCATFiRdpEmgztGxWAx48D").unwrap();
+    let create_operation = |fee: &str, expire_period: u64, op: OperationType| {
+        Operation::new_verifiable(
+            Operation {
+                fee: Amount::from_str(fee).unwrap(),
+                expire_period,
+                op,
+            },
+            OperationSerializer::new(),
+            &keypair,
+            *CHAINID,
+        )
+        .unwrap()
+    };
+    let transaction = OperationType::Transaction {
+        recipient_address,
+        amount: Amount::from_str("50").unwrap(),
+    };
+    // the sender starts with 100 coins
+    let first_transaction = create_operation("10", 10, transaction.clone());
+    let second_transaction = create_operation("11", 10, transaction.clone());
+    let expired_transaction = create_operation("0", 100, transaction);
+    let failing_call = create_operation(
+        "0",
+        10,
+        OperationType::CallSC {
+            target_addr: recipient_address,
+            target_func: String::new(),
+            param: Vec::new(),
+            max_gas: 0,
+            coins: Amount::zero(),
+        },
+    );
+
+    let verdicts = universe
+        .module_controller
+        .check_operations(vec![
+            first_transaction.clone(),
+            first_transaction,
+            second_transaction,
+            expired_transaction,
+            failing_call,
+        ])
+        .expect("operation check failed");
+    assert_eq!(verdicts.len(), 5);
+    assert_eq!(verdicts[0], Ok(()));
+    // the first transaction was already executed by the dry-run
+    assert_eq!(verdicts[1], Err(OperationCheckFailure::AlreadyExecuted));
+    // the first transaction spent the coins needed by the second one
+    assert!(matches!(
+        verdicts[2],
+        Err(OperationCheckFailure::InsufficientBalance(_))
+    ));
+    assert!(matches!(
+        verdicts[3],
+        Err(OperationCheckFailure::InvalidPeriod(_))
+    ));
+    // the target of the call is not a smart contract
+    assert!(matches!(
+        verdicts[4],
+        Err(OperationCheckFailure::ExecutionFailed(_))
+    ));
+}
+
+#[test]
+fn test_get_async_pool_messages() {
+    let exec_cfg = ExecutionConfig::default();
+    let mut foreign_controllers = ExecutionForeignControllers::new_with_mocks();
+    selector_boilerplate(&mut foreign_controllers.selector_controller);
+
+    // two messages from a first sender, and one from a second sender
+    let sender_1 =
+        Address::from_public_key(&KeyPair::from_str(TEST_SK_1).unwrap().get_public_key());
+    let sender_2 =
+        Address::from_public_key(&KeyPair::from_str(TEST_SK_2).unwrap().get_public_key());
+    let destination =
+        Address::from_str("AU1LQrXPJ3DVL8SFRqACk31E9MVxBcmCATFiRdpEmgztGxWAx48D").unwrap();
+    let create_message = |emission_index: u64, sender: Address, fee: u64| {
+        AsyncMessage::new(
+            Slot::new(1, 0),
+            emission_index,
+            sender,
+            destination,
+            String::from("receive"),
+            100_000,
+            Amount::from_raw(fee),
+            Amount::zero(),
+            Slot::new(1, 0),
+            Slot::new(10, 0),
+            Vec::new(),
+            None,
+            None,
+        )
+    };
+    let messages = vec![
+        create_message(0, sender_1, 3),
+        create_message(1, sender_2, 2),
+        create_message(2, sender_1, 1),
+    ];
+    let mut async_pool = AsyncPool::new(AsyncPoolConfig::default(), foreign_controllers.db.clone());
+    let changes = messages
+        .iter()
+        .map(|message| {
+            (
+                message.compute_id(),
+                SetUpdateOrDelete::Set(message.clone()),
+            )
+        })
+        .collect();
+    let mut db_batch = DBBatch::default();
+    async_pool.apply_changes_to_batch(&AsyncPoolChanges(changes), &mut db_batch);
+    foreign_controllers
+        .db
+        .write()
+        .write_batch(db_batch, DBBatch::default(), Some(Slot::new(1, 0)));
+    final_state_boilerplate(
+        &mut foreign_controllers.final_state,
+        foreign_controllers.db.clone(),
+        &foreign_controllers.selector_controller,
+        &mut foreign_controllers.ledger_controller,
+        None,
+        Some(async_pool),
+        None,
+    );
+    let universe = ExecutionTestUniverse::new(foreign_controllers, exec_cfg);
+
+    // the messages of the first sender, by priority
+    let sender_filter = AsyncPoolMessageFilter {
+        sender: Some(sender_1),
+        ..Default::default()
+    };
+    let page = universe
+        .module_controller
+        .get_async_pool_messages(sender_filter.clone(), None, 10);
+    assert_eq!(page.len(), 2);
+    assert_eq!(page[0].id, messages[0].compute_id());
+    assert_eq!(page[1].id, messages[2].compute_id());
+    // no block was executed, so the candidate messages are the final ones
+    assert_eq!(page[0].final_message, Some(messages[0].clone()));
+    assert_eq!(page[0].candidate_message, Some(messages[0].clone()));
+
+    // pagination
+    let first_page =
+        universe
+            .module_controller
+            .get_async_pool_messages(sender_filter.clone(), None, 1);
+    assert_eq!(first_page.len(), 1);
+    let second_page = universe.module_controller.get_async_pool_messages(
+        sender_filter,
+        Some(first_page[0].id),
+        1,
+    );
+    assert_eq!(second_page.len(), 1);
+    assert_eq!(second_page[0].id, messages[2].compute_id());
+
+    // by id
+    let page = universe.module_controller.get_async_pool_messages(
+        AsyncPoolMessageFilter {
+            message_id: Some(messages[1].compute_id()),
+            ..Default::default()
+        },
+        None,
+        10,
+    );
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].final_message, Some(messages[1].clone()));
+
+    // by destination
+    let page = universe.module_controller.get_async_pool_messages(
+        AsyncPoolMessageFilter {
+            destination: Some(sender_1),
+            ..Default::default()
+        },
+        None,
+        10,
+    );
+    assert!(page.is_empty());
+}
+
 /// Test the gas usage in nested calls using call SC operation
 ///
 /// Create a smart contract and send it in the blockclique.
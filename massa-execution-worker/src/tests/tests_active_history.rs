@@ -2,6 +2,7 @@ use crate::active_history::ActiveHistory;
 use massa_execution_exports::ExecutionOutput;
 use massa_models::slot::Slot;
 use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
 
 use massa_final_state::StateChanges;
 use massa_hash::Hash;
@@ -56,7 +57,7 @@ fn test_active_history_deferred_credits() {
         events: Default::default(),
     };
 
-    let active_history = ActiveHistory(VecDeque::from([exec_output_1]));
+    let active_history = ActiveHistory(VecDeque::from([Arc::new(exec_output_1)]));
 
     assert_eq!(
         active_history.get_adress_deferred_credit_for(&addr1, &slot2),
@@ -0,0 +1,227 @@
+use super::tools::test_address;
+use crate::final_event_store::FinalEventStore;
+use massa_hash::Hash;
+use massa_models::address::Address;
+use massa_models::execution::{EventCursor, EventFilter};
+use massa_models::operation::OperationId;
+use massa_models::output_event::{EventExecutionContext, SCOutputEvent};
+use massa_models::secure_share::Id;
+use massa_models::slot::Slot;
+use std::collections::VecDeque;
+use tempfile::TempDir;
+
+fn test_event(
+    slot: Slot,
+    index_in_slot: u64,
+    call_stack: Vec<Address>,
+    origin_operation_id: Option<OperationId>,
+) -> SCOutputEvent {
+    SCOutputEvent {
+        context: EventExecutionContext {
+            slot,
+            block: None,
+            read_only: false,
+            index_in_slot,
+            call_stack: VecDeque::from(call_stack),
+            origin_operation_id,
+            is_final: true,
+            is_error: false,
+        },
+        data: format!("{}:{}", slot, index_in_slot),
+    }
+}
+
+#[test]
+fn test_final_event_store_indexes() {
+    let tmp_dir = TempDir::new().unwrap();
+    let mut store = FinalEventStore::new(tmp_dir.path().to_path_buf(), 1000, 1000, 2, 1000);
+
+    let caller = test_address("caller");
+    let emitter_1 = test_address("emitter_1");
+    let emitter_2 = test_address("emitter_2");
+    let op_id = OperationId::new(Hash::compute_from("op".as_bytes()));
+
+    let slot_1 = Slot::new(1, 0);
+    let slot_2 = Slot::new(1, 1);
+    let slot_3 = Slot::new(2, 0);
+    let events_1 = vec![
+        test_event(slot_1, 0, vec![caller, emitter_1], Some(op_id)),
+        test_event(slot_1, 1, vec![caller, emitter_2], Some(op_id)),
+    ];
+    let events_2 = vec![test_event(slot_2, 0, vec![caller, emitter_1], None)];
+    let events_3 = vec![test_event(slot_3, 0, vec![emitter_2], None)];
+    store.insert_final_events(&slot_1, events_1.iter());
+    store.insert_final_events(&slot_2, events_2.iter());
+    store.insert_final_events(&slot_3, events_3.iter());
+
+    // no filter: every event, ordered by slot then index
+    let events = store.get_filtered_sc_output_events(&EventFilter::default());
+    let data: Vec<String> = events.into_iter().map(|e| e.data).collect();
+    assert_eq!(
+        data,
+        vec![
+            events_1[0].data.clone(),
+            events_1[1].data.clone(),
+            events_2[0].data.clone(),
+            events_3[0].data.clone(),
+        ]
+    );
+
+    // emitter index
+    let events = store.get_filtered_sc_output_events(&EventFilter {
+        emitter_address: Some(emitter_1),
+        ..Default::default()
+    });
+    assert_eq!(events.len(), 2);
+    assert!(events
+        .iter()
+        .all(|e| e.context.call_stack.back() == Some(&emitter_1)));
+
+    // caller index combined with a slot range
+    let events = store.get_filtered_sc_output_events(&EventFilter {
+        original_caller_address: Some(caller),
+        start: Some(slot_2),
+        end: Some(slot_3),
+        ..Default::default()
+    });
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].data, events_2[0].data);
+
+    // operation index combined with another criterion
+    let events = store.get_filtered_sc_output_events(&EventFilter {
+        original_operation_id: Some(op_id),
+        emitter_address: Some(emitter_2),
+        ..Default::default()
+    });
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].data, events_1[1].data);
+
+    // candidate events are never in the final store
+    let events = store.get_filtered_sc_output_events(&EventFilter {
+        is_final: Some(false),
+        ..Default::default()
+    });
+    assert!(events.is_empty());
+}
+
+#[test]
+fn test_final_event_store_pruning_and_reopening() {
+    let tmp_dir = TempDir::new().unwrap();
+    let emitter = test_address("emitter");
+    {
+        // keep at most 3 events, over a window of 2 periods
+        let mut store = FinalEventStore::new(tmp_dir.path().to_path_buf(), 3, 2, 2, 1000);
+        for period in 0..4 {
+            let slot = Slot::new(period, 0);
+            let events = vec![
+                test_event(slot, 0, vec![emitter], None),
+                test_event(slot, 1, vec![emitter], None),
+            ];
+            store.insert_final_events(&slot, events.iter());
+        }
+        let events = store.get_filtered_sc_output_events(&EventFilter::default());
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].context.slot, Slot::new(2, 0));
+        assert_eq!(events[0].context.index_in_slot, 1);
+
+        // the index entries of pruned events are removed as well
+        let events = store.get_filtered_sc_output_events(&EventFilter {
+            emitter_address: Some(emitter),
+            ..Default::default()
+        });
+        assert_eq!(events.len(), 3);
+    }
+
+    // events survive a restart, and events after the final slot are dropped
+    let mut store = FinalEventStore::new(tmp_dir.path().to_path_buf(), 3, 2, 2, 1000);
+    store.truncate_after(&Slot::new(2, 1));
+    let events = store.get_filtered_sc_output_events(&EventFilter::default());
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].context.slot, Slot::new(2, 0));
+}
+
+#[test]
+fn test_final_event_store_cursor_pagination() {
+    let tmp_dir = TempDir::new().unwrap();
+    let mut store = FinalEventStore::new(tmp_dir.path().to_path_buf(), 1000, 1000, 2, 1000);
+    let emitter = test_address("emitter");
+    let other = test_address("other");
+    for period in 0..3 {
+        let slot = Slot::new(period, 0);
+        let events = vec![
+            test_event(slot, 0, vec![emitter], None),
+            test_event(slot, 1, vec![other], None),
+            test_event(slot, 2, vec![emitter], None),
+        ];
+        store.insert_final_events(&slot, events.iter());
+    }
+
+    // page through the events of an emitter, 2 by 2
+    let mut filter = EventFilter {
+        emitter_address: Some(emitter),
+        limit: Some(2),
+        ..Default::default()
+    };
+    let mut pages = Vec::new();
+    loop {
+        let events = store.get_filtered_sc_output_events(&filter);
+        pages.push(
+            events
+                .iter()
+                .map(|e| (e.context.slot.period, e.context.index_in_slot))
+                .collect::<Vec<_>>(),
+        );
+        filter.cursor = filter.get_next_cursor(&events);
+        if events.is_empty() {
+            break;
+        }
+    }
+    assert_eq!(
+        pages,
+        vec![
+            vec![(0, 0), (0, 2)],
+            vec![(1, 0), (1, 2)],
+            vec![(2, 0), (2, 2)],
+            vec![],
+        ]
+    );
+
+    // an empty page keeps the cursor at the tip, so new events can be polled from there
+    assert_eq!(
+        filter.cursor,
+        Some(EventCursor {
+            slot: Slot::new(2, 0),
+            index_in_slot: 2,
+        })
+    );
+    let slot = Slot::new(3, 0);
+    store.insert_final_events(&slot, [test_event(slot, 0, vec![emitter], None)].iter());
+    let events = store.get_filtered_sc_output_events(&filter);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].context.slot, slot);
+
+    // the cursor is combined with the slot range
+    let events = store.get_filtered_sc_output_events(&EventFilter {
+        start: Some(Slot::new(1, 0)),
+        cursor: Some(EventCursor {
+            slot: Slot::new(0, 0),
+            index_in_slot: 2,
+        }),
+        limit: Some(2),
+        ..Default::default()
+    });
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].context.slot, Slot::new(1, 0));
+    assert_eq!(events[0].context.index_in_slot, 0);
+    let events = store.get_filtered_sc_output_events(&EventFilter {
+        start: Some(Slot::new(1, 0)),
+        cursor: Some(EventCursor {
+            slot: Slot::new(1, 1),
+            index_in_slot: 1,
+        }),
+        ..Default::default()
+    });
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0].context.slot, Slot::new(2, 0));
+    assert_eq!(events[0].context.index_in_slot, 0);
+}
@@ -0,0 +1,135 @@
+use super::tools::test_address;
+use crate::operation_history_store::OperationHistoryStore;
+use massa_executed_ops::ExecutedOpsChanges;
+use massa_execution_exports::{ExecutedBlockInfo, ExecutedBlockOperation};
+use massa_hash::Hash;
+use massa_models::address::Address;
+use massa_models::block_id::BlockId;
+use massa_models::execution::AddressOperationRole;
+use massa_models::operation::OperationId;
+use massa_models::secure_share::Id;
+use massa_models::slot::Slot;
+use tempfile::TempDir;
+
+fn test_operation_id(name: &str) -> OperationId {
+    OperationId::new(Hash::compute_from(name.as_bytes()))
+}
+
+/// Build the block info and the executed operations of a slot
+fn test_block(
+    name: &str,
+    operations: Vec<(OperationId, Address, Option<Address>, Option<bool>)>,
+) -> (ExecutedBlockInfo, ExecutedOpsChanges) {
+    let mut executed_ops_changes = ExecutedOpsChanges::default();
+    for (id, _, _, success) in operations.iter() {
+        if let Some(success) = success {
+            executed_ops_changes.insert(*id, (*success, Slot::new(1000, 0)));
+        }
+    }
+    let block_info = ExecutedBlockInfo {
+        block_id: BlockId::generate_from_hash(Hash::compute_from(name.as_bytes())),
+        current_version: 0,
+        announced_version: None,
+        operations: operations
+            .into_iter()
+            .map(|(id, sender, recipient, _)| ExecutedBlockOperation {
+                id,
+                sender,
+                recipient,
+            })
+            .collect(),
+    };
+    (block_info, executed_ops_changes)
+}
+
+#[test]
+fn test_operation_history_store() {
+    let tmp_dir = TempDir::new().unwrap();
+    let alice = test_address("alice");
+    let bob = test_address("bob");
+    let carol = test_address("carol");
+    let op_1 = test_operation_id("op_1");
+    let op_2 = test_operation_id("op_2");
+    let op_3 = test_operation_id("op_3");
+    let op_4 = test_operation_id("op_4");
+    let op_5 = test_operation_id("op_5");
+
+    {
+        let mut store = OperationHistoryStore::new(tmp_dir.path().to_path_buf(), 2);
+        let (block_1, ops_1) = test_block(
+            "block_1",
+            vec![
+                (op_1, alice, Some(bob), Some(true)),
+                // not executed: not indexed
+                (op_2, alice, Some(carol), None),
+                (op_3, bob, None, Some(false)),
+            ],
+        );
+        store.insert_final_slot(&Slot::new(1, 0), Some(&block_1), &ops_1);
+        // miss
+        store.insert_final_slot(&Slot::new(1, 1), None, &ExecutedOpsChanges::default());
+        let (block_2, ops_2) = test_block("block_2", vec![(op_4, carol, Some(alice), Some(true))]);
+        store.insert_final_slot(&Slot::new(2, 0), Some(&block_2), &ops_2);
+        let (block_3, ops_3) = test_block("block_3", vec![(op_5, alice, Some(alice), Some(true))]);
+        store.insert_final_slot(&Slot::new(2, 1), Some(&block_3), &ops_3);
+
+        // the operations of an address are listed from the most recent to the oldest one
+        let operations = store.get_address_operations(&alice, None, usize::MAX);
+        assert_eq!(
+            operations
+                .iter()
+                .map(|op| (op.operation_id, op.role))
+                .collect::<Vec<_>>(),
+            vec![
+                (op_5, AddressOperationRole::Sender),
+                (op_4, AddressOperationRole::Recipient),
+                (op_1, AddressOperationRole::Sender),
+            ]
+        );
+        assert_eq!(operations[1].slot, Slot::new(2, 0));
+        assert_eq!(operations[1].block_id, block_2.block_id);
+        assert!(operations[1].success);
+
+        let operations = store.get_address_operations(&bob, None, usize::MAX);
+        assert_eq!(
+            operations
+                .iter()
+                .map(|op| (op.operation_id, op.role, op.success))
+                .collect::<Vec<_>>(),
+            vec![
+                (op_3, AddressOperationRole::Sender, false),
+                (op_1, AddressOperationRole::Recipient, true),
+            ]
+        );
+
+        // paging with a cursor
+        let page = store.get_address_operations(&alice, None, 2);
+        assert_eq!(page.len(), 2);
+        let page = store.get_address_operations(&alice, Some(&page[1].operation_id), 2);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].operation_id, op_1);
+        assert!(store
+            .get_address_operations(&alice, Some(&op_1), 2)
+            .is_empty());
+
+        // unknown cursor
+        assert!(store
+            .get_address_operations(&alice, Some(&op_2), 2)
+            .is_empty());
+    }
+
+    // the index survives a restart, and operations after the final slot are dropped
+    let mut store = OperationHistoryStore::new(tmp_dir.path().to_path_buf(), 2);
+    store.truncate_after(&Slot::new(2, 0));
+    let operations = store.get_address_operations(&alice, None, usize::MAX);
+    assert_eq!(
+        operations
+            .iter()
+            .map(|op| op.operation_id)
+            .collect::<Vec<_>>(),
+        vec![op_4, op_1]
+    );
+    assert!(store
+        .get_address_operations(&alice, Some(&op_5), 2)
+        .is_empty());
+}
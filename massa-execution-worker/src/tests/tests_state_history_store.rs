@@ -0,0 +1,187 @@
+use super::tools::test_address;
+use crate::state_history_store::{HistoricalValue, StateHistoryStore};
+use massa_final_state::{MockFinalStateController, StateChanges};
+use massa_ledger_exports::{
+    LedgerEntryUpdate, MockLedgerControllerWrapper, SetOrDelete, SetOrKeep, SetUpdateOrDelete,
+};
+use massa_models::address::Address;
+use massa_models::amount::Amount;
+use massa_models::prehash::PreHashMap;
+use massa_models::slot::Slot;
+use parking_lot::RwLock;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+/// Final ledger values shared with the mocked final state
+#[derive(Default)]
+struct TestLedger {
+    balances: PreHashMap<Address, Amount>,
+    datastore: BTreeMap<(Address, Vec<u8>), Vec<u8>>,
+}
+
+/// Build a mocked final state whose ledger reads `ledger`
+fn test_final_state(ledger: Arc<RwLock<TestLedger>>) -> MockFinalStateController {
+    let ledger_controller = MockLedgerControllerWrapper::new();
+    ledger_controller.set_expectations(|ledger_controller| {
+        let balances = ledger.clone();
+        ledger_controller
+            .expect_get_balance()
+            .returning(move |addr| balances.read().balances.get(addr).copied());
+        let datastore = ledger.clone();
+        ledger_controller
+            .expect_get_data_entry()
+            .returning(move |addr, key| {
+                datastore
+                    .read()
+                    .datastore
+                    .get(&(*addr, key.to_vec()))
+                    .cloned()
+            });
+    });
+    let mut final_state = MockFinalStateController::new();
+    final_state
+        .expect_get_ledger()
+        .return_const(Box::new(ledger_controller));
+    final_state
+}
+
+/// Build the state changes of a slot updating the balance and the datastore of an address
+fn test_changes(
+    address: Address,
+    balance: Option<Amount>,
+    datastore: Vec<(&[u8], &[u8])>,
+) -> StateChanges {
+    let mut changes = StateChanges::default();
+    changes.ledger_changes.0.insert(
+        address,
+        SetUpdateOrDelete::Update(LedgerEntryUpdate {
+            balance: balance.map_or(SetOrKeep::Keep, SetOrKeep::Set),
+            bytecode: SetOrKeep::Keep,
+            datastore: datastore
+                .into_iter()
+                .map(|(key, value)| (key.to_vec(), SetOrDelete::Set(value.to_vec())))
+                .collect(),
+        }),
+    );
+    changes
+}
+
+#[test]
+fn test_state_history_store() {
+    let tmp_dir = TempDir::new().unwrap();
+    let alice = test_address("alice");
+    let bob = test_address("bob");
+    let ledger: Arc<RwLock<TestLedger>> = Default::default();
+    ledger.write().balances.insert(alice, Amount::from_raw(100));
+    let final_state = test_final_state(ledger.clone());
+
+    {
+        let mut store = StateHistoryStore::new(tmp_dir.path().to_path_buf(), 1, 2);
+        store.insert_final_slot(
+            &Slot::new(1, 0),
+            &test_changes(alice, Some(Amount::from_raw(200)), vec![]),
+            &final_state,
+        );
+        ledger.write().balances.insert(alice, Amount::from_raw(200));
+        store.insert_final_slot(&Slot::new(1, 1), &StateChanges::default(), &final_state);
+        store.insert_final_slot(
+            &Slot::new(2, 0),
+            &test_changes(alice, Some(Amount::from_raw(300)), vec![(b"key", b"value")]),
+            &final_state,
+        );
+        ledger.write().balances.insert(alice, Amount::from_raw(300));
+        ledger
+            .write()
+            .datastore
+            .insert((alice, b"key".to_vec()), b"value".to_vec());
+
+        // values overwritten after the queried slot
+        assert_eq!(
+            store.get_balance_at(&alice, &Slot::new(0, 1)),
+            HistoricalValue::Changed(Some(Amount::from_raw(100)))
+        );
+        assert_eq!(
+            store.get_balance_at(&alice, &Slot::new(1, 0)),
+            HistoricalValue::Changed(Some(Amount::from_raw(200)))
+        );
+        assert_eq!(
+            store.get_balance_at(&alice, &Slot::new(1, 1)),
+            HistoricalValue::Changed(Some(Amount::from_raw(200)))
+        );
+        assert_eq!(
+            store.get_data_entry_at(&alice, b"key", &Slot::new(1, 1)),
+            HistoricalValue::Changed(None)
+        );
+        // no change after the queried slot: the final value applies
+        assert_eq!(
+            store.get_balance_at(&alice, &Slot::new(2, 0)),
+            HistoricalValue::Unchanged
+        );
+        assert_eq!(
+            store.get_balance_at(&bob, &Slot::new(1, 0)),
+            HistoricalValue::Unchanged
+        );
+        assert_eq!(
+            store.get_data_entry_at(&alice, b"key", &Slot::new(2, 0)),
+            HistoricalValue::Unchanged
+        );
+        // older than the first retained slot
+        assert_eq!(
+            store.get_balance_at(&alice, &Slot::new(0, 0)),
+            HistoricalValue::NotRetained
+        );
+
+        // slots older than the retention window are pruned
+        store.insert_final_slot(&Slot::new(3, 0), &StateChanges::default(), &final_state);
+        assert_eq!(
+            store.get_balance_at(&alice, &Slot::new(1, 0)),
+            HistoricalValue::NotRetained
+        );
+        assert_eq!(
+            store.get_balance_at(&alice, &Slot::new(1, 1)),
+            HistoricalValue::Changed(Some(Amount::from_raw(200)))
+        );
+
+        // deleting an entry only records its balance: its datastore is not retained
+        let mut changes = StateChanges::default();
+        changes
+            .ledger_changes
+            .0
+            .insert(alice, SetUpdateOrDelete::Delete);
+        store.insert_final_slot(&Slot::new(3, 1), &changes, &final_state);
+        assert_eq!(
+            store.get_balance_at(&alice, &Slot::new(3, 0)),
+            HistoricalValue::Changed(Some(Amount::from_raw(300)))
+        );
+        assert_eq!(
+            store.get_data_entry_at(&alice, b"key", &Slot::new(3, 0)),
+            HistoricalValue::NotRetained
+        );
+        // a change of the key before the deletion is still known
+        assert_eq!(
+            store.get_data_entry_at(&alice, b"key", &Slot::new(1, 1)),
+            HistoricalValue::Changed(None)
+        );
+    }
+
+    // the history is kept across restarts if it follows the final state
+    {
+        let mut store = StateHistoryStore::new(tmp_dir.path().to_path_buf(), 1, 2);
+        store.reset_if_not_contiguous(&Slot::new(3, 1));
+        assert_eq!(
+            store.get_balance_at(&alice, &Slot::new(1, 1)),
+            HistoricalValue::Changed(Some(Amount::from_raw(200)))
+        );
+    }
+
+    // and dropped otherwise
+    {
+        let mut store = StateHistoryStore::new(tmp_dir.path().to_path_buf(), 1, 2);
+        store.reset_if_not_contiguous(&Slot::new(5, 0));
+        assert_eq!(
+            store.get_balance_at(&alice, &Slot::new(1, 1)),
+            HistoricalValue::NotRetained
+        );
+    }
+}
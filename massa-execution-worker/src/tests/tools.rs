@@ -0,0 +1,9 @@
+use massa_hash::Hash;
+use massa_models::address::{Address, UserAddress, UserAddressV0};
+
+/// Build a user address from a name, for tests
+pub fn test_address(name: &str) -> Address {
+    Address::User(UserAddress::UserAddressV0(UserAddressV0(
+        Hash::compute_from(name.as_bytes()),
+    )))
+}
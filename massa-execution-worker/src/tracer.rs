@@ -0,0 +1,92 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! This module implements the optional execution tracer.
+//!
+//! When enabled in the execution context, the tracer records the tree of nested calls made during an execution,
+//! along with the datastore accesses, transfers, events and asynchronous messages made through the ABIs.
+//! It is not part of the context snapshots: the steps of a call that failed are kept in the trace.
+
+use massa_models::address::Address;
+use massa_models::amount::Amount;
+use massa_models::execution::{ExecutionTraceFrame, ExecutionTraceStep};
+
+/// Records the call tree of an execution
+#[derive(Debug, Clone)]
+pub(crate) struct ExecutionTracer {
+    /// frames of the calls that are not finished yet, the current one is at the back
+    frames: Vec<ExecutionTraceFrame>,
+}
+
+impl ExecutionTracer {
+    /// Create a new tracer
+    ///
+    /// # Arguments
+    /// * `address`: address at the root of the traced execution
+    /// * `gas_available`: gas available for the whole execution, if known
+    pub fn new(address: Address, gas_available: Option<u64>) -> Self {
+        ExecutionTracer {
+            frames: vec![ExecutionTraceFrame {
+                address,
+                coins: Amount::zero(),
+                gas_available,
+                gas_used: None,
+                steps: Vec::new(),
+            }],
+        }
+    }
+
+    /// Record a step in the current call frame
+    pub fn record(&mut self, step: ExecutionTraceStep) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.steps.push(step);
+        }
+    }
+
+    /// Start a new call frame, nested in the current one
+    pub fn enter_call(&mut self, address: Address, coins: Amount, gas_available: Option<u64>) {
+        self.frames.push(ExecutionTraceFrame {
+            address,
+            coins,
+            gas_available,
+            gas_used: None,
+            steps: Vec::new(),
+        });
+    }
+
+    /// Set the gas available for the current call frame, if it is not known yet
+    pub fn set_gas_available(&mut self, gas_available: u64) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.gas_available.get_or_insert(gas_available);
+        }
+    }
+
+    /// Set the gas consumed by the current call frame from the gas remaining at its end.
+    /// Nothing is recorded if the gas available for the frame is unknown.
+    pub fn set_gas_remaining(&mut self, gas_remaining: u64) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.gas_used = frame
+                .gas_available
+                .map(|gas_available| gas_available.saturating_sub(gas_remaining));
+        }
+    }
+
+    /// Finish the current call frame and attach it to its parent.
+    /// The root frame is never finished.
+    pub fn exit_call(&mut self) {
+        if self.frames.len() > 1 {
+            if let Some(frame) = self.frames.pop() {
+                self.record(ExecutionTraceStep::Call(frame));
+            }
+        }
+    }
+
+    /// Finish the calls that are still running and return the root frame
+    pub fn finish(mut self) -> ExecutionTraceFrame {
+        while self.frames.len() > 1 {
+            self.exit_call();
+        }
+        self.frames
+            .pop()
+            .expect("execution tracer should always have a root frame")
+    }
+}
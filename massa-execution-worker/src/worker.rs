@@ -7,15 +7,20 @@
 
 use crate::controller::{ExecutionControllerImpl, ExecutionInputData, ExecutionManagerImpl};
 use crate::execution::ExecutionState;
-use crate::request_queue::RequestQueue;
+use crate::readonly_executor::{
+    start_readonly_executors, ReadOnlyDeadline, ReadOnlyInputData, TraceRequest,
+};
+use crate::request_queue::{RequestQueue, RequestWithResponseSender};
 use crate::slot_sequencer::SlotSequencer;
 use massa_execution_exports::{
     ExecutionBlockMetadata, ExecutionChannels, ExecutionConfig, ExecutionController,
-    ExecutionError, ExecutionManager, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
+    ExecutionError, ExecutionManager,
 };
 use massa_final_state::FinalStateController;
 use massa_metrics::MassaMetrics;
 use massa_models::block_id::BlockId;
+use massa_models::execution::ExecutionTraceFrame;
+use massa_models::operation::OperationId;
 use massa_models::slot::Slot;
 use massa_pos_exports::SelectorController;
 use massa_time::MassaTime;
@@ -34,10 +39,14 @@ pub(crate) struct ExecutionThread {
     slot_sequencer: SlotSequencer,
     // Execution state (see execution.rs) to which execution requests are sent
     execution_state: Arc<RwLock<ExecutionState>>,
-    /// queue for read-only requests and response MPSCs to send back their outputs
-    readonly_requests: RequestQueue<ReadOnlyExecutionRequest, ReadOnlyExecutionOutput>,
+    /// queue for operation trace requests, along with their deadline, and response MPSCs to send back their traces
+    trace_requests: RequestQueue<(OperationId, ReadOnlyDeadline), ExecutionTraceFrame>,
+    /// input data of the read-only executors, to which the trace requests are handed over
+    readonly_input_data: Arc<(Condvar, Mutex<ReadOnlyInputData>)>,
     /// Selector controller
     selector: Box<dyn SelectorController>,
+    /// prometheus metrics
+    massa_metrics: MassaMetrics,
 }
 
 impl ExecutionThread {
@@ -48,11 +57,14 @@ impl ExecutionThread {
     /// * `config`: execution configuration
     /// * `input_data`: a copy of the input data interface to get incoming requests from
     /// * `execution_state`: an thread-safe shared access to the execution state, which can be bootstrapped or newly created
+    /// * `readonly_input_data`: input data of the read-only executors, to hand the trace requests over to them
     pub fn new(
         config: ExecutionConfig,
         input_data: Arc<(Condvar, Mutex<ExecutionInputData>)>,
         execution_state: Arc<RwLock<ExecutionState>>,
+        readonly_input_data: Arc<(Condvar, Mutex<ReadOnlyInputData>)>,
         selector: Box<dyn SelectorController>,
+        massa_metrics: MassaMetrics,
     ) -> Self {
         // get the latest executed final slot, at the output of which the final ledger is attached
         // if we are restarting the network, use last genesis slot of the last start.
@@ -68,44 +80,72 @@ impl ExecutionThread {
         // create and return the ExecutionThread
         ExecutionThread {
             input_data,
-            readonly_requests: RequestQueue::new(config.readonly_queue_length),
+            trace_requests: RequestQueue::new(config.readonly_queue_length),
             execution_state,
+            readonly_input_data,
             slot_sequencer: SlotSequencer::new(config, final_cursor),
             selector,
+            massa_metrics,
         }
     }
 
-    /// Append incoming read-only requests to the relevant queue,
-    /// Cancel those that are in excess if there are too many.
-    fn update_readonly_requests(
-        &mut self,
-        new_requests: RequestQueue<ReadOnlyExecutionRequest, ReadOnlyExecutionOutput>,
-    ) {
-        // Append incoming readonly requests to our readonly request queue
-        // Excess requests are cancelled
-        self.readonly_requests.extend(new_requests);
-    }
-
-    /// Executes a read-only request from the queue, if any.
-    /// The result of the execution is sent asynchronously through the response channel provided with the request.
-    ///
-    /// # Returns
-    /// true if a request was executed, false otherwise
-    fn execute_one_readonly_request(&mut self) -> bool {
-        if let Some(req_resp) = self.readonly_requests.pop() {
-            let (req, resp_tx) = req_resp.into_request_sender_pair();
+    /// Hands the queued trace requests over to the read-only executors, along with the block of their operation
+    /// and a snapshot of the execution state at the start of its slot.
+    /// The traces are sent asynchronously through the response channels provided with the requests.
+    fn forward_trace_requests(&mut self) {
+        while let Some(req_resp) = self.trace_requests.pop() {
+            let ((operation_id, deadline), resp_tx) = req_resp.into_request_sender_pair();
+
+            // find the block of the active history that includes the operation
+            let trace_request = {
+                let execution_state = self.execution_state.read();
+                match execution_state.get_active_operation_slot(&operation_id) {
+                    Some(slot) => match self.slot_sequencer.get_slot_content(&slot) {
+                        Some(block) => Ok(TraceRequest {
+                            operation_id,
+                            block: block.clone(),
+                            snapshot: execution_state.get_trace_snapshot(&slot),
+                        }),
+                        None => Err(ExecutionError::RuntimeError(format!(
+                            "block of operation {} is not available anymore",
+                            operation_id
+                        ))),
+                    },
+                    None => Err(ExecutionError::RuntimeError(format!(
+                        "operation {} not found in the active history",
+                        operation_id
+                    ))),
+                }
+            };
 
-            // Acquire write access to the execution state (for cache updates) and execute the read-only request
-            let outcome = self.execution_state.write().execute_readonly_request(req);
-
-            // Send the execution output through resp_tx.
             // Ignore errors because they just mean that the request emitter dropped the received
             // because it doesn't need the response anymore.
-            let _ = resp_tx.send(outcome);
-
-            return true;
+            let trace_request = match trace_request {
+                Ok(trace_request) => trace_request,
+                Err(err) => {
+                    let _ = resp_tx.send(Err(err));
+                    continue;
+                }
+            };
+            let mut readonly_input_data = self.readonly_input_data.1.lock();
+            if readonly_input_data.trace_requests.is_full() {
+                let _ = resp_tx.send(Err(ExecutionError::ChannelError(
+                    "too many queued trace requests".into(),
+                )));
+                continue;
+            }
+            readonly_input_data
+                .trace_requests
+                .push(RequestWithResponseSender::new(
+                    (trace_request, deadline),
+                    resp_tx,
+                ));
+            self.massa_metrics
+                .set_readonly_queue_depth(readonly_input_data.queue_depth());
+
+            // wake up a read-only executor
+            self.readonly_input_data.0.notify_one();
         }
-        false
     }
 
     /// Waits for an event to trigger a new iteration in the execution main loop.
@@ -130,7 +170,7 @@ impl ExecutionThread {
             if input_data.new_blockclique.is_some()
                 || !input_data.finalized_blocks.is_empty()
                 || !input_data.block_metadata.is_empty()
-                || !input_data.readonly_requests.is_empty()
+                || !input_data.trace_requests.is_empty()
             {
                 return (input_data, false);
             }
@@ -140,8 +180,8 @@ impl ExecutionThread {
                 return (input_data, false);
             }
 
-            // there are read-only requests ready
-            if !self.readonly_requests.is_empty() {
+            // there are trace requests ready
+            if !self.trace_requests.is_empty() {
                 return (input_data, false);
             }
 
@@ -171,13 +211,14 @@ impl ExecutionThread {
         // It also prioritizes executions in the following order:
         // 1 - final executions
         // 2 - speculative executions
-        // 3 - read-only executions
+        // 3 - handing operation traces over to the read-only executors
+        // Read-only executions are run by the read-only executor threads (see readonly_executor.rs).
         loop {
             let (input_data, stop) = self.wait_loop_event();
             debug!("Execution loop triggered, input_data = {}", input_data);
 
-            // update the sequence of read-only requests
-            self.update_readonly_requests(input_data.readonly_requests);
+            // update the sequence of trace requests
+            self.trace_requests.extend(input_data.trace_requests);
 
             if stop {
                 // we need to stop
@@ -216,22 +257,18 @@ impl ExecutionThread {
                 continue;
             }
 
-            // low priority: execute a read-only request (note that the queue is of finite length), if there is one ready.
-            self.execute_one_readonly_request();
+            // low priority: hand the requested traces of operations of the active history over to the read-only executors
+            self.forward_trace_requests();
         }
 
         // We are quitting the loop.
 
-        // Cancel pending readonly requests
+        // Cancel pending trace requests
         let cancel_err = ExecutionError::ChannelError(
-            "readonly execution cancelled because the execution worker is closing".into(),
+            "operation trace cancelled because the execution worker is closing".into(),
         );
-        self.input_data
-            .1
-            .lock()
-            .take()
-            .readonly_requests
-            .cancel(cancel_err);
+        self.trace_requests.cancel(cancel_err.clone());
+        self.input_data.1.lock().trace_requests.cancel(cancel_err);
     }
 }
 
@@ -262,7 +299,7 @@ pub fn start_execution_worker(
         selector.clone(),
         channels,
         wallet,
-        massa_metrics,
+        massa_metrics.clone(),
     )));
 
     // define the input data interface
@@ -271,24 +308,48 @@ pub fn start_execution_worker(
         Mutex::new(ExecutionInputData::new(config.clone())),
     ));
 
+    // define the input data interface of the read-only executors, and launch them
+    let readonly_input_data =
+        Arc::new((Condvar::new(), Mutex::new(ReadOnlyInputData::new(&config))));
+    let readonly_thread_handles = start_readonly_executors(
+        &config,
+        readonly_input_data.clone(),
+        execution_state.clone(),
+        massa_metrics.clone(),
+    );
+
     // create a controller
     let controller = ExecutionControllerImpl {
         input_data: input_data.clone(),
         execution_state: execution_state.clone(),
+        readonly_input_data: readonly_input_data.clone(),
+        config: config.clone(),
+        massa_metrics: massa_metrics.clone(),
     };
 
     // launch the execution thread
     let input_data_clone = input_data.clone();
+    let readonly_input_data_clone = readonly_input_data.clone();
     let thread_builder = thread::Builder::new().name("execution".into());
     let thread_handle = thread_builder
         .spawn(move || {
-            ExecutionThread::new(config, input_data_clone, execution_state, selector).main_loop();
+            ExecutionThread::new(
+                config,
+                input_data_clone,
+                execution_state,
+                readonly_input_data_clone,
+                selector,
+                massa_metrics,
+            )
+            .main_loop();
         })
         .expect("failed to spawn thread : execution");
     // create a manager
     let manager = ExecutionManagerImpl {
         input_data,
         thread_handle: Some(thread_handle),
+        readonly_input_data,
+        readonly_thread_handles,
     };
 
     // return the execution manager and controller pair
@@ -1,5 +1,5 @@
 use massa_async_pool::AsyncPool;
-use massa_db_exports::{DBBatch, ShareableMassaDBController};
+use massa_db_exports::{DBBatch, ShareableMassaDBController, StateHashBreakdown};
 use massa_executed_ops::ExecutedDenunciations;
 use massa_hash::Hash;
 use massa_ledger_exports::LedgerController;
@@ -42,6 +42,16 @@ pub trait FinalStateController: Send + Sync {
     /// Deserialize the entire DB and check the data. Useful to check after bootstrap.
     fn is_db_valid(&self) -> bool;
 
+    /// Recompute the state hash from every key of the DB, per key prefix, and check the keys and values.
+    /// Breakdowns of two nodes can be diffed to find the keys on which they diverge.
+    ///
+    /// # Arguments
+    /// * `listed_keys`: list the hash of the value of every key starting with this prefix
+    fn get_state_hash_breakdown(
+        &self,
+        listed_keys: Option<String>,
+    ) -> Result<StateHashBreakdown, FinalStateError>;
+
     /// Initialize the execution trail hash to zero.
     fn init_execution_trail_hash_to_batch(&mut self, batch: &mut DBBatch);
 
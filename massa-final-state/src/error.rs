@@ -5,6 +5,7 @@
 use displaydoc::Display;
 use thiserror::Error;
 
+use massa_db_exports::MassaDBError;
 use massa_versioning::versioning::{ExtendFromDbError, IsConsistentWithShutdownPeriodError};
 
 /// Final state error
@@ -19,6 +20,8 @@ pub enum FinalStateError {
     PosError(String),
     /// Snapshot error: {0}
     SnapshotError(String),
+    /// database error: {0}
+    DBError(#[from] MassaDBError),
     /// ExtendFromDbError
     ExtendFromDbError(#[from] ExtendFromDbError),
     /// IsConsistentWithShutdownPeriodError
@@ -15,7 +15,9 @@ use massa_db_exports::{
     CYCLE_HISTORY_PREFIX, DEFERRED_CREDITS_PREFIX, EXECUTED_DENUNCIATIONS_PREFIX,
     EXECUTED_OPS_PREFIX, LEDGER_PREFIX, MIP_STORE_PREFIX, STATE_CF,
 };
-use massa_db_exports::{EXECUTION_TRAIL_HASH_PREFIX, MIP_STORE_STATS_PREFIX, VERSIONING_CF};
+use massa_db_exports::{
+    StateHashBreakdown, EXECUTION_TRAIL_HASH_PREFIX, MIP_STORE_STATS_PREFIX, VERSIONING_CF,
+};
 use massa_executed_ops::ExecutedDenunciations;
 use massa_executed_ops::ExecutedOps;
 use massa_hash::Hash;
@@ -555,106 +557,15 @@ impl FinalState {
 
         for (serialized_key, serialized_value) in db.iterator_cf(STATE_CF, MassaIteratorMode::Start)
         {
-            #[allow(clippy::if_same_then_else)]
-            if serialized_key.starts_with(CYCLE_HISTORY_PREFIX.as_bytes()) {
-                if !self
-                    .pos_state
-                    .is_cycle_history_key_value_valid(&serialized_key, &serialized_value)
-                {
-                    warn!(
-                        "Wrong key/value for CYCLE_HISTORY_KEY PREFIX serialized_key: {:?}, serialized_value: {:?}",
-                        serialized_key, serialized_value
-                    );
-                    return Err(anyhow!(
-                        "Wrong key/value for CYCLE_HISTORY_KEY PREFIX serialized_key: {:?}, serialized_value: {:?}",
-                        serialized_key, serialized_value
-                    ));
-                }
-            } else if serialized_key.starts_with(DEFERRED_CREDITS_PREFIX.as_bytes()) {
-                if !self
-                    .pos_state
-                    .is_deferred_credits_key_value_valid(&serialized_key, &serialized_value)
-                {
-                    warn!(
-                        "Wrong key/value for DEFERRED_CREDITS PREFIX serialized_key: {:?}, serialized_value: {:?}",
-                        serialized_key, serialized_value
-                    );
-                    return Err(anyhow!(
-                        "Wrong key/value for DEFERRED_CREDITS PREFIX serialized_key: {:?}, serialized_value: {:?}",
-                        serialized_key, serialized_value
-                    ));
-                }
-            } else if serialized_key.starts_with(ASYNC_POOL_PREFIX.as_bytes()) {
-                if !self
-                    .async_pool
-                    .is_key_value_valid(&serialized_key, &serialized_value)
-                {
-                    warn!(
-                        "Wrong key/value for ASYNC_POOL PREFIX serialized_key: {:?}, serialized_value: {:?}",
-                        serialized_key, serialized_value
-                    );
-                    return Err(anyhow!(
-                        "Wrong key/value for ASYNC_POOL PREFIX serialized_key: {:?}, serialized_value: {:?}",
-                        serialized_key, serialized_value
-                    ));
-                }
-            } else if serialized_key.starts_with(EXECUTED_OPS_PREFIX.as_bytes()) {
-                if !self
-                    .executed_ops
-                    .is_key_value_valid(&serialized_key, &serialized_value)
-                {
-                    warn!(
-                        "Wrong key/value for EXECUTED_OPS PREFIX serialized_key: {:?}, serialized_value: {:?}",
-                        serialized_key, serialized_value
-                    );
-                    return Err(anyhow!(
-                        "Wrong key/value for EXECUTED_OPS PREFIX serialized_key: {:?}, serialized_value: {:?}",
-                        serialized_key, serialized_value
-                    ));
-                }
-            } else if serialized_key.starts_with(EXECUTED_DENUNCIATIONS_PREFIX.as_bytes()) {
-                if !self
-                    .executed_denunciations
-                    .is_key_value_valid(&serialized_key, &serialized_value)
-                {
-                    warn!("Wrong key/value for EXECUTED_DENUNCIATIONS PREFIX serialized_key: {:?}, serialized_value: {:?}", serialized_key, serialized_value);
-                    return Err(anyhow!(
-                        "Wrong key/value for EXECUTED_DENUNCIATIONS PREFIX serialized_key: {:?}, serialized_value: {:?}", 
-                        serialized_key, serialized_value
-                    ));
-                }
-            } else if serialized_key.starts_with(LEDGER_PREFIX.as_bytes()) {
-                if !self
-                    .ledger
-                    .is_key_value_valid(&serialized_key, &serialized_value)
-                {
-                    warn!("Wrong key/value for LEDGER PREFIX serialized_key: {:?}, serialized_value: {:?}", serialized_key, serialized_value);
-                    return Err(anyhow!(
-                        "Wrong key/value for LEDGER PREFIX serialized_key: {:?}, serialized_value: {:?}", 
-                        serialized_key, serialized_value
-                    ));
-                }
-            } else if serialized_key.starts_with(MIP_STORE_PREFIX.as_bytes()) {
-                if !self
-                    .mip_store
-                    .is_key_value_valid(&serialized_key, &serialized_value)
-                {
-                    warn!("Wrong key/value for MIP Store");
-                    return Err(anyhow!(
-                        "Wrong key/value for MIP Store serialized_key: {:?}, serialized_value: {:?}", 
-                        serialized_key, serialized_value
-                    ));
-                }
-            } else if serialized_key.starts_with(EXECUTION_TRAIL_HASH_PREFIX.as_bytes()) {
-                // no checks here as they are performed above by direct reading
-            } else {
+            if !self.is_state_key_value_valid(&serialized_key, &serialized_value) {
                 warn!(
-                    "Key/value does not correspond to any prefix: serialized_key: {:?}, serialized_value: {:?}",
+                    "Wrong key/value in the state: serialized_key: {:?}, serialized_value: {:?}",
                     serialized_key, serialized_value
                 );
                 return Err(anyhow!(
-                    "Key/value does not correspond to any prefix: serialized_key: {:?}, serialized_value: {:?}",
-                    serialized_key, serialized_value
+                    "Wrong key/value in the state: serialized_key: {:?}, serialized_value: {:?}",
+                    serialized_key,
+                    serialized_value
                 ));
             }
         }
@@ -690,6 +601,37 @@ impl FinalState {
         Ok(())
     }
 
+    /// Checks that a key of the state column family and its value can be deserialized,
+    /// dispatching on the prefix of the key. Keys matching no prefix are invalid.
+    pub fn is_state_key_value_valid(&self, serialized_key: &[u8], serialized_value: &[u8]) -> bool {
+        if serialized_key.starts_with(CYCLE_HISTORY_PREFIX.as_bytes()) {
+            self.pos_state
+                .is_cycle_history_key_value_valid(serialized_key, serialized_value)
+        } else if serialized_key.starts_with(DEFERRED_CREDITS_PREFIX.as_bytes()) {
+            self.pos_state
+                .is_deferred_credits_key_value_valid(serialized_key, serialized_value)
+        } else if serialized_key.starts_with(ASYNC_POOL_PREFIX.as_bytes()) {
+            self.async_pool
+                .is_key_value_valid(serialized_key, serialized_value)
+        } else if serialized_key.starts_with(EXECUTED_OPS_PREFIX.as_bytes()) {
+            self.executed_ops
+                .is_key_value_valid(serialized_key, serialized_value)
+        } else if serialized_key.starts_with(EXECUTED_DENUNCIATIONS_PREFIX.as_bytes()) {
+            self.executed_denunciations
+                .is_key_value_valid(serialized_key, serialized_value)
+        } else if serialized_key.starts_with(LEDGER_PREFIX.as_bytes()) {
+            self.ledger
+                .is_key_value_valid(serialized_key, serialized_value)
+        } else if serialized_key.starts_with(MIP_STORE_PREFIX.as_bytes()) {
+            self.mip_store
+                .is_key_value_valid(serialized_key, serialized_value)
+        } else if serialized_key.starts_with(EXECUTION_TRAIL_HASH_PREFIX.as_bytes()) {
+            Hash::try_from(serialized_value).is_ok()
+        } else {
+            false
+        }
+    }
+
     /// Initializes a `FinalState` from a snapshot.
     ///
     /// # Arguments
@@ -836,6 +778,18 @@ impl FinalStateController for FinalState {
         self._is_db_valid().is_ok()
     }
 
+    fn get_state_hash_breakdown(
+        &self,
+        listed_keys: Option<String>,
+    ) -> Result<StateHashBreakdown, FinalStateError> {
+        let db = self.db.read();
+        Ok(StateHashBreakdown::compute(
+            db.as_ref(),
+            listed_keys.as_deref(),
+            |key, value| self.is_state_key_value_valid(key, value),
+        )?)
+    }
+
     fn recompute_caches(&mut self) {
         self.async_pool.recompute_message_info_cache();
         self.executed_ops.recompute_sorted_ops_and_op_exec_status();
@@ -19,6 +19,14 @@ pub struct GrpcConfig {
     pub enable_cors: bool,
     /// whether to enable gRPC health service
     pub enable_health: bool,
+    /// interval between two checks of the node health
+    pub health_check_interval: Duration,
+    /// max number of slots the final execution cursor can lag behind the current slot before being reported as unhealthy
+    pub health_max_final_cursor_lag: u64,
+    /// min number of connected peers under which the protocol is reported as unhealthy
+    pub health_min_peer_count: u64,
+    /// max number of periods without a new blockclique block before consensus is reported as unhealthy
+    pub health_max_blockclique_stall: u64,
     /// whether to enable gRPC reflection
     pub enable_reflection: bool,
     /// whether to enable TLS
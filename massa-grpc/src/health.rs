@@ -0,0 +1,259 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Health checks of the gRPC health service, derived from the state of the node modules.
+//!
+//! Each module signal is reported as a dedicated service of the standard gRPC health protocol:
+//! * `massa.health.v1.Execution`: the final execution cursor is not lagging too far behind the wall-clock slot
+//! * `massa.health.v1.Protocol`: the node is connected to enough peers
+//! * `massa.health.v1.Bootstrap`: the node is bootstrapped and does not need to bootstrap again
+//! * `massa.health.v1.Consensus`: the blockclique keeps progressing
+//!
+//! The gRPC API service itself (and the empty service name) is `SERVING` only if all the signals are healthy.
+
+use crate::config::GrpcConfig;
+use massa_bootstrap::{BootstrapStatus, SharedBootstrapStatus};
+use massa_consensus_exports::ConsensusController;
+use massa_execution_exports::ExecutionController;
+use massa_models::slot::Slot;
+use massa_models::timeslots::get_latest_block_slot_at_timestamp;
+use massa_protocol_exports::ProtocolController;
+use massa_time::MassaTime;
+use tokio::sync::oneshot;
+use tonic_health::server::HealthReporter;
+use tonic_health::ServingStatus;
+use tracing::{info, warn};
+
+/// Health service name of the execution signal
+pub const EXECUTION_HEALTH_SERVICE: &str = "massa.health.v1.Execution";
+/// Health service name of the protocol signal
+pub const PROTOCOL_HEALTH_SERVICE: &str = "massa.health.v1.Protocol";
+/// Health service name of the bootstrap signal
+pub const BOOTSTRAP_HEALTH_SERVICE: &str = "massa.health.v1.Bootstrap";
+/// Health service name of the consensus signal
+pub const CONSENSUS_HEALTH_SERVICE: &str = "massa.health.v1.Consensus";
+
+/// Status of a single health signal: `Err` contains the reason why the signal is unhealthy
+pub type SignalStatus = Result<(), String>;
+
+/// Health of the node modules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthReport {
+    /// final execution cursor lag
+    pub execution: SignalStatus,
+    /// peer count
+    pub protocol: SignalStatus,
+    /// bootstrap status
+    pub bootstrap: SignalStatus,
+    /// blockclique progress
+    pub consensus: SignalStatus,
+}
+
+impl HealthReport {
+    /// Get the status of every signal along with its health service name
+    pub fn signals(&self) -> [(&'static str, &SignalStatus); 4] {
+        [
+            (EXECUTION_HEALTH_SERVICE, &self.execution),
+            (PROTOCOL_HEALTH_SERVICE, &self.protocol),
+            (BOOTSTRAP_HEALTH_SERVICE, &self.bootstrap),
+            (CONSENSUS_HEALTH_SERVICE, &self.consensus),
+        ]
+    }
+
+    /// Whether all the signals are healthy
+    pub fn is_healthy(&self) -> bool {
+        self.signals().iter().all(|(_, status)| status.is_ok())
+    }
+}
+
+/// Computes the health of the node from the signals of its modules
+pub struct HealthChecker {
+    /// link to the consensus component
+    pub consensus_controller: Box<dyn ConsensusController>,
+    /// link to the execution component
+    pub execution_controller: Box<dyn ExecutionController>,
+    /// link to the protocol component
+    pub protocol_controller: Box<dyn ProtocolController>,
+    /// bootstrap status of the node
+    pub bootstrap_status: SharedBootstrapStatus,
+    /// gRPC configuration, containing the health thresholds
+    pub grpc_config: GrpcConfig,
+}
+
+impl HealthChecker {
+    /// Create a new `HealthChecker`
+    pub fn new(
+        consensus_controller: Box<dyn ConsensusController>,
+        execution_controller: Box<dyn ExecutionController>,
+        protocol_controller: Box<dyn ProtocolController>,
+        bootstrap_status: SharedBootstrapStatus,
+        grpc_config: GrpcConfig,
+    ) -> Self {
+        Self {
+            consensus_controller,
+            execution_controller,
+            protocol_controller,
+            bootstrap_status,
+            grpc_config,
+        }
+    }
+
+    /// Check the health of the node modules at the given timestamp
+    pub fn check(&self, now: MassaTime) -> HealthReport {
+        let current_slot = match get_latest_block_slot_at_timestamp(
+            self.grpc_config.thread_count,
+            self.grpc_config.t0,
+            self.grpc_config.genesis_timestamp,
+            now,
+        ) {
+            Ok(slot) => slot,
+            Err(e) => {
+                let reason = format!("could not compute the current slot: {}", e);
+                return HealthReport {
+                    execution: Err(reason.clone()),
+                    protocol: Err(reason.clone()),
+                    bootstrap: Err(reason.clone()),
+                    consensus: Err(reason),
+                };
+            }
+        };
+
+        let final_cursor_lag = current_slot.map(|current_slot| {
+            let final_cursor = self.execution_controller.get_stats().final_cursor;
+            current_slot
+                .slots_since(&final_cursor, self.grpc_config.thread_count)
+                .unwrap_or_default()
+        });
+
+        HealthReport {
+            execution: self.check_execution(final_cursor_lag),
+            protocol: self.check_protocol(),
+            bootstrap: self.check_bootstrap(),
+            consensus: self.check_consensus(current_slot),
+        }
+    }
+
+    /// The final execution cursor must not lag more than `health_max_final_cursor_lag` slots behind the current slot
+    fn check_execution(&self, final_cursor_lag: Option<u64>) -> SignalStatus {
+        match final_cursor_lag {
+            Some(lag) if lag > self.grpc_config.health_max_final_cursor_lag => Err(format!(
+                "final execution cursor is {} slots behind the current slot (max {})",
+                lag, self.grpc_config.health_max_final_cursor_lag
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// The node must be connected to at least `health_min_peer_count` peers
+    fn check_protocol(&self) -> SignalStatus {
+        let (network_stats, _) = self
+            .protocol_controller
+            .get_stats()
+            .map_err(|e| format!("could not get the protocol stats: {}", e))?;
+        if network_stats.active_node_count < self.grpc_config.health_min_peer_count {
+            return Err(format!(
+                "connected to {} peers (min {})",
+                network_stats.active_node_count, self.grpc_config.health_min_peer_count
+            ));
+        }
+        Ok(())
+    }
+
+    /// The node must be bootstrapped and must not have detected a desynchronization
+    fn check_bootstrap(&self) -> SignalStatus {
+        match *self.bootstrap_status.read() {
+            BootstrapStatus::Done => Ok(()),
+            BootstrapStatus::InProgress => Err("bootstrap in progress".to_string()),
+            BootstrapStatus::NeedResync => {
+                Err("desynchronization detected, the node is going to bootstrap again".to_string())
+            }
+        }
+    }
+
+    /// The latest blockclique block must not be more than `health_max_blockclique_stall` periods old
+    fn check_consensus(&self, current_slot: Option<Slot>) -> SignalStatus {
+        let Some(current_slot) = current_slot else {
+            return Ok(());
+        };
+        let latest_period = self
+            .consensus_controller
+            .get_best_parents()
+            .iter()
+            .map(|(_, period)| *period)
+            .max()
+            .unwrap_or_default();
+        let stall = current_slot.period.saturating_sub(latest_period);
+        if stall > self.grpc_config.health_max_blockclique_stall {
+            return Err(format!(
+                "blockclique did not progress for {} periods (max {})",
+                stall, self.grpc_config.health_max_blockclique_stall
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Periodically check the health of the node modules and update the health service statuses accordingly
+///
+/// # Arguments
+/// * `checker`: health checker
+/// * `reporter`: reporter of the gRPC health service
+/// * `service_name`: name of the gRPC API service whose status is the overall node health
+/// * `stop_recv`: stop signal
+pub(crate) async fn run_health_checks(
+    checker: HealthChecker,
+    mut reporter: HealthReporter,
+    service_name: &'static str,
+    mut stop_recv: oneshot::Receiver<()>,
+) {
+    let mut interval = tokio::time::interval(checker.grpc_config.health_check_interval);
+    let checker = std::sync::Arc::new(checker);
+    let mut previous_report: Option<HealthReport> = None;
+    loop {
+        tokio::select! {
+            _ = &mut stop_recv => break,
+            _ = interval.tick() => {}
+        }
+
+        // the module controllers are blocking, keep them off the async runtime threads
+        let task_checker = checker.clone();
+        let report =
+            match tokio::task::spawn_blocking(move || task_checker.check(MassaTime::now())).await {
+                Ok(report) => report,
+                Err(e) => {
+                    warn!("gRPC health | health check task failed: {}", e);
+                    continue;
+                }
+            };
+        if previous_report.as_ref() == Some(&report) {
+            continue;
+        }
+
+        let previous_signals = previous_report.as_ref().map(|previous| previous.signals());
+        for (index, (name, status)) in report.signals().into_iter().enumerate() {
+            if previous_signals.map_or(true, |previous| previous[index].1 != status) {
+                match status {
+                    Ok(()) => info!("gRPC health | {} is serving", name),
+                    Err(reason) => warn!("gRPC health | {} is not serving: {}", name, reason),
+                }
+            }
+            reporter
+                .set_service_status(name, serving_status(status.is_ok()))
+                .await;
+        }
+        let overall_status = serving_status(report.is_healthy());
+        reporter
+            .set_service_status(service_name, overall_status)
+            .await;
+        reporter.set_service_status("", overall_status).await;
+
+        previous_report = Some(report);
+    }
+}
+
+fn serving_status(healthy: bool) -> ServingStatus {
+    if healthy {
+        ServingStatus::Serving
+    } else {
+        ServingStatus::NotServing
+    }
+}
@@ -9,6 +9,7 @@
 //!
 //! * `api.rs`: implements gRPC service methods without streams.
 //! * `handler.rs`: defines the logic for handling incoming gRPC requests.
+//! * `health.rs`: computes the gRPC health service statuses from the node modules.
 //! * `server`: initializes the gRPC service and serve It.
 //! * `stream/`: contains the gRPC streaming methods implementations files.
 
@@ -29,6 +30,8 @@ pub mod config;
 pub mod error;
 /// gRPC API implementation
 pub mod handler;
+/// health checks based on the node modules
+pub mod health;
 /// business code for node management methods
 pub mod private;
 /// business code for non stream methods
@@ -30,16 +30,11 @@ use massa_versioning::versioning_factory::{FactoryStrategy, VersioningFactory};
 use std::collections::HashSet;
 use std::str::FromStr;
 
-/// Execute read only call (function or bytecode)
-pub(crate) fn execute_read_only_call(
+/// Translate a read only call into an execution request
+fn to_read_only_execution_request(
     grpc: &MassaPublicGrpc,
-    request: tonic::Request<grpc_api::ExecuteReadOnlyCallRequest>,
-) -> Result<grpc_api::ExecuteReadOnlyCallResponse, GrpcError> {
-    let call: grpc_model::ReadOnlyExecutionCall = request
-        .into_inner()
-        .call
-        .ok_or_else(|| GrpcError::InvalidArgument("no call provided".to_string()))?;
-
+    call: grpc_model::ReadOnlyExecutionCall,
+) -> Result<ReadOnlyExecutionRequest, GrpcError> {
     let caller_address = match call.caller_address {
         Some(addr) => Address::from_str(&addr)?,
         None => {
@@ -118,7 +113,7 @@ pub(crate) fn execute_read_only_call(
         ));
     };
 
-    let read_only_call = ReadOnlyExecutionRequest {
+    Ok(ReadOnlyExecutionRequest {
         max_gas: call.max_gas,
         call_stack,
         target,
@@ -130,7 +125,22 @@ pub(crate) fn execute_read_only_call(
                     .map_err(|_| GrpcError::InvalidArgument("invalid amount".to_string()))
             })
             .transpose()?,
-    };
+        state_overrides: Default::default(),
+        trace: false,
+    })
+}
+
+/// Execute read only call (function or bytecode)
+pub(crate) fn execute_read_only_call(
+    grpc: &MassaPublicGrpc,
+    request: tonic::Request<grpc_api::ExecuteReadOnlyCallRequest>,
+) -> Result<grpc_api::ExecuteReadOnlyCallResponse, GrpcError> {
+    let call: grpc_model::ReadOnlyExecutionCall = request
+        .into_inner()
+        .call
+        .ok_or_else(|| GrpcError::InvalidArgument("no call provided".to_string()))?;
+
+    let read_only_call = to_read_only_execution_request(grpc, call)?;
 
     let output = grpc
         .execution_controller
@@ -1,6 +1,7 @@
 // Copyright (c) 2023 MASSA LABS <info@massa.net>
 
 use massa_bootstrap::white_black_list::SharedWhiteBlackList;
+use massa_bootstrap::SharedBootstrapStatus;
 use massa_models::node::NodeId;
 use massa_versioning::keypair_factory::KeyPairFactory;
 use massa_versioning::versioning::MipStore;
@@ -11,6 +12,7 @@ use std::sync::{Arc, Condvar, Mutex};
 
 use crate::config::{GrpcConfig, ServiceName};
 use crate::error::GrpcError;
+use crate::health::{run_health_checks, HealthChecker};
 use futures_util::FutureExt;
 use hyper::service::Service;
 use hyper::{Body, Method, Request, Response};
@@ -33,7 +35,6 @@ use tonic::body::BoxBody;
 use tonic::codegen::CompressionEncoding;
 use tonic::transport::NamedService;
 use tonic::transport::{Certificate, Identity, ServerTlsConfig};
-use tonic_health::server::HealthReporter;
 use tonic_web::GrpcWebLayer;
 use tower_http::cors::{Any, CorsLayer};
 use tracing::{info, warn};
@@ -66,11 +67,23 @@ pub struct MassaPrivateGrpc {
     pub version: massa_models::version::Version,
     /// white/black list of bootstrap
     pub bs_white_black_list: Option<SharedWhiteBlackList<'static>>,
+    /// bootstrap status of the node, reported by the health service
+    pub bootstrap_status: SharedBootstrapStatus,
 }
 
 impl MassaPrivateGrpc {
     /// Start the gRPC PRIVATE API
     pub async fn serve(self, config: &GrpcConfig) -> Result<StopHandle, GrpcError> {
+        // only take handles on the modules when the health checks are enabled
+        let health_checker = config.enable_health.then(|| {
+            HealthChecker::new(
+                self.consensus_controller.clone(),
+                self.execution_controller.clone(),
+                self.protocol_controller.clone(),
+                self.bootstrap_status.clone(),
+                config.clone(),
+            )
+        });
         let mut service = PrivateServiceServer::new(self)
             .max_decoding_message_size(config.max_decoding_message_size)
             .max_encoding_message_size(config.max_encoding_message_size);
@@ -87,7 +100,7 @@ impl MassaPrivateGrpc {
             };
         }
 
-        serve(service, config).await
+        serve(service, config, health_checker).await
     }
 }
 
@@ -122,11 +135,23 @@ pub struct MassaPublicGrpc {
     pub version: massa_models::version::Version,
     /// keypair factory
     pub keypair_factory: KeyPairFactory,
+    /// bootstrap status of the node, reported by the health service
+    pub bootstrap_status: SharedBootstrapStatus,
 }
 
 impl MassaPublicGrpc {
     /// Start the gRPC PUBLIC API
     pub async fn serve(self, config: &GrpcConfig) -> Result<StopHandle, GrpcError> {
+        // only take handles on the modules when the health checks are enabled
+        let health_checker = config.enable_health.then(|| {
+            HealthChecker::new(
+                self.consensus_controller.clone(),
+                self.execution_controller.clone(),
+                self.protocol_controller.clone(),
+                self.bootstrap_status.clone(),
+                config.clone(),
+            )
+        });
         let mut service = PublicServiceServer::new(self)
             .max_decoding_message_size(config.max_decoding_message_size)
             .max_encoding_message_size(config.max_encoding_message_size);
@@ -142,18 +167,24 @@ impl MassaPublicGrpc {
                 service = service.send_compressed(CompressionEncoding::Gzip);
             };
         }
-        serve(service, config).await
+        serve(service, config, health_checker).await
     }
 }
 
 /// Used to be able to stop the gRPC API
 pub struct StopHandle {
     stop_cmd_sender: oneshot::Sender<()>,
+    health_stop_sender: Option<oneshot::Sender<()>>,
 }
 
 impl StopHandle {
     /// stop the gRPC API gracefully
     pub fn stop(self) {
+        if let Some(health_stop_sender) = self.health_stop_sender {
+            if health_stop_sender.send(()).is_err() {
+                warn!("gRPC health check task panicked");
+            }
+        }
         if let Err(e) = self.stop_cmd_sender.send(()) {
             warn!("gRPC API thread panicked: {:?}", e);
         } else {
@@ -162,16 +193,12 @@ impl StopHandle {
     }
 }
 
-/// Massa service health check implementation
-async fn massa_service_status(mut reporter: HealthReporter) {
-    //TODO add a complete health check based on Massa modules health
-    reporter
-        .set_serving::<PublicServiceServer<MassaPublicGrpc>>()
-        .await;
-}
-
 // Configure and start the gRPC API with the given service
-async fn serve<S>(service: S, config: &GrpcConfig) -> Result<StopHandle, GrpcError>
+async fn serve<S>(
+    service: S,
+    config: &GrpcConfig,
+    health_checker: Option<HealthChecker>,
+) -> Result<StopHandle, GrpcError>
 where
     S: Service<Request<Body>, Response = Response<BoxBody>, Error = Infallible>
         + NamedService
@@ -248,16 +275,19 @@ where
         None
     };
 
-    let health_service_opt = if config.enable_health {
-        let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
-        health_reporter
-            .set_serving::<PublicServiceServer<MassaPublicGrpc>>()
-            .await;
-        tokio::spawn(massa_service_status(health_reporter.clone()));
+    let (health_service_opt, health_stop_sender) = if let Some(health_checker) = health_checker {
+        let (health_reporter, health_service) = tonic_health::server::health_reporter();
+        let (health_stop_sender, health_stop_recv) = oneshot::channel::<()>();
+        tokio::spawn(run_health_checks(
+            health_checker,
+            health_reporter,
+            S::NAME,
+            health_stop_recv,
+        ));
         info!("gRPC health service enabled");
-        Some(health_service)
+        (Some(health_service), Some(health_stop_sender))
     } else {
-        None
+        (None, None)
     };
 
     if config.accept_http1 {
@@ -303,6 +333,7 @@ where
 
     Ok(StopHandle {
         stop_cmd_sender: shutdown_send,
+        health_stop_sender,
     })
 }
 
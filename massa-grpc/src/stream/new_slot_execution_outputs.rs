@@ -5,13 +5,20 @@ use crate::error::{match_for_io_error, GrpcError};
 use crate::server::MassaPublicGrpc;
 use crate::SlotRange;
 use futures_util::StreamExt;
+use massa_async_pool::{AsyncMessage, AsyncMessageUpdate};
 use massa_execution_exports::{ExecutionOutput, SlotExecutionOutput};
+use massa_ledger_exports::{SetOrKeep, SetUpdateOrDelete};
+use massa_models::address::Address;
+use massa_models::operation::OperationId;
+use massa_models::output_event::SCOutputEvent;
+use massa_models::prehash::PreHashSet;
 use massa_models::slot::Slot;
 use massa_proto_rs::massa::api::v1::{self as grpc_api, NewSlotExecutionOutputsRequest};
 use massa_proto_rs::massa::model::v1::{self as grpc_model};
 use std::collections::HashSet;
 use std::io::ErrorKind;
 use std::pin::Pin;
+use std::str::FromStr;
 use tokio::select;
 use tonic::{Request, Streaming};
 use tracing::{error, warn};
@@ -26,7 +33,6 @@ pub type NewSlotExecutionOutputsStreamType = Pin<
     >,
 >;
 
-//TODO implement remaining sub filters
 // Type declaration for NewSlotExecutionOutputsFilter
 #[derive(Clone, Debug, Default)]
 struct Filter {
@@ -50,6 +56,16 @@ struct Filter {
 struct AsyncPoolChangesFilter {
     // Do not return any message
     none: Option<()>,
+    // Only return the changes of these types (see grpc_model::AsyncPoolChangeType)
+    change_types: Option<HashSet<i32>>,
+    // Only return the changes of messages calling one of these handlers
+    handlers: Option<HashSet<String>>,
+    // Only return the changes of messages emitted by one of these addresses
+    sender_addresses: Option<PreHashSet<Address>>,
+    // Only return the changes of messages sent to one of these addresses
+    destination_addresses: Option<PreHashSet<Address>>,
+    // Only return the changes of messages that can (or cannot) be executed
+    can_be_executed: Option<bool>,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -62,18 +78,30 @@ struct ExecutedDenounciationFilter {
 struct ExecutionEventFilter {
     // Do not return any message
     none: Option<()>,
+    // Only return the events originally called by one of these addresses
+    caller_addresses: Option<PreHashSet<Address>>,
+    // Only return the events emitted by one of these addresses
+    emitter_addresses: Option<PreHashSet<Address>>,
+    // Only return the events generated by one of these operations
+    original_operation_ids: Option<PreHashSet<OperationId>>,
+    // Only return the events coming from a failed (or succeeded) execution
+    is_failure: Option<bool>,
 }
 
 #[derive(Clone, Debug, Default)]
 struct ExecutedOpsChangesFilter {
     // Do not return any message
     none: Option<()>,
+    // Only return the changes of these operations
+    operation_ids: Option<PreHashSet<OperationId>>,
 }
 
 #[derive(Clone, Debug, Default)]
 struct LedgerChangesFilter {
     // Do not return any message
     none: Option<()>,
+    // Only return the changes of these addresses
+    addresses: Option<PreHashSet<Address>>,
 }
 
 /// Creates a new stream of new produced and received slot execution outputs
@@ -240,20 +268,42 @@ fn get_filter(
                 },
                 grpc_api::new_slot_execution_outputs_filter::Filter::AsyncPoolChangesFilter(filter) => {
                     if let Some(filter) = filter.filter {
+                        let async_pool_filter =
+                            async_pool_changes_filter.get_or_insert_with(Default::default);
                         match filter {
                             grpc_api::async_pool_changes_filter::Filter::None(_) => {
-                                async_pool_changes_filter = Some(AsyncPoolChangesFilter {
-                                    none: Some(()),
-                                });
-                            },
-                            _ => {
-                                async_pool_changes_filter = Some(AsyncPoolChangesFilter {
-                                none: None,
-                            })
+                                async_pool_filter.none = Some(());
+                            }
+                            grpc_api::async_pool_changes_filter::Filter::EmitterAddress(address) => {
+                                async_pool_filter
+                                    .sender_addresses
+                                    .get_or_insert_with(PreHashSet::default)
+                                    .insert(Address::from_str(&address)?);
+                            }
+                            grpc_api::async_pool_changes_filter::Filter::DestinationAddress(address) => {
+                                async_pool_filter
+                                    .destination_addresses
+                                    .get_or_insert_with(PreHashSet::default)
+                                    .insert(Address::from_str(&address)?);
+                            }
+                            grpc_api::async_pool_changes_filter::Filter::Type(change_type) => {
+                                async_pool_filter
+                                    .change_types
+                                    .get_or_insert_with(HashSet::new)
+                                    .insert(change_type);
+                            }
+                            grpc_api::async_pool_changes_filter::Filter::Handler(handler) => {
+                                async_pool_filter
+                                    .handlers
+                                    .get_or_insert_with(HashSet::new)
+                                    .insert(handler);
+                            }
+                            grpc_api::async_pool_changes_filter::Filter::CanBeExecuted(can_be_executed) => {
+                                async_pool_filter.can_be_executed = Some(can_be_executed);
+                            }
                         }
                     }
                 }
-            },
                 grpc_api::new_slot_execution_outputs_filter::Filter::ExecutedDenounciationFilter(filter) => {
                     if let Some(filter) = filter.filter {
                         match filter {
@@ -261,54 +311,76 @@ fn get_filter(
                                 executed_denounciation_filter = Some(ExecutedDenounciationFilter {
                                     none: Some(()),
                                 });
-                            },
+                            }
+                        }
                     }
-                }},
+                }
                 grpc_api::new_slot_execution_outputs_filter::Filter::EventFilter(filter) => {
                     if let Some(filter) = filter.filter {
+                        let event_filter =
+                            execution_event_filter.get_or_insert_with(Default::default);
                         match filter {
                             grpc_api::execution_event_filter::Filter::None(_) => {
-                                execution_event_filter = Some(ExecutionEventFilter {
-                                    none: Some(()),
-                                });
-                            },
-                            _ => {
-                                execution_event_filter = Some(ExecutionEventFilter {
-                                none: None,
-                            })
+                                event_filter.none = Some(());
+                            }
+                            grpc_api::execution_event_filter::Filter::CallerAddress(address) => {
+                                event_filter
+                                    .caller_addresses
+                                    .get_or_insert_with(PreHashSet::default)
+                                    .insert(Address::from_str(&address)?);
+                            }
+                            grpc_api::execution_event_filter::Filter::EmitterAddress(address) => {
+                                event_filter
+                                    .emitter_addresses
+                                    .get_or_insert_with(PreHashSet::default)
+                                    .insert(Address::from_str(&address)?);
+                            }
+                            grpc_api::execution_event_filter::Filter::OriginalOperationId(operation_id) => {
+                                event_filter
+                                    .original_operation_ids
+                                    .get_or_insert_with(PreHashSet::default)
+                                    .insert(OperationId::from_str(&operation_id)?);
+                            }
+                            grpc_api::execution_event_filter::Filter::IsFailure(is_failure) => {
+                                event_filter.is_failure = Some(is_failure);
+                            }
                         }
+                    }
                 }
-                }},
                 grpc_api::new_slot_execution_outputs_filter::Filter::ExecutedOpsChangesFilter(filter) => {
                     if let Some(filter) = filter.filter {
+                        let ops_filter =
+                            executed_ops_changes_filter.get_or_insert_with(Default::default);
                         match filter {
                             grpc_api::executed_ops_changes_filter::Filter::None(_) => {
-                                executed_ops_changes_filter = Some(ExecutedOpsChangesFilter {
-                                    none: Some(()),
-                                });
-                            },
-                            _ => {
-                                executed_ops_changes_filter = Some(ExecutedOpsChangesFilter {
-                                none: None,
-                            })
+                                ops_filter.none = Some(());
+                            }
+                            grpc_api::executed_ops_changes_filter::Filter::OperationId(operation_id) => {
+                                ops_filter
+                                    .operation_ids
+                                    .get_or_insert_with(PreHashSet::default)
+                                    .insert(OperationId::from_str(&operation_id)?);
+                            }
                         }
+                    }
                 }
-                }},
                 grpc_api::new_slot_execution_outputs_filter::Filter::LedgerChangesFilter(filter) => {
                     if let Some(filter) = filter.filter {
+                        let ledger_filter =
+                            ledger_changes_filter.get_or_insert_with(Default::default);
                         match filter {
                             grpc_api::ledger_changes_filter::Filter::None(_) => {
-                                ledger_changes_filter = Some(LedgerChangesFilter {
-                                    none: Some(()),
-                                });
-                            },
-                            _ => {
-                                ledger_changes_filter = Some(LedgerChangesFilter {
-                                none: None,
-                            })
+                                ledger_filter.none = Some(());
+                            }
+                            grpc_api::ledger_changes_filter::Filter::Address(address) => {
+                                ledger_filter
+                                    .addresses
+                                    .get_or_insert_with(PreHashSet::default)
+                                    .insert(Address::from_str(&address)?);
+                            }
                         }
+                    }
                 }
-                }},
             }
         }
     }
@@ -400,12 +472,25 @@ fn filter_map_exec_output(
     if let Some(execution_event_filter) = &filters.execution_event_filter {
         if execution_event_filter.none.is_some() {
             exec_output.events.clear();
+        } else {
+            exec_output
+                .events
+                .0
+                .retain(|event| is_event_matching(event, execution_event_filter));
         }
     }
 
     if let Some(async_pool_changes_filter) = &filters.async_pool_changes_filter {
         if async_pool_changes_filter.none.is_some() {
             exec_output.state_changes.async_pool_changes.0.clear();
+        } else {
+            exec_output
+                .state_changes
+                .async_pool_changes
+                .0
+                .retain(|_, change| {
+                    is_async_pool_change_matching(change, async_pool_changes_filter)
+                });
         }
     }
     if let Some(executed_denounciation_filter) = &filters.executed_denounciation_filter {
@@ -419,13 +504,135 @@ fn filter_map_exec_output(
     if let Some(executed_ops_changes_filter) = &filters.executed_ops_changes_filter {
         if executed_ops_changes_filter.none.is_some() {
             exec_output.state_changes.executed_ops_changes.clear();
+        } else if let Some(operation_ids) = &executed_ops_changes_filter.operation_ids {
+            exec_output
+                .state_changes
+                .executed_ops_changes
+                .retain(|operation_id, _| operation_ids.contains(operation_id));
         }
     }
     if let Some(ledger_changes_filter) = &filters.ledger_changes_filter {
         if ledger_changes_filter.none.is_some() {
             exec_output.state_changes.ledger_changes.0.clear();
+        } else if let Some(addresses) = &ledger_changes_filter.addresses {
+            exec_output
+                .state_changes
+                .ledger_changes
+                .0
+                .retain(|address, _| addresses.contains(address));
         }
     }
 
     Some(exec_output)
 }
+
+// Return if the event matches all the criteria of the filter
+fn is_event_matching(event: &SCOutputEvent, filter: &ExecutionEventFilter) -> bool {
+    if let Some(caller_addresses) = &filter.caller_addresses {
+        if !event
+            .context
+            .call_stack
+            .front()
+            .map_or(false, |caller| caller_addresses.contains(caller))
+        {
+            return false;
+        }
+    }
+    if let Some(emitter_addresses) = &filter.emitter_addresses {
+        if !event
+            .context
+            .call_stack
+            .back()
+            .map_or(false, |emitter| emitter_addresses.contains(emitter))
+        {
+            return false;
+        }
+    }
+    if let Some(original_operation_ids) = &filter.original_operation_ids {
+        if !event
+            .context
+            .origin_operation_id
+            .map_or(false, |operation_id| {
+                original_operation_ids.contains(&operation_id)
+            })
+        {
+            return false;
+        }
+    }
+    if let Some(is_failure) = filter.is_failure {
+        if event.context.is_error != is_failure {
+            return false;
+        }
+    }
+    true
+}
+
+// Return if the async pool change matches all the criteria of the filter.
+//
+// Updates that do not set a filtered field and deletions only carry the message id,
+// from which the sender, destination, handler and execution status cannot be resolved
+// (the message may already be gone from the pool). They are passed through so that
+// subscribers still see the updates and deletions of the messages they track.
+fn is_async_pool_change_matching(
+    change: &SetUpdateOrDelete<AsyncMessage, AsyncMessageUpdate>,
+    filter: &AsyncPoolChangesFilter,
+) -> bool {
+    fn field<T>(value: &SetOrKeep<T>) -> Option<&T> {
+        match value {
+            SetOrKeep::Set(value) => Some(value),
+            SetOrKeep::Keep => None,
+        }
+    }
+
+    let (change_type, sender, destination, handler, can_be_executed) = match change {
+        SetUpdateOrDelete::Set(message) => (
+            grpc_model::AsyncPoolChangeType::Set,
+            Some(&message.sender),
+            Some(&message.destination),
+            Some(&message.function),
+            Some(&message.can_be_executed),
+        ),
+        SetUpdateOrDelete::Update(update) => (
+            grpc_model::AsyncPoolChangeType::Update,
+            field(&update.sender),
+            field(&update.destination),
+            field(&update.function),
+            field(&update.can_be_executed),
+        ),
+        SetUpdateOrDelete::Delete => (
+            grpc_model::AsyncPoolChangeType::Delete,
+            None,
+            None,
+            None,
+            None,
+        ),
+    };
+    if let Some(change_types) = &filter.change_types {
+        if !change_types.contains(&(change_type as i32)) {
+            return false;
+        }
+    }
+    if let (Some(sender_addresses), Some(sender)) = (&filter.sender_addresses, sender) {
+        if !sender_addresses.contains(sender) {
+            return false;
+        }
+    }
+    if let (Some(destination_addresses), Some(destination)) =
+        (&filter.destination_addresses, destination)
+    {
+        if !destination_addresses.contains(destination) {
+            return false;
+        }
+    }
+    if let (Some(handlers), Some(handler)) = (&filter.handlers, handler) {
+        if !handlers.contains(handler) {
+            return false;
+        }
+    }
+    if let (Some(expected), Some(can_be_executed)) = (filter.can_be_executed, can_be_executed) {
+        if expected != *can_be_executed {
+            return false;
+        }
+    }
+    true
+}
@@ -0,0 +1,115 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use crate::health::HealthChecker;
+use crate::tests::mock::grpc_public_service;
+use massa_bootstrap::BootstrapStatus;
+use massa_consensus_exports::MockConsensusController;
+use massa_execution_exports::MockExecutionController;
+use massa_hash::Hash;
+use massa_models::block_id::BlockId;
+use massa_models::slot::Slot;
+use massa_models::stats::{ExecutionStats, NetworkStats};
+use massa_models::timeslots::get_latest_block_slot_at_timestamp;
+use massa_protocol_exports::MockProtocolController;
+use massa_time::MassaTime;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+fn health_checker(
+    final_cursor: Slot,
+    peer_count: u64,
+    best_parents_period: u64,
+    bootstrap_status: BootstrapStatus,
+) -> HealthChecker {
+    let addr: SocketAddr = "[::]:4040".parse().unwrap();
+    let grpc_config = grpc_public_service(&addr).grpc_config;
+
+    let mut exec_ctrl = MockExecutionController::new();
+    exec_ctrl
+        .expect_get_stats()
+        .returning(move || ExecutionStats {
+            time_window_start: MassaTime::now(),
+            time_window_end: MassaTime::now(),
+            final_block_count: 0,
+            final_executed_operations_count: 0,
+            active_cursor: final_cursor,
+            final_cursor,
+        });
+
+    let mut protocol_ctrl = MockProtocolController::new();
+    protocol_ctrl.expect_get_stats().returning(move || {
+        Ok((
+            NetworkStats {
+                in_connection_count: peer_count,
+                out_connection_count: 0,
+                known_peer_count: peer_count,
+                banned_peer_count: 0,
+                active_node_count: peer_count,
+            },
+            HashMap::new(),
+        ))
+    });
+
+    let mut consensus_ctrl = MockConsensusController::new();
+    consensus_ctrl.expect_get_best_parents().returning(move || {
+        vec![(
+            BlockId::generate_from_hash(Hash::compute_from("parent".as_bytes())),
+            best_parents_period,
+        )]
+    });
+
+    HealthChecker::new(
+        Box::new(consensus_ctrl),
+        Box::new(exec_ctrl),
+        Box::new(protocol_ctrl),
+        Arc::new(RwLock::new(bootstrap_status)),
+        grpc_config,
+    )
+}
+
+#[test]
+fn health_checks_thresholds() {
+    let addr: SocketAddr = "[::]:4040".parse().unwrap();
+    let config = grpc_public_service(&addr).grpc_config;
+    let now = config
+        .genesis_timestamp
+        .saturating_add(config.t0.saturating_mul(1000));
+    let current_slot = get_latest_block_slot_at_timestamp(
+        config.thread_count,
+        config.t0,
+        config.genesis_timestamp,
+        now,
+    )
+    .unwrap()
+    .unwrap();
+
+    // healthy node
+    let checker = health_checker(current_slot, 10, current_slot.period, BootstrapStatus::Done);
+    let report = checker.check(now);
+    assert!(report.is_healthy(), "{:?}", report);
+
+    // final cursor lagging, no peers, stalled blockclique and bootstrap in progress
+    let lagging_cursor = Slot::new(current_slot.period - 100, current_slot.thread);
+    let checker = health_checker(
+        lagging_cursor,
+        0,
+        current_slot.period - 100,
+        BootstrapStatus::InProgress,
+    );
+    let report = checker.check(now);
+    assert!(!report.is_healthy());
+    assert!(report.execution.is_err());
+    assert!(report.protocol.is_err());
+    assert!(report.bootstrap.is_err());
+    assert!(report.consensus.is_err());
+
+    // the bootstrap signal follows the status reported by the node
+    let checker = health_checker(current_slot, 10, current_slot.period, BootstrapStatus::Done);
+    assert!(checker.check(now).bootstrap.is_ok());
+    *checker.bootstrap_status.write() = BootstrapStatus::NeedResync;
+    let report = checker.check(now);
+    assert!(report.bootstrap.is_err());
+    assert!(report.execution.is_ok());
+}
@@ -3,6 +3,7 @@ use std::net::SocketAddr;
 
 use crate::config::{GrpcConfig, ServiceName};
 use crate::server::MassaPublicGrpc;
+use massa_bootstrap::BootstrapStatus;
 use massa_consensus_exports::{ConsensusBroadcasts, MockConsensusController};
 use massa_execution_exports::{ExecutionChannels, MockExecutionController};
 use massa_models::{
@@ -25,7 +26,10 @@ use massa_versioning::versioning::{MipStatsConfig, MipStore};
 // use massa_wallet::test_exports::create_test_wallet;
 use massa_models::config::CHAINID;
 use num::rational::Ratio;
+use parking_lot::RwLock;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// generate a grpc public service
 /// # Arguments
@@ -49,7 +53,11 @@ pub(crate) fn grpc_public_service(addr: &SocketAddr) -> MassaPublicGrpc {
         enabled: true,
         accept_http1: true,
         enable_cors: true,
-        enable_health: true,
+        enable_health: false,
+        health_check_interval: Duration::from_millis(500),
+        health_max_final_cursor_lag: 256,
+        health_min_peer_count: 1,
+        health_max_blockclique_stall: 16,
         enable_reflection: true,
         enable_tls: false,
         enable_mtls: false,
@@ -144,5 +152,6 @@ pub(crate) fn grpc_public_service(addr: &SocketAddr) -> MassaPublicGrpc {
         keypair_factory: KeyPairFactory {
             mip_store: mip_store.clone(),
         },
+        bootstrap_status: Arc::new(RwLock::new(BootstrapStatus::Done)),
     }
 }
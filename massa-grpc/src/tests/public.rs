@@ -4,6 +4,7 @@ use crate::tests::mock::grpc_public_service;
 use massa_consensus_exports::MockConsensusController;
 use massa_execution_exports::{EventStore, MockExecutionController};
 use massa_models::address::Address;
+use massa_models::amount::Amount;
 use massa_models::block::BlockGraphStatus;
 use massa_models::block_id::BlockId;
 use massa_models::config::VERSION;
@@ -357,7 +358,9 @@ async fn execute_read_only_call() {
                     events: EventStore::default(),
                 },
                 gas_cost: 100,
+                storage_cost: Amount::zero(),
                 call_result: "toto".as_bytes().to_vec(),
+                trace: None,
             })
         });
 
@@ -4,7 +4,10 @@ use crate::tests::mock::grpc_public_service;
 use massa_consensus_exports::MockConsensusController;
 use massa_execution_exports::{ExecutionOutput, MockExecutionController, SlotExecutionOutput};
 use massa_models::{
-    address::Address, block::FilledBlock, secure_share::SecureShareSerializer, slot::Slot,
+    address::Address,
+    block::FilledBlock,
+    secure_share::{Id, SecureShareSerializer},
+    slot::Slot,
     stats::ExecutionStats,
 };
 use massa_pool_exports::MockPoolController;
@@ -26,7 +29,7 @@ use massa_protocol_exports::{
 use massa_serialization::Serializer;
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
-use std::{net::SocketAddr, ops::Add, time::Duration};
+use std::{net::SocketAddr, ops::Add, str::FromStr, time::Duration};
 use tokio_stream::StreamExt;
 
 #[tokio::test]
@@ -1188,37 +1191,200 @@ async fn new_slot_execution_outputs() {
     // start slot is after block slot
     assert!(result.is_err());
 
-    // TODO add test when filter is updated
+    stop_handle.stop();
+}
 
-    /*     filter = massa_proto_rs::massa::api::v1::NewSlotExecutionOutputsFilter {
-        filter: Some(
-            massa_proto_rs::massa::api::v1::new_slot_execution_outputs_filter::Filter::EventFilter(
-                massa_proto_rs::massa::api::v1::ExecutionEventFilter {
-                    filter: Some(
-                        massa_proto_rs::massa::api::v1::execution_event_filter::Filter::OriginalOperationId( "O1q4CBcuYo8YANEV34W4JRWVHrzcYns19VJfyAB7jT4qfitAnMC"
-                                    .to_string()
-                        ),
-                    ),
-                },
-            ),
+#[tokio::test]
+async fn new_slot_execution_outputs_sub_filters() {
+    let addr: SocketAddr = "[::]:4026".parse().unwrap();
+    let mut public_server = grpc_public_service(&addr);
+    let config = public_server.grpc_config.clone();
+
+    let (slot_tx, _slot_rx) = tokio::sync::broadcast::channel(10);
+
+    public_server
+        .execution_channels
+        .slot_execution_output_sender = slot_tx.clone();
+
+    let stop_handle = public_server.serve(&config).await.unwrap();
+
+    let address = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+    let other_address = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+    let op_id = massa_models::operation::OperationId::from_str(
+        "O1q4CBcuYo8YANEV34W4JRWVHrzcYns19VJfyAB7jT4qfitAnMC",
+    )
+    .unwrap();
+    let other_op_id = massa_models::operation::OperationId::new(massa_hash::Hash::compute_from(
+        "other".as_bytes(),
+    ));
+
+    let slot = Slot::new(1, 5);
+    let event = |emitter: Address| massa_models::output_event::SCOutputEvent {
+        context: massa_models::output_event::EventExecutionContext {
+            slot,
+            block: None,
+            read_only: false,
+            index_in_slot: 0,
+            call_stack: vec![emitter].into(),
+            origin_operation_id: None,
+            is_final: false,
+            is_error: false,
+        },
+        data: emitter.to_string(),
+    };
+    let mut state_changes = massa_final_state::StateChanges::default();
+    for addr in [address, other_address] {
+        state_changes.ledger_changes.0.insert(
+            addr,
+            massa_ledger_exports::SetUpdateOrDelete::Set(Default::default()),
+        );
+    }
+    for id in [op_id, other_op_id] {
+        state_changes.executed_ops_changes.insert(id, (true, slot));
+    }
+    // messages sent by each address, plus an update and a deletion that only carry the message id
+    let message = |index: u64, sender: Address| {
+        massa_async_pool::AsyncMessage::new(
+            slot,
+            index,
+            sender,
+            other_address,
+            "handler".to_string(),
+            1000,
+            massa_models::amount::Amount::zero(),
+            massa_models::amount::Amount::zero(),
+            slot,
+            Slot::new(10, 5),
+            Vec::new(),
+            None,
+            None,
+        )
+    };
+    for (index, sender) in [address, other_address].into_iter().enumerate() {
+        let message = message(index as u64, sender);
+        state_changes.async_pool_changes.0.insert(
+            message.compute_id(),
+            massa_ledger_exports::SetUpdateOrDelete::Set(message),
+        );
+    }
+    state_changes.async_pool_changes.0.insert(
+        message(2, other_address).compute_id(),
+        massa_ledger_exports::SetUpdateOrDelete::Update(Default::default()),
+    );
+    state_changes.async_pool_changes.0.insert(
+        message(3, other_address).compute_id(),
+        massa_ledger_exports::SetUpdateOrDelete::Delete,
+    );
+    let exec_output = ExecutionOutput {
+        slot,
+        block_info: None,
+        state_changes,
+        events: massa_execution_exports::EventStore(
+            vec![event(address), event(other_address)].into(),
         ),
     };
 
+    let (tx_request, rx) = tokio::sync::mpsc::channel(10);
+    let request_stream = tokio_stream::wrappers::ReceiverStream::new(rx);
+
+    let mut public_client = PublicServiceClient::connect(format!(
+        "grpc://localhost:{}",
+        addr.to_string().split(':').last().unwrap()
+    ))
+    .await
+    .unwrap();
+
+    let mut resp_stream = public_client
+        .new_slot_execution_outputs(request_stream)
+        .await
+        .unwrap()
+        .into_inner();
+
+    let filters = vec![
+        massa_proto_rs::massa::api::v1::NewSlotExecutionOutputsFilter {
+            filter: Some(
+                massa_proto_rs::massa::api::v1::new_slot_execution_outputs_filter::Filter::EventFilter(
+                    massa_proto_rs::massa::api::v1::ExecutionEventFilter {
+                        filter: Some(
+                            massa_proto_rs::massa::api::v1::execution_event_filter::Filter::EmitterAddress(
+                                address.to_string(),
+                            ),
+                        ),
+                    },
+                ),
+            ),
+        },
+        massa_proto_rs::massa::api::v1::NewSlotExecutionOutputsFilter {
+            filter: Some(
+                massa_proto_rs::massa::api::v1::new_slot_execution_outputs_filter::Filter::LedgerChangesFilter(
+                    massa_proto_rs::massa::api::v1::LedgerChangesFilter {
+                        filter: Some(
+                            massa_proto_rs::massa::api::v1::ledger_changes_filter::Filter::Address(
+                                address.to_string(),
+                            ),
+                        ),
+                    },
+                ),
+            ),
+        },
+        massa_proto_rs::massa::api::v1::NewSlotExecutionOutputsFilter {
+            filter: Some(
+                massa_proto_rs::massa::api::v1::new_slot_execution_outputs_filter::Filter::AsyncPoolChangesFilter(
+                    massa_proto_rs::massa::api::v1::AsyncPoolChangesFilter {
+                        filter: Some(
+                            massa_proto_rs::massa::api::v1::async_pool_changes_filter::Filter::EmitterAddress(
+                                address.to_string(),
+                            ),
+                        ),
+                    },
+                ),
+            ),
+        },
+        massa_proto_rs::massa::api::v1::NewSlotExecutionOutputsFilter {
+            filter: Some(
+                massa_proto_rs::massa::api::v1::new_slot_execution_outputs_filter::Filter::ExecutedOpsChangesFilter(
+                    massa_proto_rs::massa::api::v1::ExecutedOpsChangesFilter {
+                        filter: Some(
+                            massa_proto_rs::massa::api::v1::executed_ops_changes_filter::Filter::OperationId(
+                                op_id.to_string(),
+                            ),
+                        ),
+                    },
+                ),
+            ),
+        },
+    ];
+
     tx_request
-        .send(NewSlotExecutionOutputsRequest {
-            filters: vec![filter],
-        })
+        .send(NewSlotExecutionOutputsRequest { filters })
         .await
         .unwrap();
     tokio::time::sleep(Duration::from_millis(50)).await;
 
     slot_tx
-        .send(SlotExecutionOutput::ExecutedSlot(exec_output_1.clone()))
+        .send(SlotExecutionOutput::ExecutedSlot(exec_output))
         .unwrap();
 
-    let result = tokio::time::timeout(Duration::from_secs(2), resp_stream.next()).await;
-    dbg!(&result);
-    assert!(result.is_err()); */
+    let result = tokio::time::timeout(Duration::from_secs(5), resp_stream.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+
+    let exec_output = result.output.unwrap().execution_output.unwrap();
+    assert_eq!(exec_output.events.len(), 1);
+    assert_eq!(exec_output.events[0].data, address.to_string().into_bytes());
+    let state_changes = exec_output.state_changes.unwrap();
+    assert_eq!(state_changes.ledger_changes.len(), 1);
+    assert_eq!(state_changes.ledger_changes[0].address, address.to_string());
+    assert_eq!(state_changes.executed_ops_changes.len(), 1);
+    assert_eq!(
+        state_changes.executed_ops_changes[0].operation_id,
+        op_id.to_string()
+    );
+    // the message of the other sender is filtered out,
+    // the update and the deletion cannot be attributed and are passed through
+    assert_eq!(state_changes.async_pool_changes.len(), 3);
 
     stop_handle.stop();
 }
@@ -1,4 +1,6 @@
-use massa_models::{address::Address, amount::Amount, bytecode::Bytecode};
+use massa_models::{
+    address::Address, amount::Amount, bytecode::Bytecode, datastore::DatastoreRange,
+};
 use std::collections::BTreeSet;
 
 use crate::{LedgerChanges, LedgerError};
@@ -46,6 +48,16 @@ pub trait LedgerController: Send + Sync {
     /// A `BTreeSet` of the datastore keys
     fn get_datastore_keys(&self, addr: &Address, prefix: &[u8]) -> Option<BTreeSet<Vec<u8>>>;
 
+    /// Get a range of datastore entries for a given address, in scan order.
+    ///
+    /// # Returns
+    /// The `(key, value)` pairs of the range, or `None` if the ledger entry was not found
+    fn get_datastore_range(
+        &self,
+        addr: &Address,
+        range: &DatastoreRange,
+    ) -> Option<Vec<(Vec<u8>, Vec<u8>)>>;
+
     /// Reset the ledger
     ///
     /// USED FOR BOOTSTRAP ONLY
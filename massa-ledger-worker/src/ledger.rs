@@ -11,6 +11,7 @@ use massa_models::{
     address::Address,
     amount::{Amount, AmountDeserializer},
     bytecode::{Bytecode, BytecodeDeserializer},
+    datastore::DatastoreRange,
 };
 use massa_serialization::{DeserializeError, Deserializer};
 use std::collections::{BTreeSet, HashMap};
@@ -142,6 +143,18 @@ impl LedgerController for FinalLedger {
         self.sorted_ledger.get_datastore_keys(addr, prefix)
     }
 
+    /// Get a range of datastore entries for a given address, in scan order.
+    ///
+    /// # Returns
+    /// The `(key, value)` pairs of the range, or `None` if the ledger entry was not found
+    fn get_datastore_range(
+        &self,
+        addr: &Address,
+        range: &DatastoreRange,
+    ) -> Option<Vec<(Vec<u8>, Vec<u8>)>> {
+        self.sorted_ledger.get_datastore_range(addr, range)
+    }
+
     /// Reset the disk ledger.
     ///
     /// USED FOR BOOTSTRAP ONLY
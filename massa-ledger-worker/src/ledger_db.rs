@@ -9,7 +9,7 @@ use massa_db_exports::{
 use massa_ledger_exports::*;
 use massa_models::amount::AmountDeserializer;
 use massa_models::bytecode::BytecodeDeserializer;
-use massa_models::datastore::get_prefix_bounds;
+use massa_models::datastore::{get_prefix_bounds, DatastoreRange, DatastoreScanDirection};
 use massa_models::{
     address::Address, amount::AmountSerializer, bytecode::BytecodeSerializer, slot::Slot,
 };
@@ -210,6 +210,83 @@ impl LedgerDB {
         )
     }
 
+    /// Get a range of datastore entries for a given address, in scan order.
+    ///
+    /// # Returns
+    /// The `(key, value)` pairs of the range, or `None` if the address does not exist
+    pub fn get_datastore_range(
+        &self,
+        addr: &Address,
+        range: &DatastoreRange,
+    ) -> Option<Vec<(Vec<u8>, Vec<u8>)>> {
+        let db = self.db.read();
+
+        // check if address exists, return None if it does not
+        {
+            let key = LedgerSubEntry::Balance.derive_key(addr);
+            let mut serialized_key = Vec::new();
+            self.key_serializer_db
+                .serialize(&key, &mut serialized_key)
+                .expect(KEY_SER_ERROR);
+            db.get_cf(STATE_CF, serialized_key).expect(CRUD_ERROR)?;
+        }
+
+        // seek the bound the scan starts from
+        let address_prefix = datastore_prefix_from_address(addr, &[]);
+        let seek_key = match range.direction {
+            DatastoreScanDirection::Forward => match &range.start {
+                Bound::Included(key) | Bound::Excluded(key) => {
+                    Some(datastore_prefix_from_address(addr, key))
+                }
+                Bound::Unbounded => Some(address_prefix.clone()),
+            },
+            DatastoreScanDirection::Reverse => match &range.end {
+                Bound::Included(key) | Bound::Excluded(key) => {
+                    Some(datastore_prefix_from_address(addr, key))
+                }
+                Bound::Unbounded => end_prefix(&address_prefix),
+            },
+        };
+        let mode = match (&seek_key, range.direction) {
+            (Some(key), DatastoreScanDirection::Forward) => {
+                MassaIteratorMode::From(key, MassaDirection::Forward)
+            }
+            (Some(key), DatastoreScanDirection::Reverse) => {
+                MassaIteratorMode::From(key, MassaDirection::Reverse)
+            }
+            (None, _) => MassaIteratorMode::End,
+        };
+
+        // bound to reach before collecting entries, and bound to stay within
+        type BoundCheck = fn(&DatastoreRange, &[u8]) -> bool;
+        let (first_bound, last_bound): (BoundCheck, BoundCheck) = match range.direction {
+            DatastoreScanDirection::Forward => (
+                DatastoreRange::is_after_start,
+                DatastoreRange::is_before_end,
+            ),
+            DatastoreScanDirection::Reverse => (
+                DatastoreRange::is_before_end,
+                DatastoreRange::is_after_start,
+            ),
+        };
+
+        Some(
+            db.iterator_cf(STATE_CF, mode)
+                // a reverse scan may start after the keys of the address
+                .skip_while(|(key, _)| {
+                    range.direction == DatastoreScanDirection::Reverse
+                        && !key.starts_with(&address_prefix)
+                        && key.as_slice() > address_prefix.as_slice()
+                })
+                .take_while(|(key, _)| key.starts_with(&address_prefix))
+                .map(|(key, value)| (key[address_prefix.len()..].to_vec(), value))
+                .skip_while(|(key, _)| !first_bound(range, key))
+                .take_while(|(key, _)| last_bound(range, key))
+                .take(usize::try_from(range.limit).unwrap_or(usize::MAX))
+                .collect(),
+        )
+    }
+
     pub fn reset(&self) {
         self.db.write().delete_prefix(LEDGER_PREFIX, STATE_CF, None);
     }
@@ -674,6 +751,54 @@ mod tests {
         assert!(ledger_db.get_entire_datastore(&addr).is_empty());
     }
 
+    #[test]
+    fn test_datastore_range() {
+        let addr = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let (ledger_db, _data) = init_test_ledger(addr);
+
+        let range = DatastoreRange {
+            start: Bound::Excluded(b"1".to_vec()),
+            end: Bound::Unbounded,
+            limit: 10,
+            direction: DatastoreScanDirection::Forward,
+        };
+        assert_eq!(
+            ledger_db.get_datastore_range(&addr, &range).unwrap(),
+            vec![
+                (b"2".to_vec(), b"b".to_vec()),
+                (b"3".to_vec(), b"c".to_vec())
+            ]
+        );
+
+        let range = DatastoreRange {
+            start: Bound::Unbounded,
+            end: Bound::Excluded(b"3".to_vec()),
+            limit: 1,
+            direction: DatastoreScanDirection::Reverse,
+        };
+        assert_eq!(
+            ledger_db.get_datastore_range(&addr, &range).unwrap(),
+            vec![(b"2".to_vec(), b"b".to_vec())]
+        );
+
+        let range = DatastoreRange {
+            end: Bound::Unbounded,
+            limit: 10,
+            ..range
+        };
+        assert_eq!(
+            ledger_db.get_datastore_range(&addr, &range).unwrap(),
+            vec![
+                (b"3".to_vec(), b"c".to_vec()),
+                (b"2".to_vec(), b"b".to_vec()),
+                (b"1".to_vec(), b"a".to_vec())
+            ]
+        );
+
+        let unknown = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        assert!(ledger_db.get_datastore_range(&unknown, &range).is_none());
+    }
+
     #[test]
     fn test_end_prefix() {
         assert_eq!(end_prefix(&[5, 6, 7]), Some(vec![5, 6, 8]));
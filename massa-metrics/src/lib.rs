@@ -90,9 +90,15 @@ pub struct MassaMetrics {
 
     /// number of elements in the active_history of execution
     active_history: IntGauge,
+    /// number of read-only execution requests waiting for an executor
+    readonly_queue_depth: IntGauge,
+    /// number of read-only execution requests whose caller stopped waiting before they were executed
+    readonly_timeouts: IntCounter,
 
     /// number of operations in the operation pool
     operations_pool: IntGauge,
+    /// number of operations rejected or evicted from the operation pool because their sender exceeded one of its limits
+    operations_pool_quota_rejected: IntCounter,
     /// number of endorsements in the endorsement pool
     endorsements_pool: IntGauge,
     /// number of elements in the denunciation pool
@@ -239,6 +245,11 @@ impl MassaMetrics {
             "number of operations in the operation pool",
         )
         .unwrap();
+        let operations_pool_quota_rejected = IntCounter::new(
+            "operations_pool_quota_rejected",
+            "number of operations rejected by the operation pool because their sender exceeded one of its limits",
+        )
+        .unwrap();
         let endorsements_pool = IntGauge::new(
             "endorsements_pool",
             "number of endorsements in the endorsement pool",
@@ -283,6 +294,16 @@ impl MassaMetrics {
             "number of elements in the active_history of execution",
         )
         .unwrap();
+        let readonly_queue_depth = IntGauge::new(
+            "readonly_queue_depth",
+            "number of read-only execution requests waiting for an executor",
+        )
+        .unwrap();
+        let readonly_timeouts = IntCounter::new(
+            "readonly_timeouts",
+            "number of read-only execution requests whose caller stopped waiting before they were executed",
+        )
+        .unwrap();
 
         let know_peers =
             IntGauge::new("protocol_known_peers", "number of known peers in protocol").unwrap();
@@ -444,11 +465,14 @@ impl MassaMetrics {
                 let _ = prometheus::register(Box::new(executed_final_slot.clone()));
                 let _ = prometheus::register(Box::new(executed_final_slot_with_block.clone()));
                 let _ = prometheus::register(Box::new(active_history.clone()));
+                let _ = prometheus::register(Box::new(readonly_queue_depth.clone()));
+                let _ = prometheus::register(Box::new(readonly_timeouts.clone()));
                 let _ = prometheus::register(Box::new(bootstrap_counter.clone()));
                 let _ = prometheus::register(Box::new(bootstrap_success.clone()));
                 let _ = prometheus::register(Box::new(bootstrap_failed.clone()));
                 let _ = prometheus::register(Box::new(process_available_processors.clone()));
                 let _ = prometheus::register(Box::new(operations_pool.clone()));
+                let _ = prometheus::register(Box::new(operations_pool_quota_rejected.clone()));
                 let _ = prometheus::register(Box::new(endorsements_pool.clone()));
                 let _ = prometheus::register(Box::new(denunciations_pool.clone()));
                 let _ = prometheus::register(Box::new(protocol_tester_success.clone()));
@@ -473,7 +497,10 @@ impl MassaMetrics {
                 current_time_thread,
                 current_time_period,
                 active_history,
+                readonly_queue_depth,
+                readonly_timeouts,
                 operations_pool,
+                operations_pool_quota_rejected,
                 endorsements_pool,
                 denunciations_pool,
                 async_message_pool_size,
@@ -638,6 +665,14 @@ impl MassaMetrics {
         self.active_history.set(nb as i64);
     }
 
+    pub fn set_readonly_queue_depth(&self, nb: usize) {
+        self.readonly_queue_depth.set(nb as i64);
+    }
+
+    pub fn inc_readonly_timeouts(&self) {
+        self.readonly_timeouts.inc();
+    }
+
     pub fn inc_bootstrap_counter(&self) {
         self.bootstrap_counter.inc();
     }
@@ -654,6 +689,11 @@ impl MassaMetrics {
         self.operations_pool.set(nb as i64);
     }
 
+    pub fn set_operations_pool_quota_rejected(&self, total: u64) {
+        let diff = total.saturating_sub(self.operations_pool_quota_rejected.get());
+        self.operations_pool_quota_rejected.inc_by(diff);
+    }
+
     pub fn set_endorsements_pool(&self, nb: usize) {
         self.endorsements_pool.set(nb as i64);
     }
@@ -8,6 +8,7 @@ use nom::error::{context, ContextError, ParseError};
 use nom::multi::length_count;
 use nom::sequence::tuple;
 use nom::{IResult, Parser};
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::ops::Bound::Included;
 
@@ -169,6 +170,64 @@ pub fn get_prefix_bounds(prefix: &[u8]) -> (std::ops::Bound<Vec<u8>>, std::ops::
     )
 }
 
+/// Order in which the keys of a datastore are scanned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DatastoreScanDirection {
+    /// increasing keys
+    #[default]
+    Forward,
+    /// decreasing keys
+    Reverse,
+}
+
+/// Range of datastore keys to scan, with a maximum number of entries
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatastoreRange {
+    /// lower bound of the scanned keys
+    pub start: std::ops::Bound<Vec<u8>>,
+    /// upper bound of the scanned keys
+    pub end: std::ops::Bound<Vec<u8>>,
+    /// maximum number of entries to return
+    pub limit: u64,
+    /// order in which the keys are scanned
+    pub direction: DatastoreScanDirection,
+}
+
+impl DatastoreRange {
+    /// Whether a key satisfies the lower bound of the range
+    pub fn is_after_start(&self, key: &[u8]) -> bool {
+        match &self.start {
+            std::ops::Bound::Included(start) => key >= start.as_slice(),
+            std::ops::Bound::Excluded(start) => key > start.as_slice(),
+            std::ops::Bound::Unbounded => true,
+        }
+    }
+
+    /// Whether a key satisfies the upper bound of the range
+    pub fn is_before_end(&self, key: &[u8]) -> bool {
+        match &self.end {
+            std::ops::Bound::Included(end) => key <= end.as_slice(),
+            std::ops::Bound::Excluded(end) => key < end.as_slice(),
+            std::ops::Bound::Unbounded => true,
+        }
+    }
+
+    /// Whether a key is in the range
+    pub fn contains(&self, key: &[u8]) -> bool {
+        self.is_after_start(key) && self.is_before_end(key)
+    }
+
+    /// Select the entries of the range, in scan order, up to the limit
+    pub fn select<V>(&self, entries: BTreeMap<Vec<u8>, V>) -> Vec<(Vec<u8>, V)> {
+        let limit = usize::try_from(self.limit).unwrap_or(usize::MAX);
+        let in_range = entries.into_iter().filter(|(key, _)| self.contains(key));
+        match self.direction {
+            DatastoreScanDirection::Forward => in_range.take(limit).collect(),
+            DatastoreScanDirection::Reverse => in_range.rev().take(limit).collect(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -240,6 +299,39 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn test_datastore_range_select() {
+        let datastore: Datastore = (1u8..=5).map(|i| (vec![i], vec![i * 10])).collect();
+
+        let range = DatastoreRange {
+            start: std::ops::Bound::Excluded(vec![1]),
+            end: std::ops::Bound::Included(vec![4]),
+            limit: 2,
+            direction: DatastoreScanDirection::Forward,
+        };
+        assert_eq!(
+            range.select(datastore.clone()),
+            vec![(vec![2], vec![20]), (vec![3], vec![30])]
+        );
+
+        let range = DatastoreRange {
+            direction: DatastoreScanDirection::Reverse,
+            ..range
+        };
+        assert_eq!(
+            range.select(datastore.clone()),
+            vec![(vec![4], vec![40]), (vec![3], vec![30])]
+        );
+
+        let range = DatastoreRange {
+            start: std::ops::Bound::Unbounded,
+            end: std::ops::Bound::Excluded(vec![2]),
+            limit: 10,
+            direction: DatastoreScanDirection::Forward,
+        };
+        assert_eq!(range.select(datastore), vec![(vec![1], vec![10])]);
+    }
+
     #[test]
     fn test_datastore_serde() {
         let expected_datastore: Datastore = BTreeMap::from([
@@ -1,7 +1,96 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use crate::{address::Address, operation::OperationId, slot::Slot};
+use crate::{
+    address::Address,
+    amount::Amount,
+    block_id::BlockId,
+    bytecode::Bytecode,
+    error::ModelsError,
+    operation::OperationId,
+    output_event::SCOutputEvent,
+    slot::{Slot, SLOT_KEY_SIZE},
+};
 use serde::{Deserialize, Serialize};
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+use std::str::FromStr;
+
+/// Size in bytes of an event cursor: slot key followed by the index in slot
+const EVENT_CURSOR_SIZE_BYTES: usize = SLOT_KEY_SIZE + 8;
+
+/// Position of an event in the ordered list of events, used to resume a paginated query.
+///
+/// Events are ordered by slot then by index in slot, so the cursor of the last event of a page
+/// is enough to get the next page even while new events are emitted.
+/// It is displayed as an opaque base58check string.
+#[derive(
+    Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, SerializeDisplay, DeserializeFromStr,
+)]
+pub struct EventCursor {
+    /// slot of the event
+    pub slot: Slot,
+    /// index of the event in its slot
+    pub index_in_slot: u64,
+}
+
+impl EventCursor {
+    /// Get the cursor pointing to an event
+    pub fn from_event(event: &SCOutputEvent) -> Self {
+        EventCursor {
+            slot: event.context.slot,
+            index_in_slot: event.context.index_in_slot,
+        }
+    }
+}
+
+impl std::fmt::Display for EventCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut bytes = Vec::with_capacity(EVENT_CURSOR_SIZE_BYTES);
+        bytes.extend(self.slot.to_bytes_key());
+        bytes.extend(self.index_in_slot.to_be_bytes());
+        write!(f, "{}", bs58::encode(bytes).with_check().into_string())
+    }
+}
+
+impl FromStr for EventCursor {
+    type Err = ModelsError;
+    /// ## Example
+    /// ```rust
+    /// # use massa_models::execution::EventCursor;
+    /// # use massa_models::slot::Slot;
+    /// # use std::str::FromStr;
+    /// let cursor = EventCursor {
+    ///     slot: Slot::new(10, 3),
+    ///     index_in_slot: 4,
+    /// };
+    /// let parsed = EventCursor::from_str(&cursor.to_string()).unwrap();
+    /// assert_eq!(cursor, parsed);
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = bs58::decode(s)
+            .with_check(None)
+            .into_vec()
+            .map_err(|err| ModelsError::DeserializeError(err.to_string()))?;
+        if bytes.len() != EVENT_CURSOR_SIZE_BYTES {
+            return Err(ModelsError::DeserializeError(format!(
+                "invalid event cursor length: {}",
+                bytes.len()
+            )));
+        }
+        let (slot_bytes, index_bytes) = bytes.split_at(SLOT_KEY_SIZE);
+        Ok(EventCursor {
+            slot: Slot::from_bytes_key(
+                slot_bytes
+                    .try_into()
+                    .map_err(|_| ModelsError::DeserializeError("invalid slot".to_string()))?,
+            ),
+            index_in_slot: u64::from_be_bytes(
+                index_bytes
+                    .try_into()
+                    .map_err(|_| ModelsError::DeserializeError("invalid index".to_string()))?,
+            ),
+        })
+    }
+}
 
 /// filter used when retrieving SC output events
 #[derive(Default, Debug, Deserialize, Clone, Serialize)]
@@ -28,4 +117,179 @@ pub struct EventFilter {
     /// Some(false) means events coming from a succeeded sc execution
     /// None means both
     pub is_error: Option<bool>,
+    /// optional cursor: only the events strictly after it are returned
+    pub cursor: Option<EventCursor>,
+    /// optional maximum number of events to return
+    pub limit: Option<u64>,
+}
+
+impl EventFilter {
+    /// Get the cursor to resume from after a page of events returned for this filter.
+    ///
+    /// It points to the last event of the page, or is the cursor of the filter if the page is empty,
+    /// so that new events can be polled from the tip.
+    /// Returns `None` only if the page is empty and the filter has no cursor.
+    pub fn get_next_cursor(&self, events: &[SCOutputEvent]) -> Option<EventCursor> {
+        events.last().map(EventCursor::from_event).or(self.cursor)
+    }
+}
+
+/// Role of an address in an operation
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum AddressOperationRole {
+    /// the address created and sent the operation
+    Sender,
+    /// the address is the recipient of a transaction or the target of a smart contract call
+    Recipient,
+}
+
+/// Final operation involving an address, as recorded in the operation history index
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct AddressOperation {
+    /// operation id
+    pub operation_id: OperationId,
+    /// slot of the block in which the operation was included
+    pub slot: Slot,
+    /// block in which the operation was included
+    pub block_id: BlockId,
+    /// role of the address in the operation
+    pub role: AddressOperationRole,
+    /// whether the execution of the operation succeeded
+    pub success: bool,
+}
+
+/// Overrides of the state of an address, only visible to the read-only execution they are passed to
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct StateOverride {
+    /// balance used instead of the actual balance of the address
+    pub balance: Option<Amount>,
+    /// bytecode used instead of the actual bytecode of the address
+    pub bytecode: Option<Bytecode>,
+    /// `(key, value)` entries set in the datastore of the address
+    #[serde(default)]
+    pub datastore: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+/// Call frame recorded by the execution tracer
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct ExecutionTraceFrame {
+    /// address whose bytecode is executed in this frame
+    pub address: Address,
+    /// coins transferred to the address at the beginning of the call
+    pub coins: Amount,
+    /// gas available when the call started, if known
+    pub gas_available: Option<u64>,
+    /// gas consumed by the call, if the gas remaining at its end is known
+    pub gas_used: Option<u64>,
+    /// steps of the call, in execution order
+    pub steps: Vec<ExecutionTraceStep>,
+}
+
+/// Step recorded by the execution tracer
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum ExecutionTraceStep {
+    /// call to another smart contract
+    Call(ExecutionTraceFrame),
+    /// read of a datastore entry, with the value found if any
+    DatastoreRead {
+        /// address owning the datastore
+        address: Address,
+        /// datastore key
+        key: Vec<u8>,
+        /// value of the entry, `None` if it does not exist
+        value: Option<Vec<u8>>,
+    },
+    /// write of a datastore entry
+    DatastoreWrite {
+        /// address owning the datastore
+        address: Address,
+        /// datastore key
+        key: Vec<u8>,
+        /// value written
+        value: Vec<u8>,
+    },
+    /// data appended to a datastore entry
+    DatastoreAppend {
+        /// address owning the datastore
+        address: Address,
+        /// datastore key
+        key: Vec<u8>,
+        /// value appended
+        value: Vec<u8>,
+    },
+    /// deletion of a datastore entry
+    DatastoreDelete {
+        /// address owning the datastore
+        address: Address,
+        /// datastore key
+        key: Vec<u8>,
+    },
+    /// transfer of coins between two addresses
+    Transfer {
+        /// spending address
+        from: Address,
+        /// credited address
+        to: Address,
+        /// amount of coins transferred
+        amount: Amount,
+    },
+    /// emitted event
+    Event {
+        /// payload of the event
+        data: String,
+    },
+    /// asynchronous message sent
+    AsyncMessage {
+        /// target smart contract address
+        destination: Address,
+        /// name of the handler function
+        function: String,
+        /// maximum gas of the message execution
+        max_gas: u64,
+        /// fee paid by the sender
+        fee: Amount,
+        /// coins given to the destination
+        coins: Amount,
+        /// start of the validity period
+        validity_start: Slot,
+        /// end of the validity period
+        validity_end: Slot,
+    },
+}
+
+/// Reason why a signed operation would not be included in a block, or would fail when executed
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum OperationCheckFailure {
+    /// the operation could not be deserialized
+    InvalidFormat(String),
+    /// the signature of the operation is invalid
+    InvalidSignature(String),
+    /// the operation is expired, or not valid yet
+    InvalidPeriod(String),
+    /// the operation uses more gas than a block can hold
+    TooMuchGas(String),
+    /// the operation was already executed
+    AlreadyExecuted,
+    /// the sender cannot afford the fee or the max spending of the operation
+    InsufficientBalance(String),
+    /// the execution of the operation fails, its fee would still be spent
+    ExecutionFailed(String),
+}
+
+impl std::fmt::Display for OperationCheckFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OperationCheckFailure::InvalidFormat(msg) => write!(f, "invalid format: {}", msg),
+            OperationCheckFailure::InvalidSignature(msg) => {
+                write!(f, "invalid signature: {}", msg)
+            }
+            OperationCheckFailure::InvalidPeriod(msg) => write!(f, "invalid period: {}", msg),
+            OperationCheckFailure::TooMuchGas(msg) => write!(f, "too much gas: {}", msg),
+            OperationCheckFailure::AlreadyExecuted => write!(f, "already executed"),
+            OperationCheckFailure::InsufficientBalance(msg) => {
+                write!(f, "insufficient balance: {}", msg)
+            }
+            OperationCheckFailure::ExecutionFailed(msg) => write!(f, "execution failed: {}", msg),
+        }
+    }
 }
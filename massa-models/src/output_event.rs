@@ -1,5 +1,19 @@
+use crate::address::{AddressDeserializer, AddressSerializer};
+use crate::block_id::{BlockIdDeserializer, BlockIdSerializer};
+use crate::operation::{OperationIdDeserializer, OperationIdSerializer};
+use crate::serialization::{StringDeserializer, StringSerializer};
+use crate::slot::{SlotDeserializer, SlotSerializer};
 use crate::{address::Address, block_id::BlockId, operation::OperationId, slot::Slot};
+use massa_serialization::{
+    BoolDeserializer, BoolSerializer, Deserializer, OptionDeserializer, OptionSerializer,
+    SerializeError, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
+};
+use nom::error::{context, ContextError, ParseError};
+use nom::multi::length_count;
+use nom::sequence::tuple;
+use nom::{IResult, Parser};
 use serde::{Deserialize, Serialize};
+use std::ops::Bound::{Excluded, Included};
 use std::{collections::VecDeque, fmt::Display};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -69,3 +83,208 @@ impl Display for EventExecutionContext {
         )
     }
 }
+
+/// Serializer for `SCOutputEvent`
+#[derive(Clone)]
+pub struct SCOutputEventSerializer {
+    slot_serializer: SlotSerializer,
+    block_id_serializer: OptionSerializer<BlockId, BlockIdSerializer>,
+    bool_serializer: BoolSerializer,
+    u64_serializer: U64VarIntSerializer,
+    address_serializer: AddressSerializer,
+    operation_id_serializer: OptionSerializer<OperationId, OperationIdSerializer>,
+    data_serializer: StringSerializer<U64VarIntSerializer, u64>,
+}
+
+impl SCOutputEventSerializer {
+    /// Creates a new `SCOutputEventSerializer`
+    pub fn new() -> Self {
+        Self {
+            slot_serializer: SlotSerializer::new(),
+            block_id_serializer: OptionSerializer::new(BlockIdSerializer::new()),
+            bool_serializer: BoolSerializer::new(),
+            u64_serializer: U64VarIntSerializer::new(),
+            address_serializer: AddressSerializer::new(),
+            operation_id_serializer: OptionSerializer::new(OperationIdSerializer::new()),
+            data_serializer: StringSerializer::new(U64VarIntSerializer::new()),
+        }
+    }
+}
+
+impl Default for SCOutputEventSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serializer<SCOutputEvent> for SCOutputEventSerializer {
+    fn serialize(&self, value: &SCOutputEvent, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+        let context = &value.context;
+        self.slot_serializer.serialize(&context.slot, buffer)?;
+        self.block_id_serializer.serialize(&context.block, buffer)?;
+        self.bool_serializer.serialize(&context.read_only, buffer)?;
+        self.u64_serializer
+            .serialize(&context.index_in_slot, buffer)?;
+        self.u64_serializer
+            .serialize(&(context.call_stack.len() as u64), buffer)?;
+        for address in context.call_stack.iter() {
+            self.address_serializer.serialize(address, buffer)?;
+        }
+        self.operation_id_serializer
+            .serialize(&context.origin_operation_id, buffer)?;
+        self.bool_serializer.serialize(&context.is_final, buffer)?;
+        self.bool_serializer.serialize(&context.is_error, buffer)?;
+        self.data_serializer.serialize(&value.data, buffer)?;
+        Ok(())
+    }
+}
+
+/// Deserializer for `SCOutputEvent`
+#[derive(Clone)]
+pub struct SCOutputEventDeserializer {
+    slot_deserializer: SlotDeserializer,
+    block_id_deserializer: OptionDeserializer<BlockId, BlockIdDeserializer>,
+    bool_deserializer: BoolDeserializer,
+    index_deserializer: U64VarIntDeserializer,
+    call_stack_length_deserializer: U64VarIntDeserializer,
+    address_deserializer: AddressDeserializer,
+    operation_id_deserializer: OptionDeserializer<OperationId, OperationIdDeserializer>,
+    data_deserializer: StringDeserializer<U64VarIntDeserializer, u64>,
+}
+
+impl SCOutputEventDeserializer {
+    /// Creates a new `SCOutputEventDeserializer`
+    ///
+    /// # Arguments
+    /// * `thread_count`: number of threads
+    /// * `max_call_stack_length`: maximum number of addresses in the call stack of an event
+    /// * `max_event_data_length`: maximum size of the event data, in bytes
+    pub fn new(thread_count: u8, max_call_stack_length: u64, max_event_data_length: u64) -> Self {
+        Self {
+            slot_deserializer: SlotDeserializer::new(
+                (Included(0), Included(u64::MAX)),
+                (Included(0), Excluded(thread_count)),
+            ),
+            block_id_deserializer: OptionDeserializer::new(BlockIdDeserializer::new()),
+            bool_deserializer: BoolDeserializer::new(),
+            index_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+            call_stack_length_deserializer: U64VarIntDeserializer::new(
+                Included(0),
+                Included(max_call_stack_length),
+            ),
+            address_deserializer: AddressDeserializer::new(),
+            operation_id_deserializer: OptionDeserializer::new(OperationIdDeserializer::new()),
+            data_deserializer: StringDeserializer::new(U64VarIntDeserializer::new(
+                Included(0),
+                Included(max_event_data_length),
+            )),
+        }
+    }
+}
+
+impl Deserializer<SCOutputEvent> for SCOutputEventDeserializer {
+    /// ## Example
+    /// ```
+    /// use massa_models::output_event::{EventExecutionContext, SCOutputEvent, SCOutputEventDeserializer, SCOutputEventSerializer};
+    /// use massa_models::{address::Address, slot::Slot};
+    /// use massa_serialization::{DeserializeError, Deserializer, Serializer};
+    /// use std::collections::VecDeque;
+    /// use std::str::FromStr;
+    ///
+    /// let event = SCOutputEvent {
+    ///     context: EventExecutionContext {
+    ///         slot: Slot::new(1, 0),
+    ///         block: None,
+    ///         read_only: false,
+    ///         index_in_slot: 3,
+    ///         call_stack: VecDeque::from(vec![
+    ///             Address::from_str("AU12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x").unwrap(),
+    ///         ]),
+    ///         origin_operation_id: None,
+    ///         is_final: true,
+    ///         is_error: false,
+    ///     },
+    ///     data: String::from("test"),
+    /// };
+    /// let mut serialized = Vec::new();
+    /// SCOutputEventSerializer::new().serialize(&event, &mut serialized).unwrap();
+    /// let (rest, deserialized) = SCOutputEventDeserializer::new(32, 100, 1000)
+    ///     .deserialize::<DeserializeError>(&serialized)
+    ///     .unwrap();
+    /// assert!(rest.is_empty());
+    /// assert_eq!(deserialized.context.slot, event.context.slot);
+    /// assert_eq!(deserialized.context.call_stack, event.context.call_stack);
+    /// assert_eq!(deserialized.data, event.data);
+    /// ```
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], SCOutputEvent, E> {
+        context(
+            "Failed SCOutputEvent deserialization",
+            tuple((
+                context("Failed slot deserialization", |input| {
+                    self.slot_deserializer.deserialize(input)
+                }),
+                context("Failed block deserialization", |input| {
+                    self.block_id_deserializer.deserialize(input)
+                }),
+                context("Failed read_only deserialization", |input| {
+                    self.bool_deserializer.deserialize(input)
+                }),
+                context("Failed index_in_slot deserialization", |input| {
+                    self.index_deserializer.deserialize(input)
+                }),
+                context(
+                    "Failed call_stack deserialization",
+                    length_count(
+                        context("Failed length deserialization", |input| {
+                            self.call_stack_length_deserializer.deserialize(input)
+                        }),
+                        context("Failed address deserialization", |input| {
+                            self.address_deserializer.deserialize(input)
+                        }),
+                    ),
+                ),
+                context("Failed origin_operation_id deserialization", |input| {
+                    self.operation_id_deserializer.deserialize(input)
+                }),
+                context("Failed is_final deserialization", |input| {
+                    self.bool_deserializer.deserialize(input)
+                }),
+                context("Failed is_error deserialization", |input| {
+                    self.bool_deserializer.deserialize(input)
+                }),
+                context("Failed data deserialization", |input| {
+                    self.data_deserializer.deserialize(input)
+                }),
+            )),
+        )
+        .map(
+            |(
+                slot,
+                block,
+                read_only,
+                index_in_slot,
+                call_stack,
+                origin_operation_id,
+                is_final,
+                is_error,
+                data,
+            )| SCOutputEvent {
+                context: EventExecutionContext {
+                    slot,
+                    block,
+                    read_only,
+                    index_in_slot,
+                    call_stack: call_stack.into_iter().collect(),
+                    origin_operation_id,
+                    is_final,
+                    is_error,
+                },
+                data,
+            },
+        )
+        .parse(buffer)
+    }
+}
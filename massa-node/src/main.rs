@@ -10,7 +10,7 @@ use crate::operation_injector::start_operation_injector;
 use crate::settings::SETTINGS;
 use crate::survey::MassaSurvey;
 
-use clap::{crate_version, Parser};
+use clap::{crate_version, Parser, Subcommand};
 use crossbeam_channel::TryRecvError;
 use dialoguer::Password;
 use massa_api::{ApiServer, ApiV2, Private, Public, RpcServer, StopHandle, API};
@@ -18,8 +18,8 @@ use massa_api_exports::config::APIConfig;
 use massa_async_pool::AsyncPoolConfig;
 use massa_bootstrap::BootstrapError;
 use massa_bootstrap::{
-    get_state, start_bootstrap_server, BootstrapConfig, BootstrapManager, BootstrapTcpListener,
-    DefaultConnector,
+    get_state, start_bootstrap_server, BootstrapConfig, BootstrapManager, BootstrapStatus,
+    BootstrapTcpListener, DefaultConnector, SharedBootstrapStatus,
 };
 use massa_channel::receiver::MassaReceiver;
 use massa_channel::MassaChannel;
@@ -29,7 +29,7 @@ use massa_consensus_exports::{
 };
 use massa_consensus_worker::start_consensus_worker;
 use massa_db_exports::{MassaDBConfig, MassaDBController};
-use massa_db_worker::MassaDB;
+use massa_db_worker::{export_state_archive, import_state_archive, MassaDB};
 use massa_executed_ops::{ExecutedDenunciationsConfig, ExecutedOpsConfig};
 use massa_execution_exports::{
     ExecutionChannels, ExecutionConfig, ExecutionManager, GasCosts, StorageCostsConstants,
@@ -80,7 +80,9 @@ use massa_models::config::{
     POOL_CONTROLLER_ENDORSEMENTS_CHANNEL_SIZE, POOL_CONTROLLER_OPERATIONS_CHANNEL_SIZE,
 };
 use massa_models::slot::Slot;
-use massa_pool_exports::{PoolBroadcasts, PoolChannels, PoolConfig, PoolManager};
+use massa_pool_exports::{
+    PoolBroadcasts, PoolChannels, PoolConfig, PoolManager, PoolPersistenceConfig,
+};
 use massa_pool_worker::start_pool_controller;
 use massa_pos_exports::{PoSConfig, SelectorConfig, SelectorManager};
 use massa_pos_worker::start_selector_worker;
@@ -97,6 +99,8 @@ use num::rational::Ratio;
 use parking_lot::RwLock;
 use settings::GrpcSettings;
 use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Condvar, Mutex};
@@ -117,6 +121,7 @@ async fn launch(
     args: &Args,
     node_wallet: Arc<RwLock<Wallet>>,
     sig_int_toggled: Arc<(Mutex<bool>, Condvar)>,
+    bootstrap_status: SharedBootstrapStatus,
 ) -> (
     MassaReceiver<ConsensusEvent>,
     Option<BootstrapManager>,
@@ -219,15 +224,8 @@ async fn launch(
         }
     }
 
-    let db_config = MassaDBConfig {
-        path: SETTINGS.ledger.disk_ledger_path.clone(),
-        max_history_length: SETTINGS.ledger.final_history_length,
-        max_final_state_elements_size: MAX_BOOTSTRAP_FINAL_STATE_PARTS_SIZE.try_into().unwrap(),
-        max_versioning_elements_size: MAX_BOOTSTRAP_VERSIONING_ELEMENTS_SIZE.try_into().unwrap(),
-        thread_count: THREAD_COUNT,
-    };
     let db = Arc::new(RwLock::new(
-        Box::new(MassaDB::new(db_config)) as Box<(dyn MassaDBController + 'static)>
+        Box::new(MassaDB::new(db_config())) as Box<(dyn MassaDBController + 'static)>
     ));
 
     // Create final ledger
@@ -358,6 +356,7 @@ async fn launch(
         chain_id: *CHAINID,
     };
 
+    *bootstrap_status.write() = BootstrapStatus::InProgress;
     let bootstrap_state = match get_state(
         &bootstrap_config,
         final_state.clone(),
@@ -377,6 +376,16 @@ async fn launch(
         Err(err) => panic!("critical error detected in the bootstrap process: {}", err),
     };
 
+    if let Some(path) = &args.state_hash_breakdown {
+        if let Err(err) = write_state_hash_breakdown(
+            &*final_state.read(),
+            path,
+            args.state_hash_breakdown_keys.clone(),
+        ) {
+            warn!("could not write the state hash breakdown: {}", err);
+        }
+    }
+
     if !final_state.read().is_db_valid() {
         // TODO: Bootstrap again instead of panicking
         panic!("critical: db is not valid after bootstrap");
@@ -391,6 +400,7 @@ async fn launch(
             .compute_initial_draws()
             .expect("could not compute initial draws"); // TODO: this might just mean a bad bootstrap, no need to panic, just reboot
     }
+    *bootstrap_status.write() = BootstrapStatus::Done;
 
     let last_slot_before_downtime_ = *final_state.read().get_last_slot_before_downtime();
     if let Some(last_slot_before_downtime) = last_slot_before_downtime_ {
@@ -433,7 +443,16 @@ async fn launch(
     // launch execution module
     let execution_config = ExecutionConfig {
         max_final_events: SETTINGS.execution.max_final_events,
+        event_store_retention_periods: SETTINGS.execution.event_store_retention_periods,
+        event_store_path: SETTINGS.execution.event_store_path.clone(),
+        operation_history_enabled: SETTINGS.execution.operation_history_enabled,
+        operation_history_path: SETTINGS.execution.operation_history_path.clone(),
+        state_history_enabled: SETTINGS.execution.state_history_enabled,
+        state_history_retention_periods: SETTINGS.execution.state_history_retention_periods,
+        state_history_path: SETTINGS.execution.state_history_path.clone(),
         readonly_queue_length: SETTINGS.execution.readonly_queue_length,
+        readonly_executor_count: SETTINGS.execution.readonly_executor_count.get(),
+        readonly_request_timeout: SETTINGS.execution.readonly_request_timeout,
         cursor_delay: SETTINGS.execution.cursor_delay,
         max_async_gas: MAX_ASYNC_GAS,
         async_msg_cst_gas_cost: ASYNC_MSG_CST_GAS_COST,
@@ -453,6 +472,7 @@ async fn launch(
         max_datastore_value_size: MAX_DATASTORE_VALUE_LENGTH,
         storage_costs_constants,
         max_read_only_gas: SETTINGS.execution.max_read_only_gas,
+        gas_estimation_margin_percent: SETTINGS.execution.gas_estimation_margin_percent,
         gas_costs: gas_costs.clone(),
         base_operation_gas_cost: BASE_OPERATION_GAS_COST,
         last_start_period: final_state.read().get_last_start_period(),
@@ -502,6 +522,13 @@ async fn launch(
         max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
         max_operation_pool_size: SETTINGS.pool.max_operation_pool_size,
         max_operation_pool_excess_items: SETTINGS.pool.max_operation_pool_excess_items,
+        operation_selection_policy: SETTINGS.pool.operation_selection_policy,
+        max_block_operations_per_sender: SETTINGS.pool.max_block_operations_per_sender,
+        replace_by_fee_min_increase_percent: SETTINGS.pool.replace_by_fee_min_increase_percent,
+        operation_eviction_log_size: SETTINGS.pool.operation_eviction_log_size,
+        max_operations_per_sender: SETTINGS.pool.max_operations_per_sender,
+        max_operations_size_per_sender: SETTINGS.pool.max_operations_size_per_sender,
+        max_operations_gas_per_sender: SETTINGS.pool.max_operations_gas_per_sender,
         operation_pool_refresh_interval: SETTINGS.pool.operation_pool_refresh_interval,
         operation_max_future_start_delay: SETTINGS.pool.operation_max_future_start_delay,
         max_endorsements_pool_size_per_thread: SETTINGS.pool.max_endorsements_pool_size_per_thread,
@@ -520,28 +547,17 @@ async fn launch(
         max_denunciations_per_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
         last_start_period: final_state.read().get_last_start_period(),
     };
+    let pool_persistence_config =
+        SETTINGS
+            .pool
+            .persistence_path
+            .clone()
+            .map(|path| PoolPersistenceConfig {
+                path,
+                chain_id: *CHAINID,
+            });
 
-    let pool_channels = PoolChannels {
-        broadcasts: PoolBroadcasts {
-            endorsement_sender: broadcast::channel(
-                pool_config.broadcast_endorsements_channel_capacity,
-            )
-            .0,
-            operation_sender: broadcast::channel(pool_config.broadcast_operations_channel_capacity)
-                .0,
-        },
-        selector: selector_controller.clone(),
-        execution_controller: execution_controller.clone(),
-    };
-
-    let (pool_manager, pool_controller) = start_pool_controller(
-        pool_config,
-        &shared_storage,
-        pool_channels.clone(),
-        node_wallet.clone(),
-    );
-
-    // launch protocol controller
+    // create protocol controller, started after the pool and consensus
     let mut listeners = HashMap::default();
     listeners.insert(SETTINGS.protocol.bind, TransportType::Tcp);
     let protocol_config = ProtocolConfig {
@@ -640,6 +656,34 @@ async fn launch(
     let (protocol_controller, protocol_channels) =
         create_protocol_controller(protocol_config.clone());
 
+    let pool_channels = PoolChannels {
+        broadcasts: PoolBroadcasts {
+            endorsement_sender: broadcast::channel(
+                pool_config.broadcast_endorsements_channel_capacity,
+            )
+            .0,
+            operation_sender: broadcast::channel(pool_config.broadcast_operations_channel_capacity)
+                .0,
+        },
+        selector: selector_controller.clone(),
+        execution_controller: execution_controller.clone(),
+        protocol_controller: protocol_controller.clone(),
+    };
+
+    let (pool_manager, pool_controller) = start_pool_controller(
+        pool_config,
+        &shared_storage,
+        pool_channels.clone(),
+        node_wallet.clone(),
+        SETTINGS
+            .pool
+            .sender_quota_allowlist
+            .iter()
+            .copied()
+            .collect(),
+        pool_persistence_config,
+    );
+
     let consensus_config = ConsensusConfig {
         genesis_timestamp: *GENESIS_TIMESTAMP,
         end_timestamp: *END_TIMESTAMP,
@@ -672,6 +716,8 @@ async fn launch(
             .consensus
             .force_keep_final_periods_without_ops,
         chain_id: *CHAINID,
+        archive_enabled: SETTINGS.consensus.archive_enabled,
+        archive_path: SETTINGS.consensus.archive_path.clone(),
     };
 
     let (consensus_event_sender, consensus_event_receiver) =
@@ -813,6 +859,7 @@ async fn launch(
         consensus_controller.clone(),
         consensus_channels.broadcasts.clone(),
         execution_controller.clone(),
+        execution_channels.clone(),
         pool_channels.broadcasts.clone(),
         api_config.clone(),
         *VERSION,
@@ -857,6 +904,7 @@ async fn launch(
             keypair_factory: KeyPairFactory {
                 mip_store: mip_store.clone(),
             },
+            bootstrap_status: bootstrap_status.clone(),
         };
 
         // Spawn gRPC PUBLIC API
@@ -897,6 +945,7 @@ async fn launch(
             stop_cv: sig_int_toggled.clone(),
             node_wallet: node_wallet.clone(),
             bs_white_black_list,
+            bootstrap_status: bootstrap_status.clone(),
         };
 
         // Spawn gRPC PRIVATE API
@@ -928,6 +977,7 @@ async fn launch(
     let api_private = API::<Private>::new(
         protocol_controller.clone(),
         execution_controller.clone(),
+        pool_controller.clone(),
         api_config.clone(),
         sig_int_toggled,
         node_wallet,
@@ -1039,6 +1089,10 @@ fn configure_grpc(
         accept_http1: settings.accept_http1,
         enable_cors: settings.enable_cors,
         enable_health: settings.enable_health,
+        health_check_interval: settings.health_check_interval.to_duration(),
+        health_max_final_cursor_lag: settings.health_max_final_cursor_lag,
+        health_min_peer_count: settings.health_min_peer_count,
+        health_max_blockclique_stall: settings.health_max_blockclique_stall,
         enable_reflection: settings.enable_reflection,
         enable_tls: settings.enable_tls,
         enable_mtls: settings.enable_mtls,
@@ -1195,9 +1249,50 @@ async fn stop(
     // note that FinalLedger gets destroyed as soon as its Arc count goes to zero
 }
 
+/// Configuration of the final state database of the node
+fn db_config() -> MassaDBConfig {
+    MassaDBConfig {
+        path: SETTINGS.ledger.disk_ledger_path.clone(),
+        max_history_length: SETTINGS.ledger.final_history_length,
+        max_final_state_elements_size: MAX_BOOTSTRAP_FINAL_STATE_PARTS_SIZE.try_into().unwrap(),
+        max_versioning_elements_size: MAX_BOOTSTRAP_VERSIONING_ELEMENTS_SIZE.try_into().unwrap(),
+        thread_count: THREAD_COUNT,
+    }
+}
+
+/// Verify the final state, and write the breakdown of its hash per key prefix,
+/// listing the keys starting with `listed_keys` if any
+fn write_state_hash_breakdown(
+    final_state: &dyn FinalStateController,
+    path: &Path,
+    listed_keys: Option<String>,
+) -> anyhow::Result<()> {
+    let breakdown = final_state.get_state_hash_breakdown(listed_keys)?;
+    if breakdown.is_valid() {
+        info!(
+            "final state verified at slot {}: hash {}",
+            breakdown.slot, breakdown.computed_hash
+        );
+    } else {
+        warn!(
+            "final state verification failed at slot {}: stored hash {}, computed hash {}",
+            breakdown.slot, breakdown.stored_hash, breakdown.computed_hash
+        );
+    }
+    let mut writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(&mut writer, &breakdown)?;
+    writer.flush()?;
+    info!("state hash breakdown written to {}", path.display());
+    Ok(())
+}
+
 #[derive(Parser)]
 #[command(version = crate_version!())]
 struct Args {
+    /// Run a maintenance command instead of the node
+    #[command(subcommand)]
+    command: Option<Command>,
+
     #[arg(long = "keep-ledger")]
     keep_ledger: bool,
     /// Wallet password
@@ -1209,6 +1304,16 @@ struct Args {
     #[arg(long = "restart-from-snapshot-at-period")]
     restart_from_snapshot_at_period: Option<u64>,
 
+    /// Write the per-prefix breakdown of the final state hash (JSON) after bootstrap,
+    /// to be diffed with the one of another node (see `massa-db-inspect diff`)
+    #[arg(long = "state-hash-breakdown")]
+    state_hash_breakdown: Option<PathBuf>,
+
+    /// Also list the hash of the value of every key starting with this prefix (e.g. `ledger/`)
+    /// in the state hash breakdown, to find diverging keys. Listing every key is expensive.
+    #[arg(long = "state-hash-breakdown-keys", requires = "state_hash_breakdown")]
+    state_hash_breakdown_keys: Option<String>,
+
     #[cfg(feature = "op_spammer")]
     /// number of operations
     #[arg(
@@ -1231,6 +1336,72 @@ struct Args {
     dl_interval: u64,
 }
 
+#[derive(Subcommand)]
+enum Command {
+    /// Export the final state of the stopped node to a state archive
+    ExportState {
+        /// Path of the archive to create
+        path: PathBuf,
+    },
+    /// Import a state archive as the final state of the node.
+    /// The disk ledger must not exist yet, and the node is then started with `--restart-from-snapshot-at-period`
+    ImportState {
+        /// Path of the archive to import
+        path: PathBuf,
+    },
+}
+
+/// Run a maintenance command on the final state database
+fn run_command(command: Command) -> anyhow::Result<()> {
+    match command {
+        Command::ExportState { path } => {
+            if !SETTINGS.ledger.disk_ledger_path.exists() {
+                anyhow::bail!(
+                    "disk ledger {} does not exist",
+                    SETTINGS.ledger.disk_ledger_path.display()
+                );
+            }
+            let db = MassaDB::new(db_config());
+            let mut writer = BufWriter::new(File::create(&path)?);
+            let header = export_state_archive(&db, &mut writer)?;
+            writer.flush()?;
+            println!(
+                "Exported the final state at slot {} (hash {}) to {}",
+                header.slot,
+                header.state_hash,
+                path.display()
+            );
+        }
+        Command::ImportState { path } => {
+            if SETTINGS.ledger.disk_ledger_path.exists() {
+                anyhow::bail!(
+                    "disk ledger {} already exists, remove it before importing a state archive",
+                    SETTINGS.ledger.disk_ledger_path.display()
+                );
+            }
+            let mut db = MassaDB::new(db_config());
+            let mut reader = BufReader::new(File::open(&path)?);
+            let header = match import_state_archive(&mut db, &mut reader) {
+                Ok(header) => header,
+                Err(err) => {
+                    // do not leave a partially imported state behind
+                    drop(db);
+                    std::fs::remove_dir_all(&SETTINGS.ledger.disk_ledger_path)?;
+                    return Err(err.into());
+                }
+            };
+            println!(
+                "Imported the final state at slot {} (hash {}) from {}, \
+                 start the node with --restart-from-snapshot-at-period",
+                header.slot,
+                header.state_hash,
+                path.display()
+            );
+        }
+    }
+    Ok(())
+}
+
 /// Load wallet, asking for passwords if necessary
 fn load_wallet(
     password: Option<String>,
@@ -1261,7 +1432,10 @@ fn load_wallet(
 }
 
 fn main() -> anyhow::Result<()> {
-    let args = Args::parse();
+    let mut args = Args::parse();
+    if let Some(command) = args.command.take() {
+        return run_command(command);
+    }
 
     let tokio_rt = tokio::runtime::Builder::new_multi_thread()
         .thread_name_fn(|| {
@@ -1329,6 +1503,9 @@ async fn run(args: Args) -> anyhow::Result<()> {
     })
     .expect("Error setting Ctrl-C handler");
 
+    let bootstrap_status: SharedBootstrapStatus =
+        Arc::new(RwLock::new(BootstrapStatus::InProgress));
+
     #[cfg(feature = "resync_check")]
     let mut resync_check = Some(std::time::Instant::now() + std::time::Duration::from_secs(10));
 
@@ -1349,7 +1526,13 @@ async fn run(args: Args) -> anyhow::Result<()> {
             grpc_public_handle,
             metrics_stopper,
             massa_survey_stopper,
-        ) = launch(&cur_args, node_wallet.clone(), Arc::clone(&sig_int_toggled)).await;
+        ) = launch(
+            &cur_args,
+            node_wallet.clone(),
+            Arc::clone(&sig_int_toggled),
+            bootstrap_status.clone(),
+        )
+        .await;
 
         // loop over messages
         let restart = loop {
@@ -1358,6 +1541,7 @@ async fn run(args: Args) -> anyhow::Result<()> {
                 Ok(evt) => match evt {
                     ConsensusEvent::NeedSync => {
                         warn!("in response to a desynchronization, the node is going to bootstrap again");
+                        *bootstrap_status.write() = BootstrapStatus::NeedResync;
                         break true;
                     }
                     ConsensusEvent::Stop => {
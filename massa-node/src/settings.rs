@@ -1,10 +1,11 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 //! Build here the default node settings from the configuration file toml
-use std::{collections::HashMap, path::PathBuf};
+use std::{collections::HashMap, num::NonZeroUsize, path::PathBuf};
 
 use massa_bootstrap::IpType;
-use massa_models::{config::build_massa_settings, node::NodeId};
+use massa_models::{address::Address, config::build_massa_settings, node::NodeId};
+use massa_pool_exports::OperationSelectionPolicy;
 use massa_protocol_exports::PeerCategoryInfo;
 use massa_time::MassaTime;
 use serde::Deserialize;
@@ -22,10 +23,20 @@ pub struct LoggingSettings {
 #[derive(Clone, Debug, Deserialize)]
 pub struct ExecutionSettings {
     pub max_final_events: usize,
+    pub event_store_retention_periods: u64,
+    pub event_store_path: PathBuf,
+    pub operation_history_enabled: bool,
+    pub operation_history_path: PathBuf,
+    pub state_history_enabled: bool,
+    pub state_history_retention_periods: u64,
+    pub state_history_path: PathBuf,
     pub readonly_queue_length: usize,
+    pub readonly_executor_count: NonZeroUsize,
+    pub readonly_request_timeout: MassaTime,
     pub cursor_delay: MassaTime,
     pub stats_time_window_duration: MassaTime,
     pub max_read_only_gas: u64,
+    pub gas_estimation_margin_percent: u64,
     pub abi_gas_costs_file: PathBuf,
     pub wasm_gas_costs_file: PathBuf,
     pub hd_cache_path: PathBuf,
@@ -90,6 +101,22 @@ pub struct FactorySettings {
 pub struct PoolSettings {
     pub max_operation_pool_size: usize,
     pub max_operation_pool_excess_items: usize,
+    /// policy scoring the operations to include in blocks
+    pub operation_selection_policy: OperationSelectionPolicy,
+    /// max number of operations of a single sender included in a block
+    pub max_block_operations_per_sender: Option<u32>,
+    /// min fee increase (in percent) for an operation to replace a pending one
+    pub replace_by_fee_min_increase_percent: Option<u64>,
+    /// max number of operation evictions kept in the log of the operation pool
+    pub operation_eviction_log_size: usize,
+    /// max number of pending operations of a single sender
+    pub max_operations_per_sender: Option<usize>,
+    /// max total size of the pending operations of a single sender
+    pub max_operations_size_per_sender: Option<usize>,
+    /// max total gas of the pending operations of a single sender
+    pub max_operations_gas_per_sender: Option<u64>,
+    /// senders exempted from the per-sender limits
+    pub sender_quota_allowlist: Vec<Address>,
     pub operation_max_future_start_delay: MassaTime,
     pub operation_pool_refresh_interval: MassaTime,
     pub max_endorsements_pool_size_per_thread: usize,
@@ -98,6 +125,8 @@ pub struct PoolSettings {
     pub broadcast_endorsements_channel_capacity: usize,
     /// operations channel capacity
     pub broadcast_operations_channel_capacity: usize,
+    /// file where the pools are saved on stop, and reloaded from on startup
+    pub persistence_path: Option<PathBuf>,
 }
 
 /// API and server configuration, read from a file configuration.
@@ -166,6 +195,10 @@ pub struct ConsensusSettings {
     pub broadcast_blocks_channel_capacity: usize,
     /// filled blocks channel capacity
     pub broadcast_filled_blocks_channel_capacity: usize,
+    /// whether the final blocks, operations and endorsements are archived on disk
+    pub archive_enabled: bool,
+    /// path of the archive database
+    pub archive_path: PathBuf,
 }
 
 // TODO: Remove one date. Kept for retro compatibility.
@@ -284,6 +317,14 @@ pub struct GrpcSettings {
     pub enable_cors: bool,
     /// whether to enable gRPC health service
     pub enable_health: bool,
+    /// interval between two checks of the node health
+    pub health_check_interval: MassaTime,
+    /// max number of slots the final execution cursor can lag behind the current slot before being reported as unhealthy
+    pub health_max_final_cursor_lag: u64,
+    /// min number of connected peers under which the protocol is reported as unhealthy
+    pub health_min_peer_count: u64,
+    /// max number of periods without a new blockclique block before consensus is reported as unhealthy
+    pub health_max_blockclique_stall: u64,
     /// whether to enable gRPC reflection
     pub enable_reflection: bool,
     /// whether to enable TLS
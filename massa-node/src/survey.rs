@@ -122,6 +122,7 @@ impl MassaSurvey {
 
                                 {
                                     massa_metrics.set_operations_pool(pool_controller.get_operation_count());
+                                    massa_metrics.set_operations_pool_quota_rejected(pool_controller.get_quota_rejected_operation_count());
                                     massa_metrics.set_endorsements_pool(pool_controller.get_endorsement_count());
                                     massa_metrics.set_denunciations_pool(pool_controller.get_denunciation_count());
 
@@ -3,6 +3,18 @@
 use massa_models::amount::Amount;
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Policy deciding which operations of the pool are included first in the blocks we produce
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OperationSelectionPolicy {
+    /// Fee weighted by the block resources used and the chances that we include the operation before other stakers (default).
+    #[default]
+    Standard,
+    /// Fee per unit of gas, weighted by the chances that we include the operation before other stakers.
+    /// Greedily filling blocks by fee density approximates the most profitable blocks when gas is the bottleneck.
+    FeePerGas,
+}
 
 /// Pool configuration
 #[derive(Debug, Deserialize, Serialize, Clone, Copy)]
@@ -31,6 +43,21 @@ pub struct PoolConfig {
     pub max_operation_pool_size: usize,
     /// max excess on pool size (in-between refreshes)
     pub max_operation_pool_excess_items: usize,
+    /// policy scoring the operations to include in blocks
+    pub operation_selection_policy: OperationSelectionPolicy,
+    /// max number of operations of a single sender included in a block (no limit if `None`)
+    pub max_block_operations_per_sender: Option<u32>,
+    /// min fee increase (in percent) for an operation to replace a pending one of the same sender
+    /// with the same expire period (replace-by-fee disabled if `None`)
+    pub replace_by_fee_min_increase_percent: Option<u64>,
+    /// max number of operation evictions kept in the log of the operation pool
+    pub operation_eviction_log_size: usize,
+    /// max number of pending operations of a single sender in the pool (no limit if `None`)
+    pub max_operations_per_sender: Option<usize>,
+    /// max total size (in bytes) of the pending operations of a single sender in the pool (no limit if `None`)
+    pub max_operations_size_per_sender: Option<usize>,
+    /// max total gas of the pending operations of a single sender in the pool (no limit if `None`)
+    pub max_operations_gas_per_sender: Option<u64>,
     /// max endorsement pool size per thread (in number of endorsements)
     pub max_endorsements_pool_size_per_thread: usize,
     /// max number of endorsements per block
@@ -63,3 +90,12 @@ pub struct PoolConfig {
     /// * If from bootstrap: set during bootstrap
     pub last_start_period: u64,
 }
+
+/// Persistence of the operation and endorsement pools across restarts
+#[derive(Debug, Clone)]
+pub struct PoolPersistenceConfig {
+    /// file where the pooled operations and endorsements are saved on stop, and reloaded from on startup
+    pub path: PathBuf,
+    /// chain id, to check the signatures of the reloaded operations and endorsements
+    pub chain_id: u64,
+}
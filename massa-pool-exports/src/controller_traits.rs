@@ -1,6 +1,8 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_models::{
+    address::Address,
+    amount::Amount,
     block_id::BlockId,
     denunciation::{Denunciation, DenunciationPrecursor},
     endorsement::EndorsementId,
@@ -9,6 +11,8 @@ use massa_models::{
 };
 use massa_storage::Storage;
 
+use crate::PoolOperations;
+
 #[cfg(feature = "test-exports")]
 use std::sync::{Arc, RwLock};
 
@@ -46,6 +50,9 @@ pub trait PoolController: Send + Sync {
     /// Get the number of operations in the pool
     fn get_operation_count(&self) -> usize;
 
+    /// Get the number of operations rejected or evicted from the pool because their sender exceeded one of its limits
+    fn get_quota_rejected_operation_count(&self) -> u64;
+
     /// Check if the pool contains a list of endorsements. Returns one boolean per item.
     fn contains_endorsements(&self, endorsements: &[EndorsementId]) -> Vec<bool>;
 
@@ -55,6 +62,20 @@ pub trait PoolController: Send + Sync {
     /// Get the number of denunciations in the pool
     fn get_denunciation_count(&self) -> usize;
 
+    /// Suggest a fee for an operation sent in a given thread, so that it gets included soon.
+    /// The suggestion is based on the scores of the operations in the pool and on the fees of recently included operations.
+    fn get_fee_suggestion(&self, thread: u8) -> Amount;
+
+    /// Get the operations of the pool with their score, and the latest evictions from the pool.
+    /// If a sender is given, only its operations are listed.
+    /// At most `limit` operations are listed after skipping the `offset` best ones, along with the `limit` latest evictions.
+    fn get_pool_operations(
+        &self,
+        sender: Option<Address>,
+        offset: usize,
+        limit: usize,
+    ) -> PoolOperations;
+
     /// Returns a boxed clone of self.
     /// Useful to allow cloning `Box<dyn PoolController>`.
     fn clone_box(&self) -> Box<dyn PoolController>;
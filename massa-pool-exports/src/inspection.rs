@@ -0,0 +1,72 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use massa_models::{address::Address, amount::Amount, operation::OperationId};
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+/// Operation of the pool, with the data used to score and select it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoolOperationInfo {
+    /// operation id
+    pub id: OperationId,
+    /// address of the sender
+    pub creator_address: Address,
+    /// thread of the sender
+    pub thread: u8,
+    /// fee of the operation
+    pub fee: Amount,
+    /// score computed at the last refresh of the pool (`None` if the operation was added since)
+    pub score: Option<f32>,
+    /// first period during which the operation can be included in a block
+    pub validity_start_period: u64,
+    /// last period during which the operation can be included in a block
+    pub validity_end_period: u64,
+    /// serialized size of the operation
+    pub size: usize,
+    /// max gas used by the operation
+    pub max_gas_usage: u64,
+}
+
+/// Why an operation left the pool without being included in one of our blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PoolEvictionReason {
+    /// the pool was full, and the operation had one of the lowest scores
+    Truncated,
+    /// the balance of the sender cannot cover the operation along with its better-scored operations
+    BalanceOverflow,
+    /// the last period of the validity range of the operation is final
+    Expired,
+    /// the operation was executed in a final or candidate block
+    ExecutedElsewhere,
+    /// none of our PoS draws is in the validity range of the operation
+    NoPosDraw,
+    /// the operation uses more gas or space than a block can hold
+    ExceedsBlockLimits,
+    /// a pending operation of the same sender replaced it by fee
+    ReplacedByFee,
+    /// the sender exceeded one of its limits of pending operations, and the operation had one of its lowest fees
+    SenderQuota,
+}
+
+/// Operation evicted from the pool
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolOperationEviction {
+    /// operation id
+    pub id: OperationId,
+    /// address of the sender
+    pub creator_address: Address,
+    /// reason of the eviction
+    pub reason: PoolEvictionReason,
+    /// time of the eviction
+    pub timestamp: MassaTime,
+}
+
+/// Content of the operation pool
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PoolOperations {
+    /// operations of the pool, from the best score to the worst,
+    /// followed by the operations added since the last refresh
+    pub operations: Vec<PoolOperationInfo>,
+    /// latest evictions from the pool, oldest first
+    pub recent_evictions: Vec<PoolOperationEviction>,
+}
@@ -8,10 +8,14 @@
 mod channels;
 mod config;
 mod controller_traits;
+mod inspection;
 
 pub use channels::{PoolBroadcasts, PoolChannels};
-pub use config::PoolConfig;
+pub use config::{OperationSelectionPolicy, PoolConfig, PoolPersistenceConfig};
 pub use controller_traits::{PoolController, PoolManager};
+pub use inspection::{
+    PoolEvictionReason, PoolOperationEviction, PoolOperationInfo, PoolOperations,
+};
 
 #[cfg(feature = "test-exports")]
 pub use controller_traits::{MockPoolController, MockPoolControllerWrapper};
@@ -7,7 +7,7 @@ use massa_models::config::{
 };
 use massa_time::MassaTime;
 
-use crate::PoolConfig;
+use crate::{OperationSelectionPolicy, PoolConfig};
 
 impl Default for PoolConfig {
     fn default() -> Self {
@@ -21,6 +21,13 @@ impl Default for PoolConfig {
             base_operation_gas_cost: BASE_OPERATION_GAS_COST,
             max_operation_pool_size: 32000,
             max_operation_pool_excess_items: 10000,
+            operation_selection_policy: OperationSelectionPolicy::Standard,
+            max_block_operations_per_sender: None,
+            replace_by_fee_min_increase_percent: None,
+            operation_eviction_log_size: 1000,
+            max_operations_per_sender: None,
+            max_operations_size_per_sender: None,
+            max_operations_gas_per_sender: None,
             max_endorsements_pool_size_per_thread: 1000,
             max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
             max_block_endorsement_count: ENDORSEMENT_COUNT,
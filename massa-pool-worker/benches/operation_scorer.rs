@@ -0,0 +1,166 @@
+//! Compares the operation selection policies: block fee revenue, and scoring and selection time.
+//!
+//! Run with `cargo bench -p massa_pool_worker --features benchmarking`.
+
+#[cfg(feature = "benchmarking")]
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+#[cfg(feature = "benchmarking")]
+fn criterion_benchmark(c: &mut Criterion) {
+    use massa_models::{
+        address::Address,
+        amount::Amount,
+        config::CHAINID,
+        operation::{Operation, OperationSerializer, OperationType},
+        secure_share::SecureShareContent,
+        slot::Slot,
+    };
+    use massa_pool_exports::{OperationSelectionPolicy, PoolConfig};
+    use massa_pool_worker::{
+        new_operation_scorer, select_block_operations, OperationInfo, ScoringContext,
+    };
+    use massa_signature::KeyPair;
+    use std::cmp::Ordering;
+    use std::collections::BTreeSet;
+
+    const SENDER_COUNT: usize = 200;
+    const OPS_PER_SENDER: usize = 20;
+    const SPAMMER_OP_COUNT: usize = 2000;
+    const BLOCK_PERIOD: u64 = 5;
+
+    /// Generate a pool of call operations with pseudo-random fees and gas,
+    /// plus a spammer sending many cheap low-fee operations
+    fn generate_pool(config: &PoolConfig) -> Vec<OperationInfo> {
+        let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next_random = move |max: u64| {
+            // xorshift
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed % max
+        };
+        let target_addr = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let generate_op = |creator: &KeyPair, fee: u64, max_gas: u64| {
+            let content = Operation {
+                fee: Amount::from_raw(fee),
+                op: OperationType::CallSC {
+                    target_addr,
+                    target_func: "transfer".to_string(),
+                    param: vec![0; 32],
+                    max_gas,
+                    coins: Amount::zero(),
+                },
+                expire_period: 10,
+            };
+            let op =
+                Operation::new_verifiable(content, OperationSerializer::new(), creator, *CHAINID)
+                    .unwrap();
+            OperationInfo::from_op(
+                &op,
+                config.operation_validity_periods,
+                config.roll_price,
+                config.thread_count,
+                config.base_operation_gas_cost,
+                config.sp_compilation_cost,
+            )
+        };
+
+        let mut ops = Vec::new();
+        for _ in 0..SENDER_COUNT {
+            let creator = KeyPair::generate(0).unwrap();
+            for _ in 0..OPS_PER_SENDER {
+                let fee = 1_000_000 + next_random(100_000_000);
+                let max_gas = 1_000_000 + next_random(300_000_000);
+                ops.push(generate_op(&creator, fee, max_gas));
+            }
+        }
+        let spammer = KeyPair::generate(0).unwrap();
+        for _ in 0..SPAMMER_OP_COUNT {
+            let fee = 1_000_000 + next_random(2_000_000);
+            ops.push(generate_op(&spammer, fee, 1_000_000));
+        }
+        ops
+    }
+
+    /// Score and sort the pool, then fill a block in every thread
+    fn produce_blocks(
+        ops: &[OperationInfo],
+        config: &PoolConfig,
+        policy: OperationSelectionPolicy,
+    ) -> Amount {
+        let pos_draws: BTreeSet<Slot> = (0..config.thread_count)
+            .map(|thread| Slot::new(BLOCK_PERIOD, thread))
+            .collect();
+        let context = ScoringContext {
+            config,
+            pos_draws: &pos_draws,
+            now_period: BLOCK_PERIOD - 1,
+        };
+        let scorer = new_operation_scorer(policy);
+        let mut scored_ops: Vec<(f32, OperationInfo)> = ops
+            .iter()
+            .map(|op_info| (scorer.score(op_info, &context), op_info.clone()))
+            .collect();
+        scored_ops.sort_unstable_by(|(score1, _), (score2, _)| {
+            score2.partial_cmp(score1).unwrap_or(Ordering::Equal)
+        });
+        let sorted_ops: Vec<OperationInfo> =
+            scored_ops.into_iter().map(|(_, op_info)| op_info).collect();
+
+        let mut revenue = Amount::zero();
+        for slot in &pos_draws {
+            let selected: BTreeSet<_> = select_block_operations(&sorted_ops, slot, config)
+                .into_iter()
+                .collect();
+            for op_info in sorted_ops.iter().filter(|op| selected.contains(&op.id)) {
+                revenue = revenue.saturating_add(op_info.fee);
+            }
+        }
+        revenue
+    }
+
+    let config = PoolConfig::default();
+    let capped_config = PoolConfig {
+        max_block_operations_per_sender: Some(5),
+        ..config
+    };
+    let ops = generate_pool(&config);
+    let variants = [
+        ("standard", config, OperationSelectionPolicy::Standard),
+        ("fee per gas", config, OperationSelectionPolicy::FeePerGas),
+        (
+            "standard with sender cap",
+            capped_config,
+            OperationSelectionPolicy::Standard,
+        ),
+        (
+            "fee per gas with sender cap",
+            capped_config,
+            OperationSelectionPolicy::FeePerGas,
+        ),
+    ];
+
+    for (name, config, policy) in &variants {
+        println!(
+            "{}: block fee revenue {}",
+            name,
+            produce_blocks(&ops, config, *policy)
+        );
+    }
+    for (name, config, policy) in &variants {
+        c.bench_function(name, |b| {
+            b.iter(|| produce_blocks(black_box(&ops), config, *policy))
+        });
+    }
+}
+
+#[cfg(feature = "benchmarking")]
+criterion_group!(benches, criterion_benchmark);
+
+#[cfg(feature = "benchmarking")]
+criterion_main!(benches);
+
+#[cfg(not(feature = "benchmarking"))]
+fn main() {
+    println!("Please use the `--features benchmarking` flag to run this benchmark.");
+}
@@ -3,19 +3,21 @@
 //! Pool controller implementation
 
 use massa_models::{
-    block_id::BlockId, denunciation::Denunciation, denunciation::DenunciationPrecursor,
-    endorsement::EndorsementId, operation::OperationId, slot::Slot,
+    address::Address, amount::Amount, block_id::BlockId, denunciation::Denunciation,
+    denunciation::DenunciationPrecursor, endorsement::EndorsementId, operation::OperationId,
+    slot::Slot,
 };
-use massa_pool_exports::{PoolConfig, PoolController, PoolManager};
+use massa_pool_exports::{PoolConfig, PoolController, PoolManager, PoolOperations};
 use massa_storage::Storage;
 use parking_lot::RwLock;
+use std::path::PathBuf;
 use std::sync::mpsc::TrySendError;
 use std::sync::{mpsc::SyncSender, Arc};
 use tracing::{info, warn};
 
 use crate::{
     denunciation_pool::DenunciationPool, endorsement_pool::EndorsementPool,
-    operation_pool::OperationPool,
+    operation_pool::OperationPool, persistence::save_pools,
 };
 
 /// A generic command to send commands to a pool
@@ -204,6 +206,11 @@ impl PoolController for PoolControllerImpl {
         self.operation_pool.read().len()
     }
 
+    /// Get the number of operations rejected or evicted from the pool because their sender exceeded one of its limits
+    fn get_quota_rejected_operation_count(&self) -> u64 {
+        self.operation_pool.read().get_quota_rejected_count()
+    }
+
     /// Check if the pool contains a list of endorsements. Returns one boolean per item.
     fn contains_endorsements(&self, endorsements: &[EndorsementId]) -> Vec<bool> {
         let lck = self.endorsement_pool.read();
@@ -221,6 +228,23 @@ impl PoolController for PoolControllerImpl {
         self.denunciation_pool.read().len()
     }
 
+    /// Suggest a fee for an operation sent in a given thread
+    fn get_fee_suggestion(&self, thread: u8) -> Amount {
+        self.operation_pool.read().get_fee_suggestion(thread)
+    }
+
+    /// Get the operations of the pool with their score, and the latest evictions from the pool
+    fn get_pool_operations(
+        &self,
+        sender: Option<Address>,
+        offset: usize,
+        limit: usize,
+    ) -> PoolOperations {
+        self.operation_pool
+            .read()
+            .get_pool_operations(sender.as_ref(), offset, limit)
+    }
+
     /// Returns a boxed clone of self.
     /// Allows cloning `Box<dyn PoolController>`,
     fn clone_box(&self) -> Box<dyn PoolController> {
@@ -237,6 +261,12 @@ impl PoolController for PoolControllerImpl {
 ///
 /// Contains the operations and endorsements thread handles.
 pub struct PoolManagerImpl {
+    /// File where the pools are saved on stop
+    pub(crate) persistence_path: Option<PathBuf>,
+    /// Shared reference to the operation pool, saved on stop
+    pub(crate) operation_pool: Arc<RwLock<OperationPool>>,
+    /// Shared reference to the endorsement pool, saved on stop
+    pub(crate) endorsement_pool: Arc<RwLock<EndorsementPool>>,
     /// Handle used to join the operation thread
     pub(crate) operations_thread_handle: Option<std::thread::JoinHandle<()>>,
     /// Handle used to join the endorsement thread
@@ -273,6 +303,19 @@ impl PoolManager for PoolManagerImpl {
                 .join()
                 .expect("denunciations pool thread panicked on try to join");
         }
+        // the pools are no longer written to once their threads are joined
+        if let Some(path) = &self.persistence_path {
+            let operations = self.operation_pool.read().get_operations();
+            let endorsements = self.endorsement_pool.read().get_endorsements();
+            match save_pools(path, &operations, &endorsements) {
+                Ok(()) => info!(
+                    "saved {} operations and {} endorsements of the pool",
+                    operations.len(),
+                    endorsements.len()
+                ),
+                Err(err) => warn!("could not save the pools to {:?}: {}", path, err),
+            }
+        }
         info!("pool workers stopped");
     }
 }
@@ -28,9 +28,9 @@ pub struct DenunciationPool {
 impl DenunciationPool {
     pub fn init(config: PoolConfig, channels: PoolChannels) -> Self {
         Self {
+            last_cs_final_periods: vec![0u64; config.thread_count as usize],
             config,
             channels,
-            last_cs_final_periods: vec![0u64; config.thread_count as usize],
             denunciations_cache: Default::default(),
         }
     }
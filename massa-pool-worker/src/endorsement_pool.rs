@@ -2,7 +2,7 @@
 
 use massa_models::{
     block_id::BlockId,
-    endorsement::EndorsementId,
+    endorsement::{EndorsementId, SecureShareEndorsement},
     prehash::{CapacityAllocator, PreHashSet},
     slot::Slot,
 };
@@ -68,6 +68,16 @@ impl EndorsementPool {
         self.storage.get_endorsement_refs().contains(id)
     }
 
+    /// Get the endorsements of the pool
+    pub(crate) fn get_endorsements(&self) -> Vec<SecureShareEndorsement> {
+        let endos = self.storage.read_endorsements();
+        self.storage
+            .get_endorsement_refs()
+            .iter()
+            .filter_map(|endo_id| endos.get(endo_id).cloned())
+            .collect()
+    }
+
     /// notify of new final CS periods
     pub(crate) fn notify_final_cs_periods(&mut self, final_cs_periods: &[u64]) {
         // update internal final CS period counter
@@ -94,7 +104,18 @@ impl EndorsementPool {
     }
 
     /// Add a list of endorsements to the pool
-    pub(crate) fn add_endorsements(&mut self, mut endorsement_storage: Storage) {
+    pub(crate) fn add_endorsements(&mut self, endorsement_storage: Storage) {
+        self.insert_endorsements(endorsement_storage, true);
+    }
+
+    /// Insert endorsements saved on the last stop, before the pool accepts any input.
+    /// Unlike `add_endorsements`, they are not broadcast again.
+    pub(crate) fn reload_endorsements(&mut self, endorsement_storage: Storage) {
+        self.insert_endorsements(endorsement_storage, false);
+    }
+
+    /// Insert a list of endorsements in the pool, broadcasting them if `broadcast` is set
+    fn insert_endorsements(&mut self, mut endorsement_storage: Storage, broadcast: bool) {
         let items = endorsement_storage
             .get_endorsement_refs()
             .iter()
@@ -144,7 +165,7 @@ impl EndorsementPool {
                 }
 
                 // Broadcast endorsement to active channel subscribers.
-                if self.config.broadcast_enabled {
+                if broadcast && self.config.broadcast_enabled {
                     if let Err(err) = self
                         .channels
                         .broadcasts
@@ -9,11 +9,24 @@ mod controller_impl;
 mod denunciation_pool;
 mod endorsement_pool;
 mod operation_pool;
+mod operation_scorer;
+mod persistence;
 mod types;
 mod worker;
 
 pub use worker::start_pool_controller;
 
+#[cfg(feature = "benchmarking")]
+use criterion as _;
+#[cfg(feature = "benchmarking")]
+pub use operation_pool::select_block_operations;
+#[cfg(feature = "benchmarking")]
+pub use operation_scorer::{
+    new_operation_scorer, FeePerGasScorer, OperationScorer, ScoringContext, StandardScorer,
+};
+#[cfg(feature = "benchmarking")]
+pub use types::OperationInfo;
+
 #[cfg(test)]
 use mockall as _;
 
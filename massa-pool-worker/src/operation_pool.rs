@@ -3,21 +3,94 @@
 use massa_models::{
     address::Address,
     amount::Amount,
-    operation::OperationId,
-    prehash::{CapacityAllocator, PreHashMap, PreHashSet},
+    operation::{OperationId, SecureShareOperation},
+    prehash::{PreHashMap, PreHashSet},
     slot::Slot,
     timeslots::get_latest_block_slot_at_timestamp,
 };
-use massa_pool_exports::{PoolChannels, PoolConfig};
+use massa_pool_exports::{
+    PoolChannels, PoolConfig, PoolEvictionReason, PoolOperationEviction, PoolOperationInfo,
+    PoolOperations,
+};
 use massa_storage::Storage;
 use massa_time::MassaTime;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
-use std::{cmp::max, cmp::Ordering, cmp::PartialOrd, collections::BTreeSet, sync::Arc};
+use std::{
+    cmp::max, cmp::Ordering, cmp::PartialOrd, collections::BTreeSet, collections::HashMap,
+    collections::VecDeque, sync::Arc,
+};
 use tracing::{debug, trace, warn};
 
+use crate::operation_scorer::{new_operation_scorer, OperationScorer, ScoringContext};
 use crate::types::OperationInfo;
 
+/// Pending operation of a sender, with what counts towards the per-sender limits
+#[derive(Debug, Clone, Copy)]
+struct SenderOp {
+    /// operation id
+    id: OperationId,
+    /// fee of the operation
+    fee: Amount,
+    /// serialized size of the operation
+    size: usize,
+    /// max gas usage of the operation
+    max_gas_usage: u64,
+}
+
+/// Pending operations of a sender, with their running totals
+#[derive(Debug, Default)]
+struct SenderOps {
+    /// operations of the sender, oldest first
+    ops: Vec<SenderOp>,
+    /// total size of the operations
+    size: usize,
+    /// total max gas usage of the operations
+    gas: u64,
+}
+
+impl SenderOps {
+    fn push(&mut self, op: SenderOp) {
+        self.size = self.size.saturating_add(op.size);
+        self.gas = self.gas.saturating_add(op.max_gas_usage);
+        self.ops.push(op);
+    }
+
+    fn remove(&mut self, id: &OperationId) {
+        if let Some(index) = self.ops.iter().position(|op| &op.id == id) {
+            let op = self.ops.remove(index);
+            self.size = self.size.saturating_sub(op.size);
+            self.gas = self.gas.saturating_sub(op.max_gas_usage);
+        }
+    }
+
+    fn fits(&self, max_count: usize, max_size: usize, max_gas: u64) -> bool {
+        self.ops.len() <= max_count && self.size <= max_size && self.gas <= max_gas
+    }
+
+    /// Keep the operations with the highest fees that fit in the limits, the oldest ones first on equal fees.
+    /// Returns the ids of the other operations.
+    fn keep_best(&mut self, max_count: usize, max_size: usize, max_gas: u64) -> Vec<OperationId> {
+        let mut ops = std::mem::take(&mut self.ops);
+        // the sort is stable, so the oldest operations stay first on equal fees
+        ops.sort_by(|op1, op2| op2.fee.cmp(&op1.fee));
+        self.size = 0;
+        self.gas = 0;
+        let mut excluded = Vec::new();
+        for op in ops {
+            if self.ops.len() < max_count
+                && self.size.saturating_add(op.size) <= max_size
+                && self.gas.saturating_add(op.max_gas_usage) <= max_gas
+            {
+                self.push(op);
+            } else {
+                excluded.push(op.id);
+            }
+        }
+        excluded
+    }
+}
+
 pub struct OperationPool {
     /// configuration
     config: PoolConfig,
@@ -36,6 +109,39 @@ pub struct OperationPool {
 
     /// staking wallet, to know which addresses we are using to stake
     wallet: Arc<RwLock<Wallet>>,
+
+    /// fees of the latest operations removed from the pool because they were included in a block, newest at the back
+    recent_inclusion_fees: VecDeque<Amount>,
+
+    /// scorer of the operations, following the selection policy of the config
+    scorer: Box<dyn OperationScorer>,
+
+    /// ids and fees of the operations that can be replaced by fee, per sender and expire period.
+    /// Contains ids of operations that left the pool until the next refresh.
+    replaceable_ops: HashMap<(Address, u64), Vec<(OperationId, Amount)>>,
+
+    /// ids of the latest operations replaced by fee, newest at the back.
+    /// They are rejected if received again, so that they do not come back to the pool.
+    replaced_ops: VecDeque<OperationId>,
+
+    /// ids of `replaced_ops`, for lookups
+    replaced_op_ids: PreHashSet<OperationId>,
+
+    /// scores of the operations computed at the last refresh
+    scores: PreHashMap<OperationId, f32>,
+
+    /// latest evictions from the pool, newest at the back
+    evictions: VecDeque<PoolOperationEviction>,
+
+    /// senders exempted from the per-sender limits
+    sender_quota_allowlist: PreHashSet<Address>,
+
+    /// pending operations of each sender that is not in the quota allowlist.
+    /// Only maintained if a per-sender limit is set, and updated whenever operations enter or leave `sorted_ops`.
+    sender_ops: PreHashMap<Address, SenderOps>,
+
+    /// number of operations rejected or evicted because their sender exceeded one of its limits
+    quota_rejected_count: u64,
 }
 
 impl OperationPool {
@@ -44,6 +150,7 @@ impl OperationPool {
         storage: &Storage,
         channels: PoolChannels,
         wallet: Arc<RwLock<Wallet>>,
+        sender_quota_allowlist: PreHashSet<Address>,
     ) -> Self {
         OperationPool {
             sorted_ops: Vec::with_capacity(
@@ -52,6 +159,19 @@ impl OperationPool {
                     .saturating_add(config.max_operation_pool_excess_items),
             ),
             last_cs_final_periods: vec![0u64; config.thread_count as usize],
+            recent_inclusion_fees: VecDeque::with_capacity(
+                (config.max_operations_per_block as usize)
+                    .saturating_mul(config.thread_count as usize),
+            ),
+            scorer: new_operation_scorer(config.operation_selection_policy),
+            replaceable_ops: HashMap::new(),
+            replaced_ops: VecDeque::with_capacity(config.max_operation_pool_size),
+            replaced_op_ids: PreHashSet::default(),
+            scores: PreHashMap::default(),
+            evictions: VecDeque::with_capacity(config.operation_eviction_log_size),
+            sender_quota_allowlist,
+            sender_ops: PreHashMap::default(),
+            quota_rejected_count: 0,
             config,
             storage: storage.clone_without_refs(),
             channels,
@@ -151,49 +271,76 @@ impl OperationPool {
         pos_draws: &BTreeSet<Slot>,
         sender_balances: &PreHashMap<Address, Amount>,
     ) {
-        let mut removed = PreHashSet::default();
+        let mut evictions = Vec::new();
+        let mut included_fees = Vec::new();
         self.sorted_ops.retain(|op_info| {
             // filter out ops that use too much resources
-            let mut retain = (op_info.max_gas_usage <= self.config.max_block_gas)
-                && (op_info.size <= self.config.max_block_size as usize);
+            let mut eviction_reason = None;
+            if op_info.max_gas_usage > self.config.max_block_gas
+                || op_info.size > self.config.max_block_size as usize
+            {
+                eviction_reason = Some(PoolEvictionReason::ExceedsBlockLimits);
+            }
 
             // filter out ops that are not valid during our PoS draws
-            if retain {
-                retain = pos_draws.iter().any(|slot| {
+            if eviction_reason.is_none()
+                && !pos_draws.iter().any(|slot| {
                     op_info.thread == slot.thread
                         && op_info.validity_period_range.contains(&slot.period)
-                });
+                })
+            {
+                eviction_reason = if *op_info.validity_period_range.end()
+                    <= self.last_cs_final_periods[op_info.thread as usize]
+                {
+                    Some(PoolEvictionReason::Expired)
+                } else {
+                    Some(PoolEvictionReason::NoPosDraw)
+                };
             }
 
             // filter out ops that have been executed in final or candidate slots
             // TODO: in the re-execution followup, we should only filter out final-executed ops here (exec_status == Some(true))
-            if retain {
-                retain = !exec_statuses.contains_key(&op_info.id);
+            if eviction_reason.is_none() && exec_statuses.contains_key(&op_info.id) {
+                included_fees.push(op_info.fee);
+                eviction_reason = Some(PoolEvictionReason::ExecutedElsewhere);
             }
 
             // filter out ops that spend more than the sender's balance
-            if retain {
-                retain = match sender_balances.get(&op_info.creator_address) {
+            if eviction_reason.is_none() {
+                let retain = match sender_balances.get(&op_info.creator_address) {
                     Some(v) => &op_info.max_spending <= v,
                     None => false, // filter out ops for which the sender does not exist
                 };
+                if !retain {
+                    eviction_reason = Some(PoolEvictionReason::BalanceOverflow);
+                }
             }
 
-            if !retain {
-                removed.insert(op_info.id);
+            if let Some(reason) = eviction_reason {
+                evictions.push((op_info.id, op_info.creator_address, reason));
                 return false;
             }
             true
         });
-        // drop from storage
-        self.storage.drop_operation_refs(&removed);
+        self.drop_evicted_operations(&evictions);
+        self.record_evictions(evictions);
+
+        // remember the fees of the included ops, for fee suggestions
+        let max_recent_fees = (self.config.max_operations_per_block as usize)
+            .saturating_mul(self.config.thread_count as usize);
+        for fee in included_fees {
+            if self.recent_inclusion_fees.len() >= max_recent_fees {
+                self.recent_inclusion_fees.pop_front();
+            }
+            self.recent_inclusion_fees.push_back(fee);
+        }
     }
 
     /// Eliminate all operations that would cause a sender balance overflow.
     /// Assumes that the ops are sorted by ascending score.
     fn eliminate_balance_overflows(&mut self, sender_balances: &PreHashMap<Address, Amount>) {
         let mut balance_cache = PreHashMap::default();
-        let mut removed = PreHashSet::default();
+        let mut evictions = Vec::new();
         self.sorted_ops.retain(|op_info| {
             let balance = balance_cache
                 .entry(op_info.creator_address)
@@ -209,30 +356,38 @@ impl OperationPool {
                     true
                 }
                 None => {
-                    removed.insert(op_info.id);
+                    evictions.push((
+                        op_info.id,
+                        op_info.creator_address,
+                        PoolEvictionReason::BalanceOverflow,
+                    ));
                     false
                 }
             }
         });
-        // drop from storage
-        self.storage.drop_operation_refs(&removed);
+        self.drop_evicted_operations(&evictions);
+        self.record_evictions(evictions);
     }
 
     /// Truncates the container to the max allowed size
     fn truncate_container(&mut self) {
         if self.sorted_ops.len() > self.config.max_operation_pool_size {
-            let mut removed = PreHashSet::default();
+            let mut evictions = Vec::new();
             for op_info in self
                 .sorted_ops
                 .iter()
                 .skip(self.config.max_operation_pool_size)
             {
-                removed.insert(op_info.id);
+                evictions.push((
+                    op_info.id,
+                    op_info.creator_address,
+                    PoolEvictionReason::Truncated,
+                ));
             }
             self.sorted_ops
                 .truncate(self.config.max_operation_pool_size);
-            // drop from storage
-            self.storage.drop_operation_refs(&removed);
+            self.drop_evicted_operations(&evictions);
+            self.record_evictions(evictions);
         }
     }
 
@@ -252,77 +407,15 @@ impl OperationPool {
         .expect("could not get current slot")
         .map_or(0, |s| s.period);
 
-        let mut scores = PreHashMap::with_capacity(self.sorted_ops.len());
-        for op_info in &self.sorted_ops {
-            // fee factor
-            // (we add 1 to still sort zero-fee ops)
-            let fee_factor = op_info.fee.to_raw().saturating_add(1) as f32;
-
-            // size score:
-            //    0% of block size => score 1
-            //    100% of block size => score 0
-            let size_score = 1.0 - (op_info.size as f32) / (self.config.max_block_size as f32);
-
-            // gas score:
-            //    0% of block gas => score 1
-            //    100% of block gas => score 0
-            let gas_score =
-                1.0 - (op_info.max_gas_usage as f32) / (self.config.max_block_gas as f32);
-
-            // general resource score (mean of gas and size scores)
-            let epsilon_resource_factor = 0.0001; // avoids zero score when gas and size are a perfect fit in the block
-            let resource_factor = (epsilon_resource_factor + size_score + gas_score)
-                / (2.0 + epsilon_resource_factor);
-
-            // inclusion probability factor
-            //    If we are selected to produce a block in a long time,
-            //    there is exponential likelihood that someone includes the op before us.
-            let tau_inclusion = 2.0; // exponential decay factor
-            let earliest_inclusion_opportunity = pos_draws.iter().find_map(|s| {
-                if s.thread == op_info.thread
-                    && op_info.validity_period_range.contains(&s.period)
-                    && s.period >= now_period.saturating_sub(1)
-                {
-                    Some(s.period)
-                } else {
-                    None
-                }
-            });
-            let inclusion_factor =
-                if let Some(earliest_inclusion_opportunity) = earliest_inclusion_opportunity {
-                    // compute the number of slots other stakers have available to include the op before we do
-                    let foreign_opportunities = earliest_inclusion_opportunity.saturating_sub(max(
-                        now_period.saturating_add(1),
-                        *op_info.validity_period_range.start(),
-                    ));
-                    (-(foreign_opportunities as f32) / tau_inclusion).exp()
-                } else {
-                    // no inclusion opportunity => score 0
-                    0.0
-                };
-
-            /* TODO: re-execution followup
-            // If the op was executed previously, there is still an exponentially decaying chance of its block being cancelled
-            // so that it can be reincluded.
-            // We approximate it with a constant factor for simplicity since we don't have the inclusion slot for now.
-            let reexecution_penalty = 1.0 / 1000.0; // re-execution penalty factor
-            let reexecution_factor = if exec_statuses.contains_key(&op_info.id) {
-                // executed previously
-                reexecution_penalty
-            } else {
-                // not executed previously => score 1
-                1.0
-            };
-            */
-
-            // compute the score as being the product of all the factors and the fee
-            let score = fee_factor * resource_factor * inclusion_factor;
-            //  * reexecution_factor; // TODO: re-execution followup
-
-            // store the score
-            scores.insert(op_info.id, score);
-        }
-        scores
+        let context = ScoringContext {
+            config: &self.config,
+            pos_draws,
+            now_period,
+        };
+        self.sorted_ops
+            .iter()
+            .map(|op_info| (op_info.id, self.scorer.score(op_info, &context)))
+            .collect()
     }
 
     /// Refresh the pool.
@@ -358,6 +451,81 @@ impl OperationPool {
 
         // eliminate container size overflows
         self.truncate_container();
+
+        // keep the scores of the remaining operations, for inspection
+        self.scores = scores;
+        let op_refs = self.storage.get_op_refs();
+        self.scores.retain(|id, _| op_refs.contains(id));
+
+        // forget the operations that left the pool
+        self.replaceable_ops.retain(|_, ops| {
+            ops.retain(|(id, _)| op_refs.contains(id));
+            !ops.is_empty()
+        });
+    }
+
+    /// Whether a limit is set on the pending operations of a single sender
+    fn has_sender_quotas(&self) -> bool {
+        self.config.max_operations_per_sender.is_some()
+            || self.config.max_operations_size_per_sender.is_some()
+            || self.config.max_operations_gas_per_sender.is_some()
+    }
+
+    /// Index a pending operation for the per-sender limits,
+    /// unless no limit is set or its sender is in the quota allowlist.
+    /// Returns whether the operation was indexed.
+    fn index_sender_op(&mut self, op_info: &OperationInfo) -> bool {
+        if !self.has_sender_quotas()
+            || self
+                .sender_quota_allowlist
+                .contains(&op_info.creator_address)
+        {
+            return false;
+        }
+        self.sender_ops
+            .entry(op_info.creator_address)
+            .or_default()
+            .push(SenderOp {
+                id: op_info.id,
+                fee: op_info.fee,
+                size: op_info.size,
+                max_gas_usage: op_info.max_gas_usage,
+            });
+        true
+    }
+
+    /// Enforce the per-sender limits on operations being added to the pool, and index the ones that are kept.
+    /// When a sender exceeds one of its limits, its operations with the highest fees are kept,
+    /// the pending ones being kept first on equal fees.
+    ///
+    /// Returns the ids of the new operations to reject and of the pending operations to evict.
+    fn apply_sender_quotas(&mut self, new_ops: &[OperationInfo]) -> PreHashSet<OperationId> {
+        let mut excluded = PreHashSet::default();
+        let max_count = self.config.max_operations_per_sender.unwrap_or(usize::MAX);
+        let max_size = self
+            .config
+            .max_operations_size_per_sender
+            .unwrap_or(usize::MAX);
+        let max_gas = self
+            .config
+            .max_operations_gas_per_sender
+            .unwrap_or(u64::MAX);
+
+        let mut senders = PreHashSet::default();
+        for op_info in new_ops {
+            if self.index_sender_op(op_info) {
+                senders.insert(op_info.creator_address);
+            }
+        }
+        for sender in senders {
+            let Some(sender_ops) = self.sender_ops.get_mut(&sender) else {
+                continue;
+            };
+            if !sender_ops.fits(max_count, max_size, max_gas) {
+                excluded.extend(sender_ops.keep_best(max_count, max_size, max_gas));
+            }
+        }
+        excluded
     }
 
     /// Get the number of stored elements
@@ -370,6 +538,99 @@ impl OperationPool {
         self.storage.get_op_refs().contains(id)
     }
 
+    /// Drop operations evicted from `sorted_ops` from the storage, the scores and the per-sender index
+    fn drop_evicted_operations(
+        &mut self,
+        evictions: &[(OperationId, Address, PoolEvictionReason)],
+    ) {
+        let removed: PreHashSet<OperationId> = evictions.iter().map(|(id, _, _)| *id).collect();
+        self.storage.drop_operation_refs(&removed);
+        for (id, creator_address, _) in evictions {
+            self.scores.remove(id);
+            if let Some(sender_ops) = self.sender_ops.get_mut(creator_address) {
+                sender_ops.remove(id);
+                if sender_ops.ops.is_empty() {
+                    self.sender_ops.remove(creator_address);
+                }
+            }
+        }
+    }
+
+    /// Record operations evicted from the pool in the bounded eviction log
+    fn record_evictions(&mut self, evictions: Vec<(OperationId, Address, PoolEvictionReason)>) {
+        let timestamp = MassaTime::now();
+        for (id, creator_address, reason) in evictions {
+            if self.config.operation_eviction_log_size == 0 {
+                return;
+            }
+            if self.evictions.len() >= self.config.operation_eviction_log_size {
+                self.evictions.pop_front();
+            }
+            self.evictions.push_back(PoolOperationEviction {
+                id,
+                creator_address,
+                reason,
+                timestamp,
+            });
+        }
+    }
+
+    /// Get the operations of the pool with their score, and the latest evictions from the pool.
+    /// If a sender is given, only its operations are listed.
+    /// At most `limit` operations are listed after skipping the `offset` best ones, along with the `limit` latest evictions.
+    pub fn get_pool_operations(
+        &self,
+        sender: Option<&Address>,
+        offset: usize,
+        limit: usize,
+    ) -> PoolOperations {
+        let is_listed = |address: &Address| sender.map_or(true, |sender| sender == address);
+        let mut recent_evictions: Vec<PoolOperationEviction> = self
+            .evictions
+            .iter()
+            .rev()
+            .filter(|eviction| is_listed(&eviction.creator_address))
+            .take(limit)
+            .cloned()
+            .collect();
+        recent_evictions.reverse();
+        PoolOperations {
+            operations: self
+                .sorted_ops
+                .iter()
+                .filter(|op_info| is_listed(&op_info.creator_address))
+                .skip(offset)
+                .take(limit)
+                .map(|op_info| PoolOperationInfo {
+                    id: op_info.id,
+                    creator_address: op_info.creator_address,
+                    thread: op_info.thread,
+                    fee: op_info.fee,
+                    score: self.scores.get(&op_info.id).copied(),
+                    validity_start_period: *op_info.validity_period_range.start(),
+                    validity_end_period: *op_info.validity_period_range.end(),
+                    size: op_info.size,
+                    max_gas_usage: op_info.max_gas_usage,
+                })
+                .collect(),
+            recent_evictions,
+        }
+    }
+
+    /// Get the number of operations rejected or evicted because their sender exceeded one of its limits
+    pub fn get_quota_rejected_count(&self) -> u64 {
+        self.quota_rejected_count
+    }
+
+    /// Get the operations of the pool
+    pub(crate) fn get_operations(&self) -> Vec<SecureShareOperation> {
+        let ops = self.storage.read_operations();
+        self.sorted_ops
+            .iter()
+            .filter_map(|op_info| ops.get(&op_info.id).cloned())
+            .collect()
+    }
+
     /// notify of new final slot
     pub(crate) fn notify_final_cs_periods(&mut self, final_cs_periods: &[u64]) {
         // update internal final slot counter
@@ -380,11 +641,60 @@ impl OperationPool {
         );
     }
 
+    /// Replace-by-fee: an operation replaces the pending ones of the same sender (hence in the same thread)
+    /// with the same expire period (hence overlapping validity ranges),
+    /// if its fee is higher than theirs by at least `replace_by_fee_min_increase_percent`.
+    ///
+    /// Note that the replaced operations are only evicted from our pool:
+    /// as operations have no nonce, they remain valid and may still be included by other nodes.
+    ///
+    /// Returns the ids of the replaced operations.
+    fn replace_by_fee(&mut self, op_info: &OperationInfo) -> Vec<OperationId> {
+        let Some(min_increase_percent) = self.config.replace_by_fee_min_increase_percent else {
+            return Vec::new();
+        };
+        let mut replaced = Vec::new();
+        let same_sender_ops = self
+            .replaceable_ops
+            .entry((
+                op_info.creator_address,
+                *op_info.validity_period_range.end(),
+            ))
+            .or_default();
+        same_sender_ops.retain(|(id, fee)| {
+            // the fee must be strictly higher than fee * (100 + min_increase_percent) / 100
+            let replaces = u128::from(op_info.fee.to_raw()).saturating_mul(100)
+                > u128::from(fee.to_raw())
+                    .saturating_mul(u128::from(min_increase_percent).saturating_add(100));
+            if replaces {
+                replaced.push(*id);
+            }
+            !replaces
+        });
+        same_sender_ops.push((op_info.id, op_info.fee));
+        replaced
+    }
+
+    /// Remember the ids of operations replaced by fee, keeping at most `max_operation_pool_size` of them
+    fn remember_replaced_ops(&mut self, replaced_op_ids: &PreHashSet<OperationId>) {
+        for id in replaced_op_ids {
+            if self.replaced_ops.len() >= self.config.max_operation_pool_size {
+                if let Some(forgotten) = self.replaced_ops.pop_front() {
+                    self.replaced_op_ids.remove(&forgotten);
+                }
+            }
+            if self.replaced_op_ids.insert(*id) {
+                self.replaced_ops.push_back(*id);
+            }
+        }
+    }
+
     /// Add a list of operations to the end of the pool.
     /// They will be cleaned up at the next refresh.
     pub(crate) fn add_operations(&mut self, mut ops_storage: Storage) {
-        // List all the new operations
+        // List all the new operations, ignoring the ones that were replaced by fee
         let mut new_op_ids = ops_storage.get_op_refs() - self.storage.get_op_refs();
+        new_op_ids.retain(|id| !self.replaced_op_ids.contains(id));
 
         // If there are too many extra operations,
         // we don't want the container to fill up too much in-between refreshes so we drop any excess.
@@ -395,11 +705,22 @@ impl OperationPool {
             .saturating_add(new_op_ids.len())
             .saturating_sub(self.config.max_operation_pool_size)
             .saturating_sub(self.config.max_operation_pool_excess_items);
-        for _ in 0..dropped_items {
-            if let Some(id) = new_op_ids.iter().next().copied() {
-                new_op_ids.remove(&id);
-            } else {
-                break;
+        let mut evictions = Vec::new();
+        {
+            let ops = ops_storage.read_operations();
+            for _ in 0..dropped_items {
+                if let Some(id) = new_op_ids.iter().next().copied() {
+                    new_op_ids.remove(&id);
+                    if let Some(op) = ops.get(&id) {
+                        evictions.push((
+                            id,
+                            op.content_creator_address,
+                            PoolEvictionReason::Truncated,
+                        ));
+                    }
+                } else {
+                    break;
+                }
             }
         }
         if dropped_items > 0 {
@@ -413,31 +734,114 @@ impl OperationPool {
         // Note that the added items are put at the end of the sorted ops
         // so that they can still be picked for block production before refresh but with low priority
         // because in that case we don't know anything about their quality.
+        let mut replaced_op_ids = PreHashSet::default();
+        let mut replaced_evictions = Vec::new();
+        let mut new_op_infos = Vec::with_capacity(new_op_ids.len());
         {
             let ops = ops_storage.read_operations();
             for new_op_id in &new_op_ids {
                 let op = ops
                     .get(new_op_id)
                     .expect("operation not found in storage but listed as owned");
-
-                // Broadcast operations to active channel subscribers.
-                if self.config.broadcast_enabled {
-                    if let Err(err) = self.channels.broadcasts.operation_sender.send(op.clone()) {
-                        trace!("error, failed to broadcast operations {}: {}", op.id, err);
-                    }
-                }
-
-                self.sorted_ops.push(OperationInfo::from_op(
+                let op_info = OperationInfo::from_op(
                     op,
                     self.config.operation_validity_periods,
                     self.config.roll_price,
                     self.config.thread_count,
                     self.config.base_operation_gas_cost,
                     self.config.sp_compilation_cost,
-                ));
+                );
+                for replaced_op_id in self.replace_by_fee(&op_info) {
+                    replaced_op_ids.insert(replaced_op_id);
+                    replaced_evictions.push((
+                        replaced_op_id,
+                        op_info.creator_address,
+                        PoolEvictionReason::ReplacedByFee,
+                    ));
+                }
+                new_op_infos.push(op_info);
+            }
+        }
+
+        // Evict the replaced ops, and stop propagating them.
+        if !replaced_op_ids.is_empty() {
+            debug!("replacing {} operations by fee", replaced_op_ids.len());
+            self.sorted_ops
+                .retain(|op_info| !replaced_op_ids.contains(&op_info.id));
+            new_op_infos.retain(|op_info| !replaced_op_ids.contains(&op_info.id));
+            new_op_ids.retain(|id| !replaced_op_ids.contains(id));
+            self.drop_evicted_operations(&replaced_evictions);
+            evictions.extend(replaced_evictions);
+            self.remember_replaced_ops(&replaced_op_ids);
+            if let Err(err) = self
+                .channels
+                .protocol_controller
+                .notify_operations_replaced(replaced_op_ids)
+            {
+                warn!("could not notify protocol of replaced operations: {}", err);
             }
         }
 
+        // Enforce the per-sender limits: reject the new ops, or evict the pending ones, with the lowest fees.
+        let excluded_op_ids = self.apply_sender_quotas(&new_op_infos);
+        if !excluded_op_ids.is_empty() {
+            let mut rejected_evictions = Vec::new();
+            let mut pending_evictions = Vec::new();
+            let replaceable_ops = &mut self.replaceable_ops;
+            let mut exclude =
+                |op_info: &OperationInfo,
+                 excluded: &mut Vec<(OperationId, Address, PoolEvictionReason)>| {
+                    if !excluded_op_ids.contains(&op_info.id) {
+                        return false;
+                    }
+                    if let Some(ops) = replaceable_ops.get_mut(&(
+                        op_info.creator_address,
+                        *op_info.validity_period_range.end(),
+                    )) {
+                        ops.retain(|(id, _)| id != &op_info.id);
+                    }
+                    excluded.push((
+                        op_info.id,
+                        op_info.creator_address,
+                        PoolEvictionReason::SenderQuota,
+                    ));
+                    true
+                };
+            new_op_infos.retain(|op_info| !exclude(op_info, &mut rejected_evictions));
+            self.sorted_ops
+                .retain(|op_info| !exclude(op_info, &mut pending_evictions));
+            debug!(
+                "senders exceeding their limits: rejecting {} new operations and evicting {} pending ones",
+                rejected_evictions.len(),
+                pending_evictions.len()
+            );
+            for (id, _, _) in &rejected_evictions {
+                new_op_ids.remove(id);
+            }
+            self.drop_evicted_operations(&pending_evictions);
+            self.quota_rejected_count = self
+                .quota_rejected_count
+                .saturating_add((rejected_evictions.len() + pending_evictions.len()) as u64);
+            evictions.extend(rejected_evictions);
+            evictions.extend(pending_evictions);
+        }
+
+        // Broadcast the accepted operations to active channel subscribers.
+        if self.config.broadcast_enabled {
+            let ops = ops_storage.read_operations();
+            for op in new_op_infos
+                .iter()
+                .filter_map(|op_info| ops.get(&op_info.id))
+            {
+                if let Err(err) = self.channels.broadcasts.operation_sender.send(op.clone()) {
+                    trace!("error, failed to broadcast operations {}: {}", op.id, err);
+                }
+            }
+        }
+
+        self.sorted_ops.extend(new_op_infos);
+        self.record_evictions(evictions);
+
         // This will add the new ops to the storage without taking locks.
         // It just take the local references from `ops_storage` if they are not in `self.storage` yet.
         // If the objects are already in `self.storage` the references in ops_storage it will not add them to `self.storage` and
@@ -451,70 +855,181 @@ impl OperationPool {
         ));
     }
 
+    /// Insert operations saved on the last stop, before the pool accepts any input.
+    /// They were already accepted by the pool before the restart: unlike `add_operations`,
+    /// they are not broadcast again, and neither replace nor evict pending operations.
+    /// They will be cleaned up at the next refresh.
+    pub(crate) fn reload_operations(&mut self, mut ops_storage: Storage) {
+        let new_op_ids = ops_storage.get_op_refs() - self.storage.get_op_refs();
+        {
+            let ops = ops_storage.read_operations();
+            for new_op_id in &new_op_ids {
+                let op = ops
+                    .get(new_op_id)
+                    .expect("operation not found in storage but listed as owned");
+                let op_info = OperationInfo::from_op(
+                    op,
+                    self.config.operation_validity_periods,
+                    self.config.roll_price,
+                    self.config.thread_count,
+                    self.config.base_operation_gas_cost,
+                    self.config.sp_compilation_cost,
+                );
+                // they can still be replaced by fee later on
+                if self.config.replace_by_fee_min_increase_percent.is_some() {
+                    self.replaceable_ops
+                        .entry((
+                            op_info.creator_address,
+                            *op_info.validity_period_range.end(),
+                        ))
+                        .or_default()
+                        .push((op_info.id, op_info.fee));
+                }
+                self.index_sender_op(&op_info);
+                self.sorted_ops.push(op_info);
+            }
+        }
+        self.storage.extend(ops_storage.split_off(
+            &Default::default(),
+            &new_op_ids,
+            &Default::default(),
+        ));
+    }
+
+    /// Suggest a fee for an operation sent in a given thread, so that it gets included soon.
+    ///
+    /// The suggestion is the highest of:
+    /// - the fee of the best operation of the thread that would not fit in the next block,
+    ///   which is zero if all the operations of the thread fit in it
+    /// - the median fee of the latest operations included in blocks
+    pub fn get_fee_suggestion(&self, thread: u8) -> Amount {
+        // find the first operation of the thread, from best to worst, that does not fit in a block
+        let mut remaining_space = self.config.max_block_size as usize;
+        let mut remaining_gas = self.config.max_block_gas;
+        let mut remaining_ops = self.config.max_operations_per_block;
+        let marginal_fee = self
+            .sorted_ops
+            .iter()
+            .filter(|op_info| op_info.thread == thread)
+            .find(|op_info| {
+                if remaining_ops == 0
+                    || op_info.size > remaining_space
+                    || op_info.max_gas_usage > remaining_gas
+                {
+                    return true;
+                }
+                remaining_space -= op_info.size;
+                remaining_gas -= op_info.max_gas_usage;
+                remaining_ops -= 1;
+                false
+            })
+            .map(|op_info| op_info.fee)
+            .unwrap_or_default();
+
+        // median fee of the recently included operations
+        let mut recent_fees: Vec<Amount> = self.recent_inclusion_fees.iter().copied().collect();
+        recent_fees.sort_unstable();
+        let median_fee = recent_fees
+            .get(recent_fees.len() / 2)
+            .copied()
+            .unwrap_or_default();
+
+        max(marginal_fee, median_fee)
+    }
+
     /// get operations for block creation
     ///
     /// Searches the available operations, and selects the sub-set of operations that:
     /// - fit inside the block
     /// - is the most profitable for block producer
     pub fn get_block_operations(&self, slot: &Slot) -> (Vec<OperationId>, Storage) {
-        // init list of selected operation IDs
-        let mut op_ids = Vec::new();
+        let op_ids = select_block_operations(&self.sorted_ops, slot, &self.config);
 
-        // init remaining space
-        let mut remaining_space = self.config.max_block_size as usize;
-        // init remaining gas
-        let mut remaining_gas = self.config.max_block_gas;
-        // init remaining number of operations
-        let mut remaining_ops = self.config.max_operations_per_block;
-
-        // iterate over pool operations in the right thread, from best to worst
-        for op_info in &self.sorted_ops {
-            // if we have reached the maximum number of operations, stop
-            if remaining_ops == 0 {
-                break;
-            }
+        // generate storage
+        let mut res_storage = self.storage.clone_without_refs();
+        let claim_ops: PreHashSet<OperationId> = op_ids.iter().copied().collect();
+        let claimed_ops = res_storage.claim_operation_refs(&claim_ops);
+        if claimed_ops.len() != claim_ops.len() {
+            panic!("could not claim all operations from storage");
+        }
 
-            // check thread
-            if op_info.thread != slot.thread {
-                continue;
-            }
+        (op_ids, res_storage)
+    }
+}
 
-            // exclude ops for which the block slot is outside of their validity range
-            if !op_info.validity_period_range.contains(&slot.period) {
-                continue;
-            }
+/// Select the operations of a block, iterating over the pool operations from best to worst.
+///
+/// Selects the operations that:
+/// - are in the thread of the block, and valid at its period
+/// - fit inside the block
+/// - do not exceed the max number of operations of their sender in a block
+pub fn select_block_operations(
+    sorted_ops: &[OperationInfo],
+    slot: &Slot,
+    config: &PoolConfig,
+) -> Vec<OperationId> {
+    // init list of selected operation IDs
+    let mut op_ids = Vec::new();
 
-            // exclude ops that are too large
-            if op_info.size > remaining_space {
-                continue;
-            }
+    // init remaining space
+    let mut remaining_space = config.max_block_size as usize;
+    // init remaining gas
+    let mut remaining_gas = config.max_block_gas;
+    // init remaining number of operations
+    let mut remaining_ops = config.max_operations_per_block;
+    // init number of selected operations per sender
+    let mut sender_op_counts: PreHashMap<Address, u32> = PreHashMap::default();
 
-            // exclude ops that require too much gas
-            if op_info.max_gas_usage > remaining_gas {
-                continue;
-            }
+    // iterate over pool operations in the right thread, from best to worst
+    for op_info in sorted_ops {
+        // if we have reached the maximum number of operations, stop
+        if remaining_ops == 0 {
+            break;
+        }
 
-            // here we consider the operation as accepted
-            op_ids.push(op_info.id);
+        // check thread
+        if op_info.thread != slot.thread {
+            continue;
+        }
 
-            // update remaining block space
-            remaining_space -= op_info.size;
+        // exclude ops for which the block slot is outside of their validity range
+        if !op_info.validity_period_range.contains(&slot.period) {
+            continue;
+        }
 
-            // update remaining block gas
-            remaining_gas -= op_info.max_gas_usage;
+        // exclude ops that are too large
+        if op_info.size > remaining_space {
+            continue;
+        }
 
-            // update remaining number of operations
-            remaining_ops -= 1;
+        // exclude ops that require too much gas
+        if op_info.max_gas_usage > remaining_gas {
+            continue;
         }
 
-        // generate storage
-        let mut res_storage = self.storage.clone_without_refs();
-        let claim_ops: PreHashSet<OperationId> = op_ids.iter().copied().collect();
-        let claimed_ops = res_storage.claim_operation_refs(&claim_ops);
-        if claimed_ops.len() != claim_ops.len() {
-            panic!("could not claim all operations from storage");
+        // exclude ops whose sender already has its max number of operations in the block
+        let sender_op_count = sender_op_counts.entry(op_info.creator_address).or_default();
+        if let Some(max_sender_ops) = config.max_block_operations_per_sender {
+            if *sender_op_count >= max_sender_ops {
+                continue;
+            }
         }
 
-        (op_ids, res_storage)
+        // here we consider the operation as accepted
+        op_ids.push(op_info.id);
+
+        // update remaining block space
+        remaining_space -= op_info.size;
+
+        // update remaining block gas
+        remaining_gas -= op_info.max_gas_usage;
+
+        // update remaining number of operations
+        remaining_ops -= 1;
+
+        // update the number of operations of the sender
+        *sender_op_count += 1;
     }
+
+    op_ids
 }
@@ -0,0 +1,127 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Scoring of the operations of the pool.
+//! The pool is sorted by decreasing score, and blocks are filled greedily in that order.
+
+use massa_models::slot::Slot;
+use massa_pool_exports::{OperationSelectionPolicy, PoolConfig};
+use std::{cmp::max, collections::BTreeSet};
+
+use crate::types::OperationInfo;
+
+/// Data shared by the scoring of all the operations of the pool
+pub struct ScoringContext<'a> {
+    /// pool configuration
+    pub config: &'a PoolConfig,
+    /// our PoS draws that are strictly after the last final slot of their thread
+    pub pos_draws: &'a BTreeSet<Slot>,
+    /// current period
+    pub now_period: u64,
+}
+
+impl ScoringContext<'_> {
+    /// Chances that we include the operation before other stakers do:
+    /// if we are selected to produce a block in a long time,
+    /// there is exponential likelihood that someone includes the op before us.
+    ///
+    /// Returns zero if we have no opportunity to include the operation.
+    pub fn inclusion_factor(&self, op_info: &OperationInfo) -> f32 {
+        let tau_inclusion = 2.0; // exponential decay factor
+        let earliest_inclusion_opportunity = self.pos_draws.iter().find_map(|s| {
+            if s.thread == op_info.thread
+                && op_info.validity_period_range.contains(&s.period)
+                && s.period >= self.now_period.saturating_sub(1)
+            {
+                Some(s.period)
+            } else {
+                None
+            }
+        });
+        if let Some(earliest_inclusion_opportunity) = earliest_inclusion_opportunity {
+            // compute the number of slots other stakers have available to include the op before we do
+            let foreign_opportunities = earliest_inclusion_opportunity.saturating_sub(max(
+                self.now_period.saturating_add(1),
+                *op_info.validity_period_range.start(),
+            ));
+            (-(foreign_opportunities as f32) / tau_inclusion).exp()
+        } else {
+            // no inclusion opportunity => score 0
+            0.0
+        }
+    }
+}
+
+/// Scores the operations of the pool: the ones with the highest scores are included first in our blocks
+pub trait OperationScorer: Send + Sync {
+    /// Compute the score of an operation
+    fn score(&self, op_info: &OperationInfo, context: &ScoringContext) -> f32;
+}
+
+/// Create the scorer of a selection policy
+pub fn new_operation_scorer(policy: OperationSelectionPolicy) -> Box<dyn OperationScorer> {
+    match policy {
+        OperationSelectionPolicy::Standard => Box::new(StandardScorer),
+        OperationSelectionPolicy::FeePerGas => Box::new(FeePerGasScorer),
+    }
+}
+
+/// Fee weighted by the block resources used and the inclusion factor
+pub struct StandardScorer;
+
+impl OperationScorer for StandardScorer {
+    fn score(&self, op_info: &OperationInfo, context: &ScoringContext) -> f32 {
+        // fee factor
+        // (we add 1 to still sort zero-fee ops)
+        let fee_factor = op_info.fee.to_raw().saturating_add(1) as f32;
+
+        // size score:
+        //    0% of block size => score 1
+        //    100% of block size => score 0
+        let size_score = 1.0 - (op_info.size as f32) / (context.config.max_block_size as f32);
+
+        // gas score:
+        //    0% of block gas => score 1
+        //    100% of block gas => score 0
+        let gas_score =
+            1.0 - (op_info.max_gas_usage as f32) / (context.config.max_block_gas as f32);
+
+        // general resource score (mean of gas and size scores)
+        let epsilon_resource_factor = 0.0001; // avoids zero score when gas and size are a perfect fit in the block
+        let resource_factor =
+            (epsilon_resource_factor + size_score + gas_score) / (2.0 + epsilon_resource_factor);
+
+        // inclusion probability factor
+        let inclusion_factor = context.inclusion_factor(op_info);
+
+        /* TODO: re-execution followup
+        // If the op was executed previously, there is still an exponentially decaying chance of its block being cancelled
+        // so that it can be reincluded.
+        // We approximate it with a constant factor for simplicity since we don't have the inclusion slot for now.
+        let reexecution_penalty = 1.0 / 1000.0; // re-execution penalty factor
+        let reexecution_factor = if exec_statuses.contains_key(&op_info.id) {
+            // executed previously
+            reexecution_penalty
+        } else {
+            // not executed previously => score 1
+            1.0
+        };
+        */
+
+        // compute the score as being the product of all the factors and the fee
+        fee_factor * resource_factor * inclusion_factor
+        //  * reexecution_factor; // TODO: re-execution followup
+    }
+}
+
+/// Fee per unit of gas, weighted by the inclusion factor.
+/// Sorting by fee density is the greedy approximation of the knapsack problem of filling a block's gas.
+pub struct FeePerGasScorer;
+
+impl OperationScorer for FeePerGasScorer {
+    fn score(&self, op_info: &OperationInfo, context: &ScoringContext) -> f32 {
+        // (we add 1 to still sort zero-fee ops)
+        let fee_factor = op_info.fee.to_raw().saturating_add(1) as f32;
+        let fee_per_gas = fee_factor / (max(op_info.max_gas_usage, 1) as f32);
+        fee_per_gas * context.inclusion_factor(op_info)
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Persistence of the operation and endorsement pools across restarts.
+//!
+//! On graceful stop, the pooled operations and endorsements are written to the path of the `PoolPersistenceConfig`:
+//! the number of operations followed by the serialized operations, then the same for the endorsements.
+//!
+//! On startup, they are read back before the pool accepts network input.
+//! The ones whose signature is invalid, or that expired while the node was stopped, are dropped.
+//! The others are inserted as they were: they are not broadcast again, and do not evict pending operations.
+
+use massa_models::config::{
+    MAX_DATASTORE_VALUE_LENGTH, MAX_FUNCTION_NAME_LENGTH, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+    MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH, MAX_PARAMETERS_SIZE,
+};
+use massa_models::endorsement::{EndorsementDeserializer, SecureShareEndorsement};
+use massa_models::operation::{OperationDeserializer, SecureShareOperation};
+use massa_models::secure_share::{SecureShareDeserializer, SecureShareSerializer};
+use massa_models::slot::Slot;
+use massa_models::timeslots::get_latest_block_slot_at_timestamp;
+use massa_pool_exports::{PoolConfig, PoolPersistenceConfig};
+use massa_serialization::{
+    DeserializeError, Deserializer, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
+};
+use massa_time::MassaTime;
+use std::io::{Error, ErrorKind};
+use std::ops::Bound::Included;
+use std::path::Path;
+
+fn invalid_data(message: impl std::fmt::Display) -> Error {
+    Error::new(ErrorKind::InvalidData, message.to_string())
+}
+
+/// Deserialize a list of items prefixed by their count
+fn deserialize_list<'a, T>(
+    buffer: &'a [u8],
+    max_count: u64,
+    deserializer: &impl Deserializer<T>,
+) -> Result<(&'a [u8], Vec<T>), Error> {
+    let (mut rest, count) = U64VarIntDeserializer::new(Included(0), Included(max_count))
+        .deserialize::<DeserializeError>(buffer)
+        .map_err(invalid_data)?;
+    let mut items = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let (next, item) = deserializer
+            .deserialize::<DeserializeError>(rest)
+            .map_err(invalid_data)?;
+        items.push(item);
+        rest = next;
+    }
+    Ok((rest, items))
+}
+
+/// Write the operations and endorsements of the pools to a file
+pub(crate) fn save_pools(
+    path: &Path,
+    operations: &[SecureShareOperation],
+    endorsements: &[SecureShareEndorsement],
+) -> Result<(), Error> {
+    let count_serializer = U64VarIntSerializer::new();
+    let secure_share_serializer = SecureShareSerializer::new();
+    let mut buffer = Vec::new();
+    count_serializer
+        .serialize(&(operations.len() as u64), &mut buffer)
+        .map_err(invalid_data)?;
+    for operation in operations {
+        secure_share_serializer
+            .serialize(operation, &mut buffer)
+            .map_err(invalid_data)?;
+    }
+    count_serializer
+        .serialize(&(endorsements.len() as u64), &mut buffer)
+        .map_err(invalid_data)?;
+    for endorsement in endorsements {
+        secure_share_serializer
+            .serialize(endorsement, &mut buffer)
+            .map_err(invalid_data)?;
+    }
+
+    // write to a temporary file first, so that a crash while writing does not leave a truncated file
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, buffer)?;
+    std::fs::rename(tmp_path, path)
+}
+
+/// Read the operations and endorsements written by `save_pools`,
+/// and drop the ones whose signature is invalid or that have expired.
+pub(crate) fn load_pools(
+    persistence: &PoolPersistenceConfig,
+    config: &PoolConfig,
+) -> Result<(Vec<SecureShareOperation>, Vec<SecureShareEndorsement>), Error> {
+    let buffer = std::fs::read(&persistence.path)?;
+
+    let operation_deserializer = SecureShareDeserializer::new(
+        OperationDeserializer::new(
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        ),
+        persistence.chain_id,
+    );
+    let endorsement_deserializer = SecureShareDeserializer::new(
+        EndorsementDeserializer::new(config.thread_count, config.max_block_endorsement_count),
+        persistence.chain_id,
+    );
+    let max_operation_count = config
+        .max_operation_pool_size
+        .saturating_add(config.max_operation_pool_excess_items)
+        as u64;
+    let max_endorsement_count = config
+        .max_endorsements_pool_size_per_thread
+        .saturating_mul(config.thread_count as usize) as u64;
+    let (rest, mut operations) =
+        deserialize_list(&buffer, max_operation_count, &operation_deserializer)?;
+    let (rest, mut endorsements) =
+        deserialize_list(rest, max_endorsement_count, &endorsement_deserializer)?;
+    if !rest.is_empty() {
+        return Err(invalid_data(
+            "unexpected data after the pooled endorsements",
+        ));
+    }
+
+    // the slot of the network at this time, or the start of the network if it has not started yet
+    let now_slot = get_latest_block_slot_at_timestamp(
+        config.thread_count,
+        config.t0,
+        config.genesis_timestamp,
+        MassaTime::now(),
+    )
+    .ok()
+    .flatten()
+    .unwrap_or_else(|| Slot::new(0, 0))
+    .max(Slot::new(config.last_start_period, 0));
+
+    // an operation is expired once all the slots of its expire period are in the past,
+    // an endorsement once its slot is in the past
+    operations
+        .retain(|op| op.content.expire_period >= now_slot.period && op.verify_signature().is_ok());
+    endorsements.retain(|endo| endo.content.slot >= now_slot && endo.verify_signature().is_ok());
+
+    Ok((operations, endorsements))
+}
@@ -17,14 +17,20 @@
 //! latest period given his own thread. All operation which doesn't fit these
 //! requirements are "irrelevant"
 //!
+use crate::operation_pool::select_block_operations;
 use crate::tests::tools::OpGenerator;
+use crate::types::OperationInfo;
 
 use super::tools::{
-    create_some_operations, default_mock_execution_controller, pool_test, PoolTestBoilerPlate,
+    create_some_operations, default_mock_execution_controller, pool_test, pool_test_with_allowlist,
+    PoolTestBoilerPlate,
 };
-use massa_models::{amount::Amount, config::ENDORSEMENT_COUNT, operation::OperationId, slot::Slot};
-use massa_pool_exports::PoolConfig;
+use massa_models::{
+    address::Address, amount::Amount, config::ENDORSEMENT_COUNT, operation::OperationId, slot::Slot,
+};
+use massa_pool_exports::{PoolConfig, PoolEvictionReason, PoolOperations};
 use massa_pos_exports::{MockSelectorController, Selection};
+use massa_signature::KeyPair;
 use std::{collections::BTreeMap, time::Duration};
 
 #[test]
@@ -205,6 +211,21 @@ fn test_pool() {
 
     pool_controller.add_operations(storage);
     std::thread::sleep(Duration::from_secs(3));
+
+    // the suggested fee of a thread is the fee of its best operation that does not fit in a block
+    for (thread, lst) in thread_tx_lists.iter().enumerate() {
+        let expected_fee = lst
+            .iter()
+            .rev()
+            .nth(pool_config.max_operations_per_block as usize)
+            .map(|(op, _)| op.content.fee)
+            .unwrap_or_default();
+        assert_eq!(
+            pool_controller.get_fee_suggestion(thread as u8),
+            expected_fee
+        );
+    }
+
     // // sort from bigger fee to smaller and truncate
     for lst in thread_tx_lists.iter_mut() {
         lst.reverse();
@@ -237,3 +258,277 @@ fn test_pool() {
     }
     pool_manager.stop();
 }
+
+/// Test that an operation replaces the pending one of the same sender with the same expire period
+/// if its fee is high enough.
+#[test]
+fn test_replace_by_fee() {
+    let execution_controller = default_mock_execution_controller();
+    let selector_controller = {
+        let mut res = Box::new(MockSelectorController::new());
+        res.expect_clone_box().times(2).returning(|| {
+            let mut story = MockSelectorController::new();
+            story
+                .expect_get_available_selections_in_range()
+                .returning(|slot_range, opt_addrs| {
+                    let mut all_slots = BTreeMap::new();
+                    let addr = *opt_addrs
+                        .expect("No addresses filter given")
+                        .iter()
+                        .next()
+                        .expect("No addresses given");
+                    for i in 0..15 {
+                        for j in 0..32 {
+                            let s = Slot::new(i, j);
+                            if slot_range.contains(&s) {
+                                all_slots.insert(
+                                    s,
+                                    Selection {
+                                        producer: addr,
+                                        endorsements: vec![addr; ENDORSEMENT_COUNT as usize],
+                                    },
+                                );
+                            }
+                        }
+                    }
+                    Ok(all_slots)
+                });
+            Box::new(story)
+        });
+        res
+    };
+    pool_test(
+        PoolConfig {
+            replace_by_fee_min_increase_percent: Some(10),
+            ..Default::default()
+        },
+        execution_controller,
+        selector_controller,
+        None,
+        |mut operation_pool, storage| {
+            let creator = KeyPair::generate(0).unwrap();
+            let op_gen = |fee: u64| {
+                OpGenerator::default()
+                    .expirery(2)
+                    .creator(creator.clone())
+                    .fee(Amount::from_raw(fee))
+                    .generate()
+            };
+            let (op_1, op_2, op_3) = (op_gen(100), op_gen(111), op_gen(120));
+            for op in [&op_1, &op_2, &op_3] {
+                let mut op_storage = storage.clone_without_refs();
+                op_storage.store_operations(vec![op.clone()]);
+                operation_pool.add_operations(op_storage);
+                // Allow some time for the pool to add the operation
+                std::thread::sleep(Duration::from_millis(200));
+            }
+
+            // the fee of op_2 is more than 10% higher than the one of op_1, but not the fee of op_3 compared to op_2
+            assert_eq!(
+                operation_pool.contains_operations(&[op_1.id, op_2.id, op_3.id]),
+                vec![false, true, true]
+            );
+
+            // the replacement of op_1 is in the eviction log
+            let creator_address = Address::from_public_key(&creator.get_public_key());
+            let pool_operations =
+                operation_pool.get_pool_operations(Some(creator_address), 0, usize::MAX);
+            let mut listed_ids: Vec<OperationId> = pool_operations
+                .operations
+                .iter()
+                .map(|op_info| op_info.id)
+                .collect();
+            listed_ids.sort();
+            let mut expected_ids = vec![op_2.id, op_3.id];
+            expected_ids.sort();
+            assert_eq!(listed_ids, expected_ids);
+
+            // the operations are paged
+            let page = operation_pool.get_pool_operations(Some(creator_address), 1, 1);
+            assert_eq!(page.operations.len(), 1);
+            assert_eq!(page.operations[0].id, pool_operations.operations[1].id);
+            assert_eq!(
+                operation_pool
+                    .get_pool_operations(Some(creator_address), 0, 0)
+                    .recent_evictions,
+                Vec::new()
+            );
+
+            assert_eq!(pool_operations.recent_evictions.len(), 1);
+            assert_eq!(pool_operations.recent_evictions[0].id, op_1.id);
+            assert_eq!(
+                pool_operations.recent_evictions[0].reason,
+                PoolEvictionReason::ReplacedByFee
+            );
+
+            // the replaced operation is rejected if received again
+            let mut op_storage = storage.clone_without_refs();
+            op_storage.store_operations(vec![op_1.clone()]);
+            operation_pool.add_operations(op_storage);
+            std::thread::sleep(Duration::from_millis(200));
+            assert_eq!(operation_pool.contains_operations(&[op_1.id]), vec![false]);
+
+            // nothing is listed for another sender
+            let other_address =
+                Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+            assert_eq!(
+                operation_pool.get_pool_operations(Some(other_address), 0, usize::MAX),
+                PoolOperations::default()
+            );
+        },
+    );
+}
+
+#[test]
+fn test_block_operations_per_sender_cap() {
+    let pool_config = PoolConfig {
+        max_block_operations_per_sender: Some(3),
+        ..Default::default()
+    };
+    let creator = KeyPair::generate(0).unwrap();
+    let op_gen = OpGenerator::default().expirery(3).creator(creator);
+    let other_op_gen = OpGenerator::default().expirery(3);
+    let sorted_ops: Vec<OperationInfo> = create_some_operations(5, &op_gen)
+        .iter()
+        .chain(&create_some_operations(1, &other_op_gen))
+        .map(|op| {
+            OperationInfo::from_op(
+                op,
+                pool_config.operation_validity_periods,
+                pool_config.roll_price,
+                pool_config.thread_count,
+                pool_config.base_operation_gas_cost,
+                pool_config.sp_compilation_cost,
+            )
+        })
+        .collect();
+
+    // only the 3 best operations of the sender are selected
+    let thread = sorted_ops[0].thread;
+    let selected = select_block_operations(&sorted_ops, &Slot::new(1, thread), &pool_config);
+    let expected: Vec<OperationId> = sorted_ops[..3]
+        .iter()
+        .chain(sorted_ops[5..].iter().filter(|op| op.thread == thread))
+        .map(|op| op.id)
+        .collect();
+    assert_eq!(selected, expected);
+
+    // without cap, all of them are selected
+    let pool_config = PoolConfig {
+        max_block_operations_per_sender: None,
+        ..pool_config
+    };
+    let selected = select_block_operations(&sorted_ops, &Slot::new(1, thread), &pool_config);
+    assert_eq!(
+        selected.len(),
+        sorted_ops.iter().filter(|op| op.thread == thread).count()
+    );
+}
+
+#[test]
+fn test_sender_quotas() {
+    let execution_controller = default_mock_execution_controller();
+    let selector_controller = {
+        let mut res = Box::new(MockSelectorController::new());
+        res.expect_clone_box().times(2).returning(|| {
+            let mut story = MockSelectorController::new();
+            story
+                .expect_get_available_selections_in_range()
+                .returning(|slot_range, opt_addrs| {
+                    let mut all_slots = BTreeMap::new();
+                    let addr = *opt_addrs
+                        .expect("No addresses filter given")
+                        .iter()
+                        .next()
+                        .expect("No addresses given");
+                    for i in 0..15 {
+                        for j in 0..32 {
+                            let s = Slot::new(i, j);
+                            if slot_range.contains(&s) {
+                                all_slots.insert(
+                                    s,
+                                    Selection {
+                                        producer: addr,
+                                        endorsements: vec![addr; ENDORSEMENT_COUNT as usize],
+                                    },
+                                );
+                            }
+                        }
+                    }
+                    Ok(all_slots)
+                });
+            Box::new(story)
+        });
+        res
+    };
+    let creator = KeyPair::generate(0).unwrap();
+    let trusted_creator = KeyPair::generate(0).unwrap();
+    let trusted_address = Address::from_public_key(&trusted_creator.get_public_key());
+    pool_test_with_allowlist(
+        PoolConfig {
+            max_operations_per_sender: Some(2),
+            replace_by_fee_min_increase_percent: None,
+            ..Default::default()
+        },
+        [trusted_address].into_iter().collect(),
+        execution_controller,
+        selector_controller,
+        None,
+        |mut operation_pool, storage| {
+            let op_gen = |creator: &KeyPair, fee: u64| {
+                OpGenerator::default()
+                    .expirery(2)
+                    .creator(creator.clone())
+                    .fee(Amount::from_raw(fee))
+                    .generate()
+            };
+            let ops = [
+                op_gen(&creator, 100),
+                op_gen(&creator, 300),
+                op_gen(&creator, 200),
+                op_gen(&creator, 50),
+            ];
+            let trusted_ops = [
+                op_gen(&trusted_creator, 100),
+                op_gen(&trusted_creator, 100),
+                op_gen(&trusted_creator, 100),
+            ];
+            for op in ops.iter().chain(trusted_ops.iter()) {
+                let mut op_storage = storage.clone_without_refs();
+                op_storage.store_operations(vec![op.clone()]);
+                operation_pool.add_operations(op_storage);
+                // Allow some time for the pool to add the operation
+                std::thread::sleep(Duration::from_millis(200));
+            }
+
+            // the pending op with the lowest fee is evicted by the third one, and the fourth one is rejected
+            let ids: Vec<OperationId> = ops.iter().map(|op| op.id).collect();
+            assert_eq!(
+                operation_pool.contains_operations(&ids),
+                vec![false, true, true, false]
+            );
+            assert_eq!(operation_pool.get_quota_rejected_operation_count(), 2);
+            let creator_address = Address::from_public_key(&creator.get_public_key());
+            let evictions = operation_pool
+                .get_pool_operations(Some(creator_address), 0, usize::MAX)
+                .recent_evictions;
+            assert_eq!(
+                evictions
+                    .iter()
+                    .map(|eviction| eviction.id)
+                    .collect::<Vec<_>>(),
+                vec![ops[0].id, ops[3].id]
+            );
+            assert!(evictions
+                .iter()
+                .all(|eviction| eviction.reason == PoolEvictionReason::SenderQuota));
+
+            // the trusted sender is not limited
+            let trusted_ids: Vec<OperationId> = trusted_ops.iter().map(|op| op.id).collect();
+            assert_eq!(
+                operation_pool.contains_operations(&trusted_ids),
+                vec![true, true, true]
+            );
+        },
+    );
+}
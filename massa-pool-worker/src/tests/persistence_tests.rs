@@ -0,0 +1,62 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use super::tools::{create_endorsement, create_some_operations, OpGenerator};
+use crate::persistence::{load_pools, save_pools};
+use massa_models::{config::CHAINID, slot::Slot};
+use massa_pool_exports::{PoolConfig, PoolPersistenceConfig};
+use massa_signature::KeyPair;
+use tempfile::tempdir;
+
+/// Save the pools and reload them: the expired operations and endorsements,
+/// and the ones with an invalid signature, are dropped.
+#[test]
+fn test_pools_persistence() {
+    let config = PoolConfig {
+        last_start_period: 5,
+        ..PoolConfig::default()
+    };
+    let keypair = KeyPair::generate(0).unwrap();
+    let valid_ops = create_some_operations(
+        3,
+        &OpGenerator::default().creator(keypair.clone()).expirery(10),
+    );
+    let expired_op = OpGenerator::default().expirery(4).generate();
+    let mut forged_op = OpGenerator::default().expirery(10).generate();
+    forged_op.signature = valid_ops[0].signature;
+    let valid_endorsement = create_endorsement(&keypair, 0, Slot::new(6, 0));
+    let expired_endorsement = create_endorsement(&keypair, 1, Slot::new(4, 0));
+
+    let dir = tempdir().expect("Unable to create a temp folder");
+    let persistence = PoolPersistenceConfig {
+        path: dir.path().join("pool").join("pool_dump.bin"),
+        chain_id: *CHAINID,
+    };
+    let path = &persistence.path;
+    let mut operations = valid_ops.clone();
+    operations.extend([expired_op, forged_op]);
+    save_pools(
+        path,
+        &operations,
+        &[valid_endorsement.clone(), expired_endorsement],
+    )
+    .unwrap();
+
+    let (reloaded_ops, reloaded_endorsements) = load_pools(&persistence, &config).unwrap();
+    assert_eq!(
+        reloaded_ops.iter().map(|op| op.id).collect::<Vec<_>>(),
+        valid_ops.iter().map(|op| op.id).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        reloaded_endorsements
+            .iter()
+            .map(|endo| endo.id)
+            .collect::<Vec<_>>(),
+        vec![valid_endorsement.id]
+    );
+
+    // a corrupted file is rejected
+    let mut corrupted = std::fs::read(path).unwrap();
+    corrupted.truncate(corrupted.len() - 1);
+    std::fs::write(path, corrupted).unwrap();
+    assert!(load_pools(&persistence, &config).is_err());
+}
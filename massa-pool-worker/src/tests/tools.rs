@@ -13,12 +13,13 @@ use massa_models::{
     block_id::BlockId,
     endorsement::{Endorsement, EndorsementSerializer, SecureShareEndorsement},
     operation::{Operation, OperationSerializer, OperationType, SecureShareOperation},
-    prehash::PreHashMap,
+    prehash::{PreHashMap, PreHashSet},
     secure_share::SecureShareContent,
     slot::Slot,
 };
 use massa_pool_exports::{PoolBroadcasts, PoolChannels, PoolConfig, PoolController, PoolManager};
 use massa_pos_exports::MockSelectorController as AutoMockSelectorController;
+use massa_protocol_exports::MockProtocolController;
 use massa_signature::KeyPair;
 use massa_storage::Storage;
 use massa_wallet::test_exports::create_test_wallet;
@@ -123,8 +124,11 @@ impl PoolTestBoilerPlate {
                     operation_sender,
                 },
                 selector: selector_story,
+                protocol_controller: default_mock_protocol_controller(),
             },
             wallet,
+            PreHashSet::default(),
+            None,
         );
 
         Self {
@@ -143,6 +147,27 @@ pub fn pool_test<F>(
     test: F,
 ) where
     F: FnOnce(Box<dyn PoolController>, Storage),
+{
+    pool_test_with_allowlist(
+        cfg,
+        PreHashSet::default(),
+        execution_controller,
+        selector,
+        staker,
+        test,
+    )
+}
+
+/// Same as `pool_test`, exempting the senders of `sender_quota_allowlist` from the per-sender limits
+pub fn pool_test_with_allowlist<F>(
+    cfg: PoolConfig,
+    sender_quota_allowlist: PreHashSet<Address>,
+    execution_controller: Box<MockExecutionController>,
+    selector: Box<AutoMockSelectorController>,
+    staker: Option<(Address, KeyPair)>,
+    test: F,
+) where
+    F: FnOnce(Box<dyn PoolController>, Storage),
 {
     let endorsement_sender = broadcast::channel(2000).0;
     let operation_sender = broadcast::channel(5000).0;
@@ -165,8 +190,11 @@ pub fn pool_test<F>(
                 operation_sender,
             },
             selector,
+            protocol_controller: default_mock_protocol_controller(),
         },
         wallet,
+        sender_quota_allowlist,
+        None,
     );
     test(pool_controller, storage);
     pool_manager.stop();
@@ -218,3 +246,18 @@ pub fn default_mock_execution_controller() -> Box<MockExecutionController> {
     });
     res
 }
+
+// Create a protocol controller accepting the notifications of replaced operations
+pub fn default_mock_protocol_controller() -> Box<MockProtocolController> {
+    let mut res = Box::new(MockProtocolController::new());
+    res.expect_clone_box().returning(|| {
+        let mut story = MockProtocolController::new();
+        story
+            .expect_notify_operations_replaced()
+            .returning(|_| Ok(()));
+        Box::new(story)
+    });
+    res.expect_notify_operations_replaced()
+        .returning(|_| Ok(()));
+    res
+}
@@ -5,21 +5,29 @@ use massa_models::{
 };
 use std::ops::RangeInclusive;
 
+/// Operation of the pool, with the data used to score and select it
 #[derive(Debug, Clone)]
 pub struct OperationInfo {
+    /// operation id
     pub id: OperationId,
+    /// serialized size of the operation
     pub size: usize,
     /// The maximum amount of gas that can be used by an operation.
     pub max_gas_usage: u64,
+    /// address of the sender
     pub creator_address: Address,
+    /// thread of the sender
     pub thread: u8,
+    /// fee of the operation
     pub fee: Amount,
     /// max amount that the op might spend from the sender's balance
     pub max_spending: Amount,
+    /// periods during which the operation can be included in a block
     pub validity_period_range: RangeInclusive<u64>,
 }
 
 impl OperationInfo {
+    /// Build the info of an operation
     pub fn from_op(
         op: &SecureShareOperation,
         operation_validity_periods: u64,
@@ -5,9 +5,11 @@
 use crate::controller_impl::{Command, PoolManagerImpl};
 use crate::denunciation_pool::DenunciationPool;
 use crate::operation_pool::OperationPool;
+use crate::persistence::load_pools;
 use crate::{controller_impl::PoolControllerImpl, endorsement_pool::EndorsementPool};
-use massa_pool_exports::PoolConfig;
+use massa_models::{address::Address, prehash::PreHashSet};
 use massa_pool_exports::{PoolChannels, PoolController, PoolManager};
+use massa_pool_exports::{PoolConfig, PoolPersistenceConfig};
 use massa_storage::Storage;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
@@ -18,7 +20,7 @@ use std::{
     thread,
     thread::JoinHandle,
 };
-use tracing::warn;
+use tracing::{info, warn};
 
 /// Endorsement pool write thread instance
 pub(crate) struct EndorsementPoolThread {
@@ -181,12 +183,18 @@ impl DenunciationPoolThread {
 }
 
 /// Start pool manager and controller
+///
+/// The senders of `sender_quota_allowlist` are exempted from the per-sender limits of the operation pool.
+/// If `persistence` is set, the operations and endorsements saved on the last stop are reloaded
+/// before the pool accepts any input.
 #[allow(clippy::type_complexity)]
 pub fn start_pool_controller(
     config: PoolConfig,
     storage: &Storage,
     channels: PoolChannels,
     wallet: Arc<RwLock<Wallet>>,
+    sender_quota_allowlist: PreHashSet<Address>,
+    persistence: Option<PoolPersistenceConfig>,
 ) -> (Box<dyn PoolManager>, Box<dyn PoolController>) {
     let (operations_input_sender, operations_input_receiver) =
         sync_channel(config.operations_channel_size);
@@ -199,6 +207,7 @@ pub fn start_pool_controller(
         storage,
         channels.clone(),
         wallet.clone(),
+        sender_quota_allowlist,
     )));
     let endorsement_pool = Arc::new(RwLock::new(EndorsementPool::init(
         config,
@@ -207,6 +216,35 @@ pub fn start_pool_controller(
         wallet,
     )));
     let denunciation_pool = Arc::new(RwLock::new(DenunciationPool::init(config, channels)));
+
+    // reload the pools saved on the last stop
+    if let Some(persistence) = persistence
+        .as_ref()
+        .filter(|persistence| persistence.path.exists())
+    {
+        match load_pools(persistence, &config) {
+            Ok((operations, endorsements)) => {
+                info!(
+                    "reloading {} operations and {} endorsements into the pool",
+                    operations.len(),
+                    endorsements.len()
+                );
+                let mut operations_storage = storage.clone_without_refs();
+                operations_storage.store_operations(operations);
+                operation_pool.write().reload_operations(operations_storage);
+                let mut endorsements_storage = storage.clone_without_refs();
+                endorsements_storage.store_endorsements(endorsements);
+                endorsement_pool
+                    .write()
+                    .reload_endorsements(endorsements_storage);
+            }
+            Err(err) => warn!(
+                "could not reload the pools from {:?}: {}",
+                persistence.path, err
+            ),
+        }
+    }
+
     let controller = PoolControllerImpl {
         _config: config,
         operation_pool: operation_pool.clone(),
@@ -219,13 +257,16 @@ pub fn start_pool_controller(
     };
 
     let operations_thread_handle =
-        OperationPoolThread::spawn(operations_input_receiver, operation_pool, config);
+        OperationPoolThread::spawn(operations_input_receiver, operation_pool.clone(), config);
     let endorsements_thread_handle =
-        EndorsementPoolThread::spawn(endorsements_input_receiver, endorsement_pool);
+        EndorsementPoolThread::spawn(endorsements_input_receiver, endorsement_pool.clone());
     let denunciations_thread_handle =
         DenunciationPoolThread::spawn(denunciations_input_receiver, denunciation_pool);
 
     let manager = PoolManagerImpl {
+        persistence_path: persistence.map(|persistence| persistence.path),
+        operation_pool,
+        endorsement_pool,
         operations_thread_handle: Some(operations_thread_handle),
         endorsements_thread_handle: Some(endorsements_thread_handle),
         denunciations_thread_handle: Some(denunciations_thread_handle),
@@ -31,15 +31,22 @@ fn buf_to_array_ctr<F: Fn(&[u8; N]) -> V, V, const N: usize>(
 }
 
 // General cycle info idents
-const COMPLETE_IDENT: u8 = 0u8;
-const RNG_SEED_IDENT: u8 = 1u8;
-const FINAL_STATE_HASH_SNAPSHOT_IDENT: u8 = 2u8;
-const ROLL_COUNT_IDENT: u8 = 3u8;
-const PROD_STATS_IDENT: u8 = 4u8;
+/// Ident of the `complete` flag of a cycle
+pub const COMPLETE_IDENT: u8 = 0u8;
+/// Ident of the RNG seed of a cycle
+pub const RNG_SEED_IDENT: u8 = 1u8;
+/// Ident of the final state hash snapshot of a cycle
+pub const FINAL_STATE_HASH_SNAPSHOT_IDENT: u8 = 2u8;
+/// Ident of the roll counts of a cycle
+pub const ROLL_COUNT_IDENT: u8 = 3u8;
+/// Ident of the production stats of a cycle
+pub const PROD_STATS_IDENT: u8 = 4u8;
 
 // Production stats idents
-const PROD_STATS_FAIL_IDENT: u8 = 0u8;
-const PROD_STATS_SUCCESS_IDENT: u8 = 1u8;
+/// Ident of the block failure count of production stats
+pub const PROD_STATS_FAIL_IDENT: u8 = 0u8;
+/// Ident of the block success count of production stats
+pub const PROD_STATS_SUCCESS_IDENT: u8 = 1u8;
 
 /// Complete key formatting macro
 #[macro_export]
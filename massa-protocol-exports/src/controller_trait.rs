@@ -9,7 +9,7 @@ use crate::BootstrapPeers;
 use crate::PeerId;
 use massa_models::prehash::{PreHashMap, PreHashSet};
 use massa_models::stats::NetworkStats;
-use massa_models::{block_header::SecuredHeader, block_id::BlockId};
+use massa_models::{block_header::SecuredHeader, block_id::BlockId, operation::OperationId};
 use massa_storage::Storage;
 use peernet::peer::PeerConnectionType;
 
@@ -53,6 +53,15 @@ pub trait ProtocolController: Send + Sync {
     /// * `operations`: operations to propagate
     fn propagate_operations(&self, operations: Storage) -> Result<(), ProtocolError>;
 
+    /// Stop propagating operations that were replaced by others in the pool.
+    ///
+    /// # Arguments:
+    /// * `operation_ids`: ids of the replaced operations
+    fn notify_operations_replaced(
+        &self,
+        operation_ids: PreHashSet<OperationId>,
+    ) -> Result<(), ProtocolError>;
+
     /// Propagate a batch of endorsement (from pool).
     ///
     /// # Arguments:
@@ -4,6 +4,7 @@ use massa_channel::{sender::MassaSender, MassaChannel};
 use massa_models::{
     block_header::SecuredHeader,
     block_id::BlockId,
+    operation::OperationId,
     prehash::{PreHashMap, PreHashSet},
     stats::NetworkStats,
 };
@@ -123,6 +124,22 @@ impl ProtocolController for ProtocolControllerImpl {
             })
     }
 
+    /// Stop propagating operations that were replaced by others in the pool.
+    fn notify_operations_replaced(
+        &self,
+        operation_ids: PreHashSet<OperationId>,
+    ) -> Result<(), ProtocolError> {
+        self.sender_operation_handler
+            .as_ref()
+            .unwrap()
+            .try_send(OperationHandlerPropagationCommand::RemoveOperations(
+                operation_ids,
+            ))
+            .map_err(|_| {
+                ProtocolError::ChannelError("notify_operations_replaced command send error".into())
+            })
+    }
+
     /// propagate endorsements to connected node
     fn propagate_endorsements(&self, endorsements: Storage) -> Result<(), ProtocolError> {
         self.sender_endorsement_handler
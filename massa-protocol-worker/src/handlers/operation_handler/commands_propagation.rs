@@ -1,3 +1,4 @@
+use massa_models::{operation::OperationId, prehash::PreHashSet};
 use massa_storage::Storage;
 
 #[derive(Clone)]
@@ -5,4 +6,6 @@ pub enum OperationHandlerPropagationCommand {
     Stop,
     /// operations ids
     PropagateOperations(Storage),
+    /// ids of operations to stop propagating
+    RemoveOperations(PreHashSet<OperationId>),
 }
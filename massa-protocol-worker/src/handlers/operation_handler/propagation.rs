@@ -82,6 +82,9 @@ impl PropagationThread {
                                 }
                             }
                         }
+                        OperationHandlerPropagationCommand::RemoveOperations(op_ids) => {
+                            self.remove_operations(&op_ids);
+                        }
                         OperationHandlerPropagationCommand::Stop => {
                             info!("Stop operation propagation thread");
                             return;
@@ -141,6 +144,17 @@ impl PropagationThread {
         self.op_storage.drop_operation_refs(&removed);
     }
 
+    /// Stop propagating operations: they are neither announced nor kept anymore.
+    fn remove_operations(&mut self, op_ids: &PreHashSet<OperationId>) {
+        self.next_batch.retain(|op_id| !op_ids.contains(op_id));
+        for (_, stored_op_ids) in self.stored_for_propagation.iter_mut() {
+            stored_op_ids.retain(|op_id| !op_ids.contains(op_id));
+        }
+        self.stored_for_propagation
+            .retain(|(_, stored_op_ids)| !stored_op_ids.is_empty());
+        self.op_storage.drop_operation_refs(op_ids);
+    }
+
     fn announce_ops(&mut self) {
         // Quit if empty  to avoid iterating on nodes
         if self.next_batch.is_empty() {
@@ -17,13 +17,17 @@ use jsonrpsee_ws_client as _;
 use massa_api_exports::page::PagedVecV2;
 use massa_api_exports::ApiRequest;
 use massa_api_exports::{
-    address::AddressInfo,
+    address::{AddressInfo, AddressOperationsPage, AddressOperationsQuery},
     block::{BlockInfo, BlockSummary},
     datastore::{DatastoreEntryInput, DatastoreEntryOutput},
     endorsement::EndorsementInfo,
-    execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall},
+    execution::{
+        AsyncPoolMessagesPage, AsyncPoolMessagesQuery, ExecuteReadOnlyMulticallResponse,
+        ExecuteReadOnlyResponse, OperationEstimation, OperationEstimationInput,
+        ReadOnlyBytecodeExecution, ReadOnlyCall, ReadOnlyMulticall, SCOutputEventsPage,
+    },
     node::NodeStatus,
-    operation::{OperationInfo, OperationInput},
+    operation::{OperationCheck, OperationInfo, OperationInput},
     TimeInterval,
 };
 use massa_models::secure_share::SecureShare;
@@ -378,6 +382,18 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Get a page of events emitted by smart contracts with various filters,
+    /// along with the cursor of the next page
+    pub async fn get_filtered_sc_output_event_page(
+        &self,
+        filter: EventFilter,
+    ) -> RpcResult<SCOutputEventsPage> {
+        self.http_client
+            .request("get_filtered_sc_output_event_page", rpc_params![filter])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     /// Get the block graph within the specified time interval.
     /// Optional parameters: from `<time_start>` (included) and to `<time_end>` (excluded) millisecond timestamp
     pub(crate) async fn _get_graph_interval(
@@ -398,6 +414,30 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Get a page of the final operations sent or received by an address,
+    /// along with the cursor of the next page
+    pub async fn get_address_operations(
+        &self,
+        query: AddressOperationsQuery,
+    ) -> RpcResult<AddressOperationsPage> {
+        self.http_client
+            .request("get_address_operations", rpc_params![query])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Get a page of the messages of the asynchronous pool,
+    /// along with the cursor of the next page
+    pub async fn get_async_pool_messages(
+        &self,
+        query: AsyncPoolMessagesQuery,
+    ) -> RpcResult<AsyncPoolMessagesPage> {
+        self.http_client
+            .request("get_async_pool_messages", rpc_params![query])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     /// Get datastore entries
     pub async fn get_datastore_entries(
         &self,
@@ -422,6 +462,17 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Dry-runs operations without adding them to pool. Returns a verdict for each operation.
+    pub async fn check_operations(
+        &self,
+        operations: Vec<OperationInput>,
+    ) -> RpcResult<Vec<OperationCheck>> {
+        self.http_client
+            .request("check_operations", rpc_params![operations])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     /// execute read only bytecode
     pub async fn execute_read_only_bytecode(
         &self,
@@ -457,6 +508,28 @@ impl RpcClient {
                 to_error_obj("missing return value on execute_read_only_call".to_owned())
             })
     }
+
+    /// execute a sequence of read only bytecodes and SC calls sharing a single speculative state
+    pub async fn execute_read_only_multicall(
+        &self,
+        multicall: ReadOnlyMulticall,
+    ) -> RpcResult<ExecuteReadOnlyMulticallResponse> {
+        self.http_client
+            .request("execute_read_only_multicall", rpc_params![multicall])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// estimate the gas, the storage costs and the fee of an unsigned operation
+    pub async fn estimate_operation(
+        &self,
+        estimation_input: OperationEstimationInput,
+    ) -> RpcResult<OperationEstimation> {
+        self.http_client
+            .request("estimate_operation", rpc_params![estimation_input])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
 }
 
 /// Client V2